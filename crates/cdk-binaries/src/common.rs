@@ -1,22 +1,58 @@
 //! Common utilities for CDK binaries
 
+use alloy_primitives::{FixedBytes, U256};
 use anyhow::Result;
-use std::time::Duration;
+use cdk_observe::{MetricsServer, RunningMetricsServer};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 /// Parse checkpoint string into Checkpoint
+///
+/// `auto` and `latest` return `None`, meaning the stored checkpoint is used. A
+/// batch number returns a checkpoint at that batch, so ingestion resumes with
+/// the batch after it.
 pub fn parse_checkpoint(checkpoint_str: &str) -> Result<Option<cdk_datastream::Checkpoint>> {
     match checkpoint_str {
         "auto" => Ok(None),
         "latest" => Ok(None),
         _ => {
-            // Try to parse as specific checkpoint
-            // This is a placeholder - in real implementation, you'd parse the checkpoint format
-            tracing::warn!("Specific checkpoint parsing not implemented, using auto");
-            Ok(None)
+            let batch_number: u64 = checkpoint_str
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid checkpoint: {}", checkpoint_str))?;
+            let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+            Ok(Some(cdk_datastream::Checkpoint::new(
+                U256::from(batch_number),
+                FixedBytes::ZERO,
+                U256::ZERO,
+                timestamp,
+            )))
         }
     }
 }
 
+/// Path of the mapping database in a data directory shared by the CDK commands
+pub fn mappings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("mappings")
+}
+
+/// Serve Prometheus metrics at `address` when `enabled`
+///
+/// The recorder is installed globally, so this must run before `CdkMetrics`
+/// is created for its values to be exported. The server runs until the
+/// returned handle is shut down or the process exits.
+pub fn start_metrics(enabled: bool, address: SocketAddr) -> Result<Option<RunningMetricsServer>> {
+    if !enabled {
+        return Ok(None);
+    }
+    let server = MetricsServer::new(address)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to start metrics server on {}: {}", address, e))?;
+    Ok(Some(server))
+}
+
 /// Validate URL format
 pub fn validate_url(url: &str) -> Result<()> {
     if url.starts_with("http://") || url.starts_with("https://") {
@@ -58,6 +94,11 @@ mod tests {
     fn test_parse_checkpoint() {
         assert!(parse_checkpoint("auto").unwrap().is_none());
         assert!(parse_checkpoint("latest").unwrap().is_none());
+
+        let checkpoint = parse_checkpoint("42").unwrap().unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(42));
+        assert!(checkpoint.is_valid());
+        assert!(parse_checkpoint("batch-42").is_err());
     }
 
     #[test]
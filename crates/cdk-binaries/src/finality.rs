@@ -1,34 +1,90 @@
 //! Finality command implementation
 
-use clap::Parser;
+use clap::{Args, Parser};
 use anyhow::Result;
-use cdk_finality::{L1Client, L1ClientConfig, RollbackManager, RollbackConfig};
+use alloy_primitives::{Address, U256};
+use cdk_engine_facade::{EngineBlockUnwinder, EngineFacade};
+use cdk_finality::{FinalityOracle, FinalityWatcher, L1ClientConfig, RealFinalityOracle, RollbackAction, RollbackConfig, RollbackManager};
+use cdk_ingest::{MappingStorage, RocksMappingStorage};
 use cdk_observe::CdkMetrics;
-use std::time::Duration;
+use crate::{mappings_path, start_metrics};
+use std::{future::Future, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
-/// Monitor L1 finality and trigger rollbacks
+/// Capacity of the channel carrying rollback actions from the watcher
+const ACTION_CHANNEL_CAPACITY: usize = 256;
+
+/// Directory in the data directory holding the finality command's secondary mapping store
+const SECONDARY_MAPPINGS_DIR: &str = "finality-mappings";
+
+/// Options for following L1 finality from the ingest command
+///
+/// When a bridge is given, finalized batches are marked final and rolled back
+/// batches unwound on the engine the batches are imported into.
+#[derive(Args, Debug, Clone)]
+pub struct IngestFinalityArgs {
+    /// Bridge contract address; follow L1 finality while ingesting when set
+    #[arg(long, alias = "bridge-address")]
+    pub bridge: Option<String>,
+
+    /// L1 RPC URL
+    #[arg(long, default_value = "http://localhost:8545")]
+    pub l1_rpc: String,
+
+    /// Fallback L1 RPC URLs, tried in order whenever the active endpoint is unreachable
+    #[arg(long)]
+    pub l1_fallback_rpc: Vec<String>,
+
+    /// Finality polling interval in seconds
+    #[arg(long = "finality-poll-interval", default_value = "30")]
+    pub poll_interval: u64,
+}
+
+impl Default for IngestFinalityArgs {
+    fn default() -> Self {
+        Self {
+            bridge: None,
+            l1_rpc: "http://localhost:8545".to_string(),
+            l1_fallback_rpc: Vec::new(),
+            poll_interval: 30,
+        }
+    }
+}
+
+/// Monitor L1 finality and report finalized and rolled back batches
+///
+/// The ingest command owns the engine and holds the mapping store open for
+/// writing, so this command follows the store as a secondary instance and
+/// only reports. Pass `--bridge` to the ingest command to apply finality.
 #[derive(Parser)]
-#[command(about = "Monitor L1 finality and trigger rollbacks")]
+#[command(about = "Monitor L1 finality and report finalized and rolled back batches")]
 pub struct FinalityCommand {
     /// L1 RPC URL
     #[arg(long, default_value = "http://localhost:8545")]
     pub l1_rpc: String,
-    
-    /// Bridge contract address
+
+    /// Fallback L1 RPC URLs, tried in order whenever the active endpoint is unreachable
     #[arg(long)]
+    pub l1_fallback_rpc: Vec<String>,
+
+    /// Bridge contract address
+    #[arg(long, alias = "bridge-address")]
     pub bridge: String,
-    
-    /// Reth RPC URL
-    #[arg(long, default_value = "http://localhost:8545")]
-    pub reth_rpc: String,
-    
+
     /// Polling interval in seconds
     #[arg(long, default_value = "30")]
     pub poll_interval: u64,
-    
+
+    /// Data directory of the ingest command, whose block/batch mappings are followed
+    #[arg(long, default_value = "cdk-data")]
+    pub data_dir: PathBuf,
+
     /// Enable metrics collection
     #[arg(long, default_value = "true")]
     pub enable_metrics: bool,
+
+    /// Address the Prometheus metrics are served on when metrics are enabled
+    #[arg(long, default_value = "127.0.0.1:9001")]
+    pub metrics_addr: SocketAddr,
 }
 
 impl FinalityCommand {
@@ -36,40 +92,179 @@ impl FinalityCommand {
         tracing::info!("Starting CDK finality monitoring");
         tracing::info!("L1 RPC: {}", self.l1_rpc);
         tracing::info!("Bridge contract: {}", self.bridge);
-        tracing::info!("Reth RPC: {}", self.reth_rpc);
+        tracing::info!("Data directory: {}", self.data_dir.display());
         tracing::info!("Poll interval: {}s", self.poll_interval);
 
-        // Initialize metrics
-        let _metrics = CdkMetrics::new();
-        
-        // Create L1 client
-        let config = L1ClientConfig {
-            rpc_url: self.l1_rpc.clone(),
-            timeout: Duration::from_secs(30),
-            max_retries: 3,
-            retry_delay: Duration::from_secs(1),
-            api_key: None,
+        let bridge: Address = self.bridge.parse()?;
+
+        let oracle = connect_oracle(&self.l1_rpc, &self.l1_fallback_rpc, bridge, self.poll_interval).await?;
+
+        // Rollbacks are only recorded: the engine lives in the ingest process
+        let mapping_storage = self.open_mapping_storage()?;
+        let rollback_manager = RollbackManager::new(RollbackConfig::default());
+
+        let _metrics_server = start_metrics(self.enable_metrics, self.metrics_addr)?;
+        let metrics = CdkMetrics::new();
+        let shutdown = async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                tracing::error!("Failed to listen for shutdown signal: {}", e);
+                std::future::pending::<()>().await;
+            }
         };
-        let _l1_client = L1Client::new(config)?;
-        
-        // Create finality oracle (simplified - placeholder)
-        // let mut finality_oracle: Box<dyn FinalityOracle> = Box::new(FinalityOracle::new(l1_client));
-        
-        // Create rollback manager
-        let config = RollbackConfig::default();
-        let _rollback_manager = RollbackManager::new(config);
-        
-        // Start monitoring loop
-        let mut interval = tokio::time::interval(Duration::from_secs(self.poll_interval));
-        
-        loop {
-            interval.tick().await;
-            
-            // Simplified finality monitoring (placeholder)
-            tracing::info!("Polling for finality updates...");
-            
-            // Placeholder for actual finality checking
-            tokio::time::sleep(Duration::from_secs(1)).await;
+        self.monitor(Box::new(oracle), rollback_manager, None, &mapping_storage, &metrics, shutdown)
+            .await?;
+        Ok(())
+    }
+
+    /// Open the mapping store the ingest command keeps in `data_dir` as a secondary instance
+    ///
+    /// The ingest command may hold the store open for writing at the same time.
+    pub fn open_mapping_storage(&self) -> Result<RocksMappingStorage> {
+        Ok(RocksMappingStorage::open_secondary(
+            mappings_path(&self.data_dir),
+            self.data_dir.join(SECONDARY_MAPPINGS_DIR),
+        )?)
+    }
+
+    /// Poll `oracle` and apply the resulting rollback actions, returning the number of actions handled
+    ///
+    /// See `monitor_finality`.
+    pub async fn monitor(
+        &self,
+        oracle: Box<dyn FinalityOracle>,
+        rollback_manager: RollbackManager,
+        engine: Option<&EngineFacade>,
+        mapping_storage: &dyn MappingStorage,
+        metrics: &CdkMetrics,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<u64> {
+        monitor_finality(oracle, rollback_manager, engine, mapping_storage, metrics, shutdown).await
+    }
+}
+
+impl IngestFinalityArgs {
+    /// Connect to L1 when a bridge is configured
+    pub async fn connect_oracle(&self) -> Result<Option<RealFinalityOracle>> {
+        let Some(bridge) = &self.bridge else {
+            return Ok(None);
+        };
+        tracing::info!("Following L1 finality of bridge {}", bridge);
+        let oracle = connect_oracle(&self.l1_rpc, &self.l1_fallback_rpc, bridge.parse()?, self.poll_interval).await?;
+        Ok(Some(oracle))
+    }
+}
+
+/// Follow L1 finality from `oracle` on `engine`, returning the number of actions handled
+///
+/// Finalized batches are marked final and rolled back batches unwound on
+/// `engine`, using the block/batch mappings in `mapping_storage`. Following
+/// stops once `shutdown` completes.
+pub async fn follow_finality(
+    oracle: Box<dyn FinalityOracle>,
+    engine: Arc<EngineFacade>,
+    mapping_storage: Arc<dyn MappingStorage>,
+    metrics: &CdkMetrics,
+    shutdown: impl Future<Output = ()>,
+) -> Result<u64> {
+    let rollback_manager = RollbackManager::new(RollbackConfig::default())
+        .with_unwinder(Box::new(EngineBlockUnwinder::new(engine.clone(), mapping_storage.clone())));
+    monitor_finality(oracle, rollback_manager, Some(&engine), mapping_storage.as_ref(), metrics, shutdown).await
+}
+
+/// Build the finality oracle over the primary L1 endpoint and its fallbacks
+///
+/// Every L1 request of the oracle fails over to the next endpoint while the
+/// current one is unreachable.
+async fn connect_oracle(
+    l1_rpc: &str,
+    l1_fallback_rpc: &[String],
+    bridge: Address,
+    poll_interval: u64,
+) -> Result<RealFinalityOracle> {
+    let config = L1ClientConfig {
+        rpc_url: l1_rpc.to_string(),
+        fallback_rpc_urls: l1_fallback_rpc.to_vec(),
+        ..Default::default()
+    };
+    Ok(RealFinalityOracle::new(config, bridge, Duration::from_secs(poll_interval)).await?)
+}
+
+/// Poll `oracle` and apply the resulting rollback actions, returning the number of actions handled
+///
+/// Rollbacks are executed by `rollback_manager` itself, through its block
+/// unwinder when it has one. Finalized batches are looked up in
+/// `mapping_storage` and their last block is marked final on `engine`, or
+/// only reported without one.
+///
+/// Monitoring stops once `shutdown` completes.
+async fn monitor_finality(
+    oracle: Box<dyn FinalityOracle>,
+    rollback_manager: RollbackManager,
+    engine: Option<&EngineFacade>,
+    mapping_storage: &dyn MappingStorage,
+    metrics: &CdkMetrics,
+    shutdown: impl Future<Output = ()>,
+) -> Result<u64> {
+    let (watcher, mut actions) = FinalityWatcher::new(oracle, rollback_manager, ACTION_CHANNEL_CAPACITY);
+    let watcher = tokio::spawn(watcher.run());
+
+    let mut handled_count = 0;
+    tokio::pin!(shutdown);
+
+    loop {
+        let action = tokio::select! {
+            _ = &mut shutdown => {
+                tracing::info!("Shutdown requested, stopping finality monitoring");
+                break;
+            }
+            action = actions.recv() => action,
+        };
+        let Some(action) = action else {
+            tracing::warn!("Finality watcher stopped");
+            break;
+        };
+
+        if let Err(e) = apply_action(&action, engine, mapping_storage, metrics).await {
+            tracing::error!("Failed to apply {:?}: {}", action, e);
+            metrics.increment_error_count();
+        }
+        handled_count += 1;
+    }
+
+    watcher.abort();
+    tracing::info!("Finality monitoring completed: {} actions handled", handled_count);
+    Ok(handled_count)
+}
+
+/// Apply a rollback action emitted by the finality watcher
+async fn apply_action(
+    action: &RollbackAction,
+    engine: Option<&EngineFacade>,
+    mapping_storage: &dyn MappingStorage,
+    metrics: &CdkMetrics,
+) -> Result<()> {
+    match action {
+        RollbackAction::Finalized(batch_id) => {
+            let mapping = mapping_storage
+                .load_batch_mapping(*batch_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("No mapping for finalized batch {}", batch_id))?;
+            if let Some(engine) = engine {
+                engine.mark_final(U256::from(mapping.end_block)).await?;
+            }
+            tracing::info!("Batch {} finalized at block {}", batch_id, mapping.end_block);
+        }
+        RollbackAction::ExecuteRollback(batch_id) => {
+            metrics.increment_rollback_count();
+            tracing::info!("Rolled back batch {}", batch_id);
+        }
+        RollbackAction::Rejected { batch_id, depth } => {
+            metrics.increment_error_count();
+            tracing::error!("Rejected rollback of batch {} ({} blocks)", batch_id, depth);
+        }
+        RollbackAction::PendingRollback(_) | RollbackAction::StatusChanged(_) | RollbackAction::Expired(_) => {
+            tracing::debug!("Finality action: {:?}", action);
         }
     }
+    Ok(())
 }
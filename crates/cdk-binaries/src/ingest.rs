@@ -2,12 +2,34 @@
 
 use clap::Parser;
 use anyhow::Result;
-use cdk_datastream::{BatchSource, HttpBatchSource, HttpBatchSourceConfig};
-use cdk_ingest::{MemoryMappingStorage, MappingStorage};
-use cdk_observe::{CdkMetrics, CdkTracing};
-use std::time::{Instant, Duration};
+use alloy_primitives::{Bytes, U256};
+use cdk_datastream::{BatchSource, Checkpoint, CheckpointStorage, FileCheckpointStorage, HttpBatchSource, HttpBatchSourceConfig};
+use cdk_engine_facade::{BatchInfo, EngineFacade, ImportableBlock};
+use cdk_ingest::{
+    BlockAssembler, BlockInputs, DefaultBlockAssembler, MemoryMappingStorage, MappingStorage, RocksMappingStorage,
+};
+use cdk_observe::{CdkMetrics, CdkTracing, RollingTps};
+use cdk_types::Batch;
+use crate::{follow_finality, mappings_path, parse_checkpoint, start_metrics, IngestFinalityArgs};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+    time::{Instant, Duration},
+};
+use tokio::sync::oneshot;
 use url::Url;
 
+/// Window over which the ingestion TPS is averaged
+const TPS_WINDOW: Duration = Duration::from_secs(60);
+
+/// Name of the checkpoint file in the data directory
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// Checkpoint metadata key set on the checkpoint saved when ingestion is shut down
+pub const CLEAN_SHUTDOWN_KEY: &str = "clean_shutdown";
+
 /// Ingest batches from data source into Reth
 #[derive(Parser)]
 #[command(about = "Ingest batches from data source into Reth")]
@@ -20,48 +42,157 @@ pub struct IngestCommand {
     #[arg(long, default_value = "auto")]
     pub from_checkpoint: String,
     
-    /// Reth RPC URL
-    #[arg(long, default_value = "http://localhost:8545")]
-    pub reth_rpc: String,
-    
     /// Maximum number of batches to process (0 = unlimited)
     #[arg(long, default_value = "0")]
     pub max_batches: u64,
     
+    /// Stop after processing the batch with this number (or any later one)
+    #[arg(long)]
+    pub to_batch: Option<u64>,
+
+    /// Chain id the data source must serve; ingestion refuses to start on a mismatch
+    #[arg(long)]
+    pub chain_id: Option<u64>,
+
+    /// Directory holding the ingest checkpoint and the block/batch mappings
+    #[arg(long, default_value = "cdk-data")]
+    pub data_dir: PathBuf,
+    
     /// Enable metrics collection
     #[arg(long, default_value = "true")]
     pub enable_metrics: bool,
+
+    /// Address the Prometheus metrics are served on when metrics are enabled
+    #[arg(long, default_value = "127.0.0.1:9000")]
+    pub metrics_addr: SocketAddr,
+
+    /// L1 finality followed while ingesting
+    #[command(flatten)]
+    pub finality: IngestFinalityArgs,
 }
 
 impl IngestCommand {
     pub async fn run(&self) -> Result<()> {
         tracing::info!("Starting CDK ingest process");
         tracing::info!("Data source: {}", self.datastream);
-        tracing::info!("Reth RPC: {}", self.reth_rpc);
+        tracing::info!("Data directory: {}", self.data_dir.display());
         tracing::info!("Max batches: {}", self.max_batches);
+        if let Some(to_batch) = self.to_batch {
+            tracing::info!("Stopping at batch: {}", to_batch);
+        }
 
-        // Initialize metrics
-        let metrics = CdkMetrics::new();
-        
         // Create data source
         let config = HttpBatchSourceConfig {
             base_url: Url::parse(&self.datastream)?,
             api_key: None,
+            headers: Default::default(),
+            token_provider: None,
             timeout: Duration::from_secs(30),
             max_retries: 3,
             retry_delay: Duration::from_secs(1),
+            chain_id: 0,
         };
         let mut batch_source = HttpBatchSource::new(config);
         
-        // Create mapping storage
-        let mapping_storage = MemoryMappingStorage::default();
-        
-        // Create block assembler (simplified - placeholder)
-        // let assembler = BlockAssembler::default();
-        
+        let (checkpoint_storage, mapping_storage) = self.open_storage()?;
+        let oracle = self.finality.connect_oracle().await?;
+
+        let engine = Arc::new(EngineFacade::default());
+        let mapping_storage = Arc::new(mapping_storage);
+        let _metrics_server = start_metrics(self.enable_metrics, self.metrics_addr)?;
+        let metrics = CdkMetrics::new();
+        let shutdown = async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                tracing::error!("Failed to listen for shutdown signal: {}", e);
+                std::future::pending::<()>().await;
+            }
+        };
+
+        // Finality is followed on the same engine and stops once ingestion does
+        let (ingest_done_tx, ingest_done_rx) = oneshot::channel::<()>();
+        let ingest = async {
+            let result = self
+                .ingest(&mut batch_source, mapping_storage.as_ref(), &checkpoint_storage, &engine, &metrics, shutdown)
+                .await;
+            drop(ingest_done_tx);
+            result
+        };
+        let finality = async {
+            match oracle {
+                Some(oracle) => {
+                    let ingest_done = async {
+                        let _ = ingest_done_rx.await;
+                    };
+                    follow_finality(Box::new(oracle), engine.clone(), mapping_storage.clone(), &metrics, ingest_done)
+                        .await
+                        .map(Some)
+                }
+                None => Ok(None),
+            }
+        };
+        let (ingested, followed) = tokio::join!(ingest, finality);
+        ingested?;
+        followed?;
+        Ok(())
+    }
+
+    /// Open the checkpoint and mapping stores kept in `data_dir`, creating it if needed
+    pub fn open_storage(&self) -> Result<(FileCheckpointStorage, RocksMappingStorage)> {
+        std::fs::create_dir_all(&self.data_dir)?;
+        let checkpoint_storage = FileCheckpointStorage::new(self.data_dir.join(CHECKPOINT_FILE));
+        let mapping_storage = RocksMappingStorage::open(mappings_path(&self.data_dir))?;
+        Ok((checkpoint_storage, mapping_storage))
+    }
+
+    /// Assemble and import batches from `batch_source`, returning the number of batches processed
+    ///
+    /// When `chain_id` is set, ingestion fails before reading any batch if the
+    /// source reports serving another chain.
+    ///
+    /// Ingestion starts after the checkpoint given by `from_checkpoint`, or the
+    /// one in `checkpoint_storage` for `auto` and `latest`. Block and batch
+    /// mappings are saved to `mapping_storage` once a batch is imported, and a
+    /// checkpoint at that batch, stamped with the source, is saved to `checkpoint_storage`.
+    ///
+    /// Ingestion stops once `shutdown` completes. A batch being processed at
+    /// that point is finished first, then the latest checkpoint is saved again
+    /// marked with `CLEAN_SHUTDOWN_KEY`.
+    pub async fn ingest<S: BatchSource + ?Sized>(
+        &self,
+        batch_source: &mut S,
+        mapping_storage: &dyn MappingStorage,
+        checkpoint_storage: &dyn CheckpointStorage,
+        engine: &EngineFacade,
+        metrics: &CdkMetrics,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<u64> {
+        let source_metadata = batch_source.metadata().await?;
+        if let Some(chain_id) = self.chain_id {
+            source_metadata.ensure_chain_id(chain_id)?;
+        }
+
+        let start_checkpoint = match parse_checkpoint(&self.from_checkpoint)? {
+            Some(checkpoint) => Some(checkpoint),
+            None => checkpoint_storage.load_checkpoint().await?,
+        };
+        if let Some(checkpoint) = start_checkpoint {
+            if checkpoint.get_metadata(CLEAN_SHUTDOWN_KEY).is_some() {
+                tracing::info!("Previous ingestion was shut down cleanly");
+            }
+            tracing::info!("Resuming after batch {}", checkpoint.last_batch_id);
+            batch_source.set_checkpoint(checkpoint).await?;
+        }
+
+        // The assembler only builds the mappings; they are saved to `mapping_storage` directly
+        let mut assembler = DefaultBlockAssembler::new(Box::new(MemoryMappingStorage::default()));
+
         // Process batches
         let mut processed_count = 0;
+        let mut last_checkpoint = None;
+        let mut shutdown_requested = false;
         let start_time = Instant::now();
+        let mut tps = RollingTps::starting_at(TPS_WINDOW, start_time);
+        tokio::pin!(shutdown);
         
         loop {
             if self.max_batches > 0 && processed_count >= self.max_batches {
@@ -69,30 +200,56 @@ impl IngestCommand {
                 break;
             }
 
-            match batch_source.next().await {
+            let next = tokio::select! {
+                _ = &mut shutdown => {
+                    shutdown_requested = true;
+                    break;
+                }
+                next = batch_source.next() => next,
+            };
+
+            match next {
                 Ok(Some(batch)) => {
                     let batch_start = Instant::now();
                     
                     CdkTracing::log_ingestion_start(batch.id.number, batch.blocks.len());
-                    
-                    // Assemble blocks (simplified - placeholder)
-                    // let _block_inputs = assembler.assemble(&batch)?;
-                    
-                    // Store mappings (simplified)
-                    let batch_mapping = cdk_ingest::BatchMapping {
-                        batch_id: batch.id.number.to(),
-                        batch_hash: batch.id.hash,
-                        start_block: 0, // Simplified
-                        end_block: batch.blocks.len() as u64,
-                        block_count: batch.blocks.len() as u32,
-                        epoch_id: 0, // Simplified
-                        timestamp: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
-                    };
-                    mapping_storage.save_batch_mapping(batch_mapping).await?;
+
+                    let batch_id = u64::try_from(batch.id.number)
+                        .map_err(|_| anyhow::anyhow!("Batch number {} does not fit in u64", batch.id.number))?;
+                    let block_inputs = assembler.assemble(&batch).await?;
+                    if let (Some(first), Some(last)) = (block_inputs.first(), block_inputs.last()) {
+                        let (start_block, end_block) = (first.number, last.number);
+                        let blocks = block_inputs
+                            .iter()
+                            .zip(&batch.blocks)
+                            .map(|(input, block)| importable_block(input, &batch, block.batch_index))
+                            .collect();
+                        engine.import_batch(&batch, blocks).await?;
+
+                        // Store mappings (epochs are not tracked yet)
+                        mapping_storage.save_block_mappings_batch(assembler.block_mappings(&batch, 0)?).await?;
+                        let batch_mapping = cdk_ingest::BatchMapping {
+                            batch_id,
+                            batch_hash: batch.id.hash,
+                            start_block,
+                            end_block,
+                            block_count: block_inputs.len() as u32,
+                            epoch_id: 0,
+                            timestamp: batch.timestamp,
+                        };
+                        mapping_storage.save_batch_mapping(batch_mapping).await?;
+                    }
+
+                    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+                    let checkpoint = Checkpoint::from_batch(&batch, now).with_source(&source_metadata);
+                    checkpoint_storage.save_checkpoint(checkpoint.clone()).await?;
+                    last_checkpoint = Some(checkpoint);
                     
                     // Update metrics
                     metrics.update_batch_height(batch.id.number);
-                    metrics.update_ingest_tps(1.0 / batch_start.elapsed().as_secs_f64());
+                    metrics.update_ingest_tps(tps.record(batch.transaction_count(), Instant::now()));
+                    metrics.record_batch_processing_time(batch_start.elapsed().as_secs_f64());
+                    metrics.increment_batches_processed();
                     
                     let duration_ms = batch_start.elapsed().as_millis() as u64;
                     CdkTracing::log_ingestion_complete(batch.id.number, duration_ms);
@@ -101,6 +258,11 @@ impl IngestCommand {
                     
                     tracing::info!("Processed batch {} ({} blocks) in {}ms", 
                         batch.id.number, batch.blocks.len(), duration_ms);
+
+                    if self.reached_target_batch(batch.id.number) {
+                        tracing::info!("Reached target batch: {}", batch.id.number);
+                        break;
+                    }
                 }
                 Ok(None) => {
                     tracing::info!("No more batches available");
@@ -111,15 +273,54 @@ impl IngestCommand {
                     metrics.increment_error_count();
                     
                     // Wait before retrying
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    tokio::select! {
+                        _ = &mut shutdown => {
+                            shutdown_requested = true;
+                            break;
+                        }
+                        _ = tokio::time::sleep(tokio::time::Duration::from_secs(5)) => {}
+                    }
                 }
             }
         }
+
+        if shutdown_requested {
+            tracing::info!("Shutdown requested, stopping ingestion");
+            let checkpoint = match last_checkpoint {
+                Some(checkpoint) => Some(checkpoint),
+                None => checkpoint_storage.load_checkpoint().await?,
+            };
+            if let Some(checkpoint) = checkpoint {
+                tracing::info!("Saving checkpoint at batch {}", checkpoint.last_batch_id);
+                let checkpoint = checkpoint.with_metadata(CLEAN_SHUTDOWN_KEY.to_string(), "true".to_string());
+                checkpoint_storage.save_checkpoint(checkpoint).await?;
+            }
+        }
         
         let total_duration = start_time.elapsed();
         tracing::info!("Ingest completed: {} batches processed in {:?}", 
             processed_count, total_duration);
         
-        Ok(())
+        Ok(processed_count)
+    }
+
+    /// Whether ingestion should stop after processing the batch numbered `batch_number`
+    pub fn reached_target_batch(&self, batch_number: U256) -> bool {
+        self.to_batch.is_some_and(|to_batch| batch_number >= U256::from(to_batch))
     }
 }
+
+/// Build the block handed to the engine from assembled block inputs
+fn importable_block(input: &BlockInputs, batch: &Batch, batch_index: u32) -> ImportableBlock {
+    ImportableBlock::new(
+        U256::from(input.number),
+        input.hash,
+        input.parent_hash,
+        input.state_root,
+        input.transactions_root,
+        input.receipts_root,
+        input.timestamp,
+        Bytes::new(),
+        Some(BatchInfo::new(batch.id.number, batch.l1_origin, batch.l1_origin_hash, batch_index)),
+    )
+}
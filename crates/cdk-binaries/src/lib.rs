@@ -4,6 +4,6 @@ pub mod ingest;
 pub mod finality;
 pub mod common;
 
-pub use ingest::IngestCommand;
-pub use finality::FinalityCommand;
+pub use ingest::{IngestCommand, CLEAN_SHUTDOWN_KEY};
+pub use finality::{follow_finality, FinalityCommand, IngestFinalityArgs};
 pub use common::*;
@@ -2,22 +2,46 @@
 
 #[cfg(test)]
 mod tests {
-    use cdk_binaries::{IngestCommand, FinalityCommand, parse_checkpoint, validate_url, retry_delay, format_duration};
-    use std::time::Duration;
+    use alloy_primitives::{Address, FixedBytes, U256};
+    use cdk_binaries::{
+        IngestCommand, FinalityCommand, CLEAN_SHUTDOWN_KEY, follow_finality, parse_checkpoint, validate_url, retry_delay, format_duration, start_metrics,
+    };
+    use async_trait::async_trait;
+    use cdk_datastream::{
+        BatchSource, BatchStream, Checkpoint, CheckpointStorage, DatastreamError, FileCheckpointStorage,
+        FilesystemSource, FilesystemSourceConfig, MemoryCheckpointStorage, SourceMetadata,
+    };
+    use cdk_engine_facade::{
+        DefaultBlockImporter, EngineFacade, EngineFacadeError, FinalityManager, FinalityResult as EngineFinalityResult,
+    };
+    use cdk_finality::{FinalityOracle, FinalityResult, OracleMetadata, RollbackConfig, RollbackManager};
+    use cdk_ingest::{MappingStorage, MemoryMappingStorage};
+    use cdk_observe::CdkMetrics;
+    use cdk_types::{Batch, BatchId, BlockInBatch, FinalityStatus, FinalityTag, ProofMetadata};
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tokio::sync::oneshot;
 
     #[test]
     fn test_ingest_command_creation() {
         let cmd = IngestCommand {
             datastream: "http://localhost:8080/batches".to_string(),
             from_checkpoint: "auto".to_string(),
-            reth_rpc: "http://localhost:8545".to_string(),
             max_batches: 10,
+            to_batch: None,
+            chain_id: None,
+            data_dir: "cdk-data".into(),
             enable_metrics: true,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
         };
         
         assert_eq!(cmd.datastream, "http://localhost:8080/batches");
         assert_eq!(cmd.from_checkpoint, "auto");
-        assert_eq!(cmd.reth_rpc, "http://localhost:8545");
         assert_eq!(cmd.max_batches, 10);
         assert!(cmd.enable_metrics);
     }
@@ -26,15 +50,16 @@ mod tests {
     fn test_finality_command_creation() {
         let cmd = FinalityCommand {
             l1_rpc: "http://localhost:8545".to_string(),
+            l1_fallback_rpc: vec![],
             bridge: "0x1234567890123456789012345678901234567890".to_string(),
-            reth_rpc: "http://localhost:8545".to_string(),
             poll_interval: 30,
+            data_dir: "cdk-data".into(),
             enable_metrics: true,
+            metrics_addr: "127.0.0.1:9001".parse().unwrap(),
         };
         
         assert_eq!(cmd.l1_rpc, "http://localhost:8545");
         assert_eq!(cmd.bridge, "0x1234567890123456789012345678901234567890");
-        assert_eq!(cmd.reth_rpc, "http://localhost:8545");
         assert_eq!(cmd.poll_interval, 30);
         assert!(cmd.enable_metrics);
     }
@@ -68,4 +93,669 @@ mod tests {
         assert_eq!(format_duration(Duration::from_secs(90)), "1m 30s");
         assert_eq!(format_duration(Duration::from_secs(3661)), "1h 1m 1s");
     }
+
+    fn create_batch(number: u64, first_block: u64, block_count: u64) -> Batch {
+        let blocks = (0..block_count)
+            .map(|i| BlockInBatch {
+                batch_index: i as u32,
+                hash: FixedBytes::from([(first_block + i) as u8; 32]),
+                number: U256::from(first_block + i),
+                parent_hash: FixedBytes::from([(first_block + i - 1) as u8; 32]),
+                state_root: FixedBytes::from([0xaa; 32]),
+                tx_root: FixedBytes::from([0xbb; 32]),
+                receipt_root: FixedBytes::from([0xcc; 32]),
+                timestamp: 1234567890 + i,
+                tx_count: 0,
+                gas_used: 0,
+            })
+            .collect();
+
+        Batch::new(
+            BatchId {
+                number: U256::from(number),
+                hash: FixedBytes::from([number as u8; 32]),
+            },
+            U256::from(1000),
+            FixedBytes::from([9u8; 32]),
+            blocks,
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_ingest_saves_batch_block_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let batch = create_batch(1, 10, 3);
+        std::fs::write(dir.path().join("batch_000001.json"), serde_json::to_vec(&batch).unwrap()).unwrap();
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: "cdk-data".into(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let storage = MemoryMappingStorage::default();
+        let engine = EngineFacade::default();
+
+        let processed = cmd
+            .ingest(
+                &mut source,
+                &storage,
+                &MemoryCheckpointStorage::default(),
+                &engine,
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(processed, 1);
+
+        let mapping = storage.load_batch_mapping(1).await.unwrap().unwrap();
+        assert_eq!(mapping.start_block, 10);
+        assert_eq!(mapping.end_block, 12);
+        assert_eq!(mapping.block_count, 3);
+        assert_eq!(storage.get_blocks_for_batch(1).await.unwrap(), vec![10, 11, 12]);
+        assert_eq!(engine.get_head_block().await.unwrap(), U256::from(12));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_rejects_source_for_other_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let batch = create_batch(1, 10, 3);
+        std::fs::write(dir.path().join("batch_000001.json"), serde_json::to_vec(&batch).unwrap()).unwrap();
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            chain_id: 2,
+            ..Default::default()
+        });
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: Some(1),
+            data_dir: "cdk-data".into(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let storage = MemoryMappingStorage::default();
+
+        let error = cmd
+            .ingest(
+                &mut source,
+                &storage,
+                &MemoryCheckpointStorage::default(),
+                &EngineFacade::default(),
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DatastreamError>(),
+            Some(DatastreamError::ChainIdMismatch { expected: 1, actual: 2 })
+        ));
+        assert!(storage.load_batch_mapping(1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_stops_at_target_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        for number in 1..=4u64 {
+            let batch = create_batch(number, number * 10, 2);
+            std::fs::write(
+                dir.path().join(format!("batch_{:06}.json", number)),
+                serde_json::to_vec(&batch).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: Some(2),
+            chain_id: None,
+            data_dir: "cdk-data".into(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        assert!(!cmd.reached_target_batch(U256::from(1)));
+        assert!(cmd.reached_target_batch(U256::from(2)));
+
+        let storage = MemoryMappingStorage::default();
+        let processed = cmd
+            .ingest(
+                &mut source,
+                &storage,
+                &MemoryCheckpointStorage::default(),
+                &EngineFacade::default(),
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(processed, 2);
+        assert!(storage.load_batch_mapping(2).await.unwrap().is_some());
+        assert!(storage.load_batch_mapping(3).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_resumes_from_stored_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        for number in 1..=4u64 {
+            let batch = create_batch(number, number * 10, 2);
+            std::fs::write(
+                dir.path().join(format!("batch_{:06}.json", number)),
+                serde_json::to_vec(&batch).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let checkpoint_storage = MemoryCheckpointStorage::default();
+        checkpoint_storage
+            .save_checkpoint(Checkpoint::from_batch(&create_batch(2, 20, 2), 1234567890))
+            .await
+            .unwrap();
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: "cdk-data".into(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let storage = MemoryMappingStorage::default();
+        let processed = cmd
+            .ingest(
+                &mut source,
+                &storage,
+                &checkpoint_storage,
+                &EngineFacade::default(),
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(processed, 2);
+        assert!(storage.load_batch_mapping(2).await.unwrap().is_none());
+        assert!(storage.load_batch_mapping(3).await.unwrap().is_some());
+        let checkpoint = checkpoint_storage.load_checkpoint().await.unwrap().unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(4));
+        assert_eq!(checkpoint.get_metadata(CLEAN_SHUTDOWN_KEY), None);
+        assert_eq!(checkpoint.source(), Some("Filesystem Source"));
+        assert_eq!(checkpoint.source_url(), Some(dir.path().to_string_lossy().as_ref()));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_resumes_after_restart() {
+        let batches = tempfile::tempdir().unwrap();
+        for number in 1..=3u64 {
+            let batch = create_batch(number, number * 10, 2);
+            std::fs::write(
+                batches.path().join(format!("batch_{:06}.json", number)),
+                serde_json::to_vec(&batch).unwrap(),
+            )
+            .unwrap();
+        }
+        let data_dir = tempfile::tempdir().unwrap();
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 2,
+            to_batch: None,
+            chain_id: None,
+            data_dir: data_dir.path().to_path_buf(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+
+        // Each run opens the stores afresh, as a restarted process would
+        let mut processed = Vec::new();
+        for _ in 0..2 {
+            let (checkpoint_storage, mapping_storage) = cmd.open_storage().unwrap();
+            let mut source = FilesystemSource::new(FilesystemSourceConfig {
+                path: batches.path().to_path_buf(),
+                ..Default::default()
+            });
+            processed.push(
+                cmd.ingest(
+                    &mut source,
+                    &mapping_storage,
+                    &checkpoint_storage,
+                    &EngineFacade::default(),
+                    &CdkMetrics::new(),
+                    std::future::pending(),
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        assert_eq!(processed, vec![2, 1]);
+
+        let (checkpoint_storage, mapping_storage) = cmd.open_storage().unwrap();
+        let checkpoint = checkpoint_storage.load_checkpoint().await.unwrap().unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(3));
+        for batch_id in 1..=3 {
+            assert!(mapping_storage.load_batch_mapping(batch_id).await.unwrap().is_some());
+        }
+    }
+
+    /// Source serving a fixed list of batches, then signalling shutdown and waiting forever
+    #[derive(Debug)]
+    struct StallingSource {
+        batches: VecDeque<Batch>,
+        shutdown: Option<oneshot::Sender<()>>,
+    }
+
+    #[async_trait]
+    impl BatchSource for StallingSource {
+        async fn next(&mut self) -> Result<Option<Batch>, DatastreamError> {
+            if let Some(batch) = self.batches.pop_front() {
+                return Ok(Some(batch));
+            }
+            if let Some(shutdown) = self.shutdown.take() {
+                let _ = shutdown.send(());
+            }
+            std::future::pending().await
+        }
+
+        async fn checkpoint(&self) -> Result<Checkpoint, DatastreamError> {
+            Ok(Checkpoint::default())
+        }
+
+        async fn set_checkpoint(&mut self, _checkpoint: Checkpoint) -> Result<(), DatastreamError> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<(), DatastreamError> {
+            Ok(())
+        }
+
+        async fn metadata(&self) -> Result<SourceMetadata, DatastreamError> {
+            Ok(SourceMetadata::new("Stalling".to_string(), "1.0".to_string(), "memory".to_string(), true))
+        }
+
+        async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> Result<BatchStream, DatastreamError> {
+            Err(DatastreamError::InternalError("Not supported".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ingest_saves_checkpoint_on_shutdown() {
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let mut source = StallingSource {
+            batches: (1..=2u64).map(|number| create_batch(number, number * 10, 2)).collect(),
+            shutdown: Some(shutdown_tx),
+        };
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: "cdk-data".into(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint_path = dir.path().join("checkpoint.json");
+        let checkpoint_storage = FileCheckpointStorage::new(&checkpoint_path);
+        let shutdown = async {
+            let _ = shutdown_rx.await;
+        };
+
+        let processed = cmd
+            .ingest(
+                &mut source,
+                &MemoryMappingStorage::default(),
+                &checkpoint_storage,
+                &EngineFacade::default(),
+                &CdkMetrics::new(),
+                shutdown,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(processed, 2);
+        // Only the shutdown path marks the checkpoint, and the mark is on disk
+        let checkpoint = FileCheckpointStorage::new(&checkpoint_path).load_checkpoint().await.unwrap().unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(2));
+        assert_eq!(checkpoint.get_metadata(CLEAN_SHUTDOWN_KEY).map(String::as_str), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_rejects_batch_number_beyond_u64() {
+        let mut batch = create_batch(1, 10, 2);
+        batch.id.number = U256::from(u64::MAX) + U256::from(1);
+        let mut source = StallingSource { batches: VecDeque::from([batch]), shutdown: None };
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: "cdk-data".into(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let engine = EngineFacade::default();
+
+        let error = cmd
+            .ingest(
+                &mut source,
+                &MemoryMappingStorage::default(),
+                &MemoryCheckpointStorage::default(),
+                &engine,
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("does not fit in u64"));
+        assert_eq!(engine.get_head_block().await.unwrap(), U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_records_batch_metrics() {
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let mut source = StallingSource {
+            batches: (1..=3u64).map(|number| create_batch(number, number * 10, 2)).collect(),
+            shutdown: Some(shutdown_tx),
+        };
+        let cmd = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: "cdk-data".into(),
+            enable_metrics: true,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let metrics = metrics::with_local_recorder(&recorder, CdkMetrics::new);
+        let shutdown = async {
+            let _ = shutdown_rx.await;
+        };
+
+        let processed = cmd
+            .ingest(
+                &mut source,
+                &MemoryMappingStorage::default(),
+                &MemoryCheckpointStorage::default(),
+                &EngineFacade::default(),
+                &metrics,
+                shutdown,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(processed, 3);
+        let rendered = handle.render();
+        assert!(rendered.contains("cdk_batch_processing_seconds_count 3"));
+        assert!(rendered.contains("cdk_batches_processed_total 3"));
+    }
+
+    #[tokio::test]
+    async fn test_start_metrics_exports_cdk_metrics() {
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        assert!(start_metrics(false, address).unwrap().is_none());
+
+        // Installs the global recorder, so metrics created afterwards are exported
+        let server = start_metrics(true, address).unwrap().unwrap();
+        let metrics = CdkMetrics::new();
+        metrics.update_epoch_height(U256::from(7));
+
+        assert!(server.gather().contains("cdk_epoch_height 7"));
+        server.shutdown();
+    }
+
+    /// Oracle returning a fixed sequence of polls
+    #[derive(Debug)]
+    struct MockOracle {
+        polls: VecDeque<Vec<FinalityTag>>,
+    }
+
+    #[async_trait]
+    impl FinalityOracle for MockOracle {
+        async fn poll(&mut self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(self.polls.pop_front().unwrap_or_default())
+        }
+
+        async fn get_finality_status(&self, _batch_id: u64) -> FinalityResult<Option<FinalityStatus>> {
+            Ok(None)
+        }
+
+        async fn get_finalized_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(vec![])
+        }
+
+        async fn get_rolled_back_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(vec![])
+        }
+
+        async fn health_check(&self) -> FinalityResult<()> {
+            Ok(())
+        }
+
+        async fn metadata(&self) -> FinalityResult<OracleMetadata> {
+            Ok(OracleMetadata::new("mock".to_string(), "1.0.0".to_string(), 1, Address::ZERO))
+        }
+
+        fn set_polling_interval(&mut self, _interval: Duration) {}
+
+        fn get_polling_interval(&self) -> Duration {
+            Duration::from_millis(10)
+        }
+    }
+
+    /// Finality manager recording the blocks marked final, signalling after the first one
+    struct RecordingFinalityManager {
+        marked: Arc<Mutex<Vec<U256>>>,
+        marked_tx: Mutex<Option<oneshot::Sender<()>>>,
+    }
+
+    #[async_trait]
+    impl FinalityManager for RecordingFinalityManager {
+        async fn mark_final(&self, block_number: U256) -> Result<EngineFinalityResult, EngineFacadeError> {
+            self.marked.lock().unwrap().push(block_number);
+            if let Some(marked_tx) = self.marked_tx.lock().unwrap().take() {
+                let _ = marked_tx.send(());
+            }
+            Ok(EngineFinalityResult {
+                final_block: block_number,
+                blocks_affected: 1,
+            })
+        }
+
+        async fn process_finality_tag(&self, _tag: &FinalityTag) -> Result<EngineFinalityResult, EngineFacadeError> {
+            Err(EngineFacadeError::FinalityMarkingFailed("Not supported".to_string()))
+        }
+
+        async fn get_final_block(&self) -> Result<U256, EngineFacadeError> {
+            Ok(self.marked.lock().unwrap().last().copied().unwrap_or_default())
+        }
+
+        async fn is_final(&self, block_number: U256) -> Result<bool, EngineFacadeError> {
+            Ok(self.marked.lock().unwrap().iter().any(|marked| *marked >= block_number))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finality_marks_finalized_batch_final() {
+        let (marked_tx, marked_rx) = oneshot::channel();
+        let marked = Arc::new(Mutex::new(Vec::new()));
+        let engine = Arc::new(EngineFacade::new(
+            Box::new(DefaultBlockImporter::new()),
+            Box::new(RecordingFinalityManager {
+                marked: marked.clone(),
+                marked_tx: Mutex::new(Some(marked_tx)),
+            }),
+        ));
+
+        // The ingest command records the batch and keeps its stores open
+        let batches = tempfile::tempdir().unwrap();
+        std::fs::write(batches.path().join("batch_000001.json"), serde_json::to_vec(&create_batch(1, 10, 3)).unwrap())
+            .unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let ingest = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: data_dir.path().to_path_buf(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        let (checkpoint_storage, mapping_storage) = ingest.open_storage().unwrap();
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: batches.path().to_path_buf(),
+            ..Default::default()
+        });
+        ingest
+            .ingest(
+                &mut source,
+                &mapping_storage,
+                &checkpoint_storage,
+                &engine,
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap();
+
+        let oracle = MockOracle {
+            polls: VecDeque::from([vec![FinalityTag::new(
+                U256::from(1),
+                U256::from(1000),
+                FixedBytes::from([1u8; 32]),
+                FinalityStatus::Finalized,
+                1234567890,
+                None,
+            )]]),
+        };
+        let shutdown = async {
+            let _ = marked_rx.await;
+        };
+
+        let handled = tokio::time::timeout(
+            Duration::from_secs(5),
+            follow_finality(Box::new(oracle), engine, Arc::new(mapping_storage), &CdkMetrics::new(), shutdown),
+        )
+        .await
+        .expect("finality following timed out")
+        .unwrap();
+
+        assert_eq!(handled, 1);
+        assert_eq!(*marked.lock().unwrap(), vec![U256::from(12)]);
+    }
+
+    #[tokio::test]
+    async fn test_finality_command_follows_running_ingest() {
+        let batches = tempfile::tempdir().unwrap();
+        std::fs::write(batches.path().join("batch_000001.json"), serde_json::to_vec(&create_batch(1, 10, 3)).unwrap())
+            .unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let ingest = IngestCommand {
+            datastream: "http://localhost:8080/batches".to_string(),
+            from_checkpoint: "auto".to_string(),
+            max_batches: 0,
+            to_batch: None,
+            chain_id: None,
+            data_dir: data_dir.path().to_path_buf(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9000".parse().unwrap(),
+            finality: Default::default(),
+        };
+        // The ingest command holds the mapping store open for writing throughout
+        let (checkpoint_storage, mapping_storage) = ingest.open_storage().unwrap();
+
+        let cmd = FinalityCommand {
+            l1_rpc: "http://localhost:8545".to_string(),
+            l1_fallback_rpc: vec![],
+            bridge: "0x1234567890123456789012345678901234567890".to_string(),
+            poll_interval: 30,
+            data_dir: data_dir.path().to_path_buf(),
+            enable_metrics: false,
+            metrics_addr: "127.0.0.1:9001".parse().unwrap(),
+        };
+        let storage = cmd.open_mapping_storage().unwrap();
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: batches.path().to_path_buf(),
+            ..Default::default()
+        });
+        ingest
+            .ingest(
+                &mut source,
+                &mapping_storage,
+                &checkpoint_storage,
+                &EngineFacade::default(),
+                &CdkMetrics::new(),
+                std::future::pending(),
+            )
+            .await
+            .unwrap();
+
+        let mapping = storage.load_batch_mapping(1).await.unwrap().unwrap();
+        assert_eq!(mapping.end_block, 12);
+
+        // Finalized batches are reported without an engine
+        let oracle = MockOracle {
+            polls: VecDeque::from([vec![FinalityTag::new(
+                U256::from(1),
+                U256::from(1000),
+                FixedBytes::from([1u8; 32]),
+                FinalityStatus::Finalized,
+                1234567890,
+                None,
+            )]]),
+        };
+        let handled = cmd
+            .monitor(
+                Box::new(oracle),
+                RollbackManager::new(RollbackConfig::default()),
+                None,
+                &storage,
+                &CdkMetrics::new(),
+                tokio::time::sleep(Duration::from_millis(200)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(handled, 1);
+    }
 }
@@ -0,0 +1,309 @@
+//! Batch source decorator prefetching batches into a bounded buffer
+
+use crate::{BatchSource, BatchStream, Checkpoint, DataStreamError, DataStreamResult, SourceMetadata};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use futures::StreamExt;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::debug;
+
+/// Default number of batches buffered ahead of the consumer
+pub const DEFAULT_BUFFER_CAPACITY: usize = 16;
+
+/// Wraps a batch source and prefetches its batches on a background task
+///
+/// On the first call to `next` the inner source's stream is opened after the
+/// current checkpoint and drained by a spawned task into a bounded channel.
+/// When the channel is full the task waits, so a slow consumer pauses
+/// fetching instead of dropping batches.
+///
+/// The checkpoint tracks the last batch handed to the caller, not what the
+/// task has fetched ahead. When the stream ends or fails, or a checkpoint is
+/// set, the buffer is discarded and the stream reopens after the checkpoint
+/// on the next call.
+#[derive(Debug)]
+pub struct BufferedSource<S> {
+    inner: S,
+    capacity: usize,
+    checkpoint: Option<Checkpoint>,
+    source: Option<SourceMetadata>,
+    receiver: Option<mpsc::Receiver<DataStreamResult<Batch>>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl<S: BatchSource> BufferedSource<S> {
+    /// Wrap `inner`, buffering up to `capacity` batches ahead of the consumer
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity: capacity.max(1),
+            checkpoint: None,
+            source: None,
+            receiver: None,
+            task: None,
+        }
+    }
+
+    /// Get the inner source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the buffer capacity
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of batches currently waiting in the buffer
+    pub fn buffered(&self) -> usize {
+        self.receiver.as_ref().map_or(0, |receiver| receiver.len())
+    }
+
+    /// Open the inner stream after the checkpoint and spawn the task filling the buffer
+    async fn start(&mut self) -> DataStreamResult<()> {
+        let start_batch_number = self.checkpoint().await.ok().and_then(|checkpoint| checkpoint.next_batch_number());
+        if self.source.is_none() {
+            self.source = self.inner.metadata().await.ok();
+        }
+        let stream = self.inner.fetch_batch_stream(start_batch_number).await?;
+
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        debug!(target: "cdk::datastream::buffered", capacity = self.capacity, ?start_batch_number, "Starting buffered fetch");
+        self.task = Some(tokio::spawn(fill_buffer(stream, sender)));
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+
+    /// Stop the fetch task and discard buffered batches
+    fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.receiver = None;
+    }
+}
+
+/// Forward batches from the stream until it ends or the receiver is dropped
+async fn fill_buffer(mut stream: BatchStream, sender: mpsc::Sender<DataStreamResult<Batch>>) {
+    while let Some(result) = stream.next().await {
+        if sender.send(result).await.is_err() {
+            break;
+        }
+    }
+}
+
+impl<S> Drop for BufferedSource<S> {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl<S: BatchSource> BatchSource for BufferedSource<S> {
+    async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+        if self.receiver.is_none() {
+            self.start().await?;
+        }
+
+        let receiver = self
+            .receiver
+            .as_mut()
+            .ok_or_else(|| DataStreamError::InternalError("Buffer not started".to_string()))?;
+        match receiver.recv().await {
+            Some(Ok(batch)) => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let checkpoint = Checkpoint::from_batch(&batch, now);
+                self.checkpoint = Some(match &self.source {
+                    Some(source) => checkpoint.with_source(source),
+                    None => checkpoint,
+                });
+                Ok(Some(batch))
+            }
+            Some(Err(e)) => {
+                // Reopen after the last returned batch on the next call
+                self.stop();
+                Err(e)
+            }
+            None => {
+                debug!(target: "cdk::datastream::buffered", "Batch stream ended");
+                self.stop();
+                Ok(None)
+            }
+        }
+    }
+
+    async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+        match &self.checkpoint {
+            Some(checkpoint) => Ok(checkpoint.clone()),
+            None => self.inner.checkpoint().await,
+        }
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+        self.stop();
+        self.inner.set_checkpoint(checkpoint.clone()).await?;
+        self.checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> DataStreamResult<()> {
+        self.inner.health_check().await
+    }
+
+    async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+        self.inner.metadata().await
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        self.inner.fetch_batch_stream(start_batch_number).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use std::{
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Source streaming batches up to `total`, counting how many were pulled
+    ///
+    /// When `fail_at` is set, the first stream opened fails in place of that batch.
+    #[derive(Debug, Default)]
+    struct CountingSource {
+        total: Arc<AtomicU64>,
+        produced: Arc<AtomicUsize>,
+        opened: Arc<AtomicUsize>,
+        fail_at: Option<u64>,
+    }
+
+    impl CountingSource {
+        fn new(total: u64) -> Self {
+            Self {
+                total: Arc::new(AtomicU64::new(total)),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BatchSource for CountingSource {
+        async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+            Err(DataStreamError::InternalError("CountingSource only serves the batch stream".to_string()))
+        }
+
+        async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+            Ok(Checkpoint::default())
+        }
+
+        async fn set_checkpoint(&mut self, _checkpoint: Checkpoint) -> DataStreamResult<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> DataStreamResult<()> {
+            Ok(())
+        }
+
+        async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+            Ok(SourceMetadata::new("counting".to_string(), "1.0".to_string(), "test".to_string(), false))
+        }
+
+        async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+            let produced = self.produced.clone();
+            let first_open = self.opened.fetch_add(1, Ordering::SeqCst) == 0;
+            let fail_at = self.fail_at.filter(|_| first_open);
+            let total = self.total.load(Ordering::SeqCst);
+            let stream = futures::stream::iter(start_batch_number.unwrap_or(1)..=total).map(move |number| {
+                if fail_at == Some(number) {
+                    return Err(DataStreamError::ConnectionError("connection lost".to_string()));
+                }
+                produced.fetch_add(1, Ordering::SeqCst);
+                Ok(Batch::new(
+                    BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+                    U256::from(100),
+                    FixedBytes::from([2u8; 32]),
+                    vec![],
+                    ProofMetadata::default(),
+                    1234567890,
+                ))
+            });
+            Ok(Box::new(stream.boxed()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_preserves_order_and_bounds_buffer() {
+        let inner = CountingSource::new(20);
+        let produced = inner.produced.clone();
+        let mut source = BufferedSource::new(inner, 3);
+
+        let mut numbers = Vec::new();
+        while let Some(batch) = source.next().await.unwrap() {
+            numbers.push(batch.id.number.to::<u64>());
+
+            // Give the fetch task time to fill the buffer before the next read
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            assert!(source.buffered() <= source.capacity());
+            // At most the buffer plus the one batch the task is waiting to send
+            assert!(produced.load(Ordering::SeqCst) <= numbers.len() + source.capacity() + 1);
+        }
+
+        assert_eq!(numbers, (1..=20).collect::<Vec<_>>());
+        assert_eq!(produced.load(Ordering::SeqCst), 20);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_tracks_returned_batches() {
+        let inner = CountingSource::new(20);
+        let produced = inner.produced.clone();
+        let mut source = BufferedSource::new(inner, 3);
+
+        source.next().await.unwrap();
+        source.next().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        // The task has fetched ahead, but the checkpoint is the last batch returned
+        assert!(produced.load(Ordering::SeqCst) > 2);
+        let checkpoint = source.checkpoint().await.unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(2));
+        assert_eq!(checkpoint.source(), Some("counting"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_reopens_after_error_and_end() {
+        let inner = CountingSource {
+            fail_at: Some(3),
+            ..CountingSource::new(4)
+        };
+        let total = inner.total.clone();
+        let opened = inner.opened.clone();
+        let mut source = BufferedSource::new(inner, 3);
+
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(1));
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(2));
+        assert!(matches!(source.next().await, Err(DataStreamError::ConnectionError(_))));
+
+        // Reopened after the last returned batch
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(3));
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(4));
+        assert!(source.next().await.unwrap().is_none());
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+
+        // Batches published after the stream ended are picked up by the next call
+        total.store(6, Ordering::SeqCst);
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(5));
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(6));
+        assert_eq!(opened.load(Ordering::SeqCst), 3);
+    }
+}
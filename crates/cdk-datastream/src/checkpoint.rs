@@ -2,8 +2,20 @@
 
 use alloy_primitives::{FixedBytes, U256};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use crate::DatastreamError;
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::io::AsyncWriteExt;
+use crate::{DatastreamError, SourceMetadata};
+
+/// Checkpoint metadata key holding the name of the source that produced the batch
+pub const CHECKPOINT_SOURCE_KEY: &str = "source";
+/// Checkpoint metadata key holding the URL of the source that produced the batch
+pub const CHECKPOINT_SOURCE_URL_KEY: &str = "source_url";
+/// Checkpoint metadata key holding the chain id served by the source that produced the batch
+pub const CHECKPOINT_CHAIN_ID_KEY: &str = "chain_id";
 
 /// A checkpoint represents the state of batch ingestion
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -48,9 +60,81 @@ impl Checkpoint {
         self.metadata.get(key)
     }
 
+    /// Stamp the checkpoint with the name, URL and chain id of the source that produced it
+    ///
+    /// The chain id is only stamped when the source reports one.
+    pub fn with_source(self, source: &SourceMetadata) -> Self {
+        let checkpoint = self
+            .with_metadata(CHECKPOINT_SOURCE_KEY.to_string(), source.name.clone())
+            .with_metadata(CHECKPOINT_SOURCE_URL_KEY.to_string(), source.url.clone());
+        if source.chain_id == 0 {
+            return checkpoint;
+        }
+        checkpoint.with_metadata(CHECKPOINT_CHAIN_ID_KEY.to_string(), source.chain_id.to_string())
+    }
+
+    /// Name of the source that produced the checkpoint, if stamped
+    pub fn source(&self) -> Option<&str> {
+        self.get_metadata(CHECKPOINT_SOURCE_KEY).map(String::as_str)
+    }
+
+    /// URL of the source that produced the checkpoint, if stamped
+    pub fn source_url(&self) -> Option<&str> {
+        self.get_metadata(CHECKPOINT_SOURCE_URL_KEY).map(String::as_str)
+    }
+
+    /// Chain id of the source that produced the checkpoint, if stamped
+    ///
+    /// A missing or unparsable stamp reads as 0 (unknown).
+    pub fn chain_id(&self) -> u64 {
+        self.get_metadata(CHECKPOINT_CHAIN_ID_KEY)
+            .and_then(|chain_id| chain_id.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Check whether the checkpoint can resume the given source
+    ///
+    /// A checkpoint stamped with a source name is only compatible with a source
+    /// of that name, and one stamped with a chain id only with a source serving
+    /// that chain when the source reports its chain id. An unstamped checkpoint
+    /// (e.g. given by an operator) is compatible with any source.
+    pub fn is_compatible_with(&self, source: &SourceMetadata) -> bool {
+        let chain_id = self.chain_id();
+        self.source().is_none_or(|name| name == source.name)
+            && (chain_id == 0 || source.chain_id == 0 || chain_id == source.chain_id)
+    }
+
+    /// Fail with `DatastreamError::CheckpointError` unless the checkpoint can resume `source`
+    pub fn ensure_compatible_with(&self, source: &SourceMetadata) -> Result<(), DatastreamError> {
+        if self.is_compatible_with(source) {
+            return Ok(());
+        }
+        Err(DatastreamError::CheckpointError(format!(
+            "Checkpoint from source {} (chain {}) cannot resume source {} (chain {})",
+            self.source().unwrap_or_default(),
+            self.chain_id(),
+            source.name,
+            source.chain_id
+        )))
+    }
+
+    /// The batch position of the checkpoint, without any source-specific metadata
+    pub fn position(&self) -> Self {
+        Self::new(self.last_batch_id, self.last_batch_hash, self.last_l1_block, self.timestamp)
+    }
+
     /// Check if this checkpoint is valid
+    ///
+    /// A valid checkpoint has a timestamp and names its batch by hash, or by
+    /// number alone when the hash is unknown (e.g. a checkpoint given by an operator).
     pub fn is_valid(&self) -> bool {
-        !self.last_batch_hash.is_zero() && self.timestamp > 0
+        (!self.last_batch_hash.is_zero() || self.last_batch_id > U256::ZERO) && self.timestamp > 0
+    }
+
+    /// Number of the batch to resume from, if the checkpoint is valid
+    pub fn next_batch_number(&self) -> Option<u64> {
+        self.is_valid()
+            .then(|| self.last_batch_id.saturating_to::<u64>().saturating_add(1))
     }
 
     /// Create a checkpoint from a batch
@@ -123,3 +207,102 @@ impl Clone for MemoryCheckpointStorage {
         }
     }
 }
+
+/// Checkpoint storage keeping the checkpoint as JSON in a file
+///
+/// A save writes a temporary file next to the checkpoint and renames it over
+/// the old one, so an interrupted save leaves the previous checkpoint intact.
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStorage {
+    path: PathBuf,
+}
+
+impl FileCheckpointStorage {
+    /// Create a storage keeping the checkpoint at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the checkpoint file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path the checkpoint is written to before being renamed into place
+    fn temporary_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".tmp");
+        path.into()
+    }
+
+    fn io_error(&self, action: &str, err: std::io::Error) -> DatastreamError {
+        DatastreamError::IoError(format!("Failed to {} checkpoint {}: {}", action, self.path.display(), err))
+    }
+}
+
+#[async_trait::async_trait]
+impl CheckpointStorage for FileCheckpointStorage {
+    async fn save_checkpoint(&self, checkpoint: Checkpoint) -> Result<(), DatastreamError> {
+        let bytes = serde_json::to_vec_pretty(&checkpoint)
+            .map_err(|e| DatastreamError::SerializationError(e.to_string()))?;
+
+        let temporary_path = self.temporary_path();
+        let mut file = tokio::fs::File::create(&temporary_path).await.map_err(|e| self.io_error("write", e))?;
+        file.write_all(&bytes).await.map_err(|e| self.io_error("write", e))?;
+        file.sync_all().await.map_err(|e| self.io_error("write", e))?;
+        tokio::fs::rename(&temporary_path, &self.path).await.map_err(|e| self.io_error("write", e))
+    }
+
+    async fn load_checkpoint(&self) -> Result<Option<Checkpoint>, DatastreamError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| DatastreamError::DeserializationError(e.to_string())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(self.io_error("read", e)),
+        }
+    }
+
+    async fn delete_checkpoint(&self) -> Result<(), DatastreamError> {
+        match tokio::fs::remove_file(&self.path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(self.io_error("delete", e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_checkpoint_storage_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        let checkpoint = Checkpoint::new(U256::from(7), FixedBytes::from([7u8; 32]), U256::from(100), 1234567890)
+            .with_metadata(CHECKPOINT_SOURCE_KEY.to_string(), "test".to_string());
+
+        let storage = FileCheckpointStorage::new(&path);
+        assert_eq!(storage.load_checkpoint().await.unwrap(), None);
+        storage.save_checkpoint(checkpoint.clone()).await.unwrap();
+
+        let reopened = FileCheckpointStorage::new(&path);
+        assert_eq!(reopened.load_checkpoint().await.unwrap(), Some(checkpoint));
+        assert!(!storage.temporary_path().exists());
+
+        reopened.delete_checkpoint().await.unwrap();
+        assert_eq!(storage.load_checkpoint().await.unwrap(), None);
+        // Deleting a missing checkpoint is not an error
+        reopened.delete_checkpoint().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_checkpoint_storage_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        std::fs::write(&path, b"not a checkpoint").unwrap();
+
+        let error = FileCheckpointStorage::new(&path).load_checkpoint().await.unwrap_err();
+        assert!(matches!(error, DatastreamError::DeserializationError(_)));
+    }
+}
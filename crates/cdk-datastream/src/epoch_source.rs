@@ -0,0 +1,103 @@
+//! Batch source decorator tagging batches with their epoch
+
+use crate::{BatchSource, BatchStream, Checkpoint, DataStreamResult, SourceMetadata};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use std::fmt::Debug;
+use tracing::debug;
+
+/// Assigns incoming batches to epochs
+///
+/// Batches are passed in stream order; implementations track epoch
+/// boundaries themselves.
+pub trait EpochAssigner: Send + Sync + Debug {
+    /// Record a batch and return the id of the epoch it belongs to
+    fn assign_epoch(&mut self, batch: &Batch) -> DataStreamResult<u64>;
+}
+
+/// A batch together with the epoch it was assigned to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaggedBatch {
+    /// The batch
+    pub batch: Batch,
+    /// Epoch the batch belongs to
+    pub epoch_id: u64,
+}
+
+/// Wraps a batch source and runs every batch through an epoch assigner
+///
+/// `next_tagged` returns the batch with its epoch id; the plain
+/// `BatchSource::next` returns the batch alone and exposes the epoch through
+/// `current_epoch_id`. Checkpoints, health checks, metadata and
+/// `fetch_batch_stream` are forwarded to the inner source untouched.
+#[derive(Debug)]
+pub struct EpochTaggingSource<S, A> {
+    inner: S,
+    assigner: A,
+    current_epoch_id: Option<u64>,
+}
+
+impl<S: BatchSource, A: EpochAssigner> EpochTaggingSource<S, A> {
+    /// Wrap `inner`, assigning its batches to epochs with `assigner`
+    pub fn new(inner: S, assigner: A) -> Self {
+        Self {
+            inner,
+            assigner,
+            current_epoch_id: None,
+        }
+    }
+
+    /// Get the next batch together with its epoch id
+    pub async fn next_tagged(&mut self) -> DataStreamResult<Option<TaggedBatch>> {
+        let Some(batch) = self.inner.next().await? else {
+            return Ok(None);
+        };
+
+        let epoch_id = self.assigner.assign_epoch(&batch)?;
+        debug!(target: "cdk::datastream::epoch", batch = %batch.id.number, epoch_id, "Tagged batch with epoch");
+        self.current_epoch_id = Some(epoch_id);
+        Ok(Some(TaggedBatch { batch, epoch_id }))
+    }
+
+    /// Epoch of the most recently returned batch
+    pub fn current_epoch_id(&self) -> Option<u64> {
+        self.current_epoch_id
+    }
+
+    /// Get the inner source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the epoch assigner
+    pub fn assigner_mut(&mut self) -> &mut A {
+        &mut self.assigner
+    }
+}
+
+#[async_trait]
+impl<S: BatchSource, A: EpochAssigner> BatchSource for EpochTaggingSource<S, A> {
+    async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+        Ok(self.next_tagged().await?.map(|tagged| tagged.batch))
+    }
+
+    async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+        self.inner.checkpoint().await
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+        self.inner.set_checkpoint(checkpoint).await
+    }
+
+    async fn health_check(&self) -> DataStreamResult<()> {
+        self.inner.health_check().await
+    }
+
+    async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+        self.inner.metadata().await
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        self.inner.fetch_batch_stream(start_batch_number).await
+    }
+}
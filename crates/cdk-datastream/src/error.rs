@@ -1,5 +1,6 @@
 //! Error types for datastream operations
 
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur in datastream operations
@@ -43,6 +44,15 @@ pub enum DatastreamError {
 
     #[error("IO error: {0}")]
     IoError(String),
+
+    #[error("Batch version mismatch: expected {expected}, got {got}")]
+    VersionMismatch { expected: u32, got: u32 },
+
+    #[error("{operation} timed out after {timeout:?}")]
+    Timeout { operation: String, timeout: Duration },
+
+    #[error("Chain id mismatch: expected {expected}, source serves {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
 }
 
 /// Result type for datastream operations
@@ -0,0 +1,290 @@
+//! Batch source failing over across an ordered list of sources
+
+use crate::{BatchSource, BatchStream, Checkpoint, DataStreamError, DataStreamResult, SourceMetadata};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use tracing::{info, warn};
+
+/// Reads batches from the first working source of an ordered list
+///
+/// Batches come from the active source until one of its `next` calls fails.
+/// The next source in order then becomes active and is handed the position
+/// of the last batch returned, so it resumes right after it; a source that
+/// cannot take that position counts as failed and is skipped. After the last
+/// source the list wraps around; a call fails only once every source has
+/// failed in turn.
+#[derive(Debug)]
+pub struct FailoverSource {
+    sources: Vec<Box<dyn BatchSource>>,
+    active: usize,
+    checkpoint: Option<Checkpoint>,
+}
+
+impl FailoverSource {
+    /// Create a failover source trying `sources` in order
+    pub fn new(sources: Vec<Box<dyn BatchSource>>) -> DataStreamResult<Self> {
+        if sources.is_empty() {
+            return Err(DataStreamError::ConfigError("Failover source needs at least one source".to_string()));
+        }
+        Ok(Self {
+            sources,
+            active: 0,
+            checkpoint: None,
+        })
+    }
+
+    /// Index of the source batches are currently read from
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// Number of configured sources
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Checkpoint for a batch just returned by the active source
+    ///
+    /// The active source's own checkpoint is used when it covers the batch, so
+    /// the source provenance it stamped is kept; otherwise one is built from the batch.
+    async fn checkpoint_for(&self, batch: &Batch) -> Checkpoint {
+        match self.sources[self.active].checkpoint().await {
+            Ok(checkpoint) if checkpoint.last_batch_id == batch.id.number && checkpoint.source().is_some() => checkpoint,
+            _ => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                Checkpoint::from_batch(batch, now)
+            }
+        }
+    }
+
+    /// Make the next source active, resuming it after the last returned batch
+    async fn fail_over(&mut self) -> DataStreamResult<()> {
+        self.active = (self.active + 1) % self.sources.len();
+        info!(target: "cdk::datastream::failover", active = self.active, "Failing over to next batch source");
+        if let Some(checkpoint) = &self.checkpoint {
+            // Provenance and offsets stamped by the previous source mean nothing to this one
+            self.sources[self.active].set_checkpoint(checkpoint.position()).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BatchSource for FailoverSource {
+    async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+        let mut failures = 0;
+        loop {
+            let error = match self.sources[self.active].next().await {
+                Ok(Some(batch)) => {
+                    self.checkpoint = Some(self.checkpoint_for(&batch).await);
+                    return Ok(Some(batch));
+                }
+                Ok(None) => return Ok(None),
+                Err(e) => e,
+            };
+
+            warn!(target: "cdk::datastream::failover", active = self.active, "Batch source failed: {}", error);
+            failures += 1;
+            if failures >= self.sources.len() {
+                return Err(error);
+            }
+            while let Err(e) = self.fail_over().await {
+                warn!(target: "cdk::datastream::failover", active = self.active, "Failed to resume batch source: {}", e);
+                failures += 1;
+                if failures >= self.sources.len() {
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+        match &self.checkpoint {
+            Some(checkpoint) => Ok(checkpoint.clone()),
+            None => self.sources[self.active].checkpoint().await,
+        }
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+        self.sources[self.active].set_checkpoint(checkpoint.clone()).await?;
+        self.checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> DataStreamResult<()> {
+        let mut last_error = None;
+        for source in &self.sources {
+            match source.health_check().await {
+                Ok(()) => return Ok(()),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| DataStreamError::SourceUnavailable("No batch sources".to_string())))
+    }
+
+    async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+        self.sources[self.active].metadata().await
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        self.sources[self.active].fetch_batch_stream(start_batch_number).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use std::sync::{Arc, Mutex};
+
+    fn test_batch(number: u64) -> Batch {
+        Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
+
+    /// Source serving batches up to `last`, failing after `fail_after` batches
+    #[derive(Debug)]
+    struct ScriptedSource {
+        next_number: u64,
+        last: u64,
+        fail_after: Option<u64>,
+        served: u64,
+        reject_checkpoints: bool,
+        checkpoints: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl ScriptedSource {
+        fn new(last: u64, fail_after: Option<u64>) -> Self {
+            Self {
+                next_number: 1,
+                last,
+                fail_after,
+                served: 0,
+                reject_checkpoints: false,
+                checkpoints: Arc::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BatchSource for ScriptedSource {
+        async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+            if self.fail_after.is_some_and(|fail_after| self.served >= fail_after) {
+                return Err(DataStreamError::ConnectionError("connection lost".to_string()));
+            }
+            if self.next_number > self.last {
+                return Ok(None);
+            }
+            self.served += 1;
+            self.next_number += 1;
+            Ok(Some(test_batch(self.next_number - 1)))
+        }
+
+        async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+            let checkpoint = Checkpoint::from_batch(&test_batch(self.next_number - 1), 1234567890);
+            Ok(checkpoint.with_source(&self.metadata().await?))
+        }
+
+        async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+            if self.reject_checkpoints {
+                return Err(DataStreamError::CheckpointError("cannot seek".to_string()));
+            }
+            let last = checkpoint.last_batch_id.to::<u64>();
+            self.checkpoints.lock().unwrap().push(last);
+            self.next_number = last + 1;
+            Ok(())
+        }
+
+        async fn health_check(&self) -> DataStreamResult<()> {
+            match self.fail_after {
+                Some(_) => Err(DataStreamError::SourceUnavailable("down".to_string())),
+                None => Ok(()),
+            }
+        }
+
+        async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+            Ok(SourceMetadata::new("scripted".to_string(), "1.0".to_string(), "test".to_string(), true))
+        }
+
+        async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+            Err(DataStreamError::InternalError("Not supported".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_resumes_on_secondary() {
+        let primary = ScriptedSource::new(5, Some(2));
+        let secondary = ScriptedSource::new(5, None);
+        let secondary_checkpoints = secondary.checkpoints.clone();
+        let mut source = FailoverSource::new(vec![Box::new(primary), Box::new(secondary)]).unwrap();
+
+        let mut numbers = Vec::new();
+        while let Some(batch) = source.next().await.unwrap() {
+            numbers.push(batch.id.number.to::<u64>());
+        }
+
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5]);
+        assert_eq!(source.active_index(), 1);
+        assert_eq!(*secondary_checkpoints.lock().unwrap(), vec![2]);
+        let checkpoint = source.checkpoint().await.unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(5));
+        assert_eq!(checkpoint.source(), Some("scripted"));
+        assert!(source.health_check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failover_fails_when_every_source_fails() {
+        let sources: Vec<Box<dyn BatchSource>> =
+            vec![Box::new(ScriptedSource::new(5, Some(0))), Box::new(ScriptedSource::new(5, Some(0)))];
+        let mut source = FailoverSource::new(sources).unwrap();
+
+        assert!(matches!(source.next().await, Err(DataStreamError::ConnectionError(_))));
+        assert!(source.health_check().await.is_err());
+        assert!(FailoverSource::new(vec![]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failover_skips_source_that_cannot_resume() {
+        let primary = ScriptedSource::new(5, Some(2));
+        let secondary = ScriptedSource {
+            reject_checkpoints: true,
+            ..ScriptedSource::new(5, None)
+        };
+        let tertiary = ScriptedSource::new(5, None);
+        let tertiary_checkpoints = tertiary.checkpoints.clone();
+        let mut source = FailoverSource::new(vec![Box::new(primary), Box::new(secondary), Box::new(tertiary)]).unwrap();
+
+        let mut numbers = Vec::new();
+        while let Some(batch) = source.next().await.unwrap() {
+            numbers.push(batch.id.number.to::<u64>());
+        }
+
+        // The secondary is never read from its own position
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5]);
+        assert_eq!(source.active_index(), 2);
+        assert_eq!(*tertiary_checkpoints.lock().unwrap(), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_set_checkpoint_kept_only_when_accepted() {
+        let rejecting = ScriptedSource {
+            reject_checkpoints: true,
+            ..ScriptedSource::new(5, None)
+        };
+        let mut source = FailoverSource::new(vec![Box::new(rejecting)]).unwrap();
+        let checkpoint = Checkpoint::from_batch(&test_batch(3), 1234567890);
+
+        assert!(source.set_checkpoint(checkpoint).await.is_err());
+        assert_eq!(source.checkpoint().await.unwrap().last_batch_id, U256::ZERO);
+    }
+}
@@ -1,13 +1,15 @@
 //! Filesystem data stream source for CDK batch ingestion
 
 use crate::{
+    checkpoint::Checkpoint,
     error::{DataStreamError, DataStreamResult},
-    source::{BatchSource, BatchStream},
+    source::{decode_batch, BatchSource, BatchStream, BatchStreamCursor},
+    SourceMetadata,
 };
 use async_trait::async_trait;
 use cdk_types::Batch;
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tokio::{fs, io::AsyncReadExt};
 use futures::{stream, StreamExt};
@@ -20,18 +22,69 @@ pub struct FilesystemSourceConfig {
     pub path: PathBuf,
     /// File extension to look for (e.g., "json", "rlp")
     pub file_extension: String,
+    /// Filename prefix preceding the batch number (e.g. "batch_" for `batch_000123.json`)
+    ///
+    /// Files named `<prefix><number>.<extension>` below the requested start
+    /// batch are skipped without being read. Other files are filtered by content.
+    pub batch_file_prefix: Option<String>,
+    /// Maximum number of files read and deserialized in parallel
+    pub concurrency: usize,
+    /// Chain id of the batches served (0 if unknown)
+    pub chain_id: u64,
+}
+
+impl Default for FilesystemSourceConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("."),
+            file_extension: "json".to_string(),
+            batch_file_prefix: Some("batch_".to_string()),
+            concurrency: 4,
+            chain_id: 0,
+        }
+    }
+}
+
+impl FilesystemSourceConfig {
+    /// Parse the batch number from a filename following the configured pattern
+    pub fn batch_number_from_path(&self, path: &Path) -> Option<u64> {
+        let prefix = self.batch_file_prefix.as_deref()?;
+        let stem = path.file_stem()?.to_str()?;
+        let digits = stem.strip_prefix(prefix)?;
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    }
 }
 
 /// Filesystem implementation of `BatchSource`
 #[derive(Debug)]
 pub struct FilesystemSource {
     config: FilesystemSourceConfig,
+    cursor: BatchStreamCursor,
+    checkpoint: Option<Checkpoint>,
 }
 
 impl FilesystemSource {
     /// Create a new FilesystemSource
     pub fn new(config: FilesystemSourceConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cursor: BatchStreamCursor::default(),
+            checkpoint: None,
+        }
+    }
+
+    /// Describe this source, identified by its directory
+    fn source_metadata(&self) -> SourceMetadata {
+        SourceMetadata::new(
+            "Filesystem Source".to_string(),
+            "1.0".to_string(),
+            self.config.path.to_string_lossy().to_string(),
+            true,
+        )
+        .with_chain_id(self.config.chain_id)
     }
 
     /// Read a batch from a file
@@ -47,8 +100,12 @@ impl FilesystemSource {
             .map_err(|e| DataStreamError::IoError(format!("Failed to read file {}: {}", file_path.display(), e)))?;
 
         // Assuming batches are stored as JSON for now
-        let batch: Batch = serde_json::from_slice(&contents)
-            .map_err(|e| DataStreamError::DeserializationError(format!("Failed to deserialize batch from {}: {}", file_path.display(), e)))?;
+        let batch = decode_batch(&contents).map_err(|e| match e {
+            DataStreamError::DeserializationError(msg) => {
+                DataStreamError::DeserializationError(format!("Failed to deserialize batch from {}: {}", file_path.display(), msg))
+            }
+            other => other,
+        })?;
 
         info!(target: "cdk::datastream::filesystem", batch_number = %batch.id.number, path = %file_path.display(), "Successfully read batch from file");
         Ok(batch)
@@ -67,20 +124,32 @@ impl BatchSource for FilesystemSource {
         let mut file_paths = Vec::new();
         while let Some(entry) = entries.next_entry().await.map_err(|e| DataStreamError::IoError(format!("Failed to read directory entry: {}", e)))? {
             let path = entry.path();
-            if path.is_file() && path.extension().is_some_and(|ext| ext.to_string_lossy() == self.config.file_extension) {
-                file_paths.push(path);
+            if !(path.is_file() && path.extension().is_some_and(|ext| ext.to_string_lossy() == self.config.file_extension)) {
+                continue;
+            }
+
+            // Skip files whose name already tells us they are before the start batch
+            if let (Some(start_num), Some(file_num)) = (start_batch_number, self.config.batch_number_from_path(&path)) {
+                if file_num < start_num {
+                    debug!(target: "cdk::datastream::filesystem", path = %path.display(), "Skipping batch file below start batch number");
+                    continue;
+                }
             }
+
+            file_paths.push(path);
         }
 
         file_paths.sort_unstable(); // Ensure consistent order
 
+        // Read up to `concurrency` files at once; `buffered` keeps the sorted order
         let stream = stream::iter(file_paths)
-            .filter_map(move |file_path| {
+            .map(Self::read_batch_from_file)
+            .buffered(self.config.concurrency.max(1))
+            .filter_map(move |result| {
                 let start_batch_number = start_batch_number;
                 async move {
-                    // Extract batch number from filename or content if needed for filtering
-                    // For simplicity, we'll just read all and filter later if start_batch_number is provided
-                    match Self::read_batch_from_file(file_path).await {
+                    // Files whose name doesn't follow the pattern are filtered by content
+                    match result {
                         Ok(batch) => {
                             if let Some(start_num) = start_batch_number {
                                 if batch.id.number >= start_num {
@@ -104,15 +173,28 @@ impl BatchSource for FilesystemSource {
         Ok(Box::new(stream))
     }
 
-    async fn next(&mut self) -> Result<Option<Batch>, crate::DatastreamError> {
-        Ok(None)
+    fn stream_cursor(&mut self) -> Option<&mut BatchStreamCursor> {
+        Some(&mut self.cursor)
+    }
+
+    fn advance_checkpoint(&mut self, batch: &Batch) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.checkpoint = Some(Checkpoint::from_batch(batch, now).with_source(&self.source_metadata()));
     }
 
     async fn checkpoint(&self) -> Result<crate::Checkpoint, crate::DatastreamError> {
-        Ok(crate::Checkpoint::default())
+        Ok(self.checkpoint.clone().unwrap_or_default())
     }
 
-    async fn set_checkpoint(&mut self, _checkpoint: crate::Checkpoint) -> Result<(), crate::DatastreamError> {
+    async fn set_checkpoint(&mut self, checkpoint: crate::Checkpoint) -> Result<(), crate::DatastreamError> {
+        checkpoint.ensure_compatible_with(&self.source_metadata())?;
+        debug!(target: "cdk::datastream::filesystem", batch_number = %checkpoint.last_batch_id, "Setting checkpoint");
+        self.checkpoint = Some(checkpoint);
+        // Reopen the stream after the new checkpoint on the next read
+        self.cursor.reset();
         Ok(())
     }
 
@@ -128,11 +210,204 @@ impl BatchSource for FilesystemSource {
     }
 
     async fn metadata(&self) -> Result<crate::SourceMetadata, crate::DatastreamError> {
-        Ok(crate::SourceMetadata::new(
-            "Filesystem Source".to_string(),
-            "1.0".to_string(),
-            self.config.path.to_string_lossy().to_string(),
-            true,
-        ))
+        Ok(self.source_metadata())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+
+    async fn write_batch(dir: &Path, number: u64) {
+        write_batch_named(dir, number, &format!("batch_{:06}.json", number)).await;
+    }
+
+    async fn write_batch_named(dir: &Path, number: u64, name: &str) {
+        let batch = Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        );
+        fs::write(dir.join(name), serde_json::to_vec(&batch).unwrap()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_next_drives_batch_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        write_batch(dir.path(), 1).await;
+        write_batch(dir.path(), 2).await;
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(1));
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(2));
+        assert!(source.next().await.unwrap().is_none());
+
+        let checkpoint = source.checkpoint().await.unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(2));
+        assert_eq!(checkpoint.source(), Some("Filesystem Source"));
+        assert_eq!(checkpoint.source_url(), Some(dir.path().to_string_lossy().as_ref()));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_checkpoint_valid_for_batch_without_timestamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let batch = Batch::new(
+            BatchId::new(U256::from(1), FixedBytes::from([1u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            0,
+        );
+        fs::write(dir.path().join("batch_000001.json"), serde_json::to_vec(&batch).unwrap()).await.unwrap();
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        assert_eq!(source.next().await.unwrap().unwrap().timestamp, 0);
+
+        // The checkpoint is stamped when it is taken, so a reopened source resumes after it
+        let checkpoint = source.checkpoint().await.unwrap();
+        assert!(checkpoint.is_valid());
+        let mut reopened = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        reopened.set_checkpoint(checkpoint).await.unwrap();
+        assert!(reopened.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_marks_gap() {
+        let dir = tempfile::tempdir().unwrap();
+        for number in [1, 2, 5] {
+            write_batch(dir.path(), number).await;
+        }
+
+        let source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let events: Vec<_> = source.fetch_event_stream(None).await.unwrap().map(Result::unwrap).collect().await;
+
+        let numbers: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                crate::StreamEvent::Batch(batch) => format!("batch {}", batch.id.number),
+                crate::StreamEvent::Gap { from, to } => format!("gap {}-{}", from, to),
+            })
+            .collect();
+        assert_eq!(numbers, vec!["batch 1", "batch 2", "gap 3-4", "batch 5"]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_version_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        write_batch(dir.path(), 1).await;
+
+        let batch_path = dir.path().join("batch_000001.json");
+        let mut envelope: serde_json::Value = serde_json::from_slice(&fs::read(&batch_path).await.unwrap()).unwrap();
+        envelope["version"] = serde_json::json!(crate::SUPPORTED_BATCH_VERSION);
+        fs::write(&batch_path, serde_json::to_vec(&envelope).unwrap()).await.unwrap();
+        envelope["version"] = serde_json::json!(crate::SUPPORTED_BATCH_VERSION + 1);
+        fs::write(dir.path().join("batch_000002.json"), serde_json::to_vec(&envelope).unwrap()).await.unwrap();
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(1));
+        match source.next().await {
+            Err(DataStreamError::VersionMismatch { expected, got }) => {
+                assert_eq!(expected, crate::SUPPORTED_BATCH_VERSION);
+                assert_eq!(got, crate::SUPPORTED_BATCH_VERSION + 1);
+            }
+            other => panic!("expected VersionMismatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_checkpoint_resumes_after_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        for number in 1..=3 {
+            write_batch(dir.path(), number).await;
+        }
+
+        let mut source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(1));
+
+        let checkpoint = Checkpoint::new(U256::from(1), FixedBytes::from([1u8; 32]), U256::from(100), 1234567890);
+        source.set_checkpoint(checkpoint).await.unwrap();
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(2));
+    }
+
+    #[test]
+    fn test_batch_number_from_path() {
+        let config = FilesystemSourceConfig::default();
+        assert_eq!(config.batch_number_from_path(Path::new("/data/batch_000123.json")), Some(123));
+        assert_eq!(config.batch_number_from_path(Path::new("/data/batch_.json")), None);
+        assert_eq!(config.batch_number_from_path(Path::new("/data/batch_12a.json")), None);
+        assert_eq!(config.batch_number_from_path(Path::new("/data/snapshot_7.json")), None);
+    }
+
+    #[tokio::test]
+    async fn test_filename_filtering_skips_files_without_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        // Matching names below the start batch hold invalid JSON; reading them would yield an error
+        fs::write(dir.path().join("batch_000001.json"), b"not json").await.unwrap();
+        fs::write(dir.path().join("batch_000002.json"), b"not json").await.unwrap();
+        write_batch(dir.path(), 3).await;
+        // Non-matching names fall back to content-based filtering
+        write_batch_named(dir.path(), 1, "legacy_a.json").await;
+        write_batch_named(dir.path(), 4, "legacy_b.json").await;
+
+        let source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        let batches: Vec<_> = source.fetch_batch_stream(Some(3)).await.unwrap().collect().await;
+        let numbers: Vec<_> = batches.into_iter().map(|batch| batch.unwrap().id.number).collect();
+        assert_eq!(numbers, vec![U256::from(3), U256::from(4)]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads_preserve_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for number in 1..=20 {
+            write_batch(dir.path(), number).await;
+        }
+        fs::write(dir.path().join("batch_000010.json"), b"not json").await.unwrap();
+
+        let source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            concurrency: 8,
+            ..Default::default()
+        });
+
+        let batches: Vec<_> = source.fetch_batch_stream(None).await.unwrap().collect().await;
+        assert_eq!(batches.len(), 20);
+        for (index, result) in batches.iter().enumerate() {
+            let number = index as u64 + 1;
+            if number == 10 {
+                assert!(matches!(result, Err(DataStreamError::DeserializationError(_))));
+            } else {
+                assert_eq!(result.as_ref().unwrap().id.number, U256::from(number));
+            }
+        }
+    }
+}
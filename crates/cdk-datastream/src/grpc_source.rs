@@ -2,78 +2,218 @@
 
 use crate::{
     error::{DataStreamError, DataStreamResult},
-    source::{BatchSource, BatchStream},
+    source::{BatchSource, BatchStream, BatchStreamCursor},
+    Checkpoint, SourceMetadata,
 };
 use async_trait::async_trait;
 use cdk_types::Batch;
-use tonic::transport::Channel;
-use tracing::{info};
+use proto::{batch_stream_client::BatchStreamClient, SubscribeBatchesRequest};
+use std::path::PathBuf;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::{interceptor::InterceptedService, Interceptor},
+    transport::{Channel, Endpoint},
+    Request, Status,
+};
+use tracing::{debug, error, info};
+
+/// Generated protobuf types and gRPC client/server for `proto/cdk.proto`
+pub mod proto {
+    tonic::include_proto!("cdk.datastream.v1");
+}
 
 /// Configuration for the gRPC batch source
 #[derive(Debug, Clone)]
 pub struct GrpcSourceConfig {
     /// The URL of the gRPC endpoint
     pub url: String,
+    /// Whether to connect over TLS (requires the `grpc-tls` feature)
+    pub tls: bool,
+    /// PEM file of the CA certificate to trust, in addition to the system roots
+    pub ca_cert: Option<PathBuf>,
+    /// Token sent as `authorization: Bearer <token>` with every call
+    pub auth_token: Option<String>,
+    /// Chain id of the batches served (0 if unknown)
+    pub chain_id: u64,
+}
+
+impl Default for GrpcSourceConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:50051".to_string(),
+            tls: false,
+            ca_cert: None,
+            auth_token: None,
+            chain_id: 0,
+        }
+    }
+}
+
+impl GrpcSourceConfig {
+    /// Build the endpoint to connect to, configuring TLS when enabled
+    pub fn endpoint(&self) -> DataStreamResult<Endpoint> {
+        let endpoint = Endpoint::from_shared(self.url.clone())
+            .map_err(|e| DataStreamError::ConfigError(format!("Invalid gRPC URL: {}", e)))?;
+        if !self.tls {
+            return Ok(endpoint);
+        }
+
+        #[cfg(feature = "grpc-tls")]
+        {
+            // Several crypto backends may be linked in; pick one unless the application did
+            if rustls::crypto::CryptoProvider::get_default().is_none() {
+                let _ = rustls::crypto::ring::default_provider().install_default();
+            }
+            let mut tls = tonic::transport::ClientTlsConfig::new().with_native_roots();
+            if let Some(ca_cert) = &self.ca_cert {
+                let pem = std::fs::read(ca_cert).map_err(|e| {
+                    DataStreamError::ConfigError(format!("Failed to read CA certificate {}: {}", ca_cert.display(), e))
+                })?;
+                tls = tls.ca_certificate(tonic::transport::Certificate::from_pem(pem));
+            }
+            endpoint
+                .tls_config(tls)
+                .map_err(|e| DataStreamError::ConfigError(format!("Invalid gRPC TLS configuration: {}", e)))
+        }
+        #[cfg(not(feature = "grpc-tls"))]
+        Err(DataStreamError::ConfigError("gRPC TLS requires the grpc-tls feature".to_string()))
+    }
+
+    /// Build the interceptor attaching the auth token to every call
+    pub fn interceptor(&self) -> DataStreamResult<AuthInterceptor> {
+        let token = self
+            .auth_token
+            .as_ref()
+            .map(|token| {
+                format!("Bearer {}", token)
+                    .parse()
+                    .map_err(|e| DataStreamError::ConfigError(format!("Invalid gRPC auth token: {}", e)))
+            })
+            .transpose()?;
+        Ok(AuthInterceptor { token })
+    }
+}
+
+/// Interceptor setting the `authorization` metadata of each call, if a token is configured
+#[derive(Debug, Clone, Default)]
+pub struct AuthInterceptor {
+    token: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(token) = &self.token {
+            request.metadata_mut().insert("authorization", token.clone());
+        }
+        Ok(request)
+    }
 }
 
 /// gRPC implementation of `BatchSource`
 #[derive(Debug)]
 pub struct GrpcSource {
     config: GrpcSourceConfig,
+    client: BatchStreamClient<InterceptedService<Channel, AuthInterceptor>>,
+    cursor: BatchStreamCursor,
+    checkpoint: Option<Checkpoint>,
 }
 
 impl GrpcSource {
     /// Create a new GrpcSource
     pub async fn new(config: GrpcSourceConfig) -> DataStreamResult<Self> {
-        info!(target: "cdk::datastream::grpc", url = %config.url, "Connecting to gRPC source");
-        let _channel = Channel::from_shared(config.url.clone())
-            .map_err(|e| DataStreamError::ConnectionError(format!("Invalid gRPC URL: {}", e)))?
+        info!(target: "cdk::datastream::grpc", url = %config.url, tls = config.tls, "Connecting to gRPC source");
+        let channel = config
+            .endpoint()?
             .connect()
             .await
             .map_err(|e| DataStreamError::ConnectionError(format!("Failed to connect to gRPC: {}", e)))?;
         info!(target: "cdk::datastream::grpc", url = %config.url, "gRPC connection established");
-        Ok(Self { config })
+        let interceptor = config.interceptor()?;
+        Ok(Self {
+            config,
+            client: BatchStreamClient::with_interceptor(channel, interceptor),
+            cursor: BatchStreamCursor::default(),
+            checkpoint: None,
+        })
+    }
+
+    /// Describe this source, identified by its URL
+    fn source_metadata(&self) -> SourceMetadata {
+        SourceMetadata::new(
+            "gRPC Source".to_string(),
+            "1.0".to_string(),
+            self.config.url.clone(),
+            true,
+        )
+        .with_chain_id(self.config.chain_id)
     }
 }
 
 #[async_trait]
 impl BatchSource for GrpcSource {
-    async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
-        info!(target: "cdk::datastream::grpc", start_batch_number = ?_start_batch_number, "Subscribing to gRPC batch stream");
-        
-        // For now, return an empty stream since we don't have the actual gRPC proto definitions
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        info!(target: "cdk::datastream::grpc", start_batch_number = ?start_batch_number, "Subscribing to gRPC batch stream");
+
+        let mut client = self.client.clone();
+        let mut messages = client
+            .subscribe_batches(SubscribeBatchesRequest { start_batch_number })
+            .await
+            .map_err(|e| DataStreamError::ConnectionError(format!("Failed to subscribe to gRPC batch stream: {}", e)))?
+            .into_inner();
+
         let batch_stream = async_stream::stream! {
-            // Empty stream for now - yield nothing
-            if false {
-                yield Ok(Batch::new(
-                    cdk_types::BatchId::new(alloy_primitives::U256::ZERO, alloy_primitives::FixedBytes::ZERO),
-                    alloy_primitives::U256::ZERO,
-                    alloy_primitives::FixedBytes::ZERO,
-                    vec![],
-                    cdk_types::ProofMetadata::default(),
-                    0,
-                ));
+            loop {
+                match messages.message().await {
+                    Ok(Some(message)) => {
+                        yield serde_json::from_slice::<Batch>(&message.batch).map_err(|e| {
+                            DataStreamError::DeserializationError(format!("Failed to decode gRPC batch: {}", e))
+                        });
+                    }
+                    Ok(None) => {
+                        info!(target: "cdk::datastream::grpc", "gRPC batch stream closed by server");
+                        break;
+                    }
+                    Err(status) => {
+                        error!(target: "cdk::datastream::grpc", error = %status, "gRPC batch stream error");
+                        yield Err(DataStreamError::ConnectionError(format!("gRPC stream error: {}", status)));
+                        break;
+                    }
+                }
             }
         };
         Ok(Box::new(Box::pin(batch_stream)))
     }
 
-    async fn next(&mut self) -> Result<Option<Batch>, crate::DatastreamError> {
-        Ok(None)
+    fn stream_cursor(&mut self) -> Option<&mut BatchStreamCursor> {
+        Some(&mut self.cursor)
+    }
+
+    fn advance_checkpoint(&mut self, batch: &Batch) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.checkpoint = Some(Checkpoint::from_batch(batch, now).with_source(&self.source_metadata()));
     }
 
     async fn checkpoint(&self) -> Result<crate::Checkpoint, crate::DatastreamError> {
-        Ok(crate::Checkpoint::default())
+        Ok(self.checkpoint.clone().unwrap_or_default())
     }
 
-    async fn set_checkpoint(&mut self, _checkpoint: crate::Checkpoint) -> Result<(), crate::DatastreamError> {
+    async fn set_checkpoint(&mut self, checkpoint: crate::Checkpoint) -> Result<(), crate::DatastreamError> {
+        checkpoint.ensure_compatible_with(&self.source_metadata())?;
+        debug!(target: "cdk::datastream::grpc", batch_number = %checkpoint.last_batch_id, "Setting checkpoint");
+        self.checkpoint = Some(checkpoint);
+        // Resubscribe after the new checkpoint on the next read
+        self.cursor.reset();
         Ok(())
     }
 
     async fn health_check(&self) -> Result<(), crate::DatastreamError> {
         // Try to connect to check health
-        let _channel = Channel::from_shared(self.config.url.clone())
-            .map_err(|e| crate::DatastreamError::ConnectionError(format!("Invalid gRPC URL: {}", e)))?
+        let _channel = self
+            .config
+            .endpoint()?
             .connect()
             .await
             .map_err(|e| crate::DatastreamError::ConnectionError(format!("Failed to connect to gRPC: {}", e)))?;
@@ -81,11 +221,119 @@ impl BatchSource for GrpcSource {
     }
 
     async fn metadata(&self) -> Result<crate::SourceMetadata, crate::DatastreamError> {
-        Ok(crate::SourceMetadata::new(
-            "gRPC Source".to_string(),
-            "1.0".to_string(),
-            self.config.url.clone(),
-            true,
-        ))
+        Ok(self.source_metadata())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use futures::StreamExt;
+    use proto::{
+        batch_stream_server::{BatchStream as BatchStreamService, BatchStreamServer},
+        BatchMessage,
+    };
+    use std::pin::Pin;
+    use tonic::{transport::Server, Request, Response, Status};
+
+    struct MockBatchStreamService;
+
+    #[tonic::async_trait]
+    impl BatchStreamService for MockBatchStreamService {
+        type SubscribeBatchesStream = Pin<Box<dyn futures::Stream<Item = Result<BatchMessage, Status>> + Send>>;
+
+        async fn subscribe_batches(
+            &self,
+            request: Request<SubscribeBatchesRequest>,
+        ) -> Result<Response<Self::SubscribeBatchesStream>, Status> {
+            let start = request.into_inner().start_batch_number.unwrap_or(0);
+            let messages = (start..start + 2).map(|number| {
+                let batch = Batch::new(
+                    BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+                    U256::from(100),
+                    FixedBytes::from([2u8; 32]),
+                    vec![],
+                    ProofMetadata::default(),
+                    1234567890,
+                );
+                BatchMessage { batch: serde_json::to_vec(&batch).unwrap() }
+            });
+            Ok(Response::new(Box::pin(futures::stream::iter(messages.map(Ok)))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grpc_source_streams_batches() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(BatchStreamServer::new(MockBatchStreamService))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+
+        let config = GrpcSourceConfig {
+            url: format!("http://{}", addr),
+            ..Default::default()
+        };
+        let source = GrpcSource::new(config).await.unwrap();
+        let batches: Vec<_> = source.fetch_batch_stream(Some(5)).await.unwrap().collect().await;
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].as_ref().unwrap().id.number, U256::from(5));
+        assert_eq!(batches[1].as_ref().unwrap().id.number, U256::from(6));
+    }
+
+    #[tokio::test]
+    async fn test_next_stamps_checkpoint_with_source() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(BatchStreamServer::new(MockBatchStreamService))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+
+        let url = format!("http://{}", addr);
+        let mut source = GrpcSource::new(GrpcSourceConfig {
+            url: url.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        source
+            .set_checkpoint(Checkpoint::new(U256::from(3), FixedBytes::from([3u8; 32]), U256::ZERO, 1234567890))
+            .await
+            .unwrap();
+
+        // Resumes after the checkpoint set above
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(4));
+        let checkpoint = source.checkpoint().await.unwrap();
+        assert_eq!(checkpoint.last_batch_id, U256::from(4));
+        assert_eq!(checkpoint.source(), Some("gRPC Source"));
+        assert_eq!(checkpoint.source_url(), Some(url.as_str()));
+    }
+
+    #[cfg(feature = "grpc-tls")]
+    #[test]
+    fn test_tls_config_and_auth_token() {
+        let config = GrpcSourceConfig {
+            url: "https://batches.example.com:443".to_string(),
+            tls: true,
+            auth_token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        assert!(config.endpoint().is_ok());
+
+        let missing_ca = GrpcSourceConfig {
+            ca_cert: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..config.clone()
+        };
+        assert!(matches!(missing_ca.endpoint(), Err(DataStreamError::ConfigError(_))));
+
+        let request = config.interceptor().unwrap().call(Request::new(())).unwrap();
+        assert_eq!(request.metadata().get("authorization").unwrap(), "Bearer secret");
+    }
+}
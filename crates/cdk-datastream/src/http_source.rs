@@ -1,28 +1,44 @@
 //! HTTP-based batch data source implementation
 
 use crate::{
-    Checkpoint, DatastreamError, DatastreamResult, SourceMetadata, BatchSource,
+    decode_batch_value, Checkpoint, DatastreamError, DatastreamResult, SourceMetadata, BatchSource,
 };
 use cdk_types::Batch;
 use alloy_primitives::U256;
-use reqwest::Client;
-use std::time::Duration;
-use tracing::{debug, info};
+use futures::future::BoxFuture;
+use reqwest::{header, Client, RequestBuilder, StatusCode};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{debug, info, warn};
 use url::Url;
 
+/// Supplies a fresh bearer token for each request
+pub type TokenProvider = Arc<dyn Fn() -> BoxFuture<'static, DatastreamResult<String>> + Send + Sync>;
+
 /// Configuration for HTTP batch source
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct HttpBatchSourceConfig {
     /// Base URL for the batch API
     pub base_url: Url,
     /// API key for authentication (optional)
     pub api_key: Option<String>,
+    /// Extra headers sent with every request
+    pub headers: HashMap<String, String>,
+    /// Supplies the bearer token of each request, taking precedence over `api_key`
+    pub token_provider: Option<TokenProvider>,
     /// Request timeout
     pub timeout: Duration,
-    /// Maximum number of retries
+    /// Maximum number of retries of a request failing to connect or answered with a server error
     pub max_retries: u32,
-    /// Retry delay
+    /// Delay before each retry
     pub retry_delay: Duration,
+    /// Chain id of the batches served, used when the API does not report one (0 if unknown)
+    pub chain_id: u64,
 }
 
 impl Default for HttpBatchSourceConfig {
@@ -30,13 +46,50 @@ impl Default for HttpBatchSourceConfig {
         Self {
             base_url: Url::parse("http://localhost:8080").unwrap(),
             api_key: None,
+            headers: HashMap::new(),
+            token_provider: None,
             timeout: Duration::from_secs(30),
             max_retries: 3,
             retry_delay: Duration::from_secs(1),
+            chain_id: 0,
         }
     }
 }
 
+impl fmt::Debug for HttpBatchSourceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpBatchSourceConfig")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("token_provider", &self.token_provider.is_some())
+            .field("timeout", &self.timeout)
+            .field("max_retries", &self.max_retries)
+            .field("retry_delay", &self.retry_delay)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl HttpBatchSourceConfig {
+    /// Fetch the bearer token of each request from `provider`, e.g. to use short-lived credentials
+    pub fn with_token_provider<F, Fut>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DatastreamResult<String>> + Send + 'static,
+    {
+        self.token_provider = Some(Arc::new(move || Box::pin(provider())));
+        self
+    }
+}
+
+/// Metadata last fetched from the API, with the `ETag` it was served with
+#[derive(Debug, Clone)]
+struct CachedMetadata {
+    etag: String,
+    metadata: SourceMetadata,
+}
+
 /// HTTP-based batch source implementation
 #[derive(Debug)]
 pub struct HttpBatchSource {
@@ -44,13 +97,20 @@ pub struct HttpBatchSource {
     client: Client,
     current_checkpoint: Option<Checkpoint>,
     metadata: SourceMetadata,
+    metadata_cache: Mutex<Option<CachedMetadata>>,
 }
 
 impl HttpBatchSource {
     /// Create a new HTTP batch source
+    ///
+    /// Requests advertise gzip, brotli and deflate support via `Accept-Encoding`;
+    /// compressed responses are decoded transparently.
     pub fn new(config: HttpBatchSourceConfig) -> Self {
         let client = Client::builder()
             .timeout(config.timeout)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
             .build()
             .expect("Failed to create HTTP client");
 
@@ -59,13 +119,15 @@ impl HttpBatchSource {
             "1.0".to_string(),
             config.base_url.to_string(),
             true,
-        );
+        )
+        .with_chain_id(config.chain_id);
 
         Self {
             config,
             client,
             current_checkpoint: None,
             metadata,
+            metadata_cache: Mutex::new(None),
         }
     }
 
@@ -82,20 +144,79 @@ impl HttpBatchSource {
         Ok(Self::new(config))
     }
 
-    /// Make an authenticated request
-    async fn make_request(&self, path: &str) -> DatastreamResult<reqwest::Response> {
+    /// A copy of this source sharing its HTTP client, for streams outliving `&self`
+    fn detached(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            client: self.client.clone(),
+            current_checkpoint: None,
+            metadata: self.metadata.clone(),
+            metadata_cache: Mutex::new(None),
+        }
+    }
+
+    /// Build an authenticated request
+    ///
+    /// Configured headers are added first; the bearer token from the token
+    /// provider, or else the API key, is set last.
+    async fn request(&self, path: &str) -> DatastreamResult<RequestBuilder> {
         let url = self.config.base_url.join(path)
             .map_err(|e| DatastreamError::ConfigError(format!("Invalid path: {}", e)))?;
 
         let mut request = self.client.get(url);
 
-        if let Some(api_key) = &self.config.api_key {
-            request = request.header("Authorization", format!("Bearer {}", api_key));
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
         }
 
-        let response = request.send().await
-            .map_err(|e| DatastreamError::NetworkError(format!("Request failed: {}", e)))?;
+        if let Some(provider) = &self.config.token_provider {
+            let token = provider().await?;
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        } else if let Some(api_key) = &self.config.api_key {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", api_key));
+        }
 
+        Ok(request)
+    }
+
+    /// Make an authenticated request
+    async fn make_request(&self, path: &str) -> DatastreamResult<reqwest::Response> {
+        let response = self.send(path, None).await?;
+        Self::ensure_success(response)
+    }
+
+    /// Send an authenticated request, optionally conditional on an `ETag`
+    ///
+    /// Requests failing to connect or answered with a `5xx` status are sent
+    /// again up to `max_retries` times, `retry_delay` apart. Every attempt is
+    /// built afresh, so it carries a fresh bearer token.
+    async fn send(&self, path: &str, if_none_match: Option<&str>) -> DatastreamResult<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let mut request = self.request(path).await?;
+            if let Some(etag) = if_none_match {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+
+            let result = request.send().await
+                .map_err(|e| DatastreamError::NetworkError(format!("Request failed: {}", e)));
+            match result {
+                Ok(response) if !response.status().is_server_error() => return Ok(response),
+                result if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    match result {
+                        Ok(response) => warn!("{} answered {}, retry {} of {}", path, response.status(), attempt, self.config.max_retries),
+                        Err(e) => warn!("{}, retry {} of {}", e, attempt, self.config.max_retries),
+                    }
+                    tokio::time::sleep(self.config.retry_delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Fail with `DatastreamError::HttpError` unless the response status is a success
+    fn ensure_success(response: reqwest::Response) -> DatastreamResult<reqwest::Response> {
         if !response.status().is_success() {
             return Err(DatastreamError::HttpError {
                 status: response.status().as_u16(),
@@ -115,18 +236,43 @@ impl HttpBatchSource {
         };
 
         let response = self.make_request(&path).await?;
-        let batches: Vec<Batch> = response.json().await
+        let envelopes: Vec<serde_json::Value> = response.json().await
             .map_err(|e| DatastreamError::SerializationError(format!("Failed to parse batches: {}", e)))?;
 
-        Ok(batches)
+        envelopes.into_iter().map(decode_batch_value).collect()
     }
 
     /// Fetch source metadata
+    ///
+    /// The `ETag` of the last response is sent back as `If-None-Match`; a
+    /// `304 Not Modified` answer returns the cached metadata without a body.
     async fn fetch_metadata(&self) -> DatastreamResult<SourceMetadata> {
-        let response = self.make_request("/api/v1/metadata").await?;
-        let metadata: SourceMetadata = response.json().await
+        let cached = self.metadata_cache.lock().unwrap().clone();
+        let etag = cached.as_ref().map(|cached| cached.etag.as_str());
+        let response = self.send("/api/v1/metadata", etag).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                debug!("Source metadata not modified, using cached copy");
+                return Ok(cached.metadata);
+            }
+        }
+        let response = Self::ensure_success(response)?;
+
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let mut metadata: SourceMetadata = response.json().await
             .map_err(|e| DatastreamError::SerializationError(format!("Failed to parse metadata: {}", e)))?;
+        if metadata.chain_id == 0 {
+            metadata.chain_id = self.config.chain_id;
+        }
 
+        *self.metadata_cache.lock().unwrap() = etag.map(|etag| CachedMetadata {
+            etag,
+            metadata: metadata.clone(),
+        });
         Ok(metadata)
     }
 }
@@ -150,10 +296,11 @@ impl BatchSource for HttpBatchSource {
         let batch = batches.into_iter().next().unwrap();
         
         // Update checkpoint
-        self.current_checkpoint = Some(Checkpoint::from_batch(&batch, std::time::SystemTime::now()
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
-            .as_secs()));
+            .as_secs();
+        self.current_checkpoint = Some(Checkpoint::from_batch(&batch, now).with_source(&self.metadata));
 
         info!("Fetched batch {} with {} blocks", batch.id.number, batch.block_count());
         Ok(Some(batch))
@@ -166,6 +313,7 @@ impl BatchSource for HttpBatchSource {
     }
 
     async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DatastreamResult<()> {
+        checkpoint.ensure_compatible_with(&self.metadata)?;
         debug!("Setting checkpoint to batch {}", checkpoint.last_batch_id);
         self.current_checkpoint = Some(checkpoint);
         Ok(())
@@ -182,27 +330,37 @@ impl BatchSource for HttpBatchSource {
     }
 
     async fn metadata(&self) -> DatastreamResult<SourceMetadata> {
-        // Try to fetch fresh metadata, fall back to cached
-        match self.fetch_metadata().await {
-            Ok(metadata) => Ok(metadata),
-            Err(_) => Ok(self.metadata.clone()),
-        }
+        self.fetch_metadata().await
     }
 
-    async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> DatastreamResult<crate::BatchStream> {
-        // For HTTP source, we'll return an empty stream for now
-        // In a real implementation, this would make HTTP requests to fetch batches
+    /// Page through the batch API until it returns no more batches
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DatastreamResult<crate::BatchStream> {
+        let source = self.detached();
         let stream = async_stream::stream! {
-            // Empty stream for now - yield nothing
-            if false {
-                yield Ok(Batch::new(
-                    cdk_types::BatchId::new(U256::ZERO, alloy_primitives::FixedBytes::ZERO),
-                    U256::ZERO,
-                    alloy_primitives::FixedBytes::ZERO,
-                    vec![],
-                    cdk_types::ProofMetadata::default(),
-                    0,
-                ));
+            let mut from_batch = start_batch_number.map(U256::from);
+            loop {
+                let batches = match source.fetch_batches(from_batch).await {
+                    Ok(batches) => batches,
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                };
+                let Some(last) = batches.last() else {
+                    break;
+                };
+                // A page ending before the requested batch would be requested again forever
+                if let Some(from) = from_batch.filter(|&from| last.id.number < from) {
+                    yield Err(DatastreamError::InvalidBatchData(format!(
+                        "Batch page requested from {} ends at batch {}",
+                        from, last.id.number
+                    )));
+                    break;
+                }
+                from_batch = Some(last.id.number + U256::from(1));
+                for batch in batches {
+                    yield Ok(batch);
+                }
             }
         };
         Ok(Box::new(Box::pin(stream)))
@@ -212,7 +370,66 @@ impl BatchSource for HttpBatchSource {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{CheckpointStorage, StreamEvent, SUPPORTED_BATCH_VERSION};
     use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use flate2::{write::GzEncoder, Compression};
+    use futures::StreamExt;
+    use std::io::Write;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    /// Build a raw HTTP/1.1 response closing the connection after `body`
+    fn http_response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n", status, body.len());
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    /// Serve `responses` in order, one per connection, returning the server URL
+    /// and a handle resolving to the raw requests received
+    async fn serve(responses: Vec<Vec<u8>>) -> (Url, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buf).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                }
+                requests.push(String::from_utf8_lossy(&request).to_lowercase());
+                stream.write_all(&response).await.unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn test_batch(number: u64) -> Batch {
+        Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
 
     #[tokio::test]
     async fn test_http_batch_source_creation() {
@@ -223,6 +440,208 @@ mod tests {
         assert_eq!(source.metadata.name, "HTTP Batch Source");
     }
 
+    #[tokio::test]
+    async fn test_fetch_batches_decodes_gzip_response() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&serde_json::to_vec(&vec![test_batch(3)]).unwrap()).unwrap();
+        let body = encoder.finish().unwrap();
+        let response = http_response(
+            "200 OK",
+            &[("Content-Type", "application/json"), ("Content-Encoding", "gzip")],
+            &body,
+        );
+        let (url, server) = serve(vec![response]).await;
+        let mut source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        let batch = source.next().await.unwrap().unwrap();
+        assert_eq!(batch, test_batch(3));
+
+        let requests = server.await.unwrap();
+        let accept_encoding = requests[0].lines().find(|line| line.starts_with("accept-encoding:")).unwrap();
+        assert!(accept_encoding.contains("gzip"));
+        assert!(accept_encoding.contains("br"));
+    }
+
+    #[tokio::test]
+    async fn test_metadata_not_modified_uses_cache() {
+        let metadata = SourceMetadata::new("Upstream".to_string(), "2.0".to_string(), "http://upstream".to_string(), true)
+            .with_chain_id(7);
+        let first = http_response(
+            "200 OK",
+            &[("Content-Type", "application/json"), ("ETag", "\"v1\"")],
+            &serde_json::to_vec(&metadata).unwrap(),
+        );
+        let second = http_response("304 Not Modified", &[("ETag", "\"v1\"")], b"");
+        let (url, server) = serve(vec![first, second]).await;
+        let source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        assert_eq!(source.metadata().await.unwrap(), metadata);
+        assert_eq!(source.metadata().await.unwrap(), metadata);
+
+        let requests = server.await.unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_retried() {
+        let unavailable = http_response("503 Service Unavailable", &[], b"");
+        let (url, server) = serve(vec![unavailable.clone(), http_response("200 OK", &[], b""), unavailable.clone(), unavailable]).await;
+        let source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            max_retries: 1,
+            retry_delay: Duration::from_millis(10),
+            ..Default::default()
+        });
+
+        source.health_check().await.unwrap();
+        // Retries are bounded by max_retries
+        assert!(matches!(
+            source.health_check().await,
+            Err(DatastreamError::HttpError { status: 503, .. })
+        ));
+        assert_eq!(server.await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_fetch_errors_propagated() {
+        let (url, server) = serve(vec![http_response("404 Not Found", &[], b"")]).await;
+        let source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            source.metadata().await,
+            Err(DatastreamError::HttpError { status: 404, .. })
+        ));
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_requests_send_headers_and_fresh_tokens() {
+        let ok = http_response("200 OK", &[], b"");
+        let (url, server) = serve(vec![ok.clone(), ok]).await;
+        let refreshes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        let config = HttpBatchSourceConfig {
+            base_url: url,
+            api_key: Some("static".to_string()),
+            headers: HashMap::from([("X-Gateway-Key".to_string(), "gateway-secret".to_string())]),
+            ..Default::default()
+        }
+        .with_token_provider(move || {
+            let refresh = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            async move { Ok(format!("token-{}", refresh)) }
+        });
+        let source = HttpBatchSource::new(config);
+
+        source.health_check().await.unwrap();
+        source.health_check().await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 2);
+        for (i, request) in requests.iter().enumerate() {
+            assert!(request.contains("x-gateway-key: gateway-secret"));
+            assert!(request.contains(&format!("authorization: bearer token-{}", i + 1)));
+            assert!(!request.contains("bearer static"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_marks_gaps_across_pages() {
+        let page = |batches: Vec<Batch>| {
+            http_response("200 OK", &[("Content-Type", "application/json")], &serde_json::to_vec(&batches).unwrap())
+        };
+        let (url, server) = serve(vec![page(vec![test_batch(1), test_batch(2)]), page(vec![test_batch(5)]), page(vec![])]).await;
+        let source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        let events: Vec<_> = source.fetch_event_stream(Some(1)).await.unwrap().map(Result::unwrap).collect().await;
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Batch(Box::new(test_batch(1))),
+                StreamEvent::Batch(Box::new(test_batch(2))),
+                StreamEvent::Gap { from: 3, to: 4 },
+                StreamEvent::Batch(Box::new(test_batch(5))),
+            ]
+        );
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("get /api/v1/batches?from=1 "));
+        assert!(requests[1].starts_with("get /api/v1/batches?from=3 "));
+        assert!(requests[2].starts_with("get /api/v1/batches?from=6 "));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_batches_checks_schema_version() {
+        let page = |version: u32| {
+            let mut envelope = serde_json::to_value(test_batch(1)).unwrap();
+            envelope["version"] = serde_json::json!(version);
+            http_response("200 OK", &[("Content-Type", "application/json")], &serde_json::to_vec(&vec![envelope]).unwrap())
+        };
+        let (url, server) = serve(vec![page(SUPPORTED_BATCH_VERSION), page(SUPPORTED_BATCH_VERSION + 1)]).await;
+        let mut source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        assert_eq!(source.next().await.unwrap().unwrap(), test_batch(1));
+        let result = source.next().await;
+        assert!(matches!(
+            result,
+            Err(DatastreamError::VersionMismatch { expected, got }) if expected == SUPPORTED_BATCH_VERSION && got == SUPPORTED_BATCH_VERSION + 1
+        ));
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_batch_stream_stops_on_page_behind_cursor() {
+        let page = |batches: Vec<Batch>| {
+            http_response("200 OK", &[("Content-Type", "application/json")], &serde_json::to_vec(&batches).unwrap())
+        };
+        // The second page ignores `from` and starts over
+        let (url, server) = serve(vec![page(vec![test_batch(1), test_batch(2)]), page(vec![test_batch(1), test_batch(2)])]).await;
+        let source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        let results: Vec<_> = source.fetch_batch_stream(Some(1)).await.unwrap().collect().await;
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(Result::is_ok));
+        assert!(matches!(results[2], Err(DatastreamError::InvalidBatchData(_))));
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_valid_for_batch_without_timestamp() {
+        let mut batch = test_batch(1);
+        batch.timestamp = 0;
+        let body = serde_json::to_vec(&vec![batch]).unwrap();
+        let (url, server) = serve(vec![http_response("200 OK", &[("Content-Type", "application/json")], &body)]).await;
+        let mut source = HttpBatchSource::new(HttpBatchSourceConfig {
+            base_url: url,
+            ..Default::default()
+        });
+
+        assert_eq!(source.next().await.unwrap().unwrap().timestamp, 0);
+        // The checkpoint is stamped when it is taken, so it still restores
+        let checkpoint = source.checkpoint().await.unwrap();
+        assert!(checkpoint.is_valid());
+        assert_eq!(checkpoint.next_batch_number(), Some(2));
+        server.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_checkpoint_creation() {
         let checkpoint = Checkpoint::new(
@@ -236,6 +655,79 @@ mod tests {
         assert_eq!(checkpoint.last_batch_id, U256::from(100));
     }
 
+    #[test]
+    fn test_checkpoint_source_survives_serialization() {
+        let source = HttpBatchSource::new(HttpBatchSourceConfig::default());
+        let checkpoint = Checkpoint::new(
+            U256::from(7),
+            FixedBytes::from([1u8; 32]),
+            U256::from(100),
+            1234567890,
+        )
+        .with_source(&source.metadata);
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let restored: Checkpoint = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, checkpoint);
+        assert_eq!(restored.source(), Some("HTTP Batch Source"));
+        assert_eq!(restored.source_url(), Some(source.metadata.url.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_set_checkpoint_checks_compatibility() {
+        let mut source = HttpBatchSource::new(HttpBatchSourceConfig::default());
+        let checkpoint = Checkpoint::new(
+            U256::from(7),
+            FixedBytes::from([1u8; 32]),
+            U256::from(100),
+            1234567890,
+        );
+
+        let compatible = checkpoint.clone().with_source(&source.metadata);
+        source.set_checkpoint(compatible.clone()).await.unwrap();
+        assert_eq!(source.checkpoint().await.unwrap(), compatible);
+
+        let other = SourceMetadata::new("Kafka Source".to_string(), "1.0".to_string(), "kafka://broker".to_string(), true);
+        let incompatible = checkpoint.with_source(&other);
+        assert!(!incompatible.is_compatible_with(&source.metadata));
+        assert!(matches!(
+            source.set_checkpoint(incompatible).await,
+            Err(DatastreamError::CheckpointError(_))
+        ));
+        assert_eq!(source.checkpoint().await.unwrap(), compatible);
+    }
+
+    #[tokio::test]
+    async fn test_set_checkpoint_checks_chain_id() {
+        let mut source = HttpBatchSource::new(HttpBatchSourceConfig {
+            chain_id: 7,
+            ..Default::default()
+        });
+        let checkpoint = Checkpoint::new(
+            U256::from(7),
+            FixedBytes::from([1u8; 32]),
+            U256::from(100),
+            1234567890,
+        );
+
+        let same_chain = checkpoint.clone().with_source(&source.metadata);
+        assert_eq!(same_chain.chain_id(), 7);
+        source.set_checkpoint(same_chain).await.unwrap();
+
+        // A source not reporting its chain id leaves the stamp out
+        let unknown_chain = checkpoint.clone().with_source(&source.metadata.clone().with_chain_id(0));
+        assert_eq!(unknown_chain.chain_id(), 0);
+        source.set_checkpoint(unknown_chain).await.unwrap();
+
+        let other_chain = checkpoint.with_source(&source.metadata.clone().with_chain_id(8));
+        assert!(!other_chain.is_compatible_with(&source.metadata));
+        assert!(matches!(
+            source.set_checkpoint(other_chain).await,
+            Err(DatastreamError::CheckpointError(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_memory_checkpoint_storage() {
         let storage = crate::MemoryCheckpointStorage::default();
@@ -0,0 +1,389 @@
+//! Kafka data stream source for CDK batch ingestion
+
+use crate::{
+    error::{DataStreamError, DataStreamResult},
+    source::{BatchSource, BatchStream},
+    Checkpoint, SourceMetadata,
+};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use rdkafka::{
+    config::ClientConfig,
+    consumer::{Consumer, StreamConsumer},
+    message::Message,
+    Offset, TopicPartitionList,
+};
+use std::{fmt::Debug, sync::Arc, time::Duration};
+use tracing::{debug, error, info};
+
+/// Checkpoint metadata key holding the Kafka topic
+pub const KAFKA_TOPIC_KEY: &str = "kafka_topic";
+/// Checkpoint metadata key holding the Kafka partition
+pub const KAFKA_PARTITION_KEY: &str = "kafka_partition";
+/// Checkpoint metadata key holding the offset of the last consumed message
+pub const KAFKA_OFFSET_KEY: &str = "kafka_offset";
+
+/// Configuration for the Kafka batch source
+#[derive(Debug, Clone)]
+pub struct KafkaSourceConfig {
+    /// Comma-separated list of bootstrap brokers
+    pub brokers: String,
+    /// Topic to consume batches from
+    pub topic: String,
+    /// Partition to consume batches from
+    pub partition: i32,
+    /// Consumer group ID
+    pub group_id: String,
+    /// How long `next` waits for a message before reporting no new batch
+    pub poll_timeout: Duration,
+    /// Timeout for broker metadata and seek requests
+    pub request_timeout: Duration,
+    /// How long the batch stream waits before polling again when no message arrived
+    pub idle_backoff: Duration,
+    /// Chain id of the batches served (0 if unknown)
+    pub chain_id: u64,
+}
+
+impl Default for KafkaSourceConfig {
+    fn default() -> Self {
+        Self {
+            brokers: "localhost:9092".to_string(),
+            topic: "cdk-batches".to_string(),
+            partition: 0,
+            group_id: "reth-cdk".to_string(),
+            poll_timeout: Duration::from_secs(1),
+            request_timeout: Duration::from_secs(5),
+            idle_backoff: Duration::from_millis(100),
+            chain_id: 0,
+        }
+    }
+}
+
+/// A message consumed from a Kafka partition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaMessage {
+    /// Offset of the message within its partition
+    pub offset: i64,
+    /// Raw message payload
+    pub payload: Vec<u8>,
+}
+
+/// Minimal consumer interface used by `KafkaBatchSource`
+#[async_trait]
+pub trait KafkaConsumer: Send + Sync + Debug + 'static {
+    /// Receive the next message, or `None` if none arrived before the poll timeout
+    async fn recv(&self) -> DataStreamResult<Option<KafkaMessage>>;
+
+    /// Position the consumer so that the next message received is at `offset`
+    async fn seek(&self, offset: i64) -> DataStreamResult<()>;
+
+    /// Check that the brokers are reachable
+    async fn check_connectivity(&self) -> DataStreamResult<()>;
+}
+
+/// `KafkaConsumer` backed by an rdkafka `StreamConsumer`
+pub struct RdKafkaConsumer {
+    consumer: Arc<StreamConsumer>,
+    config: KafkaSourceConfig,
+}
+
+impl Debug for RdKafkaConsumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RdKafkaConsumer").field("config", &self.config).finish()
+    }
+}
+
+impl RdKafkaConsumer {
+    /// Create a consumer assigned to the configured topic and partition
+    pub fn new(config: KafkaSourceConfig) -> DataStreamResult<Self> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", &config.group_id)
+            .set("enable.auto.commit", "false")
+            .create()
+            .map_err(|e| DataStreamError::ConfigError(format!("Failed to create Kafka consumer: {}", e)))?;
+
+        let mut assignment = TopicPartitionList::new();
+        assignment
+            .add_partition_offset(&config.topic, config.partition, Offset::Beginning)
+            .map_err(|e| DataStreamError::ConfigError(format!("Invalid Kafka partition: {}", e)))?;
+        consumer
+            .assign(&assignment)
+            .map_err(|e| DataStreamError::ConnectionError(format!("Failed to assign Kafka partition: {}", e)))?;
+
+        Ok(Self {
+            consumer: Arc::new(consumer),
+            config,
+        })
+    }
+}
+
+#[async_trait]
+impl KafkaConsumer for RdKafkaConsumer {
+    async fn recv(&self) -> DataStreamResult<Option<KafkaMessage>> {
+        match tokio::time::timeout(self.config.poll_timeout, self.consumer.recv()).await {
+            Ok(Ok(message)) => Ok(Some(KafkaMessage {
+                offset: message.offset(),
+                payload: message.payload().map(<[u8]>::to_vec).unwrap_or_default(),
+            })),
+            Ok(Err(e)) => Err(DataStreamError::CommunicationError(format!("Failed to receive Kafka message: {}", e))),
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn seek(&self, offset: i64) -> DataStreamResult<()> {
+        self.consumer
+            .seek(&self.config.topic, self.config.partition, Offset::Offset(offset), self.config.request_timeout)
+            .map_err(|e| DataStreamError::CommunicationError(format!("Failed to seek Kafka partition: {}", e)))
+    }
+
+    async fn check_connectivity(&self) -> DataStreamResult<()> {
+        let consumer = self.consumer.clone();
+        let topic = self.config.topic.clone();
+        let timeout = self.config.request_timeout;
+
+        tokio::task::spawn_blocking(move || consumer.fetch_metadata(Some(&topic), timeout))
+            .await
+            .map_err(|e| DataStreamError::InternalError(format!("Kafka metadata task failed: {}", e)))?
+            .map_err(|e| DataStreamError::ConnectionError(format!("Failed to reach Kafka brokers: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Kafka implementation of `BatchSource`
+///
+/// Each message payload is a JSON-encoded `Batch`. The partition offset of the
+/// last consumed message is recorded in the checkpoint metadata.
+#[derive(Debug)]
+pub struct KafkaBatchSource<C: KafkaConsumer = RdKafkaConsumer> {
+    config: KafkaSourceConfig,
+    consumer: Arc<C>,
+    current_checkpoint: Option<Checkpoint>,
+}
+
+impl KafkaBatchSource<RdKafkaConsumer> {
+    /// Create a new Kafka batch source connected to the configured brokers
+    pub fn new(config: KafkaSourceConfig) -> DataStreamResult<Self> {
+        info!(target: "cdk::datastream::kafka", brokers = %config.brokers, topic = %config.topic, partition = config.partition, "Creating Kafka batch source");
+        let consumer = RdKafkaConsumer::new(config.clone())?;
+        Ok(Self::with_consumer(config, consumer))
+    }
+}
+
+impl<C: KafkaConsumer> KafkaBatchSource<C> {
+    /// Create a new Kafka batch source using the given consumer
+    pub fn with_consumer(config: KafkaSourceConfig, consumer: C) -> Self {
+        Self {
+            config,
+            consumer: Arc::new(consumer),
+            current_checkpoint: None,
+        }
+    }
+
+    /// Decode a message payload into a batch
+    fn decode_message(message: &KafkaMessage) -> DataStreamResult<Batch> {
+        serde_json::from_slice(&message.payload).map_err(|e| {
+            DataStreamError::DeserializationError(format!(
+                "Failed to deserialize batch at offset {}: {}",
+                message.offset, e
+            ))
+        })
+    }
+
+    /// Describe this source, identified by its brokers, topic and partition
+    fn source_metadata(&self) -> SourceMetadata {
+        SourceMetadata::new(
+            "Kafka Source".to_string(),
+            "1.0".to_string(),
+            format!("kafka://{}/{}/{}", self.config.brokers, self.config.topic, self.config.partition),
+            true,
+        )
+        .with_chain_id(self.config.chain_id)
+    }
+
+    /// Build a checkpoint for a batch consumed at `offset`
+    fn checkpoint_for(&self, batch: &Batch, offset: i64) -> Checkpoint {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Checkpoint::from_batch(batch, timestamp)
+            .with_source(&self.source_metadata())
+            .with_metadata(KAFKA_TOPIC_KEY.to_string(), self.config.topic.clone())
+            .with_metadata(KAFKA_PARTITION_KEY.to_string(), self.config.partition.to_string())
+            .with_metadata(KAFKA_OFFSET_KEY.to_string(), offset.to_string())
+    }
+}
+
+#[async_trait]
+impl<C: KafkaConsumer> BatchSource for KafkaBatchSource<C> {
+    async fn next(&mut self) -> Result<Option<Batch>, crate::DatastreamError> {
+        let Some(message) = self.consumer.recv().await? else {
+            debug!(target: "cdk::datastream::kafka", "No new Kafka messages available");
+            return Ok(None);
+        };
+
+        let batch = Self::decode_message(&message)?;
+        self.current_checkpoint = Some(self.checkpoint_for(&batch, message.offset));
+
+        info!(target: "cdk::datastream::kafka", batch_number = %batch.id.number, offset = message.offset, "Received batch from Kafka");
+        Ok(Some(batch))
+    }
+
+    async fn checkpoint(&self) -> Result<Checkpoint, crate::DatastreamError> {
+        self.current_checkpoint
+            .clone()
+            .ok_or_else(|| DataStreamError::CheckpointError("No checkpoint available".to_string()))
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> Result<(), crate::DatastreamError> {
+        checkpoint.ensure_compatible_with(&self.source_metadata())?;
+        if let Some(offset) = checkpoint.get_metadata(KAFKA_OFFSET_KEY) {
+            let offset: i64 = offset
+                .parse()
+                .map_err(|e| DataStreamError::CheckpointError(format!("Invalid Kafka offset {}: {}", offset, e)))?;
+            self.consumer.seek(offset + 1).await?;
+        }
+
+        debug!(target: "cdk::datastream::kafka", batch_number = %checkpoint.last_batch_id, "Setting Kafka checkpoint");
+        self.current_checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), crate::DatastreamError> {
+        self.consumer.check_connectivity().await
+    }
+
+    async fn metadata(&self) -> Result<SourceMetadata, crate::DatastreamError> {
+        Ok(self.source_metadata())
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        info!(target: "cdk::datastream::kafka", topic = %self.config.topic, start_batch_number = ?start_batch_number, "Fetching batch stream from Kafka");
+        let consumer = self.consumer.clone();
+        let idle_backoff = self.config.idle_backoff;
+
+        let stream = async_stream::stream! {
+            loop {
+                match consumer.recv().await {
+                    Ok(Some(message)) => match Self::decode_message(&message) {
+                        Ok(batch) => {
+                            if start_batch_number.is_some_and(|start| batch.id.number < alloy_primitives::U256::from(start)) {
+                                continue;
+                            }
+                            yield Ok(batch);
+                        }
+                        Err(e) => {
+                            error!(target: "cdk::datastream::kafka", error = %e, offset = message.offset, "Failed to deserialize batch from Kafka message");
+                            yield Err(e);
+                        }
+                    },
+                    Ok(None) => {
+                        debug!(target: "cdk::datastream::kafka", "No new Kafka messages available, backing off");
+                        tokio::time::sleep(idle_backoff).await;
+                    }
+                    Err(e) => {
+                        error!(target: "cdk::datastream::kafka", error = %e, "Kafka consumer error");
+                        yield Err(e);
+                        break;
+                    }
+                }
+            }
+        };
+        Ok(Box::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use futures::StreamExt;
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+    };
+
+    #[derive(Debug, Default)]
+    struct MockConsumer {
+        messages: Mutex<VecDeque<KafkaMessage>>,
+        polls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl KafkaConsumer for MockConsumer {
+        async fn recv(&self) -> DataStreamResult<Option<KafkaMessage>> {
+            self.polls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.messages.lock().unwrap().pop_front())
+        }
+
+        async fn seek(&self, offset: i64) -> DataStreamResult<()> {
+            self.messages.lock().unwrap().retain(|message| message.offset >= offset);
+            Ok(())
+        }
+
+        async fn check_connectivity(&self) -> DataStreamResult<()> {
+            Ok(())
+        }
+    }
+
+    fn batch_message(number: u64, offset: i64) -> KafkaMessage {
+        let batch = Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        );
+
+        KafkaMessage {
+            offset,
+            payload: serde_json::to_vec(&batch).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kafka_source_consumes_messages() {
+        let consumer = MockConsumer::default();
+        consumer.messages.lock().unwrap().extend([batch_message(1, 10), batch_message(2, 11)]);
+        let mut source = KafkaBatchSource::with_consumer(KafkaSourceConfig::default(), consumer);
+
+        let first = source.next().await.unwrap().unwrap();
+        assert_eq!(first.id.number, U256::from(1));
+        let first_offset: i64 = source.checkpoint().await.unwrap().get_metadata(KAFKA_OFFSET_KEY).unwrap().parse().unwrap();
+
+        let second = source.next().await.unwrap().unwrap();
+        assert_eq!(second.id.number, U256::from(2));
+        let checkpoint = source.checkpoint().await.unwrap();
+        let second_offset: i64 = checkpoint.get_metadata(KAFKA_OFFSET_KEY).unwrap().parse().unwrap();
+
+        assert_eq!(first_offset, 10);
+        assert!(second_offset > first_offset);
+        assert_eq!(checkpoint.last_batch_id, U256::from(2));
+        assert!(source.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_batch_stream_backs_off_when_idle() {
+        let consumer = MockConsumer::default();
+        let polls = consumer.polls.clone();
+        let config = KafkaSourceConfig {
+            idle_backoff: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let source = KafkaBatchSource::with_consumer(config, consumer);
+        let mut stream = source.fetch_batch_stream(None).await.unwrap();
+
+        let result = tokio::time::timeout(Duration::from_millis(350), stream.next()).await;
+
+        assert!(result.is_err());
+        // Polls at 0ms, 100ms, 200ms and 300ms before the timeout fires
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -11,6 +11,14 @@ pub mod source;
 pub mod websocket_source;
 pub mod grpc_source;
 pub mod filesystem_source;
+pub mod epoch_source;
+pub mod verifier;
+pub mod buffered_source;
+pub mod retry_source;
+pub mod timeout_source;
+pub mod failover_source;
+#[cfg(feature = "kafka")]
+pub mod kafka_source;
 
 pub use checkpoint::*;
 pub use error::*;
@@ -19,3 +27,11 @@ pub use source::*;
 pub use websocket_source::*;
 pub use grpc_source::*;
 pub use filesystem_source::*;
+pub use epoch_source::*;
+pub use verifier::*;
+pub use buffered_source::*;
+pub use retry_source::*;
+pub use timeout_source::*;
+pub use failover_source::*;
+#[cfg(feature = "kafka")]
+pub use kafka_source::*;
@@ -0,0 +1,260 @@
+//! Batch source decorator retrying transient failures
+
+use crate::{BatchSource, BatchStream, Checkpoint, DataStreamError, DataStreamResult, SourceMetadata};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use std::time::Duration;
+use tracing::warn;
+
+/// Decides whether a failed call is worth retrying
+pub type RetryPredicate = fn(&DataStreamError) -> bool;
+
+/// Retry settings for `RetryingSource`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Total number of attempts per call, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound for the retry delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Exponential backoff delay before the given retry (starting at 1)
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Default retry predicate: network and availability failures are retried,
+/// malformed data and configuration errors are not
+pub fn is_retriable(error: &DataStreamError) -> bool {
+    match error {
+        DataStreamError::NetworkError(_)
+        | DataStreamError::ConnectionError(_)
+        | DataStreamError::CommunicationError(_)
+        | DataStreamError::TimeoutError(_)
+        | DataStreamError::Timeout { .. }
+        | DataStreamError::SourceUnavailable(_)
+        | DataStreamError::IoError(_) => true,
+        DataStreamError::HttpError { status, .. } => *status == 429 || *status >= 500,
+        _ => false,
+    }
+}
+
+/// Wraps a batch source and retries `next` and `health_check` on transient errors
+///
+/// Each call is attempted up to `max_attempts` times, waiting with exponential
+/// backoff between attempts. Errors rejected by the predicate are returned
+/// immediately. All other calls are forwarded unchanged.
+#[derive(Debug)]
+pub struct RetryingSource<S> {
+    inner: S,
+    config: RetryConfig,
+    retriable: RetryPredicate,
+}
+
+impl<S: BatchSource> RetryingSource<S> {
+    /// Wrap `inner`, retrying errors accepted by `is_retriable`
+    pub fn new(inner: S, config: RetryConfig) -> Self {
+        Self {
+            inner,
+            config,
+            retriable: is_retriable,
+        }
+    }
+
+    /// Set the predicate deciding which errors are retried
+    pub fn with_predicate(mut self, retriable: RetryPredicate) -> Self {
+        self.retriable = retriable;
+        self
+    }
+
+    /// Get the inner source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the retry settings
+    pub fn config(&self) -> &RetryConfig {
+        &self.config
+    }
+
+    /// Delay before retrying after `error` on the given attempt, or `None` to give up
+    fn backoff(&self, operation: &str, error: &DataStreamError, attempt: u32) -> Option<Duration> {
+        if attempt >= self.config.max_attempts || !(self.retriable)(error) {
+            return None;
+        }
+        let delay = self.config.retry_delay(attempt);
+        warn!(
+            target: "cdk::datastream::retry",
+            attempt,
+            max_attempts = self.config.max_attempts,
+            ?delay,
+            "{} failed, retrying: {}",
+            operation,
+            error
+        );
+        Some(delay)
+    }
+}
+
+#[async_trait]
+impl<S: BatchSource> BatchSource for RetryingSource<S> {
+    async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+        let mut attempt = 1;
+        loop {
+            match self.inner.next().await {
+                Ok(batch) => return Ok(batch),
+                Err(e) => match self.backoff("next", &e, attempt) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+        self.inner.checkpoint().await
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+        self.inner.set_checkpoint(checkpoint).await
+    }
+
+    async fn health_check(&self) -> DataStreamResult<()> {
+        let mut attempt = 1;
+        loop {
+            match self.inner.health_check().await {
+                Ok(()) => return Ok(()),
+                Err(e) => match self.backoff("health_check", &e, attempt) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+        self.inner.metadata().await
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        self.inner.fetch_batch_stream(start_batch_number).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use std::collections::VecDeque;
+
+    /// Source replaying a fixed sequence of results from `next`
+    #[derive(Debug)]
+    struct FlakySource {
+        results: VecDeque<DataStreamResult<Option<Batch>>>,
+        calls: usize,
+    }
+
+    #[async_trait]
+    impl BatchSource for FlakySource {
+        async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+            self.calls += 1;
+            self.results.pop_front().unwrap_or(Ok(None))
+        }
+
+        async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+            Ok(Checkpoint::default())
+        }
+
+        async fn set_checkpoint(&mut self, _checkpoint: Checkpoint) -> DataStreamResult<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> DataStreamResult<()> {
+            Ok(())
+        }
+
+        async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+            Ok(SourceMetadata::new("flaky".to_string(), "1.0".to_string(), "test".to_string(), false))
+        }
+
+        async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+            Err(DataStreamError::InternalError("Not supported".to_string()))
+        }
+    }
+
+    fn test_config() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    fn test_batch() -> Batch {
+        Batch::new(
+            BatchId::new(U256::from(1), FixedBytes::from([1u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_next_retries_transient_errors() {
+        let inner = FlakySource {
+            results: VecDeque::from([
+                Err(DataStreamError::NetworkError("reset".to_string())),
+                Err(DataStreamError::ConnectionError("refused".to_string())),
+                Ok(Some(test_batch())),
+            ]),
+            calls: 0,
+        };
+        let mut source = RetryingSource::new(inner, test_config());
+
+        assert_eq!(source.next().await.unwrap(), Some(test_batch()));
+        assert_eq!(source.inner().calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_next_returns_permanent_errors_immediately() {
+        let inner = FlakySource {
+            results: VecDeque::from([
+                Err(DataStreamError::DeserializationError("bad json".to_string())),
+                Ok(Some(test_batch())),
+            ]),
+            calls: 0,
+        };
+        let mut source = RetryingSource::new(inner, test_config());
+
+        assert!(matches!(source.next().await, Err(DataStreamError::DeserializationError(_))));
+        assert_eq!(source.inner().calls, 1);
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        let config = test_config();
+        assert_eq!(config.retry_delay(1), Duration::from_millis(1));
+        assert_eq!(config.retry_delay(2), Duration::from_millis(2));
+        assert_eq!(config.retry_delay(10), Duration::from_millis(5));
+    }
+}
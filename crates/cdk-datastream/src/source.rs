@@ -3,17 +3,177 @@
 use cdk_types::Batch;
 use crate::{Checkpoint, DatastreamError};
 use async_trait::async_trait;
-use std::fmt::Debug;
-use futures::Stream;
+use std::{fmt::Debug, sync::Mutex};
+use futures::{Stream, StreamExt};
+
+/// Batch envelope schema version understood by this crate
+///
+/// Serialized batches may carry a top-level `version` field; batches without
+/// one are assumed to be at this version.
+pub const SUPPORTED_BATCH_VERSION: u32 = 1;
+
+/// Decode a JSON batch envelope, rejecting unsupported schema versions
+pub fn decode_batch(bytes: &[u8]) -> Result<Batch, DatastreamError> {
+    let value: serde_json::Value = serde_json::from_slice(bytes)
+        .map_err(|e| DatastreamError::DeserializationError(format!("Invalid batch JSON: {}", e)))?;
+    decode_batch_value(value)
+}
+
+/// Decode an already parsed JSON batch envelope, rejecting unsupported schema versions
+pub fn decode_batch_value(value: serde_json::Value) -> Result<Batch, DatastreamError> {
+    if let Some(version) = value.get("version") {
+        let got = version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| DatastreamError::DeserializationError(format!("Invalid batch version: {}", version)))?;
+        if got != SUPPORTED_BATCH_VERSION {
+            return Err(DatastreamError::VersionMismatch {
+                expected: SUPPORTED_BATCH_VERSION,
+                got,
+            });
+        }
+    }
+
+    serde_json::from_value(value)
+        .map_err(|e| DatastreamError::DeserializationError(format!("Failed to deserialize batch: {}", e)))
+}
 
 /// Stream of batches
 pub type BatchStream = Box<dyn Stream<Item = Result<Batch, DatastreamError>> + Send + Unpin>;
 
+/// Item of an event stream: a batch, or a run of batches the source skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// The next batch
+    Batch(Box<Batch>),
+    /// Batches `from..=to` are missing before the next batch
+    Gap { from: u64, to: u64 },
+}
+
+/// Stream of batches with explicit gap markers
+pub type EventStream = Box<dyn Stream<Item = Result<StreamEvent, DatastreamError>> + Send + Unpin>;
+
+/// Turn a batch stream into an event stream, marking jumps in batch numbers with `StreamEvent::Gap`
+///
+/// The first batch is expected to be `start_batch_number`, when given.
+pub fn with_gaps(mut batches: BatchStream, start_batch_number: Option<u64>) -> EventStream {
+    let stream = async_stream::stream! {
+        let mut expected = start_batch_number;
+        while let Some(result) = batches.next().await {
+            let batch = match result {
+                Ok(batch) => batch,
+                Err(e) => {
+                    yield Err(e);
+                    continue;
+                }
+            };
+            let number = batch.id.number.saturating_to::<u64>();
+            if let Some(from) = expected.filter(|&from| number > from) {
+                yield Ok(StreamEvent::Gap { from, to: number - 1 });
+            }
+            expected = Some(number.saturating_add(1));
+            yield Ok(StreamEvent::Batch(Box::new(batch)));
+        }
+    };
+    Box::new(Box::pin(stream))
+}
+
+/// Lazily opened batch stream backing the default `BatchSource::next`
+///
+/// The stream is only ever accessed through `&mut self`; the mutex exists to
+/// make the cursor `Sync` so sources holding it stay `Sync`.
+#[derive(Default)]
+pub struct BatchStreamCursor {
+    stream: Mutex<Option<BatchStream>>,
+}
+
+impl BatchStreamCursor {
+    /// Whether the underlying stream has been opened
+    pub fn is_open(&mut self) -> bool {
+        self.stream_mut().is_some()
+    }
+
+    /// Replace the underlying stream
+    pub fn open(&mut self, stream: BatchStream) {
+        *self.stream_mut() = Some(stream);
+    }
+
+    /// Drop the underlying stream so the next call reopens it
+    pub fn reset(&mut self) {
+        *self.stream_mut() = None;
+    }
+
+    /// Advance the underlying stream by one batch
+    ///
+    /// Returns `Ok(None)` when the cursor is not open or the stream has ended.
+    pub async fn next(&mut self) -> Result<Option<Batch>, DatastreamError> {
+        match self.stream_mut().as_mut() {
+            Some(stream) => stream.next().await.transpose(),
+            None => Ok(None),
+        }
+    }
+
+    fn stream_mut(&mut self) -> &mut Option<BatchStream> {
+        self.stream.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Debug for BatchStreamCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchStreamCursor").finish_non_exhaustive()
+    }
+}
+
 /// A source that can provide batches of data
+///
+/// Sources either implement `next` natively, or implement only
+/// `fetch_batch_stream` and expose a `BatchStreamCursor` through
+/// `stream_cursor`; the default `next` then opens the stream on first use,
+/// starting after the current checkpoint, advances it one batch per call and
+/// hands each batch to `advance_checkpoint`.
 #[async_trait]
 pub trait BatchSource: Send + Sync + Debug {
     /// Get the next batch from the source
-    async fn next(&mut self) -> Result<Option<Batch>, DatastreamError>;
+    ///
+    /// Returns `Ok(None)` when no batch is currently available.
+    async fn next(&mut self) -> Result<Option<Batch>, DatastreamError> {
+        let needs_open = match self.stream_cursor() {
+            Some(cursor) => !cursor.is_open(),
+            None => {
+                return Err(DatastreamError::InternalError(
+                    "Source implements neither next() nor stream_cursor()".to_string(),
+                ))
+            }
+        };
+
+        if needs_open {
+            let start_batch_number = self.checkpoint().await.ok().and_then(|checkpoint| checkpoint.next_batch_number());
+            let stream = self.fetch_batch_stream(start_batch_number).await?;
+            if let Some(cursor) = self.stream_cursor() {
+                cursor.open(stream);
+            }
+        }
+
+        let batch = match self.stream_cursor() {
+            Some(cursor) => cursor.next().await?,
+            None => None,
+        };
+        if let Some(batch) = &batch {
+            self.advance_checkpoint(batch);
+        }
+        Ok(batch)
+    }
+
+    /// Cursor used by the default `next` implementation
+    ///
+    /// Stream-only sources return their cursor here; sources that override
+    /// `next` can keep the default.
+    fn stream_cursor(&mut self) -> Option<&mut BatchStreamCursor> {
+        None
+    }
+
+    /// Record `batch` as the last one read by the default `next` implementation
+    fn advance_checkpoint(&mut self, _batch: &Batch) {}
 
     /// Get the current checkpoint for resumable ingestion
     async fn checkpoint(&self) -> Result<Checkpoint, DatastreamError>;
@@ -29,6 +189,12 @@ pub trait BatchSource: Send + Sync + Debug {
 
     /// Fetch a stream of batches starting from a specific batch number
     async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> Result<BatchStream, DatastreamError>;
+
+    /// Fetch a stream of batches starting from a specific batch number, with
+    /// a `StreamEvent::Gap` wherever batch numbers are not contiguous
+    async fn fetch_event_stream(&self, start_batch_number: Option<u64>) -> Result<EventStream, DatastreamError> {
+        Ok(with_gaps(self.fetch_batch_stream(start_batch_number).await?, start_batch_number))
+    }
 }
 
 /// Metadata about a data source
@@ -46,6 +212,9 @@ pub struct SourceMetadata {
     pub max_batch_size: Option<u32>,
     /// Whether the source is currently available
     pub available: bool,
+    /// Chain id of the batches served by the source (0 if unknown)
+    #[serde(default)]
+    pub chain_id: u64,
 }
 
 impl SourceMetadata {
@@ -63,6 +232,7 @@ impl SourceMetadata {
             supports_checkpoints,
             max_batch_size: None,
             available: true,
+            chain_id: 0,
         }
     }
 
@@ -77,4 +247,23 @@ impl SourceMetadata {
         self.available = available;
         self
     }
+
+    /// Set the chain id served by the source
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Fail with `DatastreamError::ChainIdMismatch` if the source serves another chain than `expected`
+    ///
+    /// A source not reporting its chain id (0) is accepted.
+    pub fn ensure_chain_id(&self, expected: u64) -> Result<(), DatastreamError> {
+        if self.chain_id == 0 || self.chain_id == expected {
+            return Ok(());
+        }
+        Err(DatastreamError::ChainIdMismatch {
+            expected,
+            actual: self.chain_id,
+        })
+    }
 }
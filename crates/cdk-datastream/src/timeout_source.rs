@@ -0,0 +1,139 @@
+//! Batch source decorator bounding the duration of each call
+
+use crate::{BatchSource, BatchStream, Checkpoint, DataStreamError, DataStreamResult, SourceMetadata};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use std::{future::Future, time::Duration};
+
+/// Wraps a batch source and fails `next` and `health_check` calls that take too long
+///
+/// A call exceeding the timeout is dropped and returns
+/// `DataStreamError::Timeout`. All other calls are forwarded unchanged.
+#[derive(Debug)]
+pub struct TimeoutSource<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S: BatchSource> TimeoutSource<S> {
+    /// Wrap `inner`, bounding each call to `timeout`
+    pub fn new(inner: S, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    /// Get the inner source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the per-call timeout
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+/// Run `call`, failing with `DataStreamError::Timeout` once `timeout` elapses
+async fn with_timeout<T>(
+    operation: &str,
+    timeout: Duration,
+    call: impl Future<Output = DataStreamResult<T>>,
+) -> DataStreamResult<T> {
+    tokio::time::timeout(timeout, call)
+        .await
+        .unwrap_or_else(|_| Err(DataStreamError::Timeout { operation: operation.to_string(), timeout }))
+}
+
+#[async_trait]
+impl<S: BatchSource> BatchSource for TimeoutSource<S> {
+    async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+        with_timeout("next", self.timeout, self.inner.next()).await
+    }
+
+    async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+        self.inner.checkpoint().await
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+        self.inner.set_checkpoint(checkpoint).await
+    }
+
+    async fn health_check(&self) -> DataStreamResult<()> {
+        with_timeout("health_check", self.timeout, self.inner.health_check()).await
+    }
+
+    async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+        self.inner.metadata().await
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        self.inner.fetch_batch_stream(start_batch_number).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Source whose `next` and `health_check` take `delay` to complete
+    #[derive(Debug)]
+    struct SlowSource {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl BatchSource for SlowSource {
+        async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+            tokio::time::sleep(self.delay).await;
+            Ok(None)
+        }
+
+        async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+            Ok(Checkpoint::default())
+        }
+
+        async fn set_checkpoint(&mut self, _checkpoint: Checkpoint) -> DataStreamResult<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> DataStreamResult<()> {
+            tokio::time::sleep(self.delay).await;
+            Ok(())
+        }
+
+        async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+            Ok(SourceMetadata::new("slow".to_string(), "1.0".to_string(), "test".to_string(), false))
+        }
+
+        async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+            Err(DataStreamError::InternalError("Not supported".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_calls_time_out() {
+        let inner = SlowSource {
+            delay: Duration::from_secs(5),
+        };
+        let mut source = TimeoutSource::new(inner, Duration::from_millis(20));
+
+        match source.next().await {
+            Err(DataStreamError::Timeout { operation, timeout }) => {
+                assert_eq!(operation, "next");
+                assert_eq!(timeout, Duration::from_millis(20));
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        assert!(matches!(source.health_check().await, Err(DataStreamError::Timeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fast_calls_pass_through() {
+        let inner = SlowSource {
+            delay: Duration::from_millis(1),
+        };
+        let mut source = TimeoutSource::new(inner, Duration::from_secs(5));
+
+        assert!(source.next().await.unwrap().is_none());
+        assert!(source.health_check().await.is_ok());
+    }
+}
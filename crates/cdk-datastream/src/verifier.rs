@@ -0,0 +1,191 @@
+//! Integrity verification of batches yielded by a source
+
+use crate::{BatchSource, BatchStream, Checkpoint, DataStreamResult, SourceMetadata};
+use async_trait::async_trait;
+use cdk_types::Batch;
+use futures::StreamExt;
+use std::{fmt::Debug, sync::Arc};
+use tracing::warn;
+
+/// Checks the integrity of a batch before it is handed to consumers
+pub trait BatchVerifier: Send + Sync + Debug {
+    /// Return an error if the batch must not be ingested
+    fn verify(&self, batch: &Batch) -> DataStreamResult<()>;
+}
+
+/// Verifier accepting every batch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopBatchVerifier;
+
+impl BatchVerifier for NoopBatchVerifier {
+    fn verify(&self, _batch: &Batch) -> DataStreamResult<()> {
+        Ok(())
+    }
+}
+
+/// What to do with a batch that fails verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerificationFailurePolicy {
+    /// Skip the batch and keep reading
+    Drop,
+    /// Surface the verification error to the consumer
+    #[default]
+    Error,
+}
+
+/// Wraps a batch source and verifies every batch it yields
+///
+/// Applies to both `next` and `fetch_batch_stream`; checkpoints, health
+/// checks and metadata are forwarded to the inner source.
+#[derive(Debug)]
+pub struct VerifyingSource<S> {
+    inner: S,
+    verifier: Arc<dyn BatchVerifier>,
+    policy: VerificationFailurePolicy,
+}
+
+impl<S: BatchSource> VerifyingSource<S> {
+    /// Wrap `inner`, verifying its batches with `verifier`
+    pub fn new(inner: S, verifier: Arc<dyn BatchVerifier>, policy: VerificationFailurePolicy) -> Self {
+        Self { inner, verifier, policy }
+    }
+
+    /// Get the inner source
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Apply the failure policy to a batch, returning `None` if it is dropped
+    fn check(
+        verifier: &dyn BatchVerifier,
+        policy: VerificationFailurePolicy,
+        batch: Batch,
+    ) -> Option<DataStreamResult<Batch>> {
+        match verifier.verify(&batch) {
+            Ok(()) => Some(Ok(batch)),
+            Err(e) if policy == VerificationFailurePolicy::Drop => {
+                warn!(target: "cdk::datastream::verifier", batch = %batch.id.number, error = %e, "Dropping batch that failed verification");
+                None
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: BatchSource> BatchSource for VerifyingSource<S> {
+    async fn next(&mut self) -> DataStreamResult<Option<Batch>> {
+        while let Some(batch) = self.inner.next().await? {
+            if let Some(result) = Self::check(self.verifier.as_ref(), self.policy, batch) {
+                return result.map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    async fn checkpoint(&self) -> DataStreamResult<Checkpoint> {
+        self.inner.checkpoint().await
+    }
+
+    async fn set_checkpoint(&mut self, checkpoint: Checkpoint) -> DataStreamResult<()> {
+        self.inner.set_checkpoint(checkpoint).await
+    }
+
+    async fn health_check(&self) -> DataStreamResult<()> {
+        self.inner.health_check().await
+    }
+
+    async fn metadata(&self) -> DataStreamResult<SourceMetadata> {
+        self.inner.metadata().await
+    }
+
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        let verifier = self.verifier.clone();
+        let policy = self.policy;
+        let stream = self
+            .inner
+            .fetch_batch_stream(start_batch_number)
+            .await?
+            .filter_map(move |result| {
+                let checked = match result {
+                    Ok(batch) => Self::check(verifier.as_ref(), policy, batch),
+                    Err(e) => Some(Err(e)),
+                };
+                futures::future::ready(checked)
+            })
+            .boxed();
+        Ok(Box::new(stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataStreamError, FilesystemSource, FilesystemSourceConfig};
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use futures::TryStreamExt;
+
+    /// Accepts batches whose hash matches the fixture hash for their number
+    #[derive(Debug)]
+    struct FixtureHashVerifier;
+
+    impl BatchVerifier for FixtureHashVerifier {
+        fn verify(&self, batch: &Batch) -> DataStreamResult<()> {
+            let number = batch.id.number.saturating_to::<u64>();
+            if batch.id.hash == FixedBytes::from([number as u8; 32]) {
+                Ok(())
+            } else {
+                Err(DataStreamError::InvalidBatchData(format!("Batch {} hash mismatch", number)))
+            }
+        }
+    }
+
+    fn write_batch(dir: &std::path::Path, number: u64, hash: [u8; 32]) {
+        let batch = Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from(hash)),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        );
+        let path = dir.join(format!("batch_{:06}.json", number));
+        std::fs::write(path, serde_json::to_vec(&batch).unwrap()).unwrap();
+    }
+
+    fn source_with_tampered_batch(dir: &std::path::Path, policy: VerificationFailurePolicy) -> VerifyingSource<FilesystemSource> {
+        write_batch(dir, 1, [1u8; 32]);
+        write_batch(dir, 2, [0xff; 32]);
+        write_batch(dir, 3, [3u8; 32]);
+
+        let source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.to_path_buf(),
+            ..Default::default()
+        });
+        VerifyingSource::new(source, Arc::new(FixtureHashVerifier), policy)
+    }
+
+    #[tokio::test]
+    async fn test_tampered_batch_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut source = source_with_tampered_batch(dir.path(), VerificationFailurePolicy::Drop);
+
+        let streamed: Vec<_> = source.fetch_batch_stream(None).await.unwrap().try_collect().await.unwrap();
+        let numbers: Vec<_> = streamed.iter().map(|batch| batch.id.number.saturating_to::<u64>()).collect();
+        assert_eq!(numbers, vec![1, 3]);
+
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(1));
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(3));
+        assert!(source.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_batch_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut source = source_with_tampered_batch(dir.path(), VerificationFailurePolicy::Error);
+
+        assert_eq!(source.next().await.unwrap().unwrap().id.number, U256::from(1));
+        assert!(matches!(source.next().await, Err(DataStreamError::InvalidBatchData(_))));
+    }
+}
@@ -2,107 +2,372 @@
 
 use crate::{
     error::{DataStreamError, DataStreamResult},
-    source::{BatchSource, BatchStream},
+    source::{BatchSource, BatchStream, BatchStreamCursor},
+    Checkpoint, SourceMetadata,
 };
 use async_trait::async_trait;
 use cdk_types::Batch;
 use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::{connect_async, tungstenite::Message, WebSocketStream};
 use url::Url;
-use tracing::{debug, info, error};
+use std::time::Duration;
+use tracing::{debug, info, error, warn};
+
+type WsStream = WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// How batches are framed in binary WebSocket messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// Each message holds exactly one JSON batch
+    #[default]
+    OnePerMessage,
+    /// Messages carry a byte stream of batches, each a 4-byte big-endian length
+    /// followed by that many bytes of JSON; a batch may span several messages
+    LengthPrefixed,
+}
+
+/// Length of the big-endian batch length preceding each length-prefixed batch
+const LENGTH_PREFIX_BYTES: usize = 4;
+
+/// Reassembles length-prefixed batches from bytes received across messages
+#[derive(Debug)]
+struct FrameBuffer {
+    bytes: Vec<u8>,
+    max_frame_bytes: usize,
+}
+
+impl FrameBuffer {
+    /// Create a buffer rejecting batches longer than `max_frame_bytes`
+    fn new(max_frame_bytes: usize) -> Self {
+        Self {
+            bytes: Vec::new(),
+            max_frame_bytes,
+        }
+    }
+
+    /// Append received bytes and take every batch payload now complete
+    ///
+    /// Fails with `DataStreamError::DeserializationError` on a length prefix
+    /// above the limit, before buffering the batch it announces.
+    fn push(&mut self, bytes: &[u8]) -> DataStreamResult<Vec<Vec<u8>>> {
+        self.bytes.extend_from_slice(bytes);
+
+        let mut frames = Vec::new();
+        let mut start = 0;
+        while let Some(prefix) = self.bytes.get(start..start + LENGTH_PREFIX_BYTES) {
+            let len = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+            if len > self.max_frame_bytes {
+                return Err(DataStreamError::DeserializationError(format!(
+                    "Batch frame of {} bytes exceeds the limit of {} bytes",
+                    len, self.max_frame_bytes
+                )));
+            }
+            let end = start + LENGTH_PREFIX_BYTES + len;
+            if self.bytes.len() < end {
+                break;
+            }
+            frames.push(self.bytes[start + LENGTH_PREFIX_BYTES..end].to_vec());
+            start = end;
+        }
+        self.bytes.drain(..start);
+        Ok(frames)
+    }
+
+    /// Drop any partially received batch
+    fn clear(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+/// Schedules keepalive pings on a connection and detects unanswered ones
+#[derive(Debug)]
+struct Keepalive {
+    interval: Option<tokio::time::Interval>,
+    awaiting_pong: bool,
+}
+
+impl Keepalive {
+    /// Start the schedule, with the first ping due one `period` from now
+    fn new(period: Option<Duration>) -> Self {
+        let interval = period.map(|period| {
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+        Self {
+            interval,
+            awaiting_pong: false,
+        }
+    }
+
+    /// Wait until the next ping is due
+    ///
+    /// Returns `false` if the previous ping is still unanswered, in which case
+    /// the connection should be considered lost. Never completes when
+    /// keepalive is disabled.
+    async fn due(&mut self) -> bool {
+        match self.interval.as_mut() {
+            Some(interval) => {
+                interval.tick().await;
+            }
+            None => std::future::pending().await,
+        }
+        if self.awaiting_pong {
+            return false;
+        }
+        self.awaiting_pong = true;
+        true
+    }
+
+    /// Record the pong answering the last ping
+    fn pong_received(&mut self) {
+        self.awaiting_pong = false;
+    }
+}
 
 /// Configuration for the WebSocket batch source
 #[derive(Debug, Clone)]
 pub struct WebSocketSourceConfig {
     /// The URL of the WebSocket endpoint
     pub url: Url,
+    /// Delay before the first reconnection attempt
+    pub reconnect_base_delay: Duration,
+    /// Upper bound for the reconnection delay
+    pub reconnect_max_delay: Duration,
+    /// Number of consecutive failed reconnection attempts before the stream fails
+    pub max_reconnect_attempts: u32,
+    /// How batches are framed in binary messages
+    pub framing: Framing,
+    /// Largest length-prefixed batch accepted, in bytes
+    ///
+    /// A larger length prefix is reported as a deserialization error and the
+    /// connection is reopened.
+    pub max_frame_bytes: usize,
+    /// Interval between pings sent to keep an idle connection open
+    ///
+    /// A pong must arrive before the next ping is due, otherwise the
+    /// connection is considered lost and reopened.
+    pub keepalive_interval: Option<Duration>,
+    /// Chain id of the batches served (0 if unknown)
+    pub chain_id: u64,
+}
+
+impl WebSocketSourceConfig {
+    /// Create a configuration for the given URL with default reconnection settings
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            reconnect_base_delay: Duration::from_millis(500),
+            reconnect_max_delay: Duration::from_secs(30),
+            max_reconnect_attempts: 10,
+            framing: Framing::OnePerMessage,
+            max_frame_bytes: 16 * 1024 * 1024,
+            keepalive_interval: Some(Duration::from_secs(30)),
+            chain_id: 0,
+        }
+    }
+
+    /// Exponential backoff delay for the given reconnection attempt (starting at 1)
+    pub fn reconnect_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.reconnect_base_delay.saturating_mul(factor).min(self.reconnect_max_delay)
+    }
 }
 
 /// WebSocket implementation of `BatchSource`
 #[derive(Debug)]
 pub struct WebSocketSource {
     config: WebSocketSourceConfig,
+    cursor: BatchStreamCursor,
+    checkpoint: Option<Checkpoint>,
 }
 
 impl WebSocketSource {
     /// Create a new WebSocketSource
     pub fn new(config: WebSocketSourceConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cursor: BatchStreamCursor::default(),
+            checkpoint: None,
+        }
+    }
+
+    /// Describe this source, identified by its URL
+    fn source_metadata(&self) -> SourceMetadata {
+        SourceMetadata::new(
+            "WebSocket Source".to_string(),
+            "1.0".to_string(),
+            self.config.url.to_string(),
+            true,
+        )
+        .with_chain_id(self.config.chain_id)
     }
 
     /// Connect to the WebSocket and return the stream
-    async fn connect(&self) -> DataStreamResult<WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>> {
-        info!(target: "cdk::datastream::websocket", url = %self.config.url, "Connecting to WebSocket source");
-        let (ws_stream, _) = connect_async(&self.config.url)
+    async fn connect(url: &Url) -> DataStreamResult<WsStream> {
+        info!(target: "cdk::datastream::websocket", url = %url, "Connecting to WebSocket source");
+        let (ws_stream, _) = connect_async(url)
             .await
             .map_err(|e| DataStreamError::ConnectionError(format!("Failed to connect to WebSocket: {}", e)))?;
-        info!(target: "cdk::datastream::websocket", url = %self.config.url, "WebSocket connection established");
+        info!(target: "cdk::datastream::websocket", url = %url, "WebSocket connection established");
+        Ok(ws_stream)
+    }
+
+    /// Connect and subscribe to batches starting at `start_batch_number`
+    async fn subscribe(url: &Url, start_batch_number: Option<u64>) -> DataStreamResult<WsStream> {
+        let mut ws_stream = Self::connect(url).await?;
+
+        let params = match start_batch_number {
+            Some(start) => serde_json::json!([start]),
+            None => serde_json::json!([]),
+        };
+        let subscribe_msg = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "cdk_subscribeBatches",
+            "params": params,
+            "id": 1,
+        });
+        ws_stream
+            .send(Message::text(subscribe_msg.to_string()))
+            .await
+            .map_err(|e| DataStreamError::CommunicationError(format!("Failed to send subscription message: {}", e)))?;
         Ok(ws_stream)
     }
 }
 
 #[async_trait]
 impl BatchSource for WebSocketSource {
-    async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
-        let mut ws_stream = self.connect().await?;
-
-        // For demonstration, we'll just send a subscription message and then
-        // simulate receiving batches. In a real scenario, the protocol
-        // for requesting and receiving batches would be more complex.
-        let subscribe_msg = Message::text(r#"{"jsonrpc":"2.0","method":"cdk_subscribeBatches","params":[],"id":1}"#);
-        ws_stream.send(subscribe_msg).await.map_err(|e| DataStreamError::CommunicationError(format!("Failed to send subscription message: {}", e)))?;
+    async fn fetch_batch_stream(&self, start_batch_number: Option<u64>) -> DataStreamResult<BatchStream> {
+        let config = self.config.clone();
+        let mut ws_stream = Self::subscribe(&config.url, start_batch_number).await?;
 
         let stream = async_stream::stream! {
-            while let Some(msg) = ws_stream.next().await {
-                match msg {
-                    Ok(Message::Text(text)) => {
-                        debug!(target: "cdk::datastream::websocket", "Received WebSocket message: {}", text);
-                        // Attempt to parse the text as a Batch
-                        match serde_json::from_str::<Batch>(&text) {
-                            Ok(batch) => {
-                                info!(target: "cdk::datastream::websocket", batch_number = %batch.id.number, "Received batch from WebSocket");
-                                yield Ok(batch);
-                            },
-                            Err(e) => {
-                                error!(target: "cdk::datastream::websocket", error = %e, "Failed to deserialize batch from WebSocket message");
-                                yield Err(DataStreamError::DeserializationError(e.to_string()));
+            // Resume point for resubscription after a reconnect
+            let mut next_batch_number = start_batch_number;
+            let mut failed_attempts = 0u32;
+            let mut frames = FrameBuffer::new(config.max_frame_bytes);
+
+            'session: loop {
+                let mut keepalive = Keepalive::new(config.keepalive_interval);
+
+                loop {
+                    let msg = tokio::select! {
+                        // Handle a pong already received before deciding that a keepalive is overdue
+                        biased;
+                        msg = ws_stream.next() => msg,
+                        answered = keepalive.due() => {
+                            if !answered {
+                                warn!(target: "cdk::datastream::websocket", "No WebSocket pong before the next keepalive, dropping connection");
+                                break;
+                            }
+                            debug!(target: "cdk::datastream::websocket", "Sending WebSocket keepalive ping");
+                            if let Err(e) = ws_stream.send(Message::Ping(Vec::new())).await {
+                                error!(target: "cdk::datastream::websocket", error = %e, "Failed to send WebSocket ping");
+                                break;
                             }
+                            continue;
                         }
-                    },
-                    Ok(Message::Binary(bin)) => {
-                        debug!(target: "cdk::datastream::websocket", "Received WebSocket binary message of {} bytes", bin.len());
-                        // Attempt to parse binary as a Batch
-                        match serde_json::from_slice::<Batch>(&bin) {
-                            Ok(batch) => {
-                                info!(target: "cdk::datastream::websocket", batch_number = %batch.id.number, "Received batch from WebSocket (binary)");
-                                yield Ok(batch);
-                            },
-                            Err(e) => {
-                                error!(target: "cdk::datastream::websocket", error = %e, "Failed to deserialize batch from WebSocket binary message");
-                                yield Err(DataStreamError::DeserializationError(e.to_string()));
+                    };
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            debug!(target: "cdk::datastream::websocket", "Received WebSocket message: {}", text);
+                            // Attempt to parse the text as a Batch
+                            match serde_json::from_str::<Batch>(&text) {
+                                Ok(batch) => {
+                                    info!(target: "cdk::datastream::websocket", batch_number = %batch.id.number, "Received batch from WebSocket");
+                                    next_batch_number = Some(batch.id.number.saturating_to::<u64>().saturating_add(1));
+                                    failed_attempts = 0;
+                                    yield Ok(batch);
+                                },
+                                Err(e) => {
+                                    error!(target: "cdk::datastream::websocket", error = %e, "Failed to deserialize batch from WebSocket message");
+                                    yield Err(DataStreamError::DeserializationError(e.to_string()));
+                                }
+                            }
+                        },
+                        Ok(Message::Binary(bin)) => {
+                            debug!(target: "cdk::datastream::websocket", "Received WebSocket binary message of {} bytes", bin.len());
+                            let payloads = match config.framing {
+                                Framing::OnePerMessage => vec![bin],
+                                Framing::LengthPrefixed => match frames.push(&bin) {
+                                    Ok(payloads) => payloads,
+                                    Err(e) => {
+                                        error!(target: "cdk::datastream::websocket", error = %e, "Rejecting oversized WebSocket batch frame, dropping connection");
+                                        yield Err(e);
+                                        break;
+                                    }
+                                },
+                            };
+                            // Attempt to parse each complete payload as a Batch
+                            for payload in payloads {
+                                match serde_json::from_slice::<Batch>(&payload) {
+                                    Ok(batch) => {
+                                        info!(target: "cdk::datastream::websocket", batch_number = %batch.id.number, "Received batch from WebSocket (binary)");
+                                        next_batch_number = Some(batch.id.number.saturating_to::<u64>().saturating_add(1));
+                                        failed_attempts = 0;
+                                        yield Ok(batch);
+                                    },
+                                    Err(e) => {
+                                        error!(target: "cdk::datastream::websocket", error = %e, "Failed to deserialize batch from WebSocket binary message");
+                                        yield Err(DataStreamError::DeserializationError(e.to_string()));
+                                    }
+                                }
+                            }
+                        },
+                        Ok(Message::Ping(p)) => {
+                            debug!(target: "cdk::datastream::websocket", "Received WebSocket ping");
+                            if let Err(e) = ws_stream.send(Message::Pong(p)).await {
+                                error!(target: "cdk::datastream::websocket", error = %e, "Failed to send WebSocket pong");
+                                break;
                             }
+                        },
+                        Ok(Message::Pong(_)) => {
+                            debug!(target: "cdk::datastream::websocket", "Received WebSocket pong");
+                            keepalive.pong_received();
+                        },
+                        Ok(Message::Close(cf)) => {
+                            info!(target: "cdk::datastream::websocket", close_frame = ?cf, "WebSocket connection closed by peer");
+                            break;
+                        },
+                        Ok(Message::Frame(_)) => {
+                            // Ignore frame messages
+                        },
+                        Err(e) => {
+                            error!(target: "cdk::datastream::websocket", error = %e, "WebSocket error");
+                            break;
                         }
-                    },
-                    Ok(Message::Ping(p)) => {
-                        debug!(target: "cdk::datastream::websocket", "Received WebSocket ping");
-                        if let Err(e) = ws_stream.send(Message::Pong(p)).await {
-                            error!(target: "cdk::datastream::websocket", error = %e, "Failed to send WebSocket pong");
-                            yield Err(DataStreamError::CommunicationError(e.to_string()));
+                    }
+                }
+
+                // The connection is gone; reconnect with exponential backoff
+                loop {
+                    failed_attempts += 1;
+                    if failed_attempts > config.max_reconnect_attempts {
+                        error!(target: "cdk::datastream::websocket", attempts = config.max_reconnect_attempts, "Giving up on WebSocket reconnection");
+                        yield Err(DataStreamError::ConnectionError(format!(
+                            "WebSocket reconnection failed after {} attempts",
+                            config.max_reconnect_attempts
+                        )));
+                        break 'session;
+                    }
+
+                    let delay = config.reconnect_delay(failed_attempts);
+                    warn!(target: "cdk::datastream::websocket", attempt = failed_attempts, delay = ?delay, next_batch_number = ?next_batch_number, "Reconnecting to WebSocket source");
+                    tokio::time::sleep(delay).await;
+
+                    match Self::subscribe(&config.url, next_batch_number).await {
+                        Ok(stream) => {
+                            ws_stream = stream;
+                            // Batches resume at `next_batch_number`; a partial one is resent whole
+                            frames.clear();
+                            continue 'session;
+                        }
+                        Err(e) => {
+                            error!(target: "cdk::datastream::websocket", error = %e, attempt = failed_attempts, "WebSocket reconnection attempt failed");
                         }
-                    },
-                    Ok(Message::Pong(_)) => {
-                        debug!(target: "cdk::datastream::websocket", "Received WebSocket pong");
-                    },
-                    Ok(Message::Close(cf)) => {
-                        info!(target: "cdk::datastream::websocket", close_frame = ?cf, "WebSocket connection closed by peer");
-                        break;
-                    },
-                    Ok(Message::Frame(_)) => {
-                        // Ignore frame messages
-                    },
-                    Err(e) => {
-                        error!(target: "cdk::datastream::websocket", error = %e, "WebSocket error");
-                        yield Err(DataStreamError::ConnectionError(e.to_string()));
                     }
                 }
             }
@@ -110,31 +375,244 @@ impl BatchSource for WebSocketSource {
         Ok(Box::new(Box::pin(stream)))
     }
 
-    async fn next(&mut self) -> Result<Option<Batch>, crate::DatastreamError> {
-        // For now, return None as we're using the stream-based approach
-        Ok(None)
+    fn stream_cursor(&mut self) -> Option<&mut BatchStreamCursor> {
+        Some(&mut self.cursor)
+    }
+
+    fn advance_checkpoint(&mut self, batch: &Batch) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.checkpoint = Some(Checkpoint::from_batch(batch, now).with_source(&self.source_metadata()));
     }
 
     async fn checkpoint(&self) -> Result<crate::Checkpoint, crate::DatastreamError> {
-        Ok(crate::Checkpoint::default())
+        Ok(self.checkpoint.clone().unwrap_or_default())
     }
 
-    async fn set_checkpoint(&mut self, _checkpoint: crate::Checkpoint) -> Result<(), crate::DatastreamError> {
+    async fn set_checkpoint(&mut self, checkpoint: crate::Checkpoint) -> Result<(), crate::DatastreamError> {
+        checkpoint.ensure_compatible_with(&self.source_metadata())?;
+        debug!(target: "cdk::datastream::websocket", batch_number = %checkpoint.last_batch_id, "Setting checkpoint");
+        self.checkpoint = Some(checkpoint);
+        // Resubscribe after the new checkpoint on the next read
+        self.cursor.reset();
         Ok(())
     }
 
     async fn health_check(&self) -> Result<(), crate::DatastreamError> {
         // Try to connect to check health
-        let _ws_stream = self.connect().await?;
+        let _ws_stream = Self::connect(&self.config.url).await?;
         Ok(())
     }
 
     async fn metadata(&self) -> Result<crate::SourceMetadata, crate::DatastreamError> {
-        Ok(crate::SourceMetadata::new(
-            "WebSocket Source".to_string(),
-            "1.0".to_string(),
-            self.config.url.to_string(),
-            true,
-        ))
+        Ok(self.source_metadata())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{BatchId, ProofMetadata};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    fn batch_json(number: u64) -> String {
+        let batch = Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        );
+        serde_json::to_string(&batch).unwrap()
+    }
+
+    #[test]
+    fn test_reconnect_delay_backoff() {
+        let mut config = WebSocketSourceConfig::new(Url::parse("ws://localhost:8546").unwrap());
+        config.reconnect_base_delay = Duration::from_millis(100);
+        config.reconnect_max_delay = Duration::from_millis(500);
+
+        assert_eq!(config.reconnect_delay(1), Duration::from_millis(100));
+        assert_eq!(config.reconnect_delay(2), Duration::from_millis(200));
+        assert_eq!(config.reconnect_delay(3), Duration::from_millis(400));
+        assert_eq!(config.reconnect_delay(4), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_set_checkpoint_checks_compatibility() {
+        let mut config = WebSocketSourceConfig::new(Url::parse("ws://localhost:8546").unwrap());
+        config.chain_id = 7;
+        let mut source = WebSocketSource::new(config);
+        let checkpoint = Checkpoint::new(U256::from(3), FixedBytes::from([3u8; 32]), U256::ZERO, 1234567890);
+
+        let compatible = checkpoint.clone().with_source(&source.source_metadata());
+        source.set_checkpoint(compatible.clone()).await.unwrap();
+        assert_eq!(source.checkpoint().await.unwrap(), compatible);
+
+        let other_source = SourceMetadata::new("gRPC Source".to_string(), "1.0".to_string(), "http://localhost:50051".to_string(), true);
+        let other_chain = source.source_metadata().with_chain_id(8);
+        for incompatible in [checkpoint.clone().with_source(&other_source), checkpoint.with_source(&other_chain)] {
+            assert!(matches!(
+                source.set_checkpoint(incompatible).await,
+                Err(DataStreamError::CheckpointError(_))
+            ));
+        }
+        assert_eq!(source.checkpoint().await.unwrap(), compatible);
+    }
+
+    #[tokio::test]
+    async fn test_length_prefixed_batch_reassembled_across_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+
+            let json = batch_json(1).into_bytes();
+            let mut framed = (json.len() as u32).to_be_bytes().to_vec();
+            framed.extend_from_slice(&json);
+            let (first, second) = framed.split_at(framed.len() / 2);
+            ws.send(Message::binary(first.to_vec())).await.unwrap();
+            ws.send(Message::binary(second.to_vec())).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        let mut config = WebSocketSourceConfig::new(Url::parse(&format!("ws://{}", addr)).unwrap());
+        config.framing = Framing::LengthPrefixed;
+        let source = WebSocketSource::new(config);
+        let mut stream = source.fetch_batch_stream(None).await.unwrap();
+
+        let batch = stream.next().await.unwrap().unwrap();
+        assert_eq!(batch.id.number, U256::from(1));
+        assert!(tokio::time::timeout(Duration::from_millis(100), stream.next()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_rejected_and_connection_reopened() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            // First connection: announce a batch larger than the limit
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.send(Message::binary(1025u32.to_be_bytes().to_vec())).await.unwrap();
+            let _first = ws;
+
+            // Second connection: serve a batch within the limit
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            let json = batch_json(1).into_bytes();
+            let mut framed = (json.len() as u32).to_be_bytes().to_vec();
+            framed.extend_from_slice(&json);
+            ws.send(Message::binary(framed)).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        let mut config = WebSocketSourceConfig::new(Url::parse(&format!("ws://{}", addr)).unwrap());
+        config.framing = Framing::LengthPrefixed;
+        config.max_frame_bytes = 1024;
+        config.reconnect_base_delay = Duration::from_millis(10);
+        let source = WebSocketSource::new(config);
+        let mut stream = source.fetch_batch_stream(None).await.unwrap();
+
+        assert!(matches!(stream.next().await.unwrap(), Err(DataStreamError::DeserializationError(_))));
+        let batch = stream.next().await.unwrap().unwrap();
+        assert_eq!(batch.id.number, U256::from(1));
     }
-}
\ No newline at end of file
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_due_on_interval() {
+        let start = tokio::time::Instant::now();
+        let mut keepalive = Keepalive::new(Some(Duration::from_millis(50)));
+
+        assert!(keepalive.due().await);
+        assert_eq!(start.elapsed(), Duration::from_millis(50));
+        keepalive.pong_received();
+        assert!(keepalive.due().await);
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+
+        // The ping sent at 100ms was never answered
+        assert!(!keepalive.due().await);
+        assert_eq!(start.elapsed(), Duration::from_millis(150));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_disabled_never_due() {
+        let mut keepalive = Keepalive::new(None);
+        assert!(tokio::time::timeout(Duration::from_secs(3600), keepalive.due()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_pings_answered_on_one_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (ping_tx, mut ping_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            // Only one connection is accepted, so a dropped connection stops the pings
+            let (tcp, _) = listener.accept().await.unwrap();
+            // Don't let Nagle's algorithm hold back the small pong frames
+            tcp.set_nodelay(true).unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            // Pongs are queued by tungstenite itself while reading
+            while let Some(Ok(msg)) = ws.next().await {
+                if msg.is_ping() {
+                    let _ = ping_tx.send(());
+                }
+            }
+        });
+
+        let mut config = WebSocketSourceConfig::new(Url::parse(&format!("ws://{}", addr)).unwrap());
+        config.keepalive_interval = Some(Duration::from_millis(100));
+        let source = WebSocketSource::new(config);
+        let mut stream = source.fetch_batch_stream(None).await.unwrap();
+        tokio::spawn(async move { while stream.next().await.is_some() {} });
+
+        for _ in 0..3 {
+            let ping = tokio::time::timeout(Duration::from_secs(5), ping_rx.recv()).await;
+            assert!(matches!(ping, Ok(Some(()))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_survives_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            // First connection: accept the subscription, then close
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.close(None).await.unwrap();
+
+            // Second connection: serve batches
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.send(Message::text(batch_json(1))).await.unwrap();
+            ws.send(Message::text(batch_json(2))).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        let mut config = WebSocketSourceConfig::new(Url::parse(&format!("ws://{}", addr)).unwrap());
+        config.reconnect_base_delay = Duration::from_millis(10);
+        let source = WebSocketSource::new(config);
+
+        let batches: Vec<_> = source.fetch_batch_stream(None).await.unwrap().take(2).collect().await;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].as_ref().unwrap().id.number, U256::from(1));
+        assert_eq!(batches[1].as_ref().unwrap().id.number, U256::from(2));
+    }
+}
@@ -4,6 +4,7 @@ use crate::{error::EngineFacadeError, types::*};
 use async_trait::async_trait;
 use cdk_types::Batch;
 use alloy_primitives::U256;
+use std::{collections::BTreeMap, sync::Mutex};
 
 /// Trait for importing blocks into the engine
 #[async_trait]
@@ -19,38 +20,54 @@ pub trait BlockImporter {
 
     /// Get the current head block number
     async fn get_head_block(&self) -> Result<U256, EngineFacadeError>;
+
+    /// Remove all blocks above `block_number`, making it the new head
+    ///
+    /// Returns the number of blocks removed.
+    async fn unwind_to(&self, block_number: U256) -> Result<usize, EngineFacadeError>;
 }
 
 /// Default implementation of block importer
+///
+/// Keeps imported blocks in memory, keyed by block number.
 pub struct DefaultBlockImporter {
-    // This would contain the actual Reth engine components
-    // For now, we'll use a placeholder
-    _engine: (),
+    blocks: Mutex<BTreeMap<U256, ImportableBlock>>,
 }
 
 impl DefaultBlockImporter {
     /// Create a new block importer
     pub fn new() -> Self {
-        Self { _engine: () }
+        Self {
+            blocks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn blocks(&self) -> Result<std::sync::MutexGuard<'_, BTreeMap<U256, ImportableBlock>>, EngineFacadeError> {
+        self.blocks
+            .lock()
+            .map_err(|e| EngineFacadeError::InternalError(format!("Block store lock poisoned: {}", e)))
     }
 }
 
 #[async_trait]
 impl BlockImporter for DefaultBlockImporter {
-    async fn import_block(&self, _block: ImportableBlock) -> Result<(), EngineFacadeError> {
-        // TODO: Implement actual block import logic
-        // This would interact with Reth's block import pipeline
+    async fn import_block(&self, block: ImportableBlock) -> Result<(), EngineFacadeError> {
+        self.blocks()?.insert(block.number, block);
         Ok(())
     }
 
     async fn import_batch(&self, _batch: &Batch, blocks: Vec<ImportableBlock>) -> Result<ImportResult, EngineFacadeError> {
-        // TODO: Implement batch import logic
         let blocks_imported = blocks.len();
         let highest_block = blocks.iter()
             .map(|b| b.number)
             .max()
             .unwrap_or(U256::ZERO);
 
+        let mut store = self.blocks()?;
+        for block in blocks {
+            store.insert(block.number, block);
+        }
+
         Ok(ImportResult {
             blocks_imported,
             highest_block,
@@ -58,13 +75,16 @@ impl BlockImporter for DefaultBlockImporter {
         })
     }
 
-    async fn block_exists(&self, _block_number: U256) -> Result<bool, EngineFacadeError> {
-        // TODO: Check if block exists in database
-        Ok(false)
+    async fn block_exists(&self, block_number: U256) -> Result<bool, EngineFacadeError> {
+        Ok(self.blocks()?.contains_key(&block_number))
     }
 
     async fn get_head_block(&self) -> Result<U256, EngineFacadeError> {
-        // TODO: Get current head block from database
-        Ok(U256::ZERO)
+        Ok(self.blocks()?.keys().next_back().copied().unwrap_or(U256::ZERO))
+    }
+
+    async fn unwind_to(&self, block_number: U256) -> Result<usize, EngineFacadeError> {
+        let removed = self.blocks()?.split_off(&(block_number + U256::from(1)));
+        Ok(removed.len())
     }
 }
@@ -3,11 +3,14 @@
 use crate::{block_import::*, error::EngineFacadeError, finality::*, types::*};
 use cdk_types::{Batch, FinalityTag};
 use alloy_primitives::U256;
+use tracing::info;
 
 /// Main engine facade that provides unified access to Reth engine operations
 pub struct EngineFacade {
     block_importer: Box<dyn BlockImporter + Send + Sync>,
     finality_manager: Box<dyn FinalityManager + Send + Sync>,
+    /// Reth backend shared with the importer and finality manager, if any
+    reth_facade: Option<crate::reth_integration::RethEngineFacade>,
 }
 
 impl EngineFacade {
@@ -19,9 +22,21 @@ impl EngineFacade {
         Self {
             block_importer,
             finality_manager,
+            reth_facade: None,
         }
     }
 
+    /// Attach the Reth backend so engine handles can be installed later
+    pub(crate) fn with_reth_facade(mut self, reth_facade: crate::reth_integration::RethEngineFacade) -> Self {
+        self.reth_facade = Some(reth_facade);
+        self
+    }
+
+    /// Get the Reth backend, if this facade is backed by Reth
+    pub(crate) fn reth_facade(&self) -> Option<&crate::reth_integration::RethEngineFacade> {
+        self.reth_facade.as_ref()
+    }
+
     /// Create a default engine facade with default implementations
     pub fn default() -> Self {
         Self::new(
@@ -52,11 +67,27 @@ impl EngineFacade {
 
     /// Rollback to a specific block
     pub async fn rollback_to(&self, block_number: U256) -> Result<RollbackResult, EngineFacadeError> {
-        // TODO: Implement rollback logic
-        // This would involve unwinding the chain state to the specified block
+        let head = self.block_importer.get_head_block().await?;
+        if block_number >= head {
+            return Ok(RollbackResult {
+                rollback_block: block_number,
+                blocks_rolled_back: 0,
+            });
+        }
+
+        if self.finality_manager.is_final(block_number + U256::from(1)).await? {
+            return Err(EngineFacadeError::RollbackFailed(format!(
+                "Cannot roll back finalized block {}",
+                block_number + U256::from(1)
+            )));
+        }
+
+        let blocks_rolled_back = self.block_importer.unwind_to(block_number).await?;
+        info!("Rolled back {} blocks from head {} to {}", blocks_rolled_back, head, block_number);
+
         Ok(RollbackResult {
             rollback_block: block_number,
-            blocks_rolled_back: 0,
+            blocks_rolled_back,
         })
     }
 
@@ -117,4 +148,34 @@ mod tests {
         let result = facade.import_block(block).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_rollback_to() {
+        let facade = EngineFacade::default();
+        for number in 1..=5u8 {
+            let block = ImportableBlock::new(
+                U256::from(number),
+                FixedBytes::from([number; 32]),
+                FixedBytes::from([number - 1; 32]),
+                FixedBytes::from([2u8; 32]),
+                FixedBytes::from([3u8; 32]),
+                FixedBytes::from([4u8; 32]),
+                1234567890,
+                Bytes::new(),
+                None,
+            );
+            facade.import_block(block).await.unwrap();
+        }
+        assert_eq!(facade.get_head_block().await.unwrap(), U256::from(5));
+
+        let result = facade.rollback_to(U256::from(2)).await.unwrap();
+        assert_eq!(result.rollback_block, U256::from(2));
+        assert_eq!(result.blocks_rolled_back, 3);
+        assert_eq!(facade.get_head_block().await.unwrap(), U256::from(2));
+        assert!(!facade.block_exists(U256::from(3)).await.unwrap());
+
+        // Rolling back to the current head is a no-op
+        let result = facade.rollback_to(U256::from(2)).await.unwrap();
+        assert_eq!(result.blocks_rolled_back, 0);
+    }
 }
@@ -14,6 +14,7 @@ pub mod error;
 pub mod finality;
 pub mod types;
 pub mod reth_integration;
+pub mod unwinder;
 
 pub use block_import::*;
 pub use engine::*;
@@ -21,3 +22,4 @@ pub use error::*;
 pub use finality::*;
 pub use types::*;
 pub use reth_integration::*;
+pub use unwinder::*;
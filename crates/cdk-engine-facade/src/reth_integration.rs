@@ -9,19 +9,33 @@ use reth_ethereum_engine_primitives::EthEngineTypes;
 use reth_payload_primitives::{BuiltPayload, PayloadTypes};
 use reth_primitives::{Block, SealedBlock};
 use reth_provider::{Provider, BlockReader, BlockWriter};
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
 use tracing::{info, warn, error};
 
+/// Convert a block number to the `u64` Reth indexes blocks by
+fn block_number_u64(block_number: U256) -> Result<u64, EngineFacadeError> {
+    block_number
+        .try_into()
+        .map_err(|_| EngineFacadeError::InvalidBlockData(format!("Block number {} does not fit in u64", block_number)))
+}
+
 /// Real Reth engine facade implementation
+///
+/// Clones share the same engine handle, so a handle installed after the
+/// facade was handed to an `EngineFacade` is picked up by every clone.
+#[derive(Clone)]
 pub struct RethEngineFacade {
     /// Provider for database operations
     provider: Arc<dyn Provider>,
     /// Engine handle for consensus operations
-    engine_handle: Option<ConsensusEngineHandle<EthEngineTypes>>,
-    /// Current head block number
-    head_block: U256,
-    /// Current finalized block number
-    finalized_block: U256,
+    engine_handle: Arc<Mutex<Option<ConsensusEngineHandle<EthEngineTypes>>>>,
+    /// Highest block number imported through this facade
+    head_block: Arc<AtomicU64>,
+    /// Highest block number marked final through this facade
+    finalized_block: Arc<AtomicU64>,
 }
 
 impl RethEngineFacade {
@@ -29,15 +43,36 @@ impl RethEngineFacade {
     pub fn new(provider: Arc<dyn Provider>) -> Self {
         Self {
             provider,
-            engine_handle: None,
-            head_block: U256::ZERO,
-            finalized_block: U256::ZERO,
+            engine_handle: Arc::new(Mutex::new(None)),
+            head_block: Arc::new(AtomicU64::new(0)),
+            finalized_block: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Highest block number imported through this facade
+    pub fn head_block(&self) -> U256 {
+        U256::from(self.head_block.load(Ordering::Acquire))
+    }
+
+    /// Highest block number marked final through this facade
+    pub fn finalized_block(&self) -> U256 {
+        U256::from(self.finalized_block.load(Ordering::Acquire))
+    }
+
     /// Set the engine handle for consensus operations
-    pub fn set_engine_handle(&mut self, handle: ConsensusEngineHandle<EthEngineTypes>) {
-        self.engine_handle = Some(handle);
+    pub fn set_engine_handle(&self, handle: ConsensusEngineHandle<EthEngineTypes>) {
+        *self.engine_handle.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(handle);
+        info!("Engine handle installed, using consensus engine for block import");
+    }
+
+    /// Check whether an engine handle has been installed
+    pub fn has_engine_handle(&self) -> bool {
+        self.engine_handle().is_some()
+    }
+
+    /// Get a copy of the current engine handle
+    fn engine_handle(&self) -> Option<ConsensusEngineHandle<EthEngineTypes>> {
+        self.engine_handle.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
     }
 
     /// Convert CDK block to Reth block
@@ -45,7 +80,7 @@ impl RethEngineFacade {
         // Create a basic block structure
         // In a real implementation, this would parse the RLP data
         let header = reth_primitives::Header {
-            number: block.number.to::<u64>(),
+            number: block_number_u64(block.number)?,
             hash: block.hash,
             parent_hash: block.parent_hash,
             state_root: block.state_root,
@@ -71,7 +106,7 @@ impl RethEngineFacade {
 
     /// Import a single block using Reth's engine
     async fn import_block_engine(&self, block: SealedBlock) -> Result<(), EngineFacadeError> {
-        if let Some(engine_handle) = &self.engine_handle {
+        if let Some(engine_handle) = self.engine_handle() {
             // Convert to payload
             let payload = EthEngineTypes::block_to_payload(block);
             
@@ -102,7 +137,7 @@ impl RethEngineFacade {
 
     /// Update fork choice using engine
     async fn update_fork_choice(&self, block_hash: FixedBytes<32>) -> Result<(), EngineFacadeError> {
-        if let Some(engine_handle) = &self.engine_handle {
+        if let Some(engine_handle) = self.engine_handle() {
             use alloy_rpc_types::engine::ForkchoiceState;
             use reth_engine_primitives::EngineApiMessageVersion;
 
@@ -133,6 +168,7 @@ impl RethEngineFacade {
 impl BlockImporter for RethEngineFacade {
     async fn import_block(&self, block: ImportableBlock) -> Result<(), EngineFacadeError> {
         info!("Importing block {} (hash: {})", block.number, block.hash);
+        let number = block_number_u64(block.number)?;
         
         // Convert to Reth block
         let reth_block = self.convert_to_reth_block(&block)?;
@@ -140,9 +176,8 @@ impl BlockImporter for RethEngineFacade {
         // Import using engine or database
         self.import_block_engine(reth_block).await?;
         
-        // Update head block
-        if block.number > self.head_block {
-            // In a real implementation, this would be atomic
+        // Advance the head; blocks may arrive out of order
+        if self.head_block.fetch_max(number, Ordering::AcqRel) < number {
             info!("Updated head block to {}", block.number);
         }
         
@@ -153,26 +188,38 @@ impl BlockImporter for RethEngineFacade {
         info!("Importing batch {} with {} blocks", batch.id.number, blocks.len());
         
         let mut imported_count = 0;
+        let mut skipped_count = 0;
         let mut highest_block = U256::ZERO;
         
         for block in blocks {
-            self.import_block(block.clone()).await?;
+            // Overlapping batches re-deliver blocks we already have
+            if self.block_exists(block.number).await? {
+                skipped_count += 1;
+                continue;
+            }
+
+            let number = block.number;
+            self.import_block(block).await?;
             imported_count += 1;
-            if block.number > highest_block {
-                highest_block = block.number;
+            if number > highest_block {
+                highest_block = number;
             }
         }
+
+        if skipped_count > 0 {
+            info!("Skipped {} already imported blocks in batch {}", skipped_count, batch.id.number);
+        }
         
         Ok(ImportResult {
             blocks_imported: imported_count,
             highest_block,
-            blocks_skipped: false,
+            blocks_skipped: skipped_count > 0,
         })
     }
 
     async fn block_exists(&self, block_number: U256) -> Result<bool, EngineFacadeError> {
         // Check if block exists in database
-        match self.provider.block_by_number(block_number.to::<u64>()) {
+        match self.provider.block_by_number(block_number_u64(block_number)?) {
             Ok(Some(_)) => Ok(true),
             Ok(None) => Ok(false),
             Err(e) => Err(EngineFacadeError::DatabaseError(e.to_string())),
@@ -186,15 +233,44 @@ impl BlockImporter for RethEngineFacade {
             Err(e) => Err(EngineFacadeError::DatabaseError(e.to_string())),
         }
     }
+
+    async fn unwind_to(&self, block_number: U256) -> Result<usize, EngineFacadeError> {
+        let head = self.get_head_block().await?;
+        if block_number >= head {
+            return Ok(0);
+        }
+        let new_head = block_number_u64(block_number)?;
+
+        let new_head_hash = match self.provider.block_hash(new_head) {
+            Ok(Some(hash)) => hash,
+            Ok(None) => return Err(EngineFacadeError::RollbackFailed(format!("Block {} not found", block_number))),
+            Err(e) => return Err(EngineFacadeError::DatabaseError(e.to_string())),
+        };
+
+        if !self.has_engine_handle() {
+            return Err(EngineFacadeError::EngineNotInitialized(
+                "Rollback requires an engine handle".to_string(),
+            ));
+        }
+
+        // Moving the fork choice to the new head makes the blocks above it non-canonical
+        self.update_fork_choice(new_head_hash).await?;
+        self.head_block.store(new_head, Ordering::Release);
+
+        let blocks_rolled_back = (head - block_number).saturating_to::<usize>();
+        info!("Unwound {} blocks to block {}", blocks_rolled_back, block_number);
+        Ok(blocks_rolled_back)
+    }
 }
 
 #[async_trait]
 impl FinalityManager for RethEngineFacade {
     async fn mark_final(&self, block_number: U256) -> Result<FinalityResult, EngineFacadeError> {
         info!("Marking block {} as final", block_number);
+        let final_block = block_number_u64(block_number)?;
         
         // Get block hash
-        let block_hash = match self.provider.block_hash(block_number.to::<u64>()) {
+        let block_hash = match self.provider.block_hash(final_block) {
             Ok(Some(hash)) => hash,
             Ok(None) => return Err(EngineFacadeError::FinalityMarkingFailed("Block not found".to_string())),
             Err(e) => return Err(EngineFacadeError::DatabaseError(e.to_string())),
@@ -202,6 +278,7 @@ impl FinalityManager for RethEngineFacade {
         
         // Update fork choice
         self.update_fork_choice(block_hash).await?;
+        self.finalized_block.fetch_max(final_block, Ordering::AcqRel);
         
         Ok(FinalityResult {
             final_block: block_number,
@@ -235,13 +312,12 @@ impl FinalityManager for RethEngineFacade {
     }
 
     async fn get_final_block(&self) -> Result<U256, EngineFacadeError> {
-        // In a real implementation, this would query the finalized block
-        Ok(self.finalized_block)
+        Ok(self.finalized_block())
     }
 
     async fn is_final(&self, block_number: U256) -> Result<bool, EngineFacadeError> {
         // Check if block is finalized
-        Ok(block_number <= self.finalized_block)
+        Ok(block_number <= self.finalized_block())
     }
 }
 
@@ -252,15 +328,22 @@ impl EngineFacade {
         
         Self::new(
             Box::new(reth_facade.clone()),
-            Box::new(reth_facade),
+            Box::new(reth_facade.clone()),
         )
+        .with_reth_facade(reth_facade)
     }
 
     /// Set engine handle for consensus operations
-    pub fn set_engine_handle(&mut self, handle: ConsensusEngineHandle<EthEngineTypes>) {
-        // This would require refactoring to support mutable engine handles
-        // For now, we'll implement this in a future iteration
-        warn!("Engine handle setting not yet implemented");
+    pub fn set_engine_handle(&self, handle: ConsensusEngineHandle<EthEngineTypes>) {
+        match self.reth_facade() {
+            Some(reth_facade) => reth_facade.set_engine_handle(handle),
+            None => warn!("Engine handle ignored: facade is not backed by Reth"),
+        }
+    }
+
+    /// Check whether the consensus engine handle has been installed
+    pub fn has_engine_handle(&self) -> bool {
+        self.reth_facade().is_some_and(RethEngineFacade::has_engine_handle)
     }
 }
 
@@ -275,8 +358,8 @@ mod tests {
         let provider = Arc::new(MockProvider::default());
         let facade = RethEngineFacade::new(provider);
         
-        assert_eq!(facade.head_block, U256::ZERO);
-        assert_eq!(facade.finalized_block, U256::ZERO);
+        assert_eq!(facade.head_block(), U256::ZERO);
+        assert_eq!(facade.finalized_block(), U256::ZERO);
     }
 
     #[tokio::test]
@@ -300,4 +383,128 @@ mod tests {
         assert_eq!(reth_block.number, 1);
         assert_eq!(reth_block.hash(), FixedBytes::from([1u8; 32]));
     }
+
+    #[tokio::test]
+    async fn test_head_tracks_highest_imported_block() {
+        let provider = Arc::new(MockProvider::default());
+        let facade = RethEngineFacade::new(provider);
+
+        for number in [3u8, 1, 5, 2] {
+            let block = ImportableBlock::new(
+                U256::from(number),
+                FixedBytes::from([number; 32]),
+                FixedBytes::from([number - 1; 32]),
+                FixedBytes::from([2u8; 32]),
+                FixedBytes::from([3u8; 32]),
+                FixedBytes::from([4u8; 32]),
+                1234567890,
+                Bytes::new(),
+                None,
+            );
+            facade.import_block(block).await.unwrap();
+        }
+
+        assert_eq!(facade.head_block(), U256::from(5));
+    }
+
+    #[tokio::test]
+    async fn test_block_numbers_beyond_u64_rejected() {
+        let provider = Arc::new(MockProvider::default());
+        let facade = RethEngineFacade::new(provider);
+        let number = U256::from(u64::MAX) + U256::from(1);
+
+        let block = ImportableBlock::new(
+            number,
+            FixedBytes::from([1u8; 32]),
+            FixedBytes::from([0u8; 32]),
+            FixedBytes::from([2u8; 32]),
+            FixedBytes::from([3u8; 32]),
+            FixedBytes::from([4u8; 32]),
+            1234567890,
+            Bytes::new(),
+            None,
+        );
+        assert!(matches!(facade.import_block(block).await, Err(EngineFacadeError::InvalidBlockData(_))));
+        assert!(matches!(facade.mark_final(number).await, Err(EngineFacadeError::InvalidBlockData(_))));
+        assert_eq!(facade.head_block(), U256::ZERO);
+        assert_eq!(facade.finalized_block(), U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_import_batch_skips_existing_blocks() {
+        let provider = MockProvider::default();
+        for number in 1..=2u64 {
+            let block = Block {
+                header: reth_primitives::Header { number, ..Default::default() },
+                ..Default::default()
+            };
+            provider.add_block(FixedBytes::from([number as u8; 32]), block);
+        }
+        let facade = RethEngineFacade::new(Arc::new(provider));
+
+        let batch = Batch::new(
+            cdk_types::BatchId::new(U256::from(1), FixedBytes::from([9u8; 32])),
+            U256::from(100),
+            FixedBytes::from([8u8; 32]),
+            vec![],
+            cdk_types::ProofMetadata::default(),
+            1234567890,
+        );
+        let blocks = (1..=4u8)
+            .map(|number| {
+                ImportableBlock::new(
+                    U256::from(number),
+                    FixedBytes::from([number; 32]),
+                    FixedBytes::from([number - 1; 32]),
+                    FixedBytes::from([2u8; 32]),
+                    FixedBytes::from([3u8; 32]),
+                    FixedBytes::from([4u8; 32]),
+                    1234567890,
+                    Bytes::new(),
+                    None,
+                )
+            })
+            .collect();
+
+        let result = facade.import_batch(&batch, blocks).await.unwrap();
+        assert!(result.blocks_skipped);
+        assert_eq!(result.blocks_imported, 2);
+        assert_eq!(result.highest_block, U256::from(4));
+    }
+
+    #[tokio::test]
+    async fn test_import_uses_engine_handle_once_set() {
+        use alloy_rpc_types::engine::{PayloadStatus, PayloadStatusEnum};
+        use reth_engine_primitives::BeaconEngineMessage;
+
+        let provider = Arc::new(MockProvider::default());
+        let facade = EngineFacade::new_reth(provider);
+        assert!(!facade.has_engine_handle());
+
+        let (to_engine, mut from_facade) = tokio::sync::mpsc::unbounded_channel();
+        facade.set_engine_handle(ConsensusEngineHandle::new(to_engine));
+        assert!(facade.has_engine_handle());
+
+        let block = ImportableBlock::new(
+            U256::from(1),
+            FixedBytes::from([1u8; 32]),
+            FixedBytes::from([0u8; 32]),
+            FixedBytes::from([2u8; 32]),
+            FixedBytes::from([3u8; 32]),
+            FixedBytes::from([4u8; 32]),
+            1234567890,
+            Bytes::new(),
+            None,
+        );
+        let import = tokio::spawn(async move { facade.import_block(block).await });
+
+        // The block must reach the consensus engine instead of the database fallback
+        match from_facade.recv().await.expect("engine should receive a message") {
+            BeaconEngineMessage::NewPayload { tx, .. } => {
+                tx.send(Ok(PayloadStatus::from_status(PayloadStatusEnum::Valid))).unwrap();
+            }
+            other => panic!("expected new payload, got {:?}", other),
+        }
+        import.await.unwrap().unwrap();
+    }
 }
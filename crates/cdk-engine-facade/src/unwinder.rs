@@ -0,0 +1,68 @@
+//! Block unwinding for finality rollbacks
+
+use crate::engine::EngineFacade;
+use alloy_primitives::U256;
+use async_trait::async_trait;
+use cdk_finality::{BlockUnwinder, FinalityError};
+use cdk_ingest::MappingStorage;
+use std::sync::Arc;
+use tracing::info;
+
+/// `BlockUnwinder` that resolves batch blocks from mapping storage and
+/// unwinds chain state through the engine facade
+pub struct EngineBlockUnwinder {
+    facade: Arc<EngineFacade>,
+    mapping_storage: Arc<dyn MappingStorage>,
+}
+
+impl EngineBlockUnwinder {
+    /// Create a new engine block unwinder
+    pub fn new(facade: Arc<EngineFacade>, mapping_storage: Arc<dyn MappingStorage>) -> Self {
+        Self {
+            facade,
+            mapping_storage,
+        }
+    }
+}
+
+impl std::fmt::Debug for EngineBlockUnwinder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EngineBlockUnwinder").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BlockUnwinder for EngineBlockUnwinder {
+    async fn affected_blocks(&self, batch_id: u64) -> Result<Vec<u64>, FinalityError> {
+        let mapping = self
+            .mapping_storage
+            .load_batch_mapping(batch_id)
+            .await
+            .map_err(|e| FinalityError::RollbackError(format!("Failed to load mapping for batch {}: {}", batch_id, e)))?
+            .ok_or_else(|| FinalityError::RollbackError(format!("No block mapping for batch {}", batch_id)))?;
+
+        Ok((mapping.start_block..=mapping.end_block).collect())
+    }
+
+    async fn head_block(&self) -> Result<u64, FinalityError> {
+        let head = self
+            .facade
+            .get_head_block()
+            .await
+            .map_err(|e| FinalityError::RollbackError(format!("Failed to read head block: {}", e)))?;
+
+        head.try_into()
+            .map_err(|_| FinalityError::RollbackError(format!("Head block {} does not fit in u64", head)))
+    }
+
+    async fn unwind_to(&self, block_number: u64) -> Result<(), FinalityError> {
+        let result = self
+            .facade
+            .rollback_to(U256::from(block_number))
+            .await
+            .map_err(|e| FinalityError::RollbackError(format!("Failed to unwind to block {}: {}", block_number, e)))?;
+
+        info!("Unwound {} blocks to block {}", result.blocks_rolled_back, block_number);
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for finality operations
 
+use alloy_primitives::FixedBytes;
 use thiserror::Error;
 
 /// Errors that can occur in finality operations
@@ -32,6 +33,13 @@ pub enum FinalityError {
     #[error("Bridge contract error: {0}")]
     BridgeContractError(String),
 
+    #[error("L1 reorg detected at block {block}: hash changed from {previous_hash} to {new_hash}")]
+    L1Reorg {
+        block: u64,
+        previous_hash: FixedBytes<32>,
+        new_hash: FixedBytes<32>,
+    },
+
     #[error("Rollback error: {0}")]
     RollbackError(String),
 
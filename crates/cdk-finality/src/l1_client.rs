@@ -2,60 +2,228 @@
 
 use crate::{FinalityError, FinalityResult, OracleMetadata};
 use alloy_primitives::{Address, FixedBytes, U256};
-use alloy_provider::{Provider, ProviderBuilder};
-use alloy_rpc_types_eth::BlockId;
-use alloy_network::Ethereum;
-use std::time::Duration;
-use tracing::{debug, info};
+use alloy_provider::{
+    transport::{RpcError, TransportResult},
+    Provider, ProviderBuilder,
+};
+use alloy_rpc_types_eth::{BlockId, Filter, Log, Transaction, TransactionRequest, TransactionTrait};
+use alloy_network::{Ethereum, TransactionResponse};
+use moka::future::Cache;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use tokio::{sync::Mutex, time::Instant};
+use tracing::{debug, info, warn};
+
+/// Boxed L1 provider
+type L1Provider = Box<dyn Provider<Ethereum> + Send + Sync>;
+
+/// Future returned by a single provider request
+type ProviderFuture<'a, T> = Pin<Box<dyn Future<Output = TransportResult<T>> + Send + 'a>>;
 
 /// L1 client configuration
 #[derive(Debug, Clone)]
 pub struct L1ClientConfig {
     /// L1 RPC URL
     pub rpc_url: String,
+    /// Fallback RPC URLs tried in order when the primary endpoint is unreachable
+    pub fallback_rpc_urls: Vec<String>,
     /// Request timeout
     pub timeout: Duration,
-    /// Maximum number of retries
+    /// Number of extra passes over all endpoints after every endpoint failed
     pub max_retries: u32,
-    /// Retry delay
+    /// Delay before each retry pass
     pub retry_delay: Duration,
     /// API key for authentication (optional)
     pub api_key: Option<String>,
+    /// Maximum number of provider requests per second (unlimited if not set)
+    pub max_requests_per_second: Option<u32>,
+    /// Maximum number of L1 blocks kept in the block cache
+    pub block_cache_size: u64,
+    /// How long a cached L1 block is trusted before it is fetched again
+    pub block_cache_ttl: Duration,
 }
 
 impl Default for L1ClientConfig {
     fn default() -> Self {
         Self {
             rpc_url: "http://localhost:8545".to_string(),
+            fallback_rpc_urls: vec![],
             timeout: Duration::from_secs(30),
             max_retries: 3,
             retry_delay: Duration::from_secs(1),
             api_key: None,
+            max_requests_per_second: None,
+            block_cache_size: 1024,
+            block_cache_ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Token-bucket limiter gating provider requests
+///
+/// The bucket holds up to one second worth of requests and refills
+/// continuously; callers wait for a token instead of failing.
+#[derive(Debug)]
+struct RateLimiter {
+    requests_per_second: f64,
+    bucket: Mutex<TokenBucket>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = f64::from(requests_per_second.max(1));
+        Self {
+            requests_per_second,
+            bucket: Mutex::new(TokenBucket {
+                tokens: requests_per_second,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a request may be dispatched
+    async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let refill = now.duration_since(bucket.last_refill).as_secs_f64() * self.requests_per_second;
+                bucket.tokens = (bucket.tokens + refill).min(self.requests_per_second);
+                bucket.last_refill = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.requests_per_second)
+            };
+
+            debug!("L1 request rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
         }
     }
 }
 
 /// L1 client for interacting with Ethereum mainnet using Alloy Provider
+///
+/// Requests go to the endpoint that last answered successfully. On connection
+/// errors or timeouts the remaining endpoints (primary first, then fallbacks)
+/// are tried in turn. When every endpoint failed, all of them are tried again
+/// up to `max_retries` times, `retry_delay` apart.
 pub struct L1Client {
-    #[allow(dead_code)]
     config: L1ClientConfig,
-    provider: Box<dyn Provider<Ethereum> + Send + Sync>,
+    providers: Vec<L1Provider>,
+    active_provider: AtomicUsize,
+    rate_limiter: Option<RateLimiter>,
+    block_cache: Cache<u64, L1Block>,
     chain_id: Option<u64>,
 }
 
 impl L1Client {
     /// Create a new L1 client
     pub fn new(config: L1ClientConfig) -> FinalityResult<Self> {
-        let provider = ProviderBuilder::new()
-            .connect_http(config.rpc_url.parse().map_err(|e| {
-                FinalityError::ConfigError(format!("Invalid RPC URL: {}", e))
-            })?);
+        let providers = std::iter::once(&config.rpc_url)
+            .chain(&config.fallback_rpc_urls)
+            .map(|url| {
+                let url = url.parse().map_err(|e| {
+                    FinalityError::ConfigError(format!("Invalid RPC URL {}: {}", url, e))
+                })?;
+                Ok(Box::new(ProviderBuilder::new().connect_http(url)) as L1Provider)
+            })
+            .collect::<FinalityResult<Vec<_>>>()?;
+
+        Ok(Self::with_providers(config, providers))
+    }
 
-        Ok(Self {
+    /// Create an L1 client over existing providers, primary first
+    pub fn with_providers(config: L1ClientConfig, providers: Vec<L1Provider>) -> Self {
+        Self {
+            rate_limiter: config.max_requests_per_second.map(RateLimiter::new),
+            block_cache: Cache::builder()
+                .max_capacity(config.block_cache_size)
+                .time_to_live(config.block_cache_ttl)
+                .build(),
             config,
-            provider: Box::new(provider),
+            providers,
+            active_provider: AtomicUsize::new(0),
             chain_id: None,
-        })
+        }
+    }
+
+    /// Index of the endpoint currently preferred for requests
+    pub fn active_endpoint(&self) -> usize {
+        self.active_provider.load(Ordering::Relaxed)
+    }
+
+    /// Run a request against the active endpoint, failing over and retrying on connection errors
+    async fn request<T, F>(&self, method: &str, f: F) -> FinalityResult<T>
+    where
+        F: for<'a> Fn(&'a (dyn Provider<Ethereum> + Send + Sync)) -> ProviderFuture<'a, T>,
+    {
+        if self.providers.is_empty() {
+            return Err(FinalityError::ConfigError("No L1 RPC endpoints configured".to_string()));
+        }
+
+        let mut attempt = 0;
+        loop {
+            match self.request_once(method, &f).await {
+                Err(e @ (FinalityError::NetworkError(_) | FinalityError::TimeoutError(_)))
+                    if attempt < self.config.max_retries =>
+                {
+                    attempt += 1;
+                    warn!("All L1 endpoints failed for {}, retry {} of {}: {}", method, attempt, self.config.max_retries, e);
+                    tokio::time::sleep(self.config.retry_delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Try every endpoint once, starting with the active one
+    async fn request_once<T, F>(&self, method: &str, f: &F) -> FinalityResult<T>
+    where
+        F: for<'a> Fn(&'a (dyn Provider<Ethereum> + Send + Sync)) -> ProviderFuture<'a, T>,
+    {
+        let start = self.active_endpoint();
+        let mut last_error = None;
+
+        for offset in 0..self.providers.len() {
+            let index = (start + offset) % self.providers.len();
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let error = match tokio::time::timeout(self.config.timeout, f(self.providers[index].as_ref())).await {
+                Ok(Ok(value)) => {
+                    if index != start {
+                        info!("L1 client failed over to endpoint {}", index);
+                        self.active_provider.store(index, Ordering::Relaxed);
+                    }
+                    return Ok(value);
+                }
+                Ok(Err(RpcError::Transport(e))) => {
+                    FinalityError::NetworkError(format!("{} failed on endpoint {}: {}", method, index, e))
+                }
+                Ok(Err(e)) => {
+                    return Err(FinalityError::L1RpcError(format!("{} failed: {}", method, e)));
+                }
+                Err(_) => FinalityError::TimeoutError(format!("{} timed out on endpoint {}", method, index)),
+            };
+
+            warn!("{}", error);
+            last_error = Some(error);
+        }
+
+        Err(last_error.expect("at least one endpoint was tried"))
     }
 
     /// Create from RPC URL string
@@ -79,27 +247,36 @@ impl L1Client {
     }
 
     /// Get chain ID using Alloy Provider
-    async fn get_chain_id(&self) -> FinalityResult<u64> {
-        let chain_id = self.provider.get_chain_id().await
-            .map_err(|e| FinalityError::L1RpcError(format!("Failed to get chain ID: {}", e)))?;
-        
-        Ok(chain_id)
+    pub async fn get_chain_id(&self) -> FinalityResult<u64> {
+        self.request("eth_chainId", |provider| Box::pin(provider.get_chain_id())).await
     }
 
     /// Get current L1 block number using Alloy Provider
     pub async fn get_current_block_number(&self) -> FinalityResult<u64> {
-        let block_number = self.provider.get_block_number().await
-            .map_err(|e| FinalityError::L1RpcError(format!("Failed to get block number: {}", e)))?;
+        let block_number = self
+            .request("eth_blockNumber", |provider| Box::pin(provider.get_block_number()))
+            .await?;
 
         debug!("Current L1 block number: {}", block_number);
         Ok(block_number)
     }
 
     /// Get block by number using Alloy Provider
+    ///
+    /// Blocks are cached for `block_cache_ttl`, so a reorged block is picked
+    /// up again once its cache entry expires.
     pub async fn get_block_by_number(&self, block_number: u64) -> FinalityResult<Option<L1Block>> {
+        if let Some(block) = self.block_cache.get(&block_number).await {
+            debug!("L1 block {} served from cache", block_number);
+            return Ok(Some(block));
+        }
+
         let block_id = BlockId::Number(block_number.into());
-        let block = self.provider.get_block(block_id).await
-            .map_err(|e| FinalityError::L1RpcError(format!("Failed to get block: {}", e)))?;
+        let block = self
+            .request("eth_getBlockByNumber", |provider| {
+                Box::pin(async move { provider.get_block(block_id).full().await })
+            })
+            .await?;
         
         match block {
             Some(block) => {
@@ -111,8 +288,15 @@ impl L1Client {
                     gas_limit: block.header.gas_limit,
                     gas_used: block.header.gas_used,
                     base_fee_per_gas: block.header.base_fee_per_gas,
-                    transactions: vec![], // Simplified for now
+                    transactions: block
+                        .transactions
+                        .as_transactions()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(L1Transaction::from)
+                        .collect(),
                 };
+                self.block_cache.insert(block_number, l1_block.clone()).await;
                 Ok(Some(l1_block))
             }
             None => Ok(None),
@@ -124,12 +308,36 @@ impl L1Client {
         &self,
         address: Address,
         data: &[u8],
-        _block_number: Option<u64>,
+        block_number: Option<u64>,
     ) -> FinalityResult<Vec<u8>> {
-        // Simplified implementation - in practice, you would use proper contract calling
-        // For now, we'll return an empty result as this is a placeholder
         debug!("Contract call to {:?} with {} bytes of data", address, data.len());
-        Ok(vec![])
+
+        let request = TransactionRequest::default()
+            .to(address)
+            .input(data.to_vec().into());
+        let block_id = block_number.map_or(BlockId::latest(), |number| BlockId::Number(number.into()));
+
+        let output = self
+            .request("eth_call", |provider| {
+                let request = request.clone();
+                Box::pin(async move { provider.call(request).block(block_id).await })
+            })
+            .await?;
+
+        Ok(output.to_vec())
+    }
+
+    /// Get the logs matching a filter using Alloy Provider
+    pub async fn get_logs(&self, filter: &Filter) -> FinalityResult<Vec<Log>> {
+        let logs = self
+            .request("eth_getLogs", |provider| {
+                let filter = filter.clone();
+                Box::pin(async move { provider.get_logs(&filter).await })
+            })
+            .await?;
+
+        debug!("Fetched {} L1 logs", logs.len());
+        Ok(logs)
     }
 
     /// Health check
@@ -185,6 +393,24 @@ pub struct L1Transaction {
     pub data: alloy_primitives::Bytes,
 }
 
+impl From<&Transaction> for L1Transaction {
+    fn from(tx: &Transaction) -> Self {
+        Self {
+            hash: tx.tx_hash(),
+            from: TransactionResponse::from(tx),
+            to: TransactionTrait::to(tx),
+            value: TransactionTrait::value(tx),
+            gas_price: tx
+                .effective_gas_price
+                .or_else(|| TransactionTrait::gas_price(tx))
+                .and_then(|price| u64::try_from(price).ok()),
+            gas_limit: TransactionTrait::gas_limit(tx),
+            nonce: TransactionTrait::nonce(tx),
+            data: TransactionTrait::input(tx).clone(),
+        }
+    }
+}
+
 /// L1 transaction receipt
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct L1TransactionReceipt {
@@ -214,6 +440,151 @@ mod tests {
         assert_eq!(config.rpc_url, "http://localhost:8545");
         assert_eq!(config.timeout, Duration::from_secs(30));
         assert_eq!(config.max_retries, 3);
+        assert!(config.fallback_rpc_urls.is_empty());
+        assert!(config.max_requests_per_second.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_block_by_number_is_cached() {
+        let asserter = alloy_provider::mock::Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let config = L1ClientConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let client = L1Client::with_providers(config, vec![Box::new(provider)]);
+
+        let mut block = alloy_rpc_types_eth::Block::<alloy_rpc_types_eth::Transaction>::default();
+        block.header.inner.number = 100;
+        block.header.hash = FixedBytes::from([7u8; 32]);
+        asserter.push_success(&block);
+
+        let first = client.get_block_by_number(100).await.unwrap().unwrap();
+        // Only one response is queued, so a second provider call would fail
+        let second = client.get_block_by_number(100).await.unwrap().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(second.hash, FixedBytes::from([7u8; 32]));
+        assert!(client.get_block_by_number(101).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_block_by_number_maps_transactions() {
+        use alloy_consensus::{Signed, TxEnvelope, TxLegacy};
+
+        let asserter = alloy_provider::mock::Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let client = L1Client::with_providers(L1ClientConfig::default(), vec![Box::new(provider)]);
+
+        let bridge = Address::from([2u8; 20]);
+        let sender = Address::from([3u8; 20]);
+        let tx_hash = FixedBytes::from([4u8; 32]);
+        let legacy = TxLegacy {
+            chain_id: Some(1),
+            nonce: 7,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: alloy_primitives::TxKind::Call(bridge),
+            value: U256::from(1000),
+            input: alloy_primitives::Bytes::from(vec![0xde, 0xad]),
+        };
+        let envelope = TxEnvelope::Legacy(Signed::new_unchecked(
+            legacy,
+            alloy_primitives::Signature::test_signature(),
+            tx_hash,
+        ));
+        let tx = Transaction {
+            inner: alloy_consensus::transaction::Recovered::new_unchecked(envelope, sender),
+            block_hash: Some(FixedBytes::from([7u8; 32])),
+            block_number: Some(100),
+            transaction_index: Some(0),
+            effective_gas_price: None,
+        };
+
+        let mut block = alloy_rpc_types_eth::Block::<Transaction>::default();
+        block.header.inner.number = 100;
+        block.transactions = alloy_rpc_types_eth::BlockTransactions::Full(vec![tx]);
+        asserter.push_success(&block);
+
+        let block = client.get_block_by_number(100).await.unwrap().unwrap();
+        assert_eq!(block.transactions.len(), 1);
+        let tx = &block.transactions[0];
+        assert_eq!(tx.hash, tx_hash);
+        assert_eq!(tx.from, sender);
+        assert_eq!(tx.to, Some(bridge));
+        assert_eq!(tx.value, U256::from(1000));
+        assert_eq!(tx.gas_price, Some(20_000_000_000));
+        assert_eq!(tx.gas_limit, 21_000);
+        assert_eq!(tx.nonce, 7);
+        assert_eq!(tx.data, alloy_primitives::Bytes::from(vec![0xde, 0xad]));
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_rate_limited() {
+        let asserter = alloy_provider::mock::Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let config = L1ClientConfig {
+            max_requests_per_second: Some(2),
+            ..Default::default()
+        };
+        let client = L1Client::with_providers(config, vec![Box::new(provider)]);
+
+        let start = std::time::Instant::now();
+        for i in 0..5u64 {
+            asserter.push_success(&U256::from(i));
+            assert_eq!(client.get_current_block_number().await.unwrap(), i);
+        }
+
+        // Two requests fit the initial burst, the other three wait 0.5s each
+        assert!(start.elapsed() >= Duration::from_millis(1400));
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_fallback_endpoint() {
+        // Nothing listens on port 1, so the primary fails with a connection error
+        let primary = ProviderBuilder::new().connect_http("http://127.0.0.1:1".parse().unwrap());
+        let asserter = alloy_provider::mock::Asserter::new();
+        let fallback = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+        let client = L1Client::with_providers(
+            L1ClientConfig::default(),
+            vec![Box::new(primary), Box::new(fallback)],
+        );
+
+        asserter.push_success(&U256::from(42));
+        assert_eq!(client.get_current_block_number().await.unwrap(), 42);
+        assert_eq!(client.active_endpoint(), 1);
+
+        // The healthy fallback is now preferred and answers directly
+        asserter.push_success(&U256::from(43));
+        assert_eq!(client.get_current_block_number().await.unwrap(), 43);
+        assert_eq!(client.active_endpoint(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_requests_retried_after_delay() {
+        let asserter = alloy_provider::mock::Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let config = L1ClientConfig {
+            max_retries: 2,
+            retry_delay: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let client = L1Client::with_providers(config, vec![Box::new(provider)]);
+
+        // The endpoint has nothing to answer until 1.5s in, so the second retry succeeds
+        let pusher = asserter.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            pusher.push_success(&U256::from(42));
+        });
+        let start = Instant::now();
+        assert_eq!(client.get_current_block_number().await.unwrap(), 42);
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+
+        // Retries are bounded by max_retries
+        let start = Instant::now();
+        assert!(matches!(client.get_current_block_number().await, Err(FinalityError::NetworkError(_))));
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
     }
 
     #[tokio::test]
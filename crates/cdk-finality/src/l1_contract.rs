@@ -1,13 +1,19 @@
 //! Simplified L1 contract interaction for CDK finality
 
-use crate::{FinalityError, FinalityResult, OracleMetadata};
+use crate::{FinalityError, FinalityResult, L1Client, L1ClientConfig, OracleMetadata};
 use alloy_primitives::{Address, FixedBytes, U256};
-use alloy_provider::{Provider, ProviderBuilder};
-use alloy_network::Ethereum;
+use alloy_rpc_types_eth::{Filter, Log};
+use alloy_sol_types::{sol, SolCall, SolEvent};
 use cdk_types::{FinalityTag, FinalityStatus};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tracing::{debug, info};
+use std::{collections::BTreeMap, time::Duration};
+use tracing::{debug, info, warn};
+
+/// Number of L1 blocks behind the last processed block that are queried again
+///
+/// Events in recently processed blocks are re-read on every poll so that a
+/// reorg of those blocks shows up as a changed block hash.
+pub const REORG_WINDOW: u64 = 64;
 
 /// CDK Bridge contract configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,11 +22,23 @@ pub struct CdkBridgeContract {
     pub address: Address,
 }
 
+sol! {
+    /// CDK bridge contract view interface
+    interface ICdkBridge {
+        function isBatchFinalized(uint256 batchId) external view returns (bool);
+
+        event BatchFinalized(uint256 indexed batchId, uint256 timestamp);
+        event BatchRolledBack(uint256 indexed batchId, uint256 timestamp);
+    }
+}
+
 /// Batch finalized event (simplified)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BatchFinalized {
     pub batch_id: U256,
     pub l1_block_number: U256,
+    pub l1_block_hash: FixedBytes<32>,
+    pub tx_hash: Option<FixedBytes<32>>,
     pub timestamp: U256,
 }
 
@@ -29,114 +47,172 @@ pub struct BatchFinalized {
 pub struct BatchRolledBack {
     pub batch_id: U256,
     pub l1_block_number: U256,
+    pub l1_block_hash: FixedBytes<32>,
+    pub tx_hash: Option<FixedBytes<32>>,
     pub timestamp: U256,
 }
 
 /// L1 contract client for CDK finality
+///
+/// All L1 requests go through an [`L1Client`], so its endpoint failover,
+/// retries, rate limiting and timeouts apply to them.
 pub struct L1ContractClient {
-    /// Provider for L1 interaction
-    provider: Box<dyn alloy_provider::Provider<Ethereum> + Send + Sync>,
+    /// L1 client for L1 interaction
+    l1_client: L1Client,
     /// Bridge contract
     bridge_contract: CdkBridgeContract,
     /// Current L1 block number
     current_l1_block: U256,
     /// Last processed block
-    #[allow(dead_code)]
     last_processed_block: U256,
 }
 
 impl L1ContractClient {
     /// Create a new L1 contract client
     pub async fn new(
-        rpc_url: &str,
+        config: L1ClientConfig,
         bridge_address: Address,
     ) -> FinalityResult<Self> {
         debug!("Creating L1 contract client for bridge: {:?}", bridge_address);
-        
-        // Create provider
-        let provider = ProviderBuilder::new()
-            .connect_http(rpc_url.parse().map_err(|e| {
-                FinalityError::ConfigError(format!("Invalid RPC URL: {}", e))
-            })?);
-
-        let bridge_contract = CdkBridgeContract {
-            address: bridge_address,
-        };
+
+        let mut client = Self::with_client(L1Client::new(config)?, bridge_address);
 
         // Get current L1 block
-        let current_l1_block = provider.get_block_number().await
-            .map_err(|e| FinalityError::L1RpcError(format!("Failed to get block number: {}", e)))?;
-        let current_l1_block = U256::from(current_l1_block);
-
-        Ok(Self {
-            provider: Box::new(provider),
-            bridge_contract,
-            current_l1_block,
+        client.get_current_block_number().await?;
+        Ok(client)
+    }
+
+    /// Create an L1 contract client on top of an existing L1 client
+    pub fn with_client(l1_client: L1Client, bridge_address: Address) -> Self {
+        Self {
+            l1_client,
+            bridge_contract: CdkBridgeContract {
+                address: bridge_address,
+            },
+            current_l1_block: U256::ZERO,
             last_processed_block: U256::ZERO,
-        })
+        }
     }
 
     /// Get finalized batches from L1 events
+    ///
+    /// Queries the bridge events from `REORG_WINDOW` blocks before the last
+    /// processed block up to the current L1 block, then advances the last
+    /// processed block to the current one.
     pub async fn get_finalized_batches(&mut self) -> FinalityResult<Vec<FinalityTag>> {
         debug!("Fetching finalized batches from L1");
-        
+
+        let to_block = self.get_current_block_number().await?.saturating_to::<u64>();
+        let from_block = self
+            .last_processed_block
+            .saturating_to::<u64>()
+            .saturating_add(1)
+            .saturating_sub(REORG_WINDOW)
+            .min(to_block);
+
         let mut finality_tags = Vec::new();
         
         // Query BatchFinalized events
-        let finalized_events = self.query_batch_finalized_events().await?;
+        let finalized_events = self.query_batch_finalized_events(from_block, to_block).await?;
         
         for event in finalized_events {
             let tag = FinalityTag::new(
                 event.batch_id,
                 event.l1_block_number,
-                FixedBytes::from([0u8; 32]), // Would get actual block hash
+                event.l1_block_hash,
                 FinalityStatus::Finalized,
-                event.timestamp.to::<u64>(),
-                None, // Would get actual tx hash
+                event.timestamp.saturating_to::<u64>(),
+                event.tx_hash,
             );
             finality_tags.push(tag);
         }
         
         // Query BatchRolledBack events
-        let rolled_back_events = self.query_batch_rolled_back_events().await?;
+        let rolled_back_events = self.query_batch_rolled_back_events(from_block, to_block).await?;
         
         for event in rolled_back_events {
             let tag = FinalityTag::new(
                 event.batch_id,
                 event.l1_block_number,
-                FixedBytes::from([0u8; 32]), // Would get actual block hash
+                event.l1_block_hash,
                 FinalityStatus::RolledBack,
-                event.timestamp.to::<u64>(),
-                None, // Would get actual tx hash
+                event.timestamp.saturating_to::<u64>(),
+                event.tx_hash,
             );
             finality_tags.push(tag);
         }
+
+        self.last_processed_block = U256::from(to_block);
         
-        info!("Found {} finality events", finality_tags.len());
+        info!("Found {} finality events in L1 blocks {}..={}", finality_tags.len(), from_block, to_block);
         Ok(finality_tags)
     }
 
     /// Query BatchFinalized events
-    async fn query_batch_finalized_events(&self) -> FinalityResult<Vec<BatchFinalized>> {
-        // In a real implementation, this would use proper event filtering
-        // For now, we'll return empty results
+    async fn query_batch_finalized_events(&self, from_block: u64, to_block: u64) -> FinalityResult<Vec<BatchFinalized>> {
         debug!("Querying BatchFinalized events");
-        Ok(vec![])
+        let logs = self.query_logs(ICdkBridge::BatchFinalized::SIGNATURE_HASH, from_block, to_block).await?;
+        logs.iter()
+            .map(|log| {
+                let (l1_block_number, l1_block_hash) = log_block(log)?;
+                let event = ICdkBridge::BatchFinalized::decode_log_data(log.data())
+                    .map_err(|e| FinalityError::L1RpcError(format!("Failed to decode BatchFinalized event: {}", e)))?;
+                Ok(BatchFinalized {
+                    batch_id: event.batchId,
+                    l1_block_number,
+                    l1_block_hash,
+                    tx_hash: log.transaction_hash,
+                    timestamp: event.timestamp,
+                })
+            })
+            .collect()
     }
 
     /// Query BatchRolledBack events
-    async fn query_batch_rolled_back_events(&self) -> FinalityResult<Vec<BatchRolledBack>> {
-        // In a real implementation, this would use proper event filtering
-        // For now, we'll return empty results
+    async fn query_batch_rolled_back_events(&self, from_block: u64, to_block: u64) -> FinalityResult<Vec<BatchRolledBack>> {
         debug!("Querying BatchRolledBack events");
-        Ok(vec![])
+        let logs = self.query_logs(ICdkBridge::BatchRolledBack::SIGNATURE_HASH, from_block, to_block).await?;
+        logs.iter()
+            .map(|log| {
+                let (l1_block_number, l1_block_hash) = log_block(log)?;
+                let event = ICdkBridge::BatchRolledBack::decode_log_data(log.data())
+                    .map_err(|e| FinalityError::L1RpcError(format!("Failed to decode BatchRolledBack event: {}", e)))?;
+                Ok(BatchRolledBack {
+                    batch_id: event.batchId,
+                    l1_block_number,
+                    l1_block_hash,
+                    tx_hash: log.transaction_hash,
+                    timestamp: event.timestamp,
+                })
+            })
+            .collect()
+    }
+
+    /// Fetch the bridge logs with the given event signature, skipping logs removed by a reorg
+    async fn query_logs(&self, signature: FixedBytes<32>, from_block: u64, to_block: u64) -> FinalityResult<Vec<Log>> {
+        let filter = Filter::new()
+            .address(self.bridge_contract.address)
+            .event_signature(signature)
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = self.l1_client.get_logs(&filter).await?;
+        Ok(logs.into_iter().filter(|log| !log.removed).collect())
+    }
+
+    /// Last L1 block whose events have been processed
+    pub fn last_processed_block(&self) -> U256 {
+        self.last_processed_block
+    }
+
+    /// Rewind event processing so the next query starts again from `block`
+    pub fn rewind_to(&mut self, block: U256) {
+        self.last_processed_block = self.last_processed_block.min(block.saturating_sub(U256::from(1)));
     }
 
     /// Get current L1 block number
     pub async fn get_current_block_number(&mut self) -> FinalityResult<U256> {
-        let block_number = self.provider.get_block_number().await
-            .map_err(|e| FinalityError::L1RpcError(format!("Failed to get block number: {}", e)))?;
-        
+        let block_number = self.l1_client.get_current_block_number().await?;
+
         self.current_l1_block = U256::from(block_number);
         Ok(self.current_l1_block)
     }
@@ -144,10 +220,15 @@ impl L1ContractClient {
     /// Check if a batch is finalized by calling the contract
     pub async fn is_batch_finalized(&self, batch_id: U256) -> FinalityResult<bool> {
         debug!("Checking if batch {} is finalized", batch_id);
-        
-        // In a real implementation, this would call the contract method
-        // For now, we'll return false
-        Ok(false)
+
+        let call = ICdkBridge::isBatchFinalizedCall { batchId: batch_id };
+        let output = self
+            .l1_client
+            .call_contract(self.bridge_contract.address, &call.abi_encode(), None)
+            .await?;
+
+        ICdkBridge::isBatchFinalizedCall::abi_decode_returns(&output)
+            .map_err(|e| FinalityError::L1RpcError(format!("Failed to decode isBatchFinalized result: {}", e)))
     }
 
     /// Get the finality status of a batch
@@ -163,19 +244,16 @@ impl L1ContractClient {
     pub async fn health_check(&self) -> FinalityResult<()> {
         debug!("Performing L1 contract client health check");
         
-        // Try to get the latest block number
-        self.provider.get_block_number().await
-            .map_err(|e| FinalityError::L1RpcError(format!("Health check failed: {}", e)))?;
-        
+        self.l1_client.health_check().await?;
+
         debug!("L1 contract client health check passed");
         Ok(())
     }
 
     /// Get client metadata
     pub async fn get_metadata(&self) -> FinalityResult<OracleMetadata> {
-        let chain_id = self.provider.get_chain_id().await
-            .map_err(|e| FinalityError::L1RpcError(format!("Failed to get chain ID: {}", e)))?;
-        
+        let chain_id = self.l1_client.get_chain_id().await?;
+
         let metadata = OracleMetadata::new(
             "L1 Contract Client".to_string(),
             "1.0".to_string(),
@@ -187,10 +265,68 @@ impl L1ContractClient {
     }
 }
 
+/// L1 block number and hash of a mined log
+fn log_block(log: &Log) -> FinalityResult<(U256, FixedBytes<32>)> {
+    match (log.block_number, log.block_hash) {
+        (Some(number), Some(hash)) => Ok((U256::from(number), hash)),
+        _ => Err(FinalityError::L1RpcError("Bridge event log without block number or hash".to_string())),
+    }
+}
+
+/// Tracks the L1 block hash behind every finality event seen so far
+///
+/// A finality event for an already-known L1 block that carries a different
+/// block hash means L1 reorged, invalidating any finality derived from that
+/// block onwards.
+#[derive(Debug, Clone, Default)]
+pub struct L1ReorgDetector {
+    block_hashes: BTreeMap<u64, FixedBytes<32>>,
+}
+
+impl L1ReorgDetector {
+    /// Create a new, empty reorg detector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the L1 blocks of the given tags, failing on the first hash mismatch
+    ///
+    /// On a reorg, every tracked block at or above the reorged block is
+    /// forgotten so that re-queried events are accepted.
+    pub fn check(&mut self, tags: &[FinalityTag]) -> FinalityResult<()> {
+        for tag in tags {
+            let block = tag.l1_block.saturating_to::<u64>();
+            match self.block_hashes.get(&block) {
+                Some(previous_hash) if *previous_hash != tag.l1_block_hash => {
+                    let previous_hash = *previous_hash;
+                    self.block_hashes.split_off(&block);
+                    return Err(FinalityError::L1Reorg {
+                        block,
+                        previous_hash,
+                        new_hash: tag.l1_block_hash,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    self.block_hashes.insert(block, tag.l1_block_hash);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the tracked hash for an L1 block
+    pub fn block_hash(&self, block: u64) -> Option<FixedBytes<32>> {
+        self.block_hashes.get(&block).copied()
+    }
+}
+
 /// Real finality oracle implementation
 pub struct RealFinalityOracle {
     /// L1 contract client
     l1_client: L1ContractClient,
+    /// L1 reorg detector
+    reorg_detector: L1ReorgDetector,
     /// Polling interval
     polling_interval: Duration,
     /// Last poll timestamp
@@ -208,18 +344,27 @@ impl std::fmt::Debug for RealFinalityOracle {
 
 impl RealFinalityOracle {
     /// Create a new real finality oracle
+    ///
+    /// The fallback endpoints in `config` are used for every L1 request, not
+    /// only while connecting.
     pub async fn new(
-        rpc_url: &str,
+        config: L1ClientConfig,
         bridge_address: Address,
         polling_interval: Duration,
     ) -> FinalityResult<Self> {
-        let l1_client = L1ContractClient::new(rpc_url, bridge_address).await?;
+        let l1_client = L1ContractClient::new(config, bridge_address).await?;
         
-        Ok(Self {
+        Ok(Self::with_client(l1_client, polling_interval))
+    }
+
+    /// Create a finality oracle on top of an existing L1 contract client
+    pub fn with_client(l1_client: L1ContractClient, polling_interval: Duration) -> Self {
+        Self {
             l1_client,
+            reorg_detector: L1ReorgDetector::new(),
             polling_interval,
             last_poll: std::time::Instant::now(),
-        })
+        }
     }
 
     /// Check if it's time to poll
@@ -244,6 +389,16 @@ impl crate::FinalityOracle for RealFinalityOracle {
         
         // Get finalized batches from L1
         let finality_tags = self.l1_client.get_finalized_batches().await?;
+
+        // On an L1 reorg, rewind and leave the poll time untouched so the next
+        // poll re-queries from the reorged block
+        if let Err(e) = self.reorg_detector.check(&finality_tags) {
+            if let FinalityError::L1Reorg { block, .. } = &e {
+                warn!("L1 reorg detected at block {}, re-querying from it", block);
+                self.l1_client.rewind_to(U256::from(*block));
+            }
+            return Err(e);
+        }
         
         // Update poll time
         self.update_poll_time();
@@ -287,6 +442,148 @@ impl crate::FinalityOracle for RealFinalityOracle {
 mod tests {
     use super::*;
     use alloy_primitives::Address;
+    use alloy_provider::ProviderBuilder;
+
+    /// Contract client whose L1 client answers from `asserter`
+    fn mocked_client(asserter: &alloy_provider::mock::Asserter, bridge: Address) -> L1ContractClient {
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        L1ContractClient::with_client(
+            L1Client::with_providers(L1ClientConfig::default(), vec![Box::new(provider)]),
+            bridge,
+        )
+    }
+
+    fn finalized_tag(batch_id: u64, l1_block: u64, l1_block_hash: [u8; 32]) -> FinalityTag {
+        FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(l1_block),
+            FixedBytes::from(l1_block_hash),
+            FinalityStatus::Finalized,
+            1234567890,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_reorg_detected_on_block_hash_mismatch() {
+        let mut detector = L1ReorgDetector::new();
+        detector
+            .check(&[finalized_tag(1, 100, [1u8; 32]), finalized_tag(2, 101, [2u8; 32])])
+            .unwrap();
+
+        // Same block, same hash: no reorg
+        detector.check(&[finalized_tag(1, 100, [1u8; 32])]).unwrap();
+
+        let err = detector.check(&[finalized_tag(1, 100, [9u8; 32])]).unwrap_err();
+        match err {
+            FinalityError::L1Reorg { block, previous_hash, new_hash } => {
+                assert_eq!(block, 100);
+                assert_eq!(previous_hash, FixedBytes::from([1u8; 32]));
+                assert_eq!(new_hash, FixedBytes::from([9u8; 32]));
+            }
+            other => panic!("expected L1Reorg, got {:?}", other),
+        }
+
+        // Blocks from the reorg point onwards are forgotten and re-learned
+        assert_eq!(detector.block_hash(101), None);
+        detector.check(&[finalized_tag(1, 100, [9u8; 32])]).unwrap();
+        assert_eq!(detector.block_hash(100), Some(FixedBytes::from([9u8; 32])));
+    }
+
+    #[tokio::test]
+    async fn test_is_batch_finalized_decodes_contract_result() {
+        let asserter = alloy_provider::mock::Asserter::new();
+        let client = mocked_client(&asserter, Address::from([1u8; 20]));
+
+        asserter.push_success(&alloy_primitives::Bytes::from(ICdkBridge::isBatchFinalizedCall::abi_encode_returns(&true)));
+        asserter.push_success(&alloy_primitives::Bytes::from(ICdkBridge::isBatchFinalizedCall::abi_encode_returns(&false)));
+        assert!(client.is_batch_finalized(U256::from(1)).await.unwrap());
+        assert!(!client.is_batch_finalized(U256::from(2)).await.unwrap());
+
+        asserter.push_success(&alloy_primitives::Bytes::new());
+        let err = client.is_batch_finalized(U256::from(3)).await.unwrap_err();
+        assert!(matches!(err, FinalityError::L1RpcError(_)));
+    }
+
+    /// Bridge log for a BatchFinalized event mined in `l1_block` with the given block hash
+    fn finalized_log(bridge: Address, batch_id: u64, l1_block: u64, l1_block_hash: [u8; 32]) -> Log {
+        let event = ICdkBridge::BatchFinalized {
+            batchId: U256::from(batch_id),
+            timestamp: U256::from(1234567890u64),
+        };
+        Log {
+            inner: alloy_primitives::Log { address: bridge, data: event.encode_log_data() },
+            block_hash: Some(FixedBytes::from(l1_block_hash)),
+            block_number: Some(l1_block),
+            block_timestamp: None,
+            transaction_hash: Some(FixedBytes::from([7u8; 32])),
+            transaction_index: Some(0),
+            log_index: Some(0),
+            removed: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_detects_reorg_of_event_block() {
+        let bridge = Address::from([1u8; 20]);
+        let asserter = alloy_provider::mock::Asserter::new();
+        let mut oracle = RealFinalityOracle::with_client(mocked_client(&asserter, bridge), Duration::ZERO);
+
+        // First poll: batch 1 finalized in L1 block 90
+        asserter.push_success(&alloy_primitives::U64::from(100));
+        asserter.push_success(&vec![finalized_log(bridge, 1, 90, [1u8; 32])]);
+        asserter.push_success(&Vec::<Log>::new());
+        let tags = crate::FinalityOracle::poll(&mut oracle).await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].l1_block, U256::from(90));
+        assert_eq!(tags[0].l1_block_hash, FixedBytes::from([1u8; 32]));
+        assert_eq!(tags[0].tx_hash, Some(FixedBytes::from([7u8; 32])));
+        assert_eq!(oracle.l1_client.last_processed_block(), U256::from(100));
+
+        // Block 90 is still within the re-queried window and now has another hash
+        asserter.push_success(&alloy_primitives::U64::from(101));
+        asserter.push_success(&vec![finalized_log(bridge, 1, 90, [2u8; 32])]);
+        asserter.push_success(&Vec::<Log>::new());
+        let err = crate::FinalityOracle::poll(&mut oracle).await.unwrap_err();
+        match err {
+            FinalityError::L1Reorg { block, previous_hash, new_hash } => {
+                assert_eq!(block, 90);
+                assert_eq!(previous_hash, FixedBytes::from([1u8; 32]));
+                assert_eq!(new_hash, FixedBytes::from([2u8; 32]));
+            }
+            other => panic!("expected L1Reorg, got {:?}", other),
+        }
+        // The next poll re-queries from the reorged block
+        assert_eq!(oracle.l1_client.last_processed_block(), U256::from(89));
+
+        asserter.push_success(&alloy_primitives::U64::from(101));
+        asserter.push_success(&vec![finalized_log(bridge, 1, 90, [2u8; 32])]);
+        asserter.push_success(&Vec::<Log>::new());
+        let tags = crate::FinalityOracle::poll(&mut oracle).await.unwrap();
+        assert_eq!(tags[0].l1_block_hash, FixedBytes::from([2u8; 32]));
+    }
+
+    #[tokio::test]
+    async fn test_poll_fails_over_to_fallback_endpoint() {
+        let bridge = Address::from([1u8; 20]);
+        // Nothing listens on port 1, so the primary fails with a connection error
+        let primary = ProviderBuilder::new().connect_http("http://127.0.0.1:1".parse().unwrap());
+        let asserter = alloy_provider::mock::Asserter::new();
+        let fallback = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let l1_client = L1Client::with_providers(
+            L1ClientConfig::default(),
+            vec![Box::new(primary), Box::new(fallback)],
+        );
+        let mut oracle = RealFinalityOracle::with_client(L1ContractClient::with_client(l1_client, bridge), Duration::ZERO);
+
+        asserter.push_success(&alloy_primitives::U64::from(100));
+        asserter.push_success(&vec![finalized_log(bridge, 1, 90, [1u8; 32])]);
+        asserter.push_success(&Vec::<Log>::new());
+        let tags = crate::FinalityOracle::poll(&mut oracle).await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].batch_id, U256::from(1));
+        assert_eq!(oracle.l1_client.l1_client.active_endpoint(), 1);
+    }
 
     #[tokio::test]
     async fn test_l1_contract_client_creation() {
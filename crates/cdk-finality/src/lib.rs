@@ -9,9 +9,13 @@ pub mod oracle;
 pub mod l1_client;
 pub mod rollback;
 pub mod l1_contract;
+pub mod watcher;
+pub mod quorum;
 
 pub use error::*;
 pub use oracle::*;
 pub use l1_client::*;
 pub use rollback::*;
 pub use l1_contract::*;
+pub use watcher::*;
+pub use quorum::*;
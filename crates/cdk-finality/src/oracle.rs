@@ -1,7 +1,7 @@
 //! Core traits for finality oracle
 
 use cdk_types::{FinalityTag, FinalityStatus};
-use crate::{FinalityResult};
+use crate::{FinalityError, FinalityResult};
 use async_trait::async_trait;
 use std::fmt::Debug;
 
@@ -103,6 +103,12 @@ pub struct FinalityUpdate {
     pub detected_at: u64,
 }
 
+/// Batch number of a finality tag as the `u64` that per-batch state is keyed by
+pub fn tag_batch_number(tag: &FinalityTag) -> FinalityResult<u64> {
+    u64::try_from(tag.batch_id)
+        .map_err(|_| FinalityError::InvalidFinalityData(format!("Batch id {} does not fit in u64", tag.batch_id)))
+}
+
 /// Type of finality event
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FinalityEventType {
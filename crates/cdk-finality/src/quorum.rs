@@ -0,0 +1,374 @@
+//! Finality oracle requiring a quorum of inner oracles to agree
+
+use crate::{tag_batch_number, FinalityError, FinalityOracle, FinalityResult, OracleMetadata};
+use cdk_types::{FinalityStatus, FinalityTag};
+use std::{collections::HashMap, time::Duration};
+use tracing::{debug, warn};
+
+/// Finality oracle aggregating several oracles behind a quorum threshold
+///
+/// A batch is only reported finalized (or rolled back) once at least
+/// `threshold` inner oracles have reported that status for it. Reports are
+/// remembered across polls, so the oracles do not need to agree in the same
+/// poll.
+///
+/// Once a batch reaches finalized quorum, reports and accepted tags for the
+/// batches below it are dropped, and later reports for them are ignored.
+#[derive(Debug)]
+pub struct QuorumFinalityOracle {
+    oracles: Vec<Box<dyn FinalityOracle>>,
+    threshold: usize,
+    /// Latest tag reported by each inner oracle, per batch
+    reports: HashMap<u64, Vec<Option<FinalityTag>>>,
+    /// Tags that reached quorum
+    accepted: HashMap<u64, FinalityTag>,
+    /// Highest batch that reached finalized quorum
+    finalized_height: Option<u64>,
+    polling_interval: Duration,
+}
+
+impl QuorumFinalityOracle {
+    /// Create a new quorum oracle over the given oracles
+    pub fn new(oracles: Vec<Box<dyn FinalityOracle>>, threshold: usize) -> FinalityResult<Self> {
+        if threshold == 0 || threshold > oracles.len() {
+            return Err(FinalityError::ConfigError(format!(
+                "Quorum threshold {} must be between 1 and the number of oracles ({})",
+                threshold,
+                oracles.len()
+            )));
+        }
+
+        let polling_interval = oracles
+            .iter()
+            .map(|oracle| oracle.get_polling_interval())
+            .min()
+            .unwrap_or(Duration::from_secs(12));
+
+        Ok(Self {
+            oracles,
+            threshold,
+            reports: HashMap::new(),
+            accepted: HashMap::new(),
+            finalized_height: None,
+            polling_interval,
+        })
+    }
+
+    /// Get the quorum threshold
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Return the tag for `status` if at least `threshold` oracles reported it
+    fn quorum_tag(&self, batch_id: u64, status: &FinalityStatus) -> Option<FinalityTag> {
+        let reports = self.reports.get(&batch_id)?;
+        let mut agreeing = reports.iter().flatten().filter(|tag| &tag.status == status);
+        let first = agreeing.next()?.clone();
+        (1 + agreeing.count() >= self.threshold).then_some(first)
+    }
+
+    /// Collect the status each inner oracle currently reports for a batch
+    async fn inner_statuses(&self, batch_id: u64) -> Vec<FinalityStatus> {
+        let mut statuses = Vec::new();
+        for oracle in &self.oracles {
+            match oracle.get_finality_status(batch_id).await {
+                Ok(Some(status)) => statuses.push(status),
+                Ok(None) => {}
+                Err(e) => warn!("Inner oracle failed to report status for batch {}: {}", batch_id, e),
+            }
+        }
+        statuses
+    }
+
+    /// Raise the finalized height to `batch_id` and drop the state it covers
+    ///
+    /// The accepted tag of the finalized height itself is kept, so the latest
+    /// finalized batch is still reported.
+    fn prune_finalized(&mut self, batch_id: u64) {
+        let height = self.finalized_height.map_or(batch_id, |height| height.max(batch_id));
+        self.finalized_height = Some(height);
+        self.reports.retain(|id, _| *id > height);
+        self.accepted.retain(|id, _| *id >= height);
+    }
+
+    fn accepted_with_status(&self, status: FinalityStatus) -> Vec<FinalityTag> {
+        let mut tags: Vec<_> = self.accepted.values().filter(|tag| tag.status == status).cloned().collect();
+        tags.sort_by_key(|tag| tag.batch_id);
+        tags
+    }
+}
+
+#[async_trait::async_trait]
+impl FinalityOracle for QuorumFinalityOracle {
+    async fn poll(&mut self) -> FinalityResult<Vec<FinalityTag>> {
+        let oracle_count = self.oracles.len();
+        let mut touched = Vec::new();
+
+        for (index, oracle) in self.oracles.iter_mut().enumerate() {
+            let tags = match oracle.poll().await {
+                Ok(tags) => tags,
+                Err(e) => {
+                    warn!("Inner oracle {} failed to poll: {}", index, e);
+                    continue;
+                }
+            };
+
+            for tag in tags {
+                let batch_id = match tag_batch_number(&tag) {
+                    Ok(batch_id) => batch_id,
+                    Err(e) => {
+                        warn!("Inner oracle {} reported an invalid tag: {}", index, e);
+                        continue;
+                    }
+                };
+                if self.finalized_height.is_some_and(|height| batch_id <= height) {
+                    continue;
+                }
+                self.reports.entry(batch_id).or_insert_with(|| vec![None; oracle_count])[index] = Some(tag);
+                if !touched.contains(&batch_id) {
+                    touched.push(batch_id);
+                }
+            }
+        }
+
+        let mut updates = Vec::new();
+        for batch_id in touched {
+            for status in [FinalityStatus::Finalized, FinalityStatus::RolledBack] {
+                let Some(tag) = self.quorum_tag(batch_id, &status) else {
+                    continue;
+                };
+                if self.accepted.get(&batch_id).is_some_and(|accepted| accepted.status == status) {
+                    continue;
+                }
+
+                debug!("Batch {} reached {:?} quorum", batch_id, status);
+                self.accepted.insert(batch_id, tag.clone());
+                updates.push(tag);
+            }
+        }
+
+        let finalized = updates
+            .iter()
+            .filter(|tag| tag.status == FinalityStatus::Finalized)
+            .filter_map(|tag| tag_batch_number(tag).ok())
+            .max();
+        if let Some(batch_id) = finalized {
+            self.prune_finalized(batch_id);
+        }
+
+        Ok(updates)
+    }
+
+    async fn get_finality_status(&self, batch_id: u64) -> FinalityResult<Option<FinalityStatus>> {
+        let statuses = self.inner_statuses(batch_id).await;
+        if statuses.is_empty() {
+            return Ok(None);
+        }
+
+        for status in [FinalityStatus::Finalized, FinalityStatus::RolledBack] {
+            if statuses.iter().filter(|reported| **reported == status).count() >= self.threshold {
+                return Ok(Some(status));
+            }
+        }
+        Ok(Some(FinalityStatus::Pending))
+    }
+
+    async fn get_finalized_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+        Ok(self.accepted_with_status(FinalityStatus::Finalized))
+    }
+
+    async fn get_rolled_back_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+        Ok(self.accepted_with_status(FinalityStatus::RolledBack))
+    }
+
+    async fn health_check(&self) -> FinalityResult<()> {
+        let mut healthy = 0;
+        for (index, oracle) in self.oracles.iter().enumerate() {
+            match oracle.health_check().await {
+                Ok(()) => healthy += 1,
+                Err(e) => warn!("Inner oracle {} is unhealthy: {}", index, e),
+            }
+        }
+
+        if healthy >= self.threshold {
+            Ok(())
+        } else {
+            Err(FinalityError::HealthCheckError(format!(
+                "Only {} of {} oracles healthy, quorum requires {}",
+                healthy,
+                self.oracles.len(),
+                self.threshold
+            )))
+        }
+    }
+
+    async fn metadata(&self) -> FinalityResult<OracleMetadata> {
+        let mut last_error = None;
+        for oracle in &self.oracles {
+            match oracle.metadata().await {
+                Ok(inner) => {
+                    return Ok(OracleMetadata::new(
+                        "Quorum Finality Oracle".to_string(),
+                        "1.0".to_string(),
+                        inner.l1_chain_id,
+                        inner.bridge_address,
+                    )
+                    .update_l1_block(inner.current_l1_block));
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| FinalityError::OracleError("No inner oracles".to_string())))
+    }
+
+    fn set_polling_interval(&mut self, interval: Duration) {
+        self.polling_interval = interval;
+        for oracle in &mut self.oracles {
+            oracle.set_polling_interval(interval);
+        }
+    }
+
+    fn get_polling_interval(&self) -> Duration {
+        self.polling_interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, FixedBytes, U256};
+    use std::collections::VecDeque;
+
+    #[derive(Debug)]
+    struct MockOracle {
+        polls: VecDeque<Vec<FinalityTag>>,
+        statuses: HashMap<u64, FinalityStatus>,
+        healthy: bool,
+    }
+
+    impl MockOracle {
+        fn new(polls: Vec<Vec<FinalityTag>>) -> Self {
+            let statuses = polls
+                .iter()
+                .flatten()
+                .map(|tag| (tag.batch_id.saturating_to::<u64>(), tag.status.clone()))
+                .collect();
+            Self {
+                polls: polls.into(),
+                statuses,
+                healthy: true,
+            }
+        }
+
+        fn unhealthy(mut self) -> Self {
+            self.healthy = false;
+            self
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FinalityOracle for MockOracle {
+        async fn poll(&mut self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(self.polls.pop_front().unwrap_or_default())
+        }
+
+        async fn get_finality_status(&self, batch_id: u64) -> FinalityResult<Option<FinalityStatus>> {
+            Ok(self.statuses.get(&batch_id).cloned())
+        }
+
+        async fn get_finalized_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(vec![])
+        }
+
+        async fn get_rolled_back_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(vec![])
+        }
+
+        async fn health_check(&self) -> FinalityResult<()> {
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(FinalityError::HealthCheckError("mock unhealthy".to_string()))
+            }
+        }
+
+        async fn metadata(&self) -> FinalityResult<OracleMetadata> {
+            Ok(OracleMetadata::new("mock".to_string(), "1.0".to_string(), 1, Address::ZERO))
+        }
+
+        fn set_polling_interval(&mut self, _interval: Duration) {}
+
+        fn get_polling_interval(&self) -> Duration {
+            Duration::from_secs(12)
+        }
+    }
+
+    fn tag(batch_id: u64, status: FinalityStatus) -> FinalityTag {
+        FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(1000),
+            FixedBytes::from([1u8; 32]),
+            status,
+            1234567890,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_finality_requires_quorum() {
+        let oracles: Vec<Box<dyn FinalityOracle>> = vec![
+            Box::new(MockOracle::new(vec![vec![
+                tag(1, FinalityStatus::Finalized),
+                tag(2, FinalityStatus::Finalized),
+            ]])),
+            Box::new(MockOracle::new(vec![vec![tag(1, FinalityStatus::Finalized)]])),
+            Box::new(MockOracle::new(vec![
+                vec![tag(1, FinalityStatus::Pending)],
+                vec![tag(2, FinalityStatus::Finalized)],
+            ])),
+        ];
+        let mut oracle = QuorumFinalityOracle::new(oracles, 2).unwrap();
+
+        // Batch 1 has two finalized reports, batch 2 only one
+        let updates = oracle.poll().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].batch_id, U256::from(1));
+        assert_eq!(updates[0].status, FinalityStatus::Finalized);
+
+        // The third oracle confirms batch 2; batch 1 is not reported again
+        let updates = oracle.poll().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].batch_id, U256::from(2));
+
+        // Batch 1 is pruned once batch 2 is finalized
+        let finalized = oracle.get_finalized_batches().await.unwrap();
+        assert_eq!(finalized.len(), 1);
+        assert_eq!(finalized[0].batch_id, U256::from(2));
+        assert!(oracle.reports.is_empty());
+        assert_eq!(oracle.get_finality_status(1).await.unwrap(), Some(FinalityStatus::Finalized));
+        assert_eq!(oracle.get_finality_status(3).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_requires_quorum() {
+        let oracles: Vec<Box<dyn FinalityOracle>> = vec![
+            Box::new(MockOracle::new(vec![])),
+            Box::new(MockOracle::new(vec![])),
+            Box::new(MockOracle::new(vec![]).unhealthy()),
+        ];
+        assert!(QuorumFinalityOracle::new(oracles, 2).unwrap().health_check().await.is_ok());
+
+        let oracles: Vec<Box<dyn FinalityOracle>> = vec![
+            Box::new(MockOracle::new(vec![])),
+            Box::new(MockOracle::new(vec![]).unhealthy()),
+            Box::new(MockOracle::new(vec![]).unhealthy()),
+        ];
+        assert!(QuorumFinalityOracle::new(oracles, 2).unwrap().health_check().await.is_err());
+    }
+
+    #[test]
+    fn test_invalid_threshold_rejected() {
+        let oracles: Vec<Box<dyn FinalityOracle>> = vec![Box::new(MockOracle::new(vec![]))];
+        assert!(QuorumFinalityOracle::new(oracles, 2).is_err());
+    }
+}
@@ -1,9 +1,90 @@
 //! Rollback management for finality operations
 
-use crate::{FinalityError, FinalityResult, FinalityUpdate, FinalityEventType};
+use crate::{tag_batch_number, FinalityError, FinalityResult, FinalityUpdate, FinalityEventType};
 use alloy_primitives::FixedBytes;
-use std::collections::HashMap;
-use tracing::{debug, info, warn};
+use async_trait::async_trait;
+use cdk_types::FinalityStatus;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, error, info, warn};
+
+/// Chain-state hook used by `RollbackManager` to unwind L2 blocks
+#[async_trait]
+pub trait BlockUnwinder: Send + Sync + Debug {
+    /// Get the L2 block numbers belonging to a batch, in ascending order
+    async fn affected_blocks(&self, batch_id: u64) -> FinalityResult<Vec<u64>>;
+
+    /// Get the current L2 head block number
+    async fn head_block(&self) -> FinalityResult<u64>;
+
+    /// Unwind the chain so that `block_number` becomes the new head
+    async fn unwind_to(&self, block_number: u64) -> FinalityResult<()>;
+}
+
+/// Persistence for rollback history and pending rollbacks
+#[async_trait]
+pub trait RollbackStore: Send + Sync + Debug {
+    /// Save an executed rollback record
+    async fn save_record(&self, record: RollbackRecord) -> FinalityResult<()>;
+
+    /// Load all rollback records
+    async fn load_records(&self) -> FinalityResult<Vec<RollbackRecord>>;
+
+    /// Delete the rollback record for a batch
+    async fn delete_record(&self, batch_id: u64) -> FinalityResult<()>;
+
+    /// Save a pending rollback
+    async fn save_pending(&self, pending: PendingRollback) -> FinalityResult<()>;
+
+    /// Load all pending rollbacks
+    async fn load_pending(&self) -> FinalityResult<Vec<PendingRollback>>;
+
+    /// Delete the pending rollback for a batch
+    async fn delete_pending(&self, batch_id: u64) -> FinalityResult<()>;
+}
+
+/// In-memory rollback store
+///
+/// Clones share the same underlying maps.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryRollbackStore {
+    records: Arc<Mutex<HashMap<u64, RollbackRecord>>>,
+    pending: Arc<Mutex<HashMap<u64, PendingRollback>>>,
+}
+
+#[async_trait]
+impl RollbackStore for MemoryRollbackStore {
+    async fn save_record(&self, record: RollbackRecord) -> FinalityResult<()> {
+        self.records.lock().unwrap().insert(record.batch_id, record);
+        Ok(())
+    }
+
+    async fn load_records(&self) -> FinalityResult<Vec<RollbackRecord>> {
+        Ok(self.records.lock().unwrap().values().cloned().collect())
+    }
+
+    async fn delete_record(&self, batch_id: u64) -> FinalityResult<()> {
+        self.records.lock().unwrap().remove(&batch_id);
+        Ok(())
+    }
+
+    async fn save_pending(&self, pending: PendingRollback) -> FinalityResult<()> {
+        self.pending.lock().unwrap().insert(pending.batch_id, pending);
+        Ok(())
+    }
+
+    async fn load_pending(&self) -> FinalityResult<Vec<PendingRollback>> {
+        Ok(self.pending.lock().unwrap().values().cloned().collect())
+    }
+
+    async fn delete_pending(&self, batch_id: u64) -> FinalityResult<()> {
+        self.pending.lock().unwrap().remove(&batch_id);
+        Ok(())
+    }
+}
 
 /// Rollback manager for handling batch rollbacks
 #[derive(Debug)]
@@ -14,6 +95,16 @@ pub struct RollbackManager {
     pending_rollbacks: HashMap<u64, PendingRollback>,
     /// Rollback configuration
     config: RollbackConfig,
+    /// Hook used to resolve and unwind affected blocks
+    unwinder: Option<Box<dyn BlockUnwinder>>,
+    /// Last known finality status per batch above `finalized_height`
+    batch_statuses: HashMap<u64, FinalityStatus>,
+    /// Highest finalized batch; every batch at or below it is treated as finalized
+    finalized_height: Option<u64>,
+    /// Persistent store mirroring history and pending rollbacks
+    store: Box<dyn RollbackStore>,
+    /// Pending rollbacks that were dropped or failed instead of executing
+    aborted_rollbacks: u64,
 }
 
 /// Rollback record
@@ -59,7 +150,7 @@ pub struct PendingRollback {
 pub struct RollbackConfig {
     /// Required confirmations before executing rollback
     pub required_confirmations: u64,
-    /// Maximum rollback depth
+    /// Maximum number of blocks a rollback may unwind from the current head
     pub max_rollback_depth: u64,
     /// Rollback timeout
     pub rollback_timeout: std::time::Duration,
@@ -88,9 +179,49 @@ impl RollbackManager {
             rollback_history: HashMap::new(),
             pending_rollbacks: HashMap::new(),
             config,
+            unwinder: None,
+            batch_statuses: HashMap::new(),
+            finalized_height: None,
+            store: Box::new(MemoryRollbackStore::default()),
+            aborted_rollbacks: 0,
         }
     }
 
+    /// Create a rollback manager backed by `store`, restoring its saved state
+    pub async fn with_store(config: RollbackConfig, store: Box<dyn RollbackStore>) -> FinalityResult<Self> {
+        let rollback_history: HashMap<u64, RollbackRecord> = store
+            .load_records()
+            .await?
+            .into_iter()
+            .map(|record| (record.batch_id, record))
+            .collect();
+        let pending_rollbacks: HashMap<u64, PendingRollback> = store
+            .load_pending()
+            .await?
+            .into_iter()
+            .map(|pending| (pending.batch_id, pending))
+            .collect();
+
+        info!(
+            "Restored {} rollback records and {} pending rollbacks",
+            rollback_history.len(),
+            pending_rollbacks.len()
+        );
+
+        Ok(Self {
+            rollback_history,
+            pending_rollbacks,
+            store,
+            ..Self::new(config)
+        })
+    }
+
+    /// Set the block unwinder used when executing rollbacks
+    pub fn with_unwinder(mut self, unwinder: Box<dyn BlockUnwinder>) -> Self {
+        self.unwinder = Some(unwinder);
+        self
+    }
+
     /// Process a finality update
     pub async fn process_finality_update(
         &mut self,
@@ -98,44 +229,98 @@ impl RollbackManager {
     ) -> FinalityResult<Vec<RollbackAction>> {
         debug!("Processing finality update: {:?}", update);
 
-        match update.event_type {
+        let batch_id = tag_batch_number(&update.tag)?;
+        let next_status = match update.event_type {
+            FinalityEventType::RolledBack => FinalityStatus::RolledBack,
+            FinalityEventType::Finalized => FinalityStatus::Finalized,
+            FinalityEventType::StatusChanged => update.tag.status.clone(),
+        };
+        if let Some(current) = self.known_status(batch_id) {
+            Self::validate_transition(current, next_status.clone())?;
+        }
+
+        let actions = match update.event_type {
             FinalityEventType::RolledBack => {
-                self.handle_rollback(update).await
+                self.handle_rollback(batch_id, update).await
             }
             FinalityEventType::Finalized => {
-                self.handle_finalization(update).await
+                self.handle_finalization(batch_id).await
             }
             FinalityEventType::StatusChanged => {
-                self.handle_status_change(update).await
+                self.handle_status_change(batch_id, update).await
             }
+        }?;
+
+        if next_status == FinalityStatus::Finalized {
+            self.prune_finalized(batch_id);
+        } else {
+            self.batch_statuses.insert(batch_id, next_status);
+        }
+        Ok(actions)
+    }
+
+    /// Last known finality status of a batch
+    fn known_status(&self, batch_id: u64) -> Option<FinalityStatus> {
+        match self.finalized_height {
+            Some(height) if batch_id <= height => Some(FinalityStatus::Finalized),
+            _ => self.batch_statuses.get(&batch_id).cloned(),
+        }
+    }
+
+    /// Raise the finalized height to `batch_id` and drop the statuses it covers
+    fn prune_finalized(&mut self, batch_id: u64) {
+        let height = self.finalized_height.map_or(batch_id, |height| height.max(batch_id));
+        self.finalized_height = Some(height);
+        self.batch_statuses.retain(|id, _| *id > height);
+    }
+
+    /// Check that a batch may move from `current` to `next` finality status
+    ///
+    /// Finalized batches are terminal. Rolled back batches may only return to
+    /// pending when they are resubmitted.
+    pub fn validate_transition(current: FinalityStatus, next: FinalityStatus) -> FinalityResult<()> {
+        use FinalityStatus::*;
+
+        match (&current, &next) {
+            (Pending, _) |
+            (Finalized, Finalized) |
+            (RolledBack, RolledBack) |
+            (RolledBack, Pending) => Ok(()),
+            _ => Err(FinalityError::RollbackError(format!(
+                "Illegal finality status transition from {:?} to {:?}",
+                current, next
+            ))),
         }
     }
 
     /// Handle rollback event
     async fn handle_rollback(
         &mut self,
+        batch_id: u64,
         update: FinalityUpdate,
     ) -> FinalityResult<Vec<RollbackAction>> {
-        let batch_id = update.tag.batch_id.to::<u64>();
-        
         // Check if rollback is already processed
         if self.rollback_history.contains_key(&batch_id) {
             warn!("Rollback for batch {} already processed", batch_id);
             return Ok(vec![]);
         }
 
-        // Create pending rollback
-        let pending_rollback = PendingRollback {
-            batch_id,
-            batch_hash: update.tag.l1_block_hash,
-            l1_block_number: update.l1_block_number,
-            tx_hash: update.tx_hash,
-            timestamp: update.detected_at,
-            confirmations: 0,
-            required_confirmations: self.config.required_confirmations,
-        };
-
-        self.pending_rollbacks.insert(batch_id, pending_rollback);
+        // Create pending rollback, keeping the confirmations of one reported
+        // again, e.g. after a failed attempt
+        if !self.pending_rollbacks.contains_key(&batch_id) {
+            let pending_rollback = PendingRollback {
+                batch_id,
+                batch_hash: update.tag.l1_block_hash,
+                l1_block_number: update.l1_block_number,
+                tx_hash: update.tx_hash,
+                timestamp: update.detected_at,
+                confirmations: 0,
+                required_confirmations: self.config.required_confirmations,
+            };
+
+            self.store.save_pending(pending_rollback.clone()).await?;
+            self.pending_rollbacks.insert(batch_id, pending_rollback);
+        }
 
         if self.config.auto_execute {
             // Check if we have enough confirmations
@@ -150,12 +335,12 @@ impl RollbackManager {
     /// Handle finalization event
     async fn handle_finalization(
         &mut self,
-        update: FinalityUpdate,
+        batch_id: u64,
     ) -> FinalityResult<Vec<RollbackAction>> {
-        let batch_id = update.tag.batch_id.to::<u64>();
-        
         // Remove from pending rollbacks if it was there
         if self.pending_rollbacks.remove(&batch_id).is_some() {
+            self.aborted_rollbacks += 1;
+            self.store.delete_pending(batch_id).await?;
             info!("Batch {} was finalized, removing from pending rollbacks", batch_id);
         }
 
@@ -165,16 +350,18 @@ impl RollbackManager {
     /// Handle status change event
     async fn handle_status_change(
         &mut self,
+        batch_id: u64,
         update: FinalityUpdate,
     ) -> FinalityResult<Vec<RollbackAction>> {
-        debug!("Status change for batch {}: {:?}", update.tag.batch_id, update.tag.status);
-        Ok(vec![RollbackAction::StatusChanged(update.tag.batch_id.to::<u64>())])
+        debug!("Status change for batch {}: {:?}", batch_id, update.tag.status);
+        Ok(vec![RollbackAction::StatusChanged(batch_id)])
     }
 
     /// Check rollback confirmations
     async fn check_rollback_confirmations(&mut self, batch_id: u64) -> FinalityResult<bool> {
         if let Some(pending) = self.pending_rollbacks.get_mut(&batch_id) {
             pending.confirmations += 1;
+            self.store.save_pending(pending.clone()).await?;
             
             if pending.confirmations >= pending.required_confirmations {
                 debug!("Rollback for batch {} has enough confirmations", batch_id);
@@ -186,10 +373,45 @@ impl RollbackManager {
     }
 
     /// Execute rollback
+    ///
+    /// The pending rollback is only dropped once the chain has been unwound.
     async fn execute_rollback(&mut self, batch_id: u64) -> FinalityResult<Vec<RollbackAction>> {
-        let pending = self.pending_rollbacks.remove(&batch_id)
+        let pending = self.pending_rollbacks.get(&batch_id).cloned()
             .ok_or_else(|| FinalityError::RollbackError(format!("No pending rollback for batch {}", batch_id)))?;
 
+        let affected_blocks = match self.calculate_affected_blocks(batch_id).await {
+            Ok(affected_blocks) => affected_blocks,
+            Err(e) => return self.abort_rollback(batch_id, e).await,
+        };
+
+        // Refuse to unwind further than configured, e.g. on a malformed L1 event.
+        // Blocks built on top of the batch are unwound too, so the depth is
+        // measured from the current head.
+        let depth = match (&self.unwinder, affected_blocks.first()) {
+            (Some(unwinder), Some(first_block)) => match unwinder.head_block().await {
+                Ok(head) => head.saturating_sub(first_block.saturating_sub(1)),
+                Err(e) => return self.abort_rollback(batch_id, e).await,
+            },
+            _ => 0,
+        };
+        if depth > self.config.max_rollback_depth {
+            error!(
+                "Rejecting rollback for batch {}: depth {} exceeds maximum {}",
+                batch_id, depth, self.config.max_rollback_depth
+            );
+            self.aborted_rollbacks += 1;
+            self.store.delete_pending(batch_id).await?;
+            self.pending_rollbacks.remove(&batch_id);
+            return Ok(vec![RollbackAction::Rejected { batch_id, depth }]);
+        }
+
+        // Unwind to the block just before the first block of the rolled back batch
+        if let (Some(unwinder), Some(first_block)) = (&self.unwinder, affected_blocks.first()) {
+            if let Err(e) = unwinder.unwind_to(first_block.saturating_sub(1)).await {
+                return self.abort_rollback(batch_id, e).await;
+            }
+        }
+
         // Create rollback record
         let rollback_record = RollbackRecord {
             batch_id,
@@ -198,9 +420,12 @@ impl RollbackManager {
             tx_hash: pending.tx_hash,
             timestamp: pending.timestamp,
             reason: "L1 finality rollback".to_string(),
-            affected_blocks: self.calculate_affected_blocks(batch_id).await?,
+            affected_blocks,
         };
 
+        self.store.save_record(rollback_record.clone()).await?;
+        self.store.delete_pending(batch_id).await?;
+        self.pending_rollbacks.remove(&batch_id);
         self.rollback_history.insert(batch_id, rollback_record);
 
         info!("Executing rollback for batch {} affecting {} blocks", 
@@ -209,12 +434,22 @@ impl RollbackManager {
         Ok(vec![RollbackAction::ExecuteRollback(batch_id)])
     }
 
+    /// Record a failed rollback attempt, keeping its pending entry so it can be retried
+    async fn abort_rollback(&mut self, batch_id: u64, error: FinalityError) -> FinalityResult<Vec<RollbackAction>> {
+        warn!("Rollback for batch {} failed, keeping it pending: {}", batch_id, error);
+        self.aborted_rollbacks += 1;
+        Err(error)
+    }
+
     /// Calculate affected blocks for a rollback
     async fn calculate_affected_blocks(&self, batch_id: u64) -> FinalityResult<Vec<u64>> {
-        // In a real implementation, this would query the database
-        // to find all blocks that belong to this batch
-        // For now, we return a placeholder
-        Ok(vec![batch_id * 100, batch_id * 100 + 1, batch_id * 100 + 2])
+        match &self.unwinder {
+            Some(unwinder) => unwinder.affected_blocks(batch_id).await,
+            None => {
+                warn!("No block unwinder configured, cannot resolve blocks for batch {}", batch_id);
+                Ok(vec![])
+            }
+        }
     }
 
     /// Get rollback history
@@ -237,15 +472,79 @@ impl RollbackManager {
         self.rollback_history.get(&batch_id)
     }
 
+    /// Drop pending rollbacks detected more than `rollback_timeout` before `now`
+    ///
+    /// Intended to be called from the oracle poll loop; `now` is a unix timestamp in seconds.
+    pub async fn expire_stale_pending(&mut self, now: u64) -> FinalityResult<Vec<RollbackAction>> {
+        let timeout = self.config.rollback_timeout.as_secs();
+        let mut expired: Vec<u64> = self.pending_rollbacks.values()
+            .filter(|pending| now.saturating_sub(pending.timestamp) > timeout)
+            .map(|pending| pending.batch_id)
+            .collect();
+        expired.sort_unstable();
+
+        let mut actions = Vec::with_capacity(expired.len());
+        for batch_id in expired {
+            self.store.delete_pending(batch_id).await?;
+            self.pending_rollbacks.remove(&batch_id);
+            self.aborted_rollbacks += 1;
+            warn!("Pending rollback for batch {} expired before reaching confirmations", batch_id);
+            actions.push(RollbackAction::Expired(batch_id));
+        }
+
+        Ok(actions)
+    }
+
+    /// Compute live rollback statistics
+    pub fn stats(&self) -> RollbackStats {
+        let total_rollbacks = self.rollback_history.len() as u64;
+        let total_depth: usize = self.rollback_history.values().map(|record| record.affected_blocks.len()).sum();
+        let attempted = total_rollbacks + self.aborted_rollbacks;
+
+        RollbackStats {
+            total_rollbacks,
+            pending_rollbacks: self.pending_rollbacks.len() as u64,
+            avg_rollback_depth: if total_rollbacks == 0 {
+                0.0
+            } else {
+                total_depth as f64 / total_rollbacks as f64
+            },
+            last_rollback: self.rollback_history.values().map(|record| record.timestamp).max().unwrap_or(0),
+            success_rate: if attempted == 0 {
+                100.0
+            } else {
+                total_rollbacks as f64 / attempted as f64 * 100.0
+            },
+        }
+    }
+
     /// Clean up old rollback records
-    pub fn cleanup_old_records(&mut self, max_age: std::time::Duration) {
+    pub async fn cleanup_old_records(&mut self, max_age: std::time::Duration) -> FinalityResult<()> {
         let cutoff_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs() - max_age.as_secs();
 
-        self.rollback_history.retain(|_, record| record.timestamp > cutoff_time);
-        self.pending_rollbacks.retain(|_, pending| pending.timestamp > cutoff_time);
+        let expired_records: Vec<u64> = self.rollback_history.iter()
+            .filter(|(_, record)| record.timestamp <= cutoff_time)
+            .map(|(batch_id, _)| *batch_id)
+            .collect();
+        for batch_id in expired_records {
+            self.store.delete_record(batch_id).await?;
+            self.rollback_history.remove(&batch_id);
+        }
+
+        let expired_pending: Vec<u64> = self.pending_rollbacks.iter()
+            .filter(|(_, pending)| pending.timestamp <= cutoff_time)
+            .map(|(batch_id, _)| *batch_id)
+            .collect();
+        for batch_id in expired_pending {
+            self.store.delete_pending(batch_id).await?;
+            self.pending_rollbacks.remove(&batch_id);
+            self.aborted_rollbacks += 1;
+        }
+
+        Ok(())
     }
 }
 
@@ -260,6 +559,15 @@ pub enum RollbackAction {
     Finalized(u64),
     /// Status changed
     StatusChanged(u64),
+    /// Pending rollback timed out before reaching confirmations
+    Expired(u64),
+    /// Rollback refused because it would unwind more than `max_rollback_depth` blocks
+    Rejected {
+        /// Batch ID
+        batch_id: u64,
+        /// Number of blocks the rollback would have unwound
+        depth: u64,
+    },
 }
 
 /// Rollback statistics
@@ -292,7 +600,58 @@ impl Default for RollbackStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::FinalityEventType;
     use alloy_primitives::{FixedBytes, U256};
+    use cdk_types::{FinalityStatus, FinalityTag};
+
+    #[derive(Debug, Default)]
+    struct MockUnwinder {
+        unwound_to: Arc<Mutex<Option<u64>>>,
+        failing_batches: Vec<u64>,
+        failing_unwinds: Arc<Mutex<u64>>,
+        head: u64,
+    }
+
+    #[async_trait]
+    impl BlockUnwinder for MockUnwinder {
+        async fn affected_blocks(&self, batch_id: u64) -> FinalityResult<Vec<u64>> {
+            if self.failing_batches.contains(&batch_id) {
+                return Err(FinalityError::RollbackError(format!("batch {} unavailable", batch_id)));
+            }
+            Ok((batch_id * 10..batch_id * 10 + 4).collect())
+        }
+
+        async fn head_block(&self) -> FinalityResult<u64> {
+            Ok(self.head)
+        }
+
+        async fn unwind_to(&self, block_number: u64) -> FinalityResult<()> {
+            let mut failing_unwinds = self.failing_unwinds.lock().unwrap();
+            if *failing_unwinds > 0 {
+                *failing_unwinds -= 1;
+                return Err(FinalityError::RollbackError("engine unavailable".to_string()));
+            }
+            *self.unwound_to.lock().unwrap() = Some(block_number);
+            Ok(())
+        }
+    }
+
+    fn rollback_update(batch_id: u64) -> FinalityUpdate {
+        FinalityUpdate {
+            tag: FinalityTag::new(
+                U256::from(batch_id),
+                U256::from(1000),
+                FixedBytes::from([1u8; 32]),
+                FinalityStatus::RolledBack,
+                1234567890,
+                None,
+            ),
+            event_type: FinalityEventType::RolledBack,
+            l1_block_number: 1000,
+            tx_hash: None,
+            detected_at: 1234567890,
+        }
+    }
 
     #[tokio::test]
     async fn test_rollback_manager_creation() {
@@ -335,4 +694,275 @@ mod tests {
         assert_eq!(stats.pending_rollbacks, 0);
         assert_eq!(stats.success_rate, 100.0);
     }
+
+    #[tokio::test]
+    async fn test_execute_rollback_unwinds_blocks() {
+        let unwinder = MockUnwinder::default();
+        let unwound_to = unwinder.unwound_to.clone();
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::new(config).with_unwinder(Box::new(unwinder));
+
+        let actions = manager.process_finality_update(rollback_update(5)).await.unwrap();
+        assert_eq!(actions, vec![RollbackAction::ExecuteRollback(5)]);
+
+        // Batch 5 covers blocks 50..=53, so the chain unwinds to block 49
+        assert_eq!(*unwound_to.lock().unwrap(), Some(49));
+        let record = manager.get_rollback_record(5).unwrap();
+        assert_eq!(record.affected_blocks, vec![50, 51, 52, 53]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rollback_without_unwinder() {
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::new(config);
+
+        manager.process_finality_update(rollback_update(5)).await.unwrap();
+        assert!(manager.get_rollback_record(5).unwrap().affected_blocks.is_empty());
+    }
+
+    #[test]
+    fn test_validate_transition() {
+        use FinalityStatus::*;
+
+        let allowed = [
+            (Pending, Pending),
+            (Pending, Finalized),
+            (Pending, RolledBack),
+            (Finalized, Finalized),
+            (RolledBack, RolledBack),
+            (RolledBack, Pending),
+        ];
+        for (current, next) in allowed {
+            assert!(RollbackManager::validate_transition(current.clone(), next.clone()).is_ok(), "{:?} -> {:?}", current, next);
+        }
+
+        let disallowed = [
+            (Finalized, Pending),
+            (Finalized, RolledBack),
+            (RolledBack, Finalized),
+        ];
+        for (current, next) in disallowed {
+            assert!(matches!(
+                RollbackManager::validate_transition(current.clone(), next.clone()),
+                Err(FinalityError::RollbackError(_))
+            ), "{:?} -> {:?}", current, next);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rollback_of_finalized_batch_rejected() {
+        let mut manager = RollbackManager::new(RollbackConfig::default());
+
+        let mut finalized = rollback_update(7);
+        finalized.event_type = FinalityEventType::Finalized;
+        finalized.tag.status = FinalityStatus::Finalized;
+        manager.process_finality_update(finalized).await.unwrap();
+
+        let result = manager.process_finality_update(rollback_update(7)).await;
+        assert!(matches!(result, Err(FinalityError::RollbackError(_))));
+        assert!(manager.get_pending_rollbacks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_statuses_pruned_at_finalized_height() {
+        let mut manager = RollbackManager::new(RollbackConfig::default());
+
+        for batch_id in [3, 5, 9] {
+            let mut pending = rollback_update(batch_id);
+            pending.event_type = FinalityEventType::StatusChanged;
+            pending.tag.status = FinalityStatus::Pending;
+            manager.process_finality_update(pending).await.unwrap();
+        }
+
+        let mut finalized = rollback_update(5);
+        finalized.event_type = FinalityEventType::Finalized;
+        finalized.tag.status = FinalityStatus::Finalized;
+        manager.process_finality_update(finalized).await.unwrap();
+        assert_eq!(manager.batch_statuses.keys().copied().collect::<Vec<_>>(), vec![9]);
+
+        // Batches at or below the finalized height stay finalized after pruning
+        let result = manager.process_finality_update(rollback_update(3)).await;
+        assert!(matches!(result, Err(FinalityError::RollbackError(_))));
+        assert!(manager.process_finality_update(rollback_update(9)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_batch_id_beyond_u64_rejected() {
+        let mut manager = RollbackManager::new(RollbackConfig::default());
+
+        let mut update = rollback_update(1);
+        update.tag.batch_id = U256::from(u64::MAX) + U256::from(1);
+        let result = manager.process_finality_update(update).await;
+        assert!(matches!(result, Err(FinalityError::InvalidFinalityData(_))));
+        assert!(manager.get_pending_rollbacks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_rollbacks_restored_from_store() {
+        let store = MemoryRollbackStore::default();
+        let config = RollbackConfig {
+            required_confirmations: 3,
+            ..Default::default()
+        };
+
+        let mut manager = RollbackManager::with_store(config.clone(), Box::new(store.clone())).await.unwrap();
+        manager.process_finality_update(rollback_update(1)).await.unwrap();
+        manager.process_finality_update(rollback_update(2)).await.unwrap();
+        assert_eq!(manager.get_pending_rollbacks().len(), 2);
+        drop(manager);
+
+        // A new manager over the same store picks up where the old one stopped
+        let restored = RollbackManager::with_store(config, Box::new(store)).await.unwrap();
+        assert_eq!(restored.get_pending_rollbacks().len(), 2);
+        let pending = &restored.get_pending_rollbacks()[&1];
+        assert_eq!(pending.confirmations, 1);
+        assert_eq!(pending.required_confirmations, 3);
+    }
+
+    #[tokio::test]
+    async fn test_executed_rollback_persisted() {
+        let store = MemoryRollbackStore::default();
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+
+        let mut manager = RollbackManager::with_store(config.clone(), Box::new(store.clone())).await.unwrap();
+        manager.process_finality_update(rollback_update(4)).await.unwrap();
+
+        assert!(store.load_pending().await.unwrap().is_empty());
+        let restored = RollbackManager::with_store(config, Box::new(store)).await.unwrap();
+        assert!(restored.is_batch_rolled_back(4));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_stats() {
+        let unwinder = MockUnwinder {
+            failing_batches: vec![3],
+            ..Default::default()
+        };
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::new(config).with_unwinder(Box::new(unwinder));
+        assert_eq!(manager.stats(), RollbackStats::default());
+
+        let mut first = rollback_update(1);
+        first.detected_at = 100;
+        let mut second = rollback_update(2);
+        second.detected_at = 200;
+        manager.process_finality_update(first).await.unwrap();
+        manager.process_finality_update(second).await.unwrap();
+        assert!(manager.process_finality_update(rollback_update(3)).await.is_err());
+
+        let stats = manager.stats();
+        assert_eq!(stats.total_rollbacks, 2);
+        assert_eq!(stats.pending_rollbacks, 1);
+        assert_eq!(stats.avg_rollback_depth, 4.0);
+        assert_eq!(stats.last_rollback, 200);
+        assert!((stats.success_rate - 200.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_failed_unwind_stays_pending_for_retry() {
+        let store = MemoryRollbackStore::default();
+        let unwinder = MockUnwinder {
+            failing_unwinds: Arc::new(Mutex::new(1)),
+            ..Default::default()
+        };
+        let unwound_to = unwinder.unwound_to.clone();
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::with_store(config, Box::new(store.clone()))
+            .await
+            .unwrap()
+            .with_unwinder(Box::new(unwinder));
+
+        assert!(manager.process_finality_update(rollback_update(5)).await.is_err());
+        assert!(!manager.is_batch_rolled_back(5));
+        assert_eq!(manager.get_pending_rollbacks()[&5].confirmations, 1);
+        assert_eq!(store.load_pending().await.unwrap().len(), 1);
+        assert_eq!(manager.stats().success_rate, 0.0);
+
+        // The rollback is reported again and the retry unwinds the chain
+        let actions = manager.process_finality_update(rollback_update(5)).await.unwrap();
+        assert_eq!(actions, vec![RollbackAction::ExecuteRollback(5)]);
+        assert_eq!(*unwound_to.lock().unwrap(), Some(49));
+        assert!(manager.get_pending_rollbacks().is_empty());
+        assert!(store.load_pending().await.unwrap().is_empty());
+        assert_eq!(manager.stats().success_rate, 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_expire_stale_pending() {
+        let config = RollbackConfig {
+            required_confirmations: 12,
+            rollback_timeout: std::time::Duration::from_secs(3600),
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::new(config);
+
+        let mut stale = rollback_update(1);
+        stale.detected_at = 1_000;
+        let mut fresh = rollback_update(2);
+        fresh.detected_at = 5_000;
+        manager.process_finality_update(stale).await.unwrap();
+        manager.process_finality_update(fresh).await.unwrap();
+
+        let actions = manager.expire_stale_pending(5_000).await.unwrap();
+        assert_eq!(actions, vec![RollbackAction::Expired(1)]);
+        assert!(!manager.get_pending_rollbacks().contains_key(&1));
+        assert!(manager.get_pending_rollbacks().contains_key(&2));
+        assert!(!manager.is_batch_rolled_back(1));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_exceeding_max_depth_rejected() {
+        let unwinder = MockUnwinder {
+            head: 53,
+            ..Default::default()
+        };
+        let unwound_to = unwinder.unwound_to.clone();
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            max_rollback_depth: 3,
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::new(config).with_unwinder(Box::new(unwinder));
+
+        let actions = manager.process_finality_update(rollback_update(5)).await.unwrap();
+        assert_eq!(actions, vec![RollbackAction::Rejected { batch_id: 5, depth: 4 }]);
+        assert!(!manager.is_batch_rolled_back(5));
+        assert!(manager.get_pending_rollbacks().is_empty());
+        assert_eq!(*unwound_to.lock().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_depth_measured_from_head() {
+        let unwinder = MockUnwinder {
+            head: 60,
+            ..Default::default()
+        };
+        let unwound_to = unwinder.unwound_to.clone();
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            max_rollback_depth: 8,
+            ..Default::default()
+        };
+        let mut manager = RollbackManager::new(config).with_unwinder(Box::new(unwinder));
+
+        // Batch 5 only spans blocks 50..=53, but unwinding to block 49 drops blocks 50..=60
+        let actions = manager.process_finality_update(rollback_update(5)).await.unwrap();
+        assert_eq!(actions, vec![RollbackAction::Rejected { batch_id: 5, depth: 11 }]);
+        assert_eq!(*unwound_to.lock().unwrap(), None);
+    }
 }
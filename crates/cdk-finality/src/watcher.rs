@@ -0,0 +1,296 @@
+//! Poll loop turning finality oracle results into rollback actions
+
+use crate::{
+    tag_batch_number, FinalityEventType, FinalityOracle, FinalityResult, FinalityUpdate, RollbackAction,
+    RollbackManager,
+};
+use cdk_types::{FinalityStatus, FinalityTag};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Drives a finality oracle and feeds status changes into a rollback manager
+///
+/// Each poll is diffed against the last status seen per batch; only changes are
+/// dispatched to the rollback manager, and the resulting actions are sent to
+/// the receiver returned by `FinalityWatcher::new`. A status is only recorded
+/// once the rollback manager accepted it, so failed updates are retried on the
+/// next poll.
+#[derive(Debug)]
+pub struct FinalityWatcher {
+    oracle: Box<dyn FinalityOracle>,
+    rollback_manager: RollbackManager,
+    seen_statuses: HashMap<u64, FinalityStatus>,
+    /// Highest batch seen finalized; statuses at or below it are pruned
+    finalized_height: Option<u64>,
+    actions: mpsc::Sender<RollbackAction>,
+}
+
+impl FinalityWatcher {
+    /// Create a new watcher and the receiver for its rollback actions
+    pub fn new(
+        oracle: Box<dyn FinalityOracle>,
+        rollback_manager: RollbackManager,
+        channel_capacity: usize,
+    ) -> (Self, mpsc::Receiver<RollbackAction>) {
+        let (actions, receiver) = mpsc::channel(channel_capacity);
+        let watcher = Self {
+            oracle,
+            rollback_manager,
+            seen_statuses: HashMap::new(),
+            finalized_height: None,
+            actions,
+        };
+        (watcher, receiver)
+    }
+
+    /// Get the rollback manager
+    pub fn rollback_manager(&self) -> &RollbackManager {
+        &self.rollback_manager
+    }
+
+    /// Poll the oracle once and dispatch any status changes
+    ///
+    /// Returns the number of actions sent.
+    pub async fn poll_once(&mut self) -> FinalityResult<usize> {
+        let tags = self.oracle.poll().await?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut actions = Vec::new();
+        for tag in tags {
+            let batch_id = match tag_batch_number(&tag) {
+                Ok(batch_id) => batch_id,
+                Err(e) => {
+                    warn!("Skipping finality tag: {}", e);
+                    continue;
+                }
+            };
+            let Some(update) = self.to_update(batch_id, tag, now) else {
+                continue;
+            };
+            let status = update.tag.status.clone();
+
+            match self.rollback_manager.process_finality_update(update).await {
+                Ok(batch_actions) => {
+                    self.record_status(batch_id, status);
+                    actions.extend(batch_actions);
+                }
+                Err(e) => warn!("Failed to process finality update for batch {}: {}", batch_id, e),
+            }
+        }
+        actions.extend(self.rollback_manager.expire_stale_pending(now).await?);
+
+        let sent = actions.len();
+        for action in actions {
+            if self.actions.send(action).await.is_err() {
+                debug!("Rollback action receiver dropped");
+                break;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Poll the oracle at its polling interval until the action receiver is dropped
+    pub async fn run(mut self) -> FinalityResult<()> {
+        let mut interval = tokio::time::interval(self.oracle.get_polling_interval());
+        info!("Starting finality watcher");
+
+        while !self.actions.is_closed() {
+            interval.tick().await;
+            if let Err(e) = self.poll_once().await {
+                warn!("Finality poll failed: {}", e);
+            }
+        }
+
+        info!("Finality watcher stopped");
+        Ok(())
+    }
+
+    /// Record the status of a dispatched update, pruning batches at or below the finalized height
+    fn record_status(&mut self, batch_id: u64, status: FinalityStatus) {
+        if status == FinalityStatus::Finalized {
+            let height = self.finalized_height.map_or(batch_id, |height| height.max(batch_id));
+            self.finalized_height = Some(height);
+            self.seen_statuses.retain(|id, _| *id > height);
+        } else {
+            self.seen_statuses.insert(batch_id, status);
+        }
+    }
+
+    /// Convert a polled tag into an update, or `None` if its status is unchanged
+    fn to_update(&self, batch_id: u64, tag: FinalityTag, now: u64) -> Option<FinalityUpdate> {
+        let seen = match self.finalized_height {
+            Some(height) if batch_id <= height => Some(&FinalityStatus::Finalized),
+            _ => self.seen_statuses.get(&batch_id),
+        };
+        if seen == Some(&tag.status) {
+            return None;
+        }
+
+        let event_type = match tag.status {
+            FinalityStatus::Finalized => FinalityEventType::Finalized,
+            FinalityStatus::RolledBack => FinalityEventType::RolledBack,
+            FinalityStatus::Pending => FinalityEventType::StatusChanged,
+        };
+
+        Some(FinalityUpdate {
+            l1_block_number: tag.l1_block.saturating_to::<u64>(),
+            tx_hash: tag.tx_hash,
+            detected_at: now,
+            event_type,
+            tag,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockUnwinder, FinalityError, OracleMetadata, RollbackConfig};
+    use alloy_primitives::{Address, FixedBytes, U256};
+    use async_trait::async_trait;
+    use std::{
+        collections::VecDeque,
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    };
+
+    #[derive(Debug)]
+    struct MockOracle {
+        polls: VecDeque<Vec<FinalityTag>>,
+    }
+
+    #[async_trait]
+    impl FinalityOracle for MockOracle {
+        async fn poll(&mut self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(self.polls.pop_front().unwrap_or_default())
+        }
+
+        async fn get_finality_status(&self, _batch_id: u64) -> FinalityResult<Option<FinalityStatus>> {
+            Ok(None)
+        }
+
+        async fn get_finalized_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(vec![])
+        }
+
+        async fn get_rolled_back_batches(&self) -> FinalityResult<Vec<FinalityTag>> {
+            Ok(vec![])
+        }
+
+        async fn health_check(&self) -> FinalityResult<()> {
+            Ok(())
+        }
+
+        async fn metadata(&self) -> FinalityResult<OracleMetadata> {
+            Ok(OracleMetadata::new("mock".to_string(), "1.0.0".to_string(), 1, Address::ZERO))
+        }
+
+        fn set_polling_interval(&mut self, _interval: Duration) {}
+
+        fn get_polling_interval(&self) -> Duration {
+            Duration::from_millis(10)
+        }
+    }
+
+    /// Unwinder whose first block lookup fails
+    #[derive(Debug, Default)]
+    struct FlakyUnwinder {
+        failed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl BlockUnwinder for FlakyUnwinder {
+        async fn affected_blocks(&self, batch_id: u64) -> FinalityResult<Vec<u64>> {
+            if !self.failed.swap(true, Ordering::SeqCst) {
+                return Err(FinalityError::RollbackError("mapping unavailable".to_string()));
+            }
+            Ok(vec![batch_id * 10])
+        }
+
+        async fn head_block(&self) -> FinalityResult<u64> {
+            Ok(100)
+        }
+
+        async fn unwind_to(&self, _block_number: u64) -> FinalityResult<()> {
+            Ok(())
+        }
+    }
+
+    fn tag(batch_id: u64, status: FinalityStatus) -> FinalityTag {
+        FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(1000),
+            FixedBytes::from([1u8; 32]),
+            status,
+            1234567890,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_watcher_emits_actions_for_status_changes() {
+        let oracle = MockOracle {
+            polls: VecDeque::from([
+                vec![tag(1, FinalityStatus::Pending), tag(2, FinalityStatus::RolledBack)],
+                vec![tag(1, FinalityStatus::Finalized), tag(2, FinalityStatus::RolledBack)],
+            ]),
+        };
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+        let (mut watcher, mut actions) = FinalityWatcher::new(Box::new(oracle), RollbackManager::new(config), 16);
+
+        assert_eq!(watcher.poll_once().await.unwrap(), 2);
+        assert_eq!(actions.recv().await, Some(RollbackAction::StatusChanged(1)));
+        assert_eq!(actions.recv().await, Some(RollbackAction::ExecuteRollback(2)));
+
+        // Batch 2 is unchanged on the second poll and must not be dispatched again
+        assert_eq!(watcher.poll_once().await.unwrap(), 1);
+        assert_eq!(actions.recv().await, Some(RollbackAction::Finalized(1)));
+        assert!(actions.try_recv().is_err());
+        assert!(watcher.rollback_manager().is_batch_rolled_back(2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_update_retried_on_next_poll() {
+        let oracle = MockOracle {
+            polls: VecDeque::from([vec![tag(2, FinalityStatus::RolledBack)], vec![tag(2, FinalityStatus::RolledBack)]]),
+        };
+        let config = RollbackConfig {
+            required_confirmations: 1,
+            ..Default::default()
+        };
+        let manager = RollbackManager::new(config).with_unwinder(Box::new(FlakyUnwinder::default()));
+        let (mut watcher, mut actions) = FinalityWatcher::new(Box::new(oracle), manager, 16);
+
+        assert_eq!(watcher.poll_once().await.unwrap(), 0);
+        assert!(watcher.seen_statuses.is_empty());
+
+        assert_eq!(watcher.poll_once().await.unwrap(), 1);
+        assert_eq!(actions.recv().await, Some(RollbackAction::ExecuteRollback(2)));
+        assert!(watcher.rollback_manager().is_batch_rolled_back(2));
+    }
+
+    #[tokio::test]
+    async fn test_seen_statuses_pruned_at_finalized_height() {
+        let pending = vec![tag(1, FinalityStatus::Pending), tag(2, FinalityStatus::Pending), tag(3, FinalityStatus::Pending)];
+        let finalized = vec![tag(1, FinalityStatus::Finalized), tag(2, FinalityStatus::Finalized), tag(3, FinalityStatus::Pending)];
+        let oracle = MockOracle {
+            polls: VecDeque::from([pending, finalized.clone(), finalized]),
+        };
+        let (mut watcher, _actions) =
+            FinalityWatcher::new(Box::new(oracle), RollbackManager::new(RollbackConfig::default()), 16);
+
+        assert_eq!(watcher.poll_once().await.unwrap(), 3);
+        assert_eq!(watcher.poll_once().await.unwrap(), 2);
+        assert_eq!(watcher.seen_statuses.keys().copied().collect::<Vec<_>>(), vec![3]);
+
+        // Pruned batches are still known to be finalized
+        assert_eq!(watcher.poll_once().await.unwrap(), 0);
+    }
+}
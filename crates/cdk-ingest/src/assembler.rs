@@ -3,6 +3,7 @@
 use cdk_types::Batch;
 use crate::IngestError;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 /// Input data for block assembly
@@ -96,7 +97,7 @@ pub trait BlockAssembler: Send + Sync + Debug {
 }
 
 /// Block mapping information
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlockMapping {
     /// Block number
     pub block_number: u64,
@@ -113,7 +114,7 @@ pub struct BlockMapping {
 }
 
 /// Batch mapping information
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BatchMapping {
     /// Batch ID
     pub batch_id: u64,
@@ -132,7 +133,7 @@ pub struct BatchMapping {
 }
 
 /// Epoch mapping information
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EpochMapping {
     /// Epoch ID
     pub epoch_id: u64,
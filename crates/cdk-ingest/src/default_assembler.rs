@@ -0,0 +1,220 @@
+//! Default block assembler
+
+use cdk_types::{Batch, BlockInBatch};
+use crate::{
+    AssemblyStats, BatchMapping, BatchValidator, BlockAssembler, BlockInputs, BlockMapping, EpochMapping,
+    IngestError, IngestResult, MappingManager, MappingStorage,
+};
+use alloy_primitives::{Bytes, U256};
+use async_trait::async_trait;
+use std::fmt;
+use tracing::debug;
+
+/// Default gas limit given to assembled blocks
+pub const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Block assembler turning batch blocks into block inputs
+///
+/// Batches only carry block headers, so assembled blocks have no transactions
+/// and no base fee. Their gas limit is the configured one, raised to the gas
+/// used when a block exceeds it.
+pub struct DefaultBlockAssembler {
+    validator: BatchValidator,
+    mappings: MappingManager,
+    gas_limit: u64,
+}
+
+impl DefaultBlockAssembler {
+    /// Create a new assembler persisting mappings to `storage`
+    pub fn new(storage: Box<dyn MappingStorage>) -> Self {
+        Self::with_validator(storage, BatchValidator::default())
+    }
+
+    /// Create a new assembler using a custom batch validator
+    pub fn with_validator(storage: Box<dyn MappingStorage>, validator: BatchValidator) -> Self {
+        Self {
+            validator,
+            mappings: MappingManager::new(storage),
+            gas_limit: DEFAULT_BLOCK_GAS_LIMIT,
+        }
+    }
+
+    /// Set the gas limit given to assembled blocks
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Build the block mappings for the blocks of `batch`
+    pub fn block_mappings(&self, batch: &Batch, epoch_id: u64) -> IngestResult<Vec<BlockMapping>> {
+        let batch_id = to_u64(batch.id.number, "Batch number")?;
+        batch
+            .blocks
+            .iter()
+            .map(|block| {
+                Ok(self.mappings.create_block_mapping(
+                    to_u64(block.number, "Block number")?,
+                    block.hash,
+                    batch_id,
+                    block.batch_index,
+                    epoch_id,
+                ))
+            })
+            .collect()
+    }
+
+    /// Convert a block of a batch into block inputs
+    fn convert_block(&self, block: &BlockInBatch) -> IngestResult<BlockInputs> {
+        Ok(BlockInputs {
+            number: to_u64(block.number, "Block number")?,
+            hash: block.hash,
+            parent_hash: block.parent_hash,
+            state_root: block.state_root,
+            receipts_root: block.receipt_root,
+            transactions_root: block.tx_root,
+            timestamp: block.timestamp,
+            gas_limit: self.gas_limit.max(block.gas_used),
+            gas_used: block.gas_used,
+            base_fee_per_gas: None,
+            extra_data: Bytes::new(),
+            transactions: Vec::new(),
+        })
+    }
+}
+
+impl fmt::Debug for DefaultBlockAssembler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultBlockAssembler")
+            .field("validator", &self.validator)
+            .field("gas_limit", &self.gas_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+fn to_u64(value: U256, what: &str) -> IngestResult<u64> {
+    u64::try_from(value).map_err(|_| IngestError::BlockConversionError(format!("{} {} does not fit in u64", what, value)))
+}
+
+#[async_trait]
+impl BlockAssembler for DefaultBlockAssembler {
+    async fn assemble(&mut self, batch: &Batch) -> Result<Vec<BlockInputs>, IngestError> {
+        self.validate_batch(batch).await?;
+
+        let inputs = batch
+            .blocks
+            .iter()
+            .map(|block| self.convert_block(block))
+            .collect::<IngestResult<Vec<_>>>()?;
+        debug!("Assembled {} blocks from batch {}", inputs.len(), batch.id.number);
+        Ok(inputs)
+    }
+
+    async fn validate_batch(&self, batch: &Batch) -> Result<(), IngestError> {
+        self.validator.validate_batch(batch).await
+    }
+
+    async fn get_block_mapping(&self, block_number: u64) -> Result<Option<BlockMapping>, IngestError> {
+        self.mappings.storage().load_block_mapping(block_number).await
+    }
+
+    async fn get_batch_mapping(&self, batch_id: u64) -> Result<Option<BatchMapping>, IngestError> {
+        self.mappings.storage().load_batch_mapping(batch_id).await
+    }
+
+    async fn get_epoch_mapping(&self, epoch_id: u64) -> Result<Option<EpochMapping>, IngestError> {
+        self.mappings.storage().load_epoch_mapping(epoch_id).await
+    }
+
+    async fn update_mappings(&mut self, mappings: Vec<BlockMapping>) -> Result<(), IngestError> {
+        self.mappings.save_mappings(mappings).await
+    }
+
+    async fn get_stats(&self) -> Result<AssemblyStats, IngestError> {
+        Ok(self.mappings.get_stats().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryMappingStorage;
+    use alloy_primitives::FixedBytes;
+    use cdk_types::{BatchId, ProofMetadata};
+
+    fn create_batch(block_count: u64) -> Batch {
+        let blocks = (0..block_count)
+            .map(|i| BlockInBatch {
+                batch_index: i as u32,
+                hash: FixedBytes::from([(i + 2) as u8; 32]),
+                number: U256::from(100 + i),
+                parent_hash: FixedBytes::from([(i + 1) as u8; 32]),
+                state_root: FixedBytes::from([0xaa; 32]),
+                tx_root: FixedBytes::from([0xbb; 32]),
+                receipt_root: FixedBytes::from([0xcc; 32]),
+                timestamp: 1234567890 + i,
+                tx_count: 0,
+                gas_used: 21_000 * i,
+            })
+            .collect();
+
+        Batch::new(
+            BatchId {
+                number: U256::from(7),
+                hash: FixedBytes::from([7u8; 32]),
+            },
+            U256::from(1000),
+            FixedBytes::from([9u8; 32]),
+            blocks,
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_assemble_links_blocks() {
+        let mut assembler = DefaultBlockAssembler::new(Box::new(MemoryMappingStorage::default()));
+        let batch = create_batch(5);
+
+        let inputs = assembler.assemble(&batch).await.unwrap();
+        assert_eq!(inputs.len(), 5);
+        for (i, input) in inputs.iter().enumerate() {
+            let block = &batch.blocks[i];
+            assert_eq!(input.number, 100 + i as u64);
+            assert_eq!(input.hash, block.hash);
+            assert_eq!(input.state_root, block.state_root);
+            assert_eq!(input.receipts_root, block.receipt_root);
+            assert_eq!(input.transactions_root, block.tx_root);
+            assert_eq!(input.timestamp, block.timestamp);
+            assert_eq!(input.gas_limit, DEFAULT_BLOCK_GAS_LIMIT);
+            assert!(input.transactions.is_empty());
+        }
+        for pair in inputs.windows(2) {
+            assert_eq!(pair[1].parent_hash, pair[0].hash);
+            assert_eq!(pair[1].number, pair[0].number + 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assemble_rejects_invalid_batch() {
+        let mut assembler = DefaultBlockAssembler::new(Box::new(MemoryMappingStorage::default()));
+        let mut batch = create_batch(3);
+        batch.blocks[2].parent_hash = FixedBytes::from([0xff; 32]);
+
+        assert!(matches!(assembler.assemble(&batch).await, Err(IngestError::InvalidBatchData(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_mappings_persists() {
+        let mut assembler = DefaultBlockAssembler::new(Box::new(MemoryMappingStorage::default()));
+        let batch = create_batch(3);
+
+        let mappings = assembler.block_mappings(&batch, 2).unwrap();
+        assembler.update_mappings(mappings).await.unwrap();
+
+        let mapping = assembler.get_block_mapping(101).await.unwrap().unwrap();
+        assert_eq!(mapping.batch_id, 7);
+        assert_eq!(mapping.batch_index, 1);
+        assert_eq!(mapping.epoch_id, 2);
+        assert_eq!(assembler.get_stats().await.unwrap().total_blocks, 3);
+    }
+}
@@ -0,0 +1,390 @@
+//! Epoch boundary detection over an incoming batch stream
+
+use cdk_datastream::{DataStreamError, DataStreamResult, EpochAssigner};
+use cdk_types::{Batch, Epoch, EpochId};
+use crate::{EpochMapping, IngestError, IngestResult};
+use alloy_primitives::{keccak256, FixedBytes, U256};
+use std::time::Duration;
+use tracing::debug;
+
+/// Boundary conditions that close the current epoch
+///
+/// Every condition that is set is checked; the first one hit closes the epoch.
+#[derive(Debug, Clone)]
+pub struct EpochBuilderConfig {
+    /// Maximum number of batches in a single epoch
+    pub max_batches_per_epoch: Option<u32>,
+    /// Maximum number of blocks spanned by a single epoch
+    pub max_block_span: Option<u64>,
+    /// Maximum time window covered by a single epoch, based on batch timestamps
+    pub max_epoch_duration: Option<Duration>,
+}
+
+impl Default for EpochBuilderConfig {
+    fn default() -> Self {
+        Self {
+            max_batches_per_epoch: Some(100),
+            max_block_span: None,
+            max_epoch_duration: None,
+        }
+    }
+}
+
+/// Epoch currently being accumulated
+#[derive(Debug, Clone)]
+struct PendingEpoch {
+    start_block: U256,
+    end_block: U256,
+    start_batch: U256,
+    end_batch: U256,
+    start_timestamp: u64,
+    end_timestamp: u64,
+    batch_count: u32,
+    batch_hashes: Vec<FixedBytes<32>>,
+}
+
+/// Groups consecutive batches into epochs
+///
+/// A batch that would push the current epoch past one of its boundaries closes
+/// that epoch and starts the next one, so `add_batch` returns at most one
+/// finished epoch. Call `flush` to close the trailing epoch.
+#[derive(Debug)]
+pub struct EpochBuilder {
+    config: EpochBuilderConfig,
+    next_epoch_number: u64,
+    current: Option<PendingEpoch>,
+    /// Epochs closed while assigning batches through `EpochAssigner`
+    completed: Vec<(Epoch, EpochMapping)>,
+}
+
+impl EpochBuilder {
+    /// Create a new epoch builder starting at epoch 0
+    pub fn new(config: EpochBuilderConfig) -> Self {
+        Self::with_start_epoch(config, 0)
+    }
+
+    /// Create a new epoch builder starting at the given epoch number
+    pub fn with_start_epoch(config: EpochBuilderConfig, next_epoch_number: u64) -> Self {
+        Self {
+            config,
+            next_epoch_number,
+            current: None,
+            completed: Vec::new(),
+        }
+    }
+
+    /// Add a batch, returning the previous epoch if this batch closed it
+    pub fn add_batch(&mut self, batch: &Batch) -> IngestResult<Option<(Epoch, EpochMapping)>> {
+        let (first_block, last_block) = match (batch.blocks.first(), batch.blocks.last()) {
+            (Some(first), Some(last)) => (first.number, last.number),
+            _ => {
+                return Err(IngestError::InvalidBatchData(format!(
+                    "Cannot add empty batch {} to an epoch",
+                    batch.id.number
+                )))
+            }
+        };
+
+        let finished = match &self.current {
+            Some(pending) if !self.fits(pending, last_block, batch.timestamp) => self.flush()?,
+            _ => None,
+        };
+
+        match &mut self.current {
+            Some(pending) => {
+                pending.end_block = pending.end_block.max(last_block);
+                pending.end_batch = batch.id.number;
+                pending.end_timestamp = pending.end_timestamp.max(batch.timestamp);
+                pending.batch_count += 1;
+                pending.batch_hashes.push(batch.id.hash);
+            }
+            None => {
+                self.current = Some(PendingEpoch {
+                    start_block: first_block,
+                    end_block: last_block,
+                    start_batch: batch.id.number,
+                    end_batch: batch.id.number,
+                    start_timestamp: batch.timestamp,
+                    end_timestamp: batch.timestamp,
+                    batch_count: 1,
+                    batch_hashes: vec![batch.id.hash],
+                });
+            }
+        }
+
+        Ok(finished)
+    }
+
+    /// Force-close the current epoch, if any
+    ///
+    /// Fails, leaving the epoch open, if it spans more blocks than an
+    /// `EpochMapping` can count.
+    pub fn flush(&mut self) -> IngestResult<Option<(Epoch, EpochMapping)>> {
+        let Some(pending) = &self.current else {
+            return Ok(None);
+        };
+        let start_block = pending.start_block.saturating_to::<u64>();
+        let end_block = pending.end_block.saturating_to::<u64>();
+        let block_count = u32::try_from(end_block - start_block)
+            .ok()
+            .and_then(|span| span.checked_add(1))
+            .ok_or_else(|| {
+                IngestError::MappingError(format!(
+                    "Epoch {} spans blocks {}-{}, more than {} blocks",
+                    self.next_epoch_number,
+                    start_block,
+                    end_block,
+                    u32::MAX
+                ))
+            })?;
+
+        let pending = self.current.take().expect("pending epoch checked above");
+        let epoch_number = self.next_epoch_number;
+        self.next_epoch_number += 1;
+
+        let epoch_hash = keccak256(pending.batch_hashes.concat());
+        let epoch = Epoch::new(
+            EpochId::new(U256::from(epoch_number), epoch_hash),
+            pending.start_block,
+            pending.end_block,
+            pending.start_batch,
+            pending.end_batch,
+            pending.start_timestamp,
+            pending.end_timestamp,
+        );
+
+        let mapping = EpochMapping {
+            epoch_id: epoch_number,
+            epoch_hash,
+            start_block,
+            end_block,
+            block_count,
+            batch_count: pending.batch_count,
+            timestamp: pending.end_timestamp,
+        };
+
+        debug!(
+            "Closed epoch {} with {} batches (blocks {}-{})",
+            epoch_number, pending.batch_count, start_block, end_block
+        );
+        Ok(Some((epoch, mapping)))
+    }
+
+    /// Number of batches accumulated in the current epoch
+    pub fn pending_batch_count(&self) -> u32 {
+        self.current.as_ref().map_or(0, |pending| pending.batch_count)
+    }
+
+    /// Number of the next epoch to be emitted
+    pub fn next_epoch_number(&self) -> u64 {
+        self.next_epoch_number
+    }
+
+    /// Take the epochs closed while assigning batches through `EpochAssigner`
+    pub fn take_completed_epochs(&mut self) -> Vec<(Epoch, EpochMapping)> {
+        std::mem::take(&mut self.completed)
+    }
+
+    /// Drop completed epochs whose last batch is at or below `finalized_batch`
+    ///
+    /// Epochs that are never taken would otherwise accumulate for the lifetime
+    /// of the builder.
+    pub fn prune_finalized(&mut self, finalized_batch: U256) {
+        self.completed.retain(|(epoch, _)| epoch.end_batch > finalized_batch);
+    }
+
+    /// Check whether a batch ending at `last_block` fits into the pending epoch
+    fn fits(&self, pending: &PendingEpoch, last_block: U256, timestamp: u64) -> bool {
+        if let Some(max_batches) = self.config.max_batches_per_epoch {
+            if pending.batch_count >= max_batches {
+                return false;
+            }
+        }
+
+        if let Some(max_span) = self.config.max_block_span {
+            let span = last_block.max(pending.end_block).saturating_sub(pending.start_block) + U256::from(1);
+            if span > U256::from(max_span) {
+                return false;
+            }
+        }
+
+        if let Some(max_duration) = self.config.max_epoch_duration {
+            if timestamp.saturating_sub(pending.start_timestamp) >= max_duration.as_secs() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl EpochAssigner for EpochBuilder {
+    fn assign_epoch(&mut self, batch: &Batch) -> DataStreamResult<u64> {
+        let finished = self
+            .add_batch(batch)
+            .map_err(|e| DataStreamError::InvalidBatchData(e.to_string()))?;
+        self.completed.extend(finished);
+        // The batch always lands in the pending epoch, which is numbered on flush
+        Ok(self.next_epoch_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdk_types::{BatchId, BlockInBatch, ProofMetadata};
+
+    fn batch_with_blocks(number: u64, first_block: u64, block_count: u64, timestamp: u64) -> Batch {
+        let blocks = (0..block_count)
+            .map(|i| {
+                BlockInBatch::new(
+                    i as u32,
+                    FixedBytes::from([(first_block + i) as u8; 32]),
+                    U256::from(first_block + i),
+                    FixedBytes::from([(first_block + i - 1) as u8; 32]),
+                    FixedBytes::from([3u8; 32]),
+                    FixedBytes::from([4u8; 32]),
+                    FixedBytes::from([5u8; 32]),
+                    timestamp,
+                )
+            })
+            .collect();
+
+        Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            blocks,
+            ProofMetadata::default(),
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn test_epoch_boundary_by_batch_count() {
+        let config = EpochBuilderConfig {
+            max_batches_per_epoch: Some(2),
+            max_block_span: None,
+            max_epoch_duration: None,
+        };
+        let mut builder = EpochBuilder::new(config);
+
+        assert!(builder.add_batch(&batch_with_blocks(1, 1, 3, 1000)).unwrap().is_none());
+        assert!(builder.add_batch(&batch_with_blocks(2, 4, 3, 1010)).unwrap().is_none());
+
+        let (epoch, mapping) = builder.add_batch(&batch_with_blocks(3, 7, 3, 1020)).unwrap().unwrap();
+        assert_eq!(epoch.id.number, U256::ZERO);
+        assert_eq!(epoch.start_block, U256::from(1));
+        assert_eq!(epoch.end_block, U256::from(6));
+        assert_eq!(epoch.start_batch, U256::from(1));
+        assert_eq!(epoch.end_batch, U256::from(2));
+        assert_eq!(mapping.start_block, 1);
+        assert_eq!(mapping.end_block, 6);
+        assert_eq!(mapping.block_count, 6);
+        assert_eq!(mapping.batch_count, 2);
+        // Stamped with the epoch's last batch, not the wall clock
+        assert_eq!(mapping.timestamp, 1010);
+
+        let (epoch, mapping) = builder.flush().unwrap().unwrap();
+        assert_eq!(epoch.id.number, U256::from(1));
+        assert_eq!(epoch.start_block, U256::from(7));
+        assert_eq!(epoch.end_block, U256::from(9));
+        assert_eq!(mapping.batch_count, 1);
+        assert!(builder.flush().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_epoch_boundary_by_block_span() {
+        let config = EpochBuilderConfig {
+            max_batches_per_epoch: None,
+            max_block_span: Some(10),
+            max_epoch_duration: None,
+        };
+        let mut builder = EpochBuilder::new(config);
+
+        assert!(builder.add_batch(&batch_with_blocks(1, 1, 4, 1000)).unwrap().is_none());
+        assert!(builder.add_batch(&batch_with_blocks(2, 5, 4, 1010)).unwrap().is_none());
+
+        // Blocks 9..=12 would stretch the epoch to 12 blocks
+        let (epoch, mapping) = builder.add_batch(&batch_with_blocks(3, 9, 4, 1020)).unwrap().unwrap();
+        assert_eq!(epoch.start_block, U256::from(1));
+        assert_eq!(epoch.end_block, U256::from(8));
+        assert_eq!(mapping.block_count, 8);
+        assert_eq!(mapping.batch_count, 2);
+        assert_eq!(builder.pending_batch_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_epoch_tagging_source_assigns_monotonic_epochs() {
+        use cdk_datastream::{EpochTaggingSource, FilesystemSource, FilesystemSourceConfig};
+
+        let dir = tempfile::tempdir().unwrap();
+        for number in 1..=5u64 {
+            let batch = batch_with_blocks(number, number * 10, 2, 1000 + number);
+            let path = dir.path().join(format!("batch_{:06}.json", number));
+            std::fs::write(path, serde_json::to_vec(&batch).unwrap()).unwrap();
+        }
+
+        let source = FilesystemSource::new(FilesystemSourceConfig {
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let config = EpochBuilderConfig {
+            max_batches_per_epoch: Some(2),
+            max_block_span: None,
+            max_epoch_duration: None,
+        };
+        let mut source = EpochTaggingSource::new(source, EpochBuilder::new(config));
+
+        let mut epoch_ids = Vec::new();
+        while let Some(tagged) = source.next_tagged().await.unwrap() {
+            epoch_ids.push(tagged.epoch_id);
+        }
+        assert_eq!(epoch_ids, vec![0, 0, 1, 1, 2]);
+        assert_eq!(source.current_epoch_id(), Some(2));
+
+        let completed = source.assigner_mut().take_completed_epochs();
+        assert_eq!(completed.len(), 2);
+        assert_eq!(completed[1].1.epoch_id, 1);
+    }
+
+    #[test]
+    fn test_prune_finalized_drops_completed_epochs() {
+        let config = EpochBuilderConfig {
+            max_batches_per_epoch: Some(1),
+            max_block_span: None,
+            max_epoch_duration: None,
+        };
+        let mut builder = EpochBuilder::new(config);
+        for number in 1..=4u64 {
+            builder.assign_epoch(&batch_with_blocks(number, number * 10, 2, 1000 + number)).unwrap();
+        }
+
+        // Batches 1, 2 and 3 each closed an epoch; batch 4 is still pending
+        builder.prune_finalized(U256::from(2));
+        let completed = builder.take_completed_epochs();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].0.end_batch, U256::from(3));
+        assert_eq!(builder.pending_batch_count(), 1);
+    }
+
+    #[test]
+    fn test_flush_rejects_epoch_too_long_to_count() {
+        let mut builder = EpochBuilder::new(EpochBuilderConfig::default());
+        let mut batch = batch_with_blocks(1, 1, 2, 1000);
+        batch.blocks[1].number = U256::from(1 + u64::from(u32::MAX));
+        builder.add_batch(&batch).unwrap();
+
+        assert!(matches!(builder.flush(), Err(IngestError::MappingError(_))));
+        // The epoch stays open instead of being lost
+        assert_eq!(builder.pending_batch_count(), 1);
+        assert_eq!(builder.next_epoch_number(), 0);
+    }
+
+    #[test]
+    fn test_empty_batch_rejected() {
+        let mut builder = EpochBuilder::new(EpochBuilderConfig::default());
+        let batch = batch_with_blocks(1, 1, 0, 1000);
+        assert!(builder.add_batch(&batch).is_err());
+    }
+}
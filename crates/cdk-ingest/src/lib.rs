@@ -5,11 +5,19 @@
 //! It also maintains mappings between blocks and batches/epochs.
 
 pub mod assembler;
+pub mod default_assembler;
+pub mod epoch_builder;
 pub mod error;
 pub mod mapping;
 pub mod validator;
+#[cfg(feature = "rocksdb")]
+pub mod rocks_storage;
 
 pub use assembler::*;
+pub use default_assembler::*;
+pub use epoch_builder::*;
 pub use error::*;
 pub use mapping::*;
 pub use validator::*;
+#[cfg(feature = "rocksdb")]
+pub use rocks_storage::*;
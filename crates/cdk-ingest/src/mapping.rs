@@ -1,8 +1,8 @@
 //! Block, batch, and epoch mapping management
 
-use crate::{BlockMapping, BatchMapping, EpochMapping, IngestResult, AssemblyStats};
+use crate::{BlockMapping, BatchMapping, EpochMapping, IngestError, IngestResult, AssemblyStats};
 use alloy_primitives::FixedBytes;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use tracing::debug;
 
 /// Mapping storage trait for persisting block/batch/epoch mappings
@@ -11,6 +11,20 @@ pub trait MappingStorage: Send + Sync {
     /// Save block mapping
     async fn save_block_mapping(&self, mapping: BlockMapping) -> IngestResult<()>;
 
+    /// Save a set of block mappings atomically
+    ///
+    /// Either every mapping is stored or, if any of them fails, none are. A
+    /// set naming the same block twice is rejected. The default saves the
+    /// mappings one at a time, so it is only atomic against that check;
+    /// storages able to write them together should override it.
+    async fn save_block_mappings_batch(&self, mappings: Vec<BlockMapping>) -> IngestResult<()> {
+        ensure_distinct_blocks(&mappings)?;
+        for mapping in mappings {
+            self.save_block_mapping(mapping).await?;
+        }
+        Ok(())
+    }
+
     /// Load block mapping by block number
     async fn load_block_mapping(&self, block_number: u64) -> IngestResult<Option<BlockMapping>>;
 
@@ -40,6 +54,24 @@ pub trait MappingStorage: Send + Sync {
         end_batch: u64,
     ) -> IngestResult<Vec<BatchMapping>>;
 
+    /// Get the numbers of the blocks mapped to a batch, in ascending order
+    ///
+    /// Served from a batch-to-blocks index kept up to date as block mappings
+    /// are saved and deleted.
+    async fn get_blocks_for_batch(&self, batch_id: u64) -> IngestResult<Vec<u64>>;
+
+    /// Get the number of stored batch mappings
+    async fn batch_mapping_count(&self) -> IngestResult<u64>;
+
+    /// Get the batch mapping with the highest batch ID
+    async fn latest_batch_mapping(&self) -> IngestResult<Option<BatchMapping>>;
+
+    /// Get the number of stored epoch mappings
+    async fn epoch_mapping_count(&self) -> IngestResult<u64>;
+
+    /// Get the epoch mapping with the highest epoch ID
+    async fn latest_epoch_mapping(&self) -> IngestResult<Option<EpochMapping>>;
+
     /// Delete block mapping
     async fn delete_block_mapping(&self, block_number: u64) -> IngestResult<()>;
 
@@ -48,6 +80,34 @@ pub trait MappingStorage: Send + Sync {
 
     /// Delete epoch mapping
     async fn delete_epoch_mapping(&self, epoch_id: u64) -> IngestResult<()>;
+
+    /// Delete all block mappings in a range
+    async fn delete_block_mappings_range(&self, start_block: u64, end_block: u64) -> IngestResult<()> {
+        for block_number in start_block..=end_block {
+            self.delete_block_mapping(block_number).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete all batch mappings in a range
+    async fn delete_batch_mappings_range(&self, start_batch: u64, end_batch: u64) -> IngestResult<()> {
+        for batch_id in start_batch..=end_batch {
+            self.delete_batch_mapping(batch_id).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Fail with `IngestError::MappingError` if two mappings are for the same block
+pub(crate) fn ensure_distinct_blocks(mappings: &[BlockMapping]) -> IngestResult<()> {
+    let mut seen = HashSet::with_capacity(mappings.len());
+    match mappings.iter().find(|mapping| !seen.insert(mapping.block_number)) {
+        Some(duplicate) => Err(IngestError::MappingError(format!(
+            "Duplicate mapping for block {} in batch",
+            duplicate.block_number
+        ))),
+        None => Ok(()),
+    }
 }
 
 /// In-memory mapping storage for testing
@@ -56,6 +116,27 @@ pub struct MemoryMappingStorage {
     block_mappings: std::sync::Arc<std::sync::Mutex<HashMap<u64, BlockMapping>>>,
     batch_mappings: std::sync::Arc<std::sync::Mutex<HashMap<u64, BatchMapping>>>,
     epoch_mappings: std::sync::Arc<std::sync::Mutex<HashMap<u64, EpochMapping>>>,
+    batch_blocks: std::sync::Arc<std::sync::Mutex<HashMap<u64, BTreeSet<u64>>>>,
+}
+
+impl MemoryMappingStorage {
+    /// Add a block to the batch index, replacing its previous entry
+    fn index_block(index: &mut HashMap<u64, BTreeSet<u64>>, previous: Option<&BlockMapping>, mapping: &BlockMapping) {
+        if let Some(previous) = previous {
+            Self::unindex_block(index, previous);
+        }
+        index.entry(mapping.batch_id).or_default().insert(mapping.block_number);
+    }
+
+    /// Remove a block from the batch index
+    fn unindex_block(index: &mut HashMap<u64, BTreeSet<u64>>, mapping: &BlockMapping) {
+        if let Some(blocks) = index.get_mut(&mapping.batch_id) {
+            blocks.remove(&mapping.block_number);
+            if blocks.is_empty() {
+                index.remove(&mapping.batch_id);
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -63,11 +144,27 @@ impl MappingStorage for MemoryMappingStorage {
     async fn save_block_mapping(&self, mapping: BlockMapping) -> IngestResult<()> {
         let block_number = mapping.block_number;
         let mut storage = self.block_mappings.lock().unwrap();
+        let mut index = self.batch_blocks.lock().unwrap();
+        Self::index_block(&mut index, storage.get(&block_number), &mapping);
         storage.insert(block_number, mapping);
         debug!("Saved block mapping for block {}", block_number);
         Ok(())
     }
 
+    async fn save_block_mappings_batch(&self, mappings: Vec<BlockMapping>) -> IngestResult<()> {
+        // Nothing can fail past this check, so inserting under the locks is atomic
+        ensure_distinct_blocks(&mappings)?;
+        let count = mappings.len();
+        let mut storage = self.block_mappings.lock().unwrap();
+        let mut index = self.batch_blocks.lock().unwrap();
+        for mapping in mappings {
+            Self::index_block(&mut index, storage.get(&mapping.block_number), &mapping);
+            storage.insert(mapping.block_number, mapping);
+        }
+        debug!("Saved {} block mappings", count);
+        Ok(())
+    }
+
     async fn load_block_mapping(&self, block_number: u64) -> IngestResult<Option<BlockMapping>> {
         let storage = self.block_mappings.lock().unwrap();
         Ok(storage.get(&block_number).cloned())
@@ -127,9 +224,34 @@ impl MappingStorage for MemoryMappingStorage {
         Ok(mappings)
     }
 
+    async fn get_blocks_for_batch(&self, batch_id: u64) -> IngestResult<Vec<u64>> {
+        let index = self.batch_blocks.lock().unwrap();
+        Ok(index.get(&batch_id).map(|blocks| blocks.iter().copied().collect()).unwrap_or_default())
+    }
+
+    async fn batch_mapping_count(&self) -> IngestResult<u64> {
+        Ok(self.batch_mappings.lock().unwrap().len() as u64)
+    }
+
+    async fn latest_batch_mapping(&self) -> IngestResult<Option<BatchMapping>> {
+        let storage = self.batch_mappings.lock().unwrap();
+        Ok(storage.iter().max_by_key(|(batch_id, _)| **batch_id).map(|(_, mapping)| mapping.clone()))
+    }
+
+    async fn epoch_mapping_count(&self) -> IngestResult<u64> {
+        Ok(self.epoch_mappings.lock().unwrap().len() as u64)
+    }
+
+    async fn latest_epoch_mapping(&self) -> IngestResult<Option<EpochMapping>> {
+        let storage = self.epoch_mappings.lock().unwrap();
+        Ok(storage.iter().max_by_key(|(epoch_id, _)| **epoch_id).map(|(_, mapping)| mapping.clone()))
+    }
+
     async fn delete_block_mapping(&self, block_number: u64) -> IngestResult<()> {
         let mut storage = self.block_mappings.lock().unwrap();
-        storage.remove(&block_number);
+        if let Some(mapping) = storage.remove(&block_number) {
+            Self::unindex_block(&mut self.batch_blocks.lock().unwrap(), &mapping);
+        }
         debug!("Deleted block mapping for block {}", block_number);
         Ok(())
     }
@@ -147,6 +269,27 @@ impl MappingStorage for MemoryMappingStorage {
         debug!("Deleted epoch mapping for epoch {}", epoch_id);
         Ok(())
     }
+
+    async fn delete_block_mappings_range(&self, start_block: u64, end_block: u64) -> IngestResult<()> {
+        let mut storage = self.block_mappings.lock().unwrap();
+        let mut index = self.batch_blocks.lock().unwrap();
+        storage.retain(|block_number, mapping| {
+            let keep = !(start_block..=end_block).contains(block_number);
+            if !keep {
+                Self::unindex_block(&mut index, mapping);
+            }
+            keep
+        });
+        debug!("Deleted block mappings for blocks {}..={}", start_block, end_block);
+        Ok(())
+    }
+
+    async fn delete_batch_mappings_range(&self, start_batch: u64, end_batch: u64) -> IngestResult<()> {
+        let mut storage = self.batch_mappings.lock().unwrap();
+        storage.retain(|batch_id, _| !(start_batch..=end_batch).contains(batch_id));
+        debug!("Deleted batch mappings for batches {}..={}", start_batch, end_batch);
+        Ok(())
+    }
 }
 
 /// Mapping manager for handling block/batch/epoch relationships
@@ -234,21 +377,57 @@ impl MappingManager {
 
     /// Save mappings and update statistics
     pub async fn save_mappings(&mut self, mappings: Vec<BlockMapping>) -> IngestResult<()> {
+        let count = mappings.len() as u64;
+        self.storage.save_block_mappings_batch(mappings).await?;
+        self.stats.total_blocks += count;
+        self.update_stats();
+        Ok(())
+    }
+
+    /// Save batch mappings and update statistics
+    pub async fn save_batch_mappings(&mut self, mappings: Vec<BatchMapping>) -> IngestResult<()> {
         for mapping in mappings {
-            self.storage.save_block_mapping(mapping.clone()).await?;
-            self.stats.total_blocks += 1;
+            self.storage.save_batch_mapping(mapping).await?;
+            self.stats.total_batches += 1;
         }
-        self.stats.last_assembly = std::time::SystemTime::now()
+        self.update_stats();
+        Ok(())
+    }
+
+    /// Save epoch mappings and update statistics
+    pub async fn save_epoch_mappings(&mut self, mappings: Vec<EpochMapping>) -> IngestResult<()> {
+        for mapping in mappings {
+            self.storage.save_epoch_mapping(mapping).await?;
+            self.stats.total_epochs += 1;
+        }
+        self.update_stats();
+        Ok(())
+    }
+
+    /// Recompute the running averages and stamp the assembly time
+    fn update_stats(&mut self) {
+        let stats = &mut self.stats;
+        if stats.total_batches > 0 {
+            stats.avg_blocks_per_batch = stats.total_blocks as f64 / stats.total_batches as f64;
+        }
+        if stats.total_epochs > 0 {
+            stats.avg_batches_per_epoch = stats.total_batches as f64 / stats.total_epochs as f64;
+        }
+        stats.last_assembly = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        Ok(())
     }
 
     /// Get current statistics
     pub fn get_stats(&self) -> &AssemblyStats {
         &self.stats
     }
+
+    /// Get the underlying storage
+    pub fn storage(&self) -> &dyn MappingStorage {
+        self.storage.as_ref()
+    }
 }
 
 impl Clone for MemoryMappingStorage {
@@ -257,6 +436,7 @@ impl Clone for MemoryMappingStorage {
             block_mappings: self.block_mappings.clone(),
             batch_mappings: self.batch_mappings.clone(),
             epoch_mappings: self.epoch_mappings.clone(),
+            batch_blocks: self.batch_blocks.clone(),
         }
     }
 }
@@ -290,6 +470,98 @@ mod tests {
         assert_eq!(loaded, None);
     }
 
+    #[tokio::test]
+    async fn test_delete_mappings_range() {
+        let storage = MemoryMappingStorage::default();
+        let manager = MappingManager::new(Box::new(MemoryMappingStorage::default()));
+        for number in 0..10u64 {
+            let block = manager.create_block_mapping(number, FixedBytes::from([1u8; 32]), number, 0, 0);
+            storage.save_block_mapping(block).await.unwrap();
+            let batch = manager.create_batch_mapping(number, FixedBytes::from([2u8; 32]), number, number, 0);
+            storage.save_batch_mapping(batch).await.unwrap();
+        }
+
+        storage.delete_block_mappings_range(3, 6).await.unwrap();
+        storage.delete_batch_mappings_range(0, 4).await.unwrap();
+
+        let mut blocks: Vec<u64> = storage
+            .get_block_mappings_range(0, 9)
+            .await
+            .unwrap()
+            .iter()
+            .map(|mapping| mapping.block_number)
+            .collect();
+        blocks.sort();
+        assert_eq!(blocks, vec![0, 1, 2, 7, 8, 9]);
+
+        let mut batches: Vec<u64> = storage
+            .get_batch_mappings_range(0, 9)
+            .await
+            .unwrap()
+            .iter()
+            .map(|mapping| mapping.batch_id)
+            .collect();
+        batches.sort();
+        assert_eq!(batches, vec![5, 6, 7, 8, 9]);
+        assert_eq!(storage.batch_mapping_count().await.unwrap(), 5);
+        assert_eq!(storage.latest_batch_mapping().await.unwrap().map(|mapping| mapping.batch_id), Some(9));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_save_leaves_storage_unchanged() {
+        let storage = MemoryMappingStorage::default();
+        let mut manager = MappingManager::new(Box::new(storage.clone()));
+        let block = |number: u64| BlockMapping {
+            block_number: number,
+            block_hash: FixedBytes::from([1u8; 32]),
+            batch_id: 1,
+            batch_index: 0,
+            epoch_id: 1,
+            timestamp: 1234567890,
+        };
+
+        manager.save_mappings(vec![block(1), block(2)]).await.unwrap();
+        let before = storage.get_block_mappings_range(0, 10).await.unwrap().len();
+
+        // The duplicate of block 4 fails the whole batch before anything is stored
+        let result = manager.save_mappings(vec![block(3), block(4), block(4), block(5)]).await;
+        assert!(matches!(result, Err(IngestError::MappingError(_))));
+
+        assert_eq!(storage.get_block_mappings_range(0, 10).await.unwrap().len(), before);
+        assert_eq!(storage.load_block_mapping(3).await.unwrap(), None);
+        assert_eq!(manager.get_stats().total_blocks, 2);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_for_batch_index() {
+        let storage = MemoryMappingStorage::default();
+        let block = |block_number: u64, batch_id: u64| BlockMapping {
+            block_number,
+            block_hash: FixedBytes::from([1u8; 32]),
+            batch_id,
+            batch_index: 0,
+            epoch_id: 1,
+            timestamp: 1234567890,
+        };
+
+        storage.save_block_mappings_batch(vec![block(12, 2), block(10, 2), block(11, 2)]).await.unwrap();
+        storage.save_block_mapping(block(13, 3)).await.unwrap();
+        storage.save_block_mapping(block(9, 2)).await.unwrap();
+        assert_eq!(storage.get_blocks_for_batch(2).await.unwrap(), vec![9, 10, 11, 12]);
+        assert_eq!(storage.get_blocks_for_batch(3).await.unwrap(), vec![13]);
+        assert!(storage.get_blocks_for_batch(4).await.unwrap().is_empty());
+
+        // Remapping a block moves it between batches
+        storage.save_block_mapping(block(12, 3)).await.unwrap();
+        assert_eq!(storage.get_blocks_for_batch(2).await.unwrap(), vec![9, 10, 11]);
+        assert_eq!(storage.get_blocks_for_batch(3).await.unwrap(), vec![12, 13]);
+
+        storage.delete_block_mapping(10).await.unwrap();
+        storage.delete_block_mappings_range(12, 13).await.unwrap();
+        assert_eq!(storage.get_blocks_for_batch(2).await.unwrap(), vec![9, 11]);
+        assert!(storage.get_blocks_for_batch(3).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_mapping_manager() {
         let storage = Box::new(MemoryMappingStorage::default());
@@ -308,6 +580,43 @@ mod tests {
         assert_eq!(block_mapping.epoch_id, 1);
     }
 
+    #[tokio::test]
+    async fn test_save_mappings_updates_averages() {
+        let mut manager = MappingManager::new(Box::new(MemoryMappingStorage::default()));
+
+        // Two epochs of five batches of three blocks each
+        for epoch_id in 0..2u64 {
+            let mut batches = Vec::new();
+            for batch in 0..5u64 {
+                let batch_id = epoch_id * 5 + batch;
+                let start_block = batch_id * 3;
+                let blocks = (0..3)
+                    .map(|index| {
+                        let hash = FixedBytes::from([1u8; 32]);
+                        manager.create_block_mapping(start_block + index, hash, batch_id, index as u32, epoch_id)
+                    })
+                    .collect();
+                manager.save_mappings(blocks).await.unwrap();
+
+                let hash = FixedBytes::from([2u8; 32]);
+                batches.push(manager.create_batch_mapping(batch_id, hash, start_block, start_block + 2, epoch_id));
+            }
+            manager.save_batch_mappings(batches).await.unwrap();
+
+            let hash = FixedBytes::from([3u8; 32]);
+            let epoch = manager.create_epoch_mapping(epoch_id, hash, epoch_id * 15, epoch_id * 15 + 14, 5);
+            manager.save_epoch_mappings(vec![epoch]).await.unwrap();
+        }
+
+        let stats = manager.get_stats();
+        assert_eq!(stats.total_blocks, 30);
+        assert_eq!(stats.total_batches, 10);
+        assert_eq!(stats.total_epochs, 2);
+        assert_eq!(stats.avg_blocks_per_batch, 3.0);
+        assert_eq!(stats.avg_batches_per_epoch, 5.0);
+        assert!(stats.last_assembly > 0);
+    }
+
     #[test]
     fn test_batch_mapping_creation() {
         let storage = MemoryMappingStorage::default();
@@ -0,0 +1,701 @@
+//! RocksDB-backed mapping storage
+
+use crate::{mapping::ensure_distinct_blocks, BatchMapping, BlockMapping, EpochMapping, IngestError, IngestResult, MappingStorage};
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tracing::debug;
+
+/// Column family holding block mappings keyed by block number
+pub const BLOCK_MAPPINGS_CF: &str = "block_mappings";
+
+/// Column family holding batch mappings keyed by batch id
+pub const BATCH_MAPPINGS_CF: &str = "batch_mappings";
+
+/// Column family holding epoch mappings keyed by epoch id
+pub const EPOCH_MAPPINGS_CF: &str = "epoch_mappings";
+
+/// Column family indexing block numbers by batch, keyed by batch id then block number
+pub const BATCH_BLOCKS_CF: &str = "batch_blocks";
+
+/// Column family holding the number of batch and epoch mappings, keyed by their column family name
+pub const MAPPING_COUNTS_CF: &str = "mapping_counts";
+
+/// Column families of the database
+const COLUMN_FAMILIES: [&str; 5] = [BLOCK_MAPPINGS_CF, BATCH_MAPPINGS_CF, EPOCH_MAPPINGS_CF, BATCH_BLOCKS_CF, MAPPING_COUNTS_CF];
+
+/// Column families whose number of entries is kept in `MAPPING_COUNTS_CF`
+const COUNTED_CFS: [&str; 2] = [BATCH_MAPPINGS_CF, EPOCH_MAPPINGS_CF];
+
+/// Durable mapping storage in a RocksDB database
+///
+/// Each mapping kind lives in its own column family, keyed by its `u64` id in
+/// big-endian order so iteration follows numeric order and range queries are
+/// plain iterator scans. Values are JSON-encoded. Block mappings are also
+/// indexed by batch, with keys of the batch id followed by the block number.
+/// The number of batch and epoch mappings is kept alongside and updated in
+/// the same write as the mappings.
+///
+/// RocksDB calls block the calling thread, so every trait method runs its
+/// database work on the blocking thread pool.
+///
+/// Only one process can open the database for writing. Other processes can
+/// follow it through a secondary instance (see `open_secondary`).
+#[derive(Debug, Clone)]
+pub struct RocksMappingStorage {
+    inner: Arc<RocksDatabase>,
+}
+
+impl RocksMappingStorage {
+    /// Open or create the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> IngestResult<Self> {
+        Ok(Self { inner: Arc::new(RocksDatabase::open(path)?) })
+    }
+
+    /// Open the database at `primary_path` as a secondary instance
+    ///
+    /// A secondary instance reads the database while another process holds it
+    /// open for writing, keeping its own state in `secondary_path`. It catches
+    /// up with the primary before every call, and every write fails.
+    pub fn open_secondary(primary_path: impl AsRef<Path>, secondary_path: impl AsRef<Path>) -> IngestResult<Self> {
+        Ok(Self { inner: Arc::new(RocksDatabase::open_secondary(primary_path, secondary_path)?) })
+    }
+
+    /// Run `f` against the database on the blocking thread pool
+    async fn blocking<T, F>(&self, f: F) -> IngestResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&RocksDatabase) -> IngestResult<T> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || {
+            if inner.secondary {
+                inner.db.try_catch_up_with_primary().map_err(storage_error)?;
+            }
+            f(&inner)
+        })
+            .await
+            .map_err(|e| IngestError::StorageError(format!("Storage task failed: {}", e)))?
+    }
+}
+
+#[derive(Debug)]
+struct RocksDatabase {
+    db: DB,
+    /// Whether this is a secondary instance following another process
+    secondary: bool,
+    /// Held while a block mapping write reads the mappings it replaces, so
+    /// concurrent writers cannot leave stale entries in the batch index
+    block_writes: Mutex<()>,
+    /// Held while a batch or epoch mapping write reads the count it updates
+    count_writes: Mutex<()>,
+}
+
+impl RocksDatabase {
+    /// Open or create the database and its column families
+    fn open(path: impl AsRef<Path>) -> IngestResult<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let db = DB::open_cf(&options, path, COLUMN_FAMILIES).map_err(storage_error)?;
+        let database = Self { db, secondary: false, block_writes: Mutex::new(()), count_writes: Mutex::new(()) };
+        database.init_counts()?;
+        Ok(database)
+    }
+
+    /// Open the database as a secondary instance of the one at `primary_path`
+    fn open_secondary(primary_path: impl AsRef<Path>, secondary_path: impl AsRef<Path>) -> IngestResult<Self> {
+        let mut options = Options::default();
+        // Secondary instances must keep every table file open
+        options.set_max_open_files(-1);
+
+        let db = DB::open_cf_as_secondary(&options, primary_path.as_ref(), secondary_path.as_ref(), COLUMN_FAMILIES)
+            .map_err(storage_error)?;
+        Ok(Self { db, secondary: true, block_writes: Mutex::new(()), count_writes: Mutex::new(()) })
+    }
+
+    fn lock_block_writes(&self) -> MutexGuard<'_, ()> {
+        self.block_writes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock_count_writes(&self) -> MutexGuard<'_, ()> {
+        self.count_writes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Store the counts of databases written before counts were kept
+    fn init_counts(&self) -> IngestResult<()> {
+        for cf in COUNTED_CFS {
+            if self.stored_count(cf)?.is_none() {
+                let count = self.count_entries(cf)?;
+                let mut batch = WriteBatch::default();
+                self.stage_count(&mut batch, cf, count)?;
+                self.db.write(batch).map_err(storage_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn stored_count(&self, cf: &str) -> IngestResult<Option<u64>> {
+        self.db
+            .get_cf(self.cf(MAPPING_COUNTS_CF)?, cf.as_bytes())
+            .map_err(storage_error)?
+            .map(|bytes| decode_key(&bytes))
+            .transpose()
+    }
+
+    /// Number of entries in a counted column family
+    fn count(&self, cf: &str) -> IngestResult<u64> {
+        // A secondary may follow a primary that has not stored the count yet
+        match self.stored_count(cf)? {
+            Some(count) => Ok(count),
+            None => self.count_entries(cf),
+        }
+    }
+
+    fn count_entries(&self, cf: &str) -> IngestResult<u64> {
+        let mut count = 0;
+        for entry in self.db.iterator_cf(self.cf(cf)?, IteratorMode::Start) {
+            entry.map_err(storage_error)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn stage_count(&self, batch: &mut WriteBatch, cf: &str, count: u64) -> IngestResult<()> {
+        batch.put_cf(self.cf(MAPPING_COUNTS_CF)?, cf.as_bytes(), encode_key(count));
+        Ok(())
+    }
+
+    fn contains(&self, cf: &str, id: u64) -> IngestResult<bool> {
+        Ok(self.db.get_cf(self.cf(cf)?, encode_key(id)).map_err(storage_error)?.is_some())
+    }
+
+    /// Store a value in a counted column family, counting it if it is new
+    fn put_counted<T: Serialize>(&self, cf: &str, id: u64, value: &T) -> IngestResult<()> {
+        let _guard = self.lock_count_writes();
+        let mut batch = WriteBatch::default();
+        if !self.contains(cf, id)? {
+            self.stage_count(&mut batch, cf, self.count(cf)? + 1)?;
+        }
+        batch.put_cf(self.cf(cf)?, encode_key(id), encode_value(value)?);
+        self.db.write(batch).map_err(storage_error)
+    }
+
+    /// Delete a value from a counted column family
+    fn delete_counted(&self, cf: &str, id: u64) -> IngestResult<()> {
+        let _guard = self.lock_count_writes();
+        if !self.contains(cf, id)? {
+            return Ok(());
+        }
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf(cf)?, encode_key(id));
+        self.stage_count(&mut batch, cf, self.count(cf)?.saturating_sub(1))?;
+        self.db.write(batch).map_err(storage_error)
+    }
+
+    /// Delete all values with ids in `start..=end` from a counted column family in one write
+    fn delete_range_counted(&self, cf: &str, start: u64, end: u64) -> IngestResult<()> {
+        let _guard = self.lock_count_writes();
+        let removed = self.count_range(cf, start, end)?;
+        let mut batch = WriteBatch::default();
+        self.stage_delete_range(&mut batch, cf, start, end)?;
+        self.stage_count(&mut batch, cf, self.count(cf)?.saturating_sub(removed))?;
+        self.db.write(batch).map_err(storage_error)
+    }
+
+    /// Value with the highest id
+    fn latest<T: DeserializeOwned>(&self, cf: &str) -> IngestResult<Option<T>> {
+        match self.db.iterator_cf(self.cf(cf)?, IteratorMode::End).next() {
+            Some(entry) => {
+                let (_, value) = entry.map_err(storage_error)?;
+                Ok(Some(decode_value(&value)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn cf(&self, name: &str) -> IngestResult<&ColumnFamily> {
+        self.db
+            .cf_handle(name)
+            .ok_or_else(|| IngestError::StorageError(format!("Missing column family {}", name)))
+    }
+
+    fn get<T: DeserializeOwned>(&self, cf: &str, id: u64) -> IngestResult<Option<T>> {
+        self.db
+            .get_cf(self.cf(cf)?, encode_key(id))
+            .map_err(storage_error)?
+            .map(|bytes| decode_value(&bytes))
+            .transpose()
+    }
+
+    /// Load all values with ids in `start..=end`
+    fn range<T: DeserializeOwned>(&self, cf: &str, start: u64, end: u64) -> IngestResult<Vec<T>> {
+        let start_key = encode_key(start);
+        let iter = self.db.iterator_cf(self.cf(cf)?, IteratorMode::From(&start_key, Direction::Forward));
+
+        let mut values = Vec::new();
+        for entry in iter {
+            let (key, value) = entry.map_err(storage_error)?;
+            if decode_key(&key)? > end {
+                break;
+            }
+            values.push(decode_value(&value)?);
+        }
+        Ok(values)
+    }
+
+    /// Number of values with ids in `start..=end`
+    fn count_range(&self, cf: &str, start: u64, end: u64) -> IngestResult<u64> {
+        let start_key = encode_key(start);
+        let iter = self.db.iterator_cf(self.cf(cf)?, IteratorMode::From(&start_key, Direction::Forward));
+
+        let mut count = 0;
+        for entry in iter {
+            let (key, _) = entry.map_err(storage_error)?;
+            if decode_key(&key)? > end {
+                break;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn stage_delete_range(&self, batch: &mut WriteBatch, cf: &str, start: u64, end: u64) -> IngestResult<()> {
+        // An empty range deletes nothing, as in the in-memory storage
+        if start > end {
+            return Ok(());
+        }
+        let cf = self.cf(cf)?;
+        // The range end is exclusive, so the last id is deleted on its own
+        batch.delete_range_cf(cf, encode_key(start), encode_key(end));
+        batch.delete_cf(cf, encode_key(end));
+        Ok(())
+    }
+
+    /// Stage a block mapping and its index entry, dropping the entry of the mapping it replaces
+    fn stage_block_mapping(
+        &self,
+        batch: &mut WriteBatch,
+        previous: Option<&BlockMapping>,
+        mapping: &BlockMapping,
+    ) -> IngestResult<()> {
+        let index = self.cf(BATCH_BLOCKS_CF)?;
+        if let Some(previous) = previous {
+            batch.delete_cf(index, encode_index_key(previous.batch_id, previous.block_number));
+        }
+        batch.put_cf(self.cf(BLOCK_MAPPINGS_CF)?, encode_key(mapping.block_number), encode_value(mapping)?);
+        batch.put_cf(index, encode_index_key(mapping.batch_id, mapping.block_number), b"");
+        Ok(())
+    }
+
+    fn save_block_mappings(&self, mappings: &[BlockMapping]) -> IngestResult<()> {
+        let _guard = self.lock_block_writes();
+        let mut batch = WriteBatch::default();
+        for mapping in mappings {
+            let previous: Option<BlockMapping> = self.get(BLOCK_MAPPINGS_CF, mapping.block_number)?;
+            self.stage_block_mapping(&mut batch, previous.as_ref(), mapping)?;
+        }
+        self.db.write(batch).map_err(storage_error)
+    }
+
+    fn blocks_for_batch(&self, batch_id: u64) -> IngestResult<Vec<u64>> {
+        let start_key = encode_index_key(batch_id, 0);
+        let iter = self
+            .db
+            .iterator_cf(self.cf(BATCH_BLOCKS_CF)?, IteratorMode::From(&start_key, Direction::Forward));
+
+        let mut blocks = Vec::new();
+        for entry in iter {
+            let (key, _) = entry.map_err(storage_error)?;
+            if key.len() != 16 || decode_key(&key[..8])? != batch_id {
+                break;
+            }
+            blocks.push(decode_key(&key[8..])?);
+        }
+        Ok(blocks)
+    }
+
+    /// Delete a block mapping and its index entry, returning whether it existed
+    fn delete_block_mapping(&self, block_number: u64) -> IngestResult<bool> {
+        let _guard = self.lock_block_writes();
+        let Some(mapping) = self.get::<BlockMapping>(BLOCK_MAPPINGS_CF, block_number)? else {
+            return Ok(false);
+        };
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf(BLOCK_MAPPINGS_CF)?, encode_key(block_number));
+        batch.delete_cf(self.cf(BATCH_BLOCKS_CF)?, encode_index_key(mapping.batch_id, block_number));
+        self.db.write(batch).map_err(storage_error)?;
+        Ok(true)
+    }
+
+    fn delete_block_mappings_range(&self, start_block: u64, end_block: u64) -> IngestResult<()> {
+        let _guard = self.lock_block_writes();
+        let index = self.cf(BATCH_BLOCKS_CF)?;
+        let mut batch = WriteBatch::default();
+        for mapping in self.range::<BlockMapping>(BLOCK_MAPPINGS_CF, start_block, end_block)? {
+            batch.delete_cf(index, encode_index_key(mapping.batch_id, mapping.block_number));
+        }
+        self.stage_delete_range(&mut batch, BLOCK_MAPPINGS_CF, start_block, end_block)?;
+        self.db.write(batch).map_err(storage_error)
+    }
+}
+
+fn encode_key(id: u64) -> [u8; 8] {
+    id.to_be_bytes()
+}
+
+fn encode_index_key(batch_id: u64, block_number: u64) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    key[..8].copy_from_slice(&batch_id.to_be_bytes());
+    key[8..].copy_from_slice(&block_number.to_be_bytes());
+    key
+}
+
+fn decode_key(key: &[u8]) -> IngestResult<u64> {
+    let bytes: [u8; 8] = key
+        .try_into()
+        .map_err(|_| IngestError::StorageError(format!("Invalid key length {}", key.len())))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn encode_value<T: Serialize>(value: &T) -> IngestResult<Vec<u8>> {
+    serde_json::to_vec(value).map_err(|e| IngestError::StorageError(format!("Failed to encode mapping: {}", e)))
+}
+
+fn decode_value<T: DeserializeOwned>(bytes: &[u8]) -> IngestResult<T> {
+    serde_json::from_slice(bytes).map_err(|e| IngestError::StorageError(format!("Failed to decode mapping: {}", e)))
+}
+
+fn storage_error(error: rocksdb::Error) -> IngestError {
+    IngestError::StorageError(error.to_string())
+}
+
+#[async_trait::async_trait]
+impl MappingStorage for RocksMappingStorage {
+    async fn save_block_mapping(&self, mapping: BlockMapping) -> IngestResult<()> {
+        let block_number = mapping.block_number;
+        self.blocking(move |db| db.save_block_mappings(&[mapping])).await?;
+        debug!("Saved block mapping for block {}", block_number);
+        Ok(())
+    }
+
+    async fn save_block_mappings_batch(&self, mappings: Vec<BlockMapping>) -> IngestResult<()> {
+        ensure_distinct_blocks(&mappings)?;
+        let count = mappings.len();
+        self.blocking(move |db| db.save_block_mappings(&mappings)).await?;
+        debug!("Saved {} block mappings", count);
+        Ok(())
+    }
+
+    async fn load_block_mapping(&self, block_number: u64) -> IngestResult<Option<BlockMapping>> {
+        self.blocking(move |db| db.get(BLOCK_MAPPINGS_CF, block_number)).await
+    }
+
+    async fn save_batch_mapping(&self, mapping: BatchMapping) -> IngestResult<()> {
+        let batch_id = mapping.batch_id;
+        self.blocking(move |db| db.put_counted(BATCH_MAPPINGS_CF, mapping.batch_id, &mapping)).await?;
+        debug!("Saved batch mapping for batch {}", batch_id);
+        Ok(())
+    }
+
+    async fn load_batch_mapping(&self, batch_id: u64) -> IngestResult<Option<BatchMapping>> {
+        self.blocking(move |db| db.get(BATCH_MAPPINGS_CF, batch_id)).await
+    }
+
+    async fn save_epoch_mapping(&self, mapping: EpochMapping) -> IngestResult<()> {
+        let epoch_id = mapping.epoch_id;
+        self.blocking(move |db| db.put_counted(EPOCH_MAPPINGS_CF, mapping.epoch_id, &mapping)).await?;
+        debug!("Saved epoch mapping for epoch {}", epoch_id);
+        Ok(())
+    }
+
+    async fn load_epoch_mapping(&self, epoch_id: u64) -> IngestResult<Option<EpochMapping>> {
+        self.blocking(move |db| db.get(EPOCH_MAPPINGS_CF, epoch_id)).await
+    }
+
+    async fn get_block_mappings_range(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> IngestResult<Vec<BlockMapping>> {
+        self.blocking(move |db| db.range(BLOCK_MAPPINGS_CF, start_block, end_block)).await
+    }
+
+    async fn get_batch_mappings_range(
+        &self,
+        start_batch: u64,
+        end_batch: u64,
+    ) -> IngestResult<Vec<BatchMapping>> {
+        self.blocking(move |db| db.range(BATCH_MAPPINGS_CF, start_batch, end_batch)).await
+    }
+
+    async fn get_blocks_for_batch(&self, batch_id: u64) -> IngestResult<Vec<u64>> {
+        self.blocking(move |db| db.blocks_for_batch(batch_id)).await
+    }
+
+    async fn batch_mapping_count(&self) -> IngestResult<u64> {
+        self.blocking(|db| db.count(BATCH_MAPPINGS_CF)).await
+    }
+
+    async fn latest_batch_mapping(&self) -> IngestResult<Option<BatchMapping>> {
+        self.blocking(|db| db.latest(BATCH_MAPPINGS_CF)).await
+    }
+
+    async fn epoch_mapping_count(&self) -> IngestResult<u64> {
+        self.blocking(|db| db.count(EPOCH_MAPPINGS_CF)).await
+    }
+
+    async fn latest_epoch_mapping(&self) -> IngestResult<Option<EpochMapping>> {
+        self.blocking(|db| db.latest(EPOCH_MAPPINGS_CF)).await
+    }
+
+    async fn delete_block_mapping(&self, block_number: u64) -> IngestResult<()> {
+        if self.blocking(move |db| db.delete_block_mapping(block_number)).await? {
+            debug!("Deleted block mapping for block {}", block_number);
+        }
+        Ok(())
+    }
+
+    async fn delete_batch_mapping(&self, batch_id: u64) -> IngestResult<()> {
+        self.blocking(move |db| db.delete_counted(BATCH_MAPPINGS_CF, batch_id)).await?;
+        debug!("Deleted batch mapping for batch {}", batch_id);
+        Ok(())
+    }
+
+    async fn delete_epoch_mapping(&self, epoch_id: u64) -> IngestResult<()> {
+        self.blocking(move |db| db.delete_counted(EPOCH_MAPPINGS_CF, epoch_id)).await?;
+        debug!("Deleted epoch mapping for epoch {}", epoch_id);
+        Ok(())
+    }
+
+    async fn delete_block_mappings_range(&self, start_block: u64, end_block: u64) -> IngestResult<()> {
+        self.blocking(move |db| db.delete_block_mappings_range(start_block, end_block)).await?;
+        debug!("Deleted block mappings for blocks {}..={}", start_block, end_block);
+        Ok(())
+    }
+
+    async fn delete_batch_mappings_range(&self, start_batch: u64, end_batch: u64) -> IngestResult<()> {
+        self.blocking(move |db| db.delete_range_counted(BATCH_MAPPINGS_CF, start_batch, end_batch)).await?;
+        debug!("Deleted batch mappings for batches {}..={}", start_batch, end_batch);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::FixedBytes;
+
+    fn block_mapping(block_number: u64, batch_id: u64) -> BlockMapping {
+        BlockMapping {
+            block_number,
+            block_hash: FixedBytes::from([block_number as u8; 32]),
+            batch_id,
+            batch_index: 0,
+            epoch_id: 1,
+            timestamp: 1234567890,
+        }
+    }
+
+    fn batch_mapping(batch_id: u64) -> BatchMapping {
+        BatchMapping {
+            batch_id,
+            batch_hash: FixedBytes::from([batch_id as u8; 32]),
+            start_block: batch_id * 10,
+            end_block: batch_id * 10 + 9,
+            block_count: 10,
+            epoch_id: 1,
+            timestamp: 1234567890,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_load_range_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+
+        for number in 0..10u64 {
+            storage.save_block_mapping(block_mapping(number, number / 5)).await.unwrap();
+        }
+        for batch_id in 0..300u64 {
+            storage.save_batch_mapping(batch_mapping(batch_id)).await.unwrap();
+        }
+
+        assert_eq!(storage.load_block_mapping(3).await.unwrap(), Some(block_mapping(3, 0)));
+        assert_eq!(storage.load_block_mapping(42).await.unwrap(), None);
+
+        // Keys sort numerically, across byte boundaries too
+        let batches: Vec<u64> = storage
+            .get_batch_mappings_range(250, 260)
+            .await
+            .unwrap()
+            .iter()
+            .map(|mapping| mapping.batch_id)
+            .collect();
+        assert_eq!(batches, (250..=260).collect::<Vec<_>>());
+
+        storage.delete_block_mappings_range(2, 4).await.unwrap();
+        storage.delete_block_mapping(9).await.unwrap();
+        let blocks: Vec<u64> = storage
+            .get_block_mappings_range(0, 9)
+            .await
+            .unwrap()
+            .iter()
+            .map(|mapping| mapping.block_number)
+            .collect();
+        assert_eq!(blocks, vec![0, 1, 5, 6, 7, 8]);
+
+        let epoch = EpochMapping {
+            epoch_id: 1,
+            epoch_hash: FixedBytes::from([9u8; 32]),
+            start_block: 0,
+            end_block: 9,
+            block_count: 10,
+            batch_count: 2,
+            timestamp: 1234567890,
+        };
+        storage.save_epoch_mapping(epoch.clone()).await.unwrap();
+        assert_eq!(storage.load_epoch_mapping(1).await.unwrap(), Some(epoch));
+        storage.delete_epoch_mapping(1).await.unwrap();
+        assert_eq!(storage.load_epoch_mapping(1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_for_batch_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+
+        storage
+            .save_block_mappings_batch(vec![block_mapping(258, 2), block_mapping(3, 2), block_mapping(4, 2)])
+            .await
+            .unwrap();
+        storage.save_block_mapping(block_mapping(5, 3)).await.unwrap();
+        assert_eq!(storage.get_blocks_for_batch(2).await.unwrap(), vec![3, 4, 258]);
+
+        storage.save_block_mapping(block_mapping(4, 3)).await.unwrap();
+        storage.delete_block_mapping(3).await.unwrap();
+        assert_eq!(storage.get_blocks_for_batch(2).await.unwrap(), vec![258]);
+        assert_eq!(storage.get_blocks_for_batch(3).await.unwrap(), vec![4, 5]);
+
+        storage.delete_block_mappings_range(0, 300).await.unwrap();
+        assert!(storage.get_blocks_for_batch(2).await.unwrap().is_empty());
+        assert!(storage.get_blocks_for_batch(3).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mappings_persist_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let storage = RocksMappingStorage::open(dir.path()).unwrap();
+            storage
+                .save_block_mappings_batch(vec![block_mapping(1, 1), block_mapping(2, 1)])
+                .await
+                .unwrap();
+            storage.save_batch_mapping(batch_mapping(1)).await.unwrap();
+        }
+
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.load_block_mapping(2).await.unwrap(), Some(block_mapping(2, 1)));
+        assert_eq!(storage.load_batch_mapping(1).await.unwrap(), Some(batch_mapping(1)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_save_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+
+        let result = storage
+            .save_block_mappings_batch(vec![block_mapping(1, 1), block_mapping(1, 1)])
+            .await;
+        assert!(matches!(result, Err(IngestError::MappingError(_))));
+        assert_eq!(storage.load_block_mapping(1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_reversed_range_deletes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+
+        for number in 0..10u64 {
+            storage.save_block_mapping(block_mapping(number, 1)).await.unwrap();
+            storage.save_batch_mapping(batch_mapping(number)).await.unwrap();
+        }
+
+        storage.delete_block_mappings_range(5, 2).await.unwrap();
+        storage.delete_batch_mappings_range(5, 2).await.unwrap();
+        assert_eq!(storage.get_block_mappings_range(0, 9).await.unwrap().len(), 10);
+        assert_eq!(storage.get_batch_mappings_range(0, 9).await.unwrap().len(), 10);
+        assert_eq!(storage.get_blocks_for_batch(1).await.unwrap().len(), 10);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_block_writes_keep_index_consistent() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+
+        // Every task moves the same blocks to its own batch
+        let tasks: Vec<_> = (0..8u64)
+            .map(|batch_id| {
+                let storage = storage.clone();
+                tokio::spawn(async move {
+                    let mappings = (0..16).map(|number| block_mapping(number, batch_id)).collect();
+                    storage.save_block_mappings_batch(mappings).await.unwrap();
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let mut indexed = 0;
+        for batch_id in 0..8u64 {
+            indexed += storage.get_blocks_for_batch(batch_id).await.unwrap().len();
+        }
+        assert_eq!(indexed, 16);
+    }
+
+    #[tokio::test]
+    async fn test_counts_and_latest_mappings() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let storage = RocksMappingStorage::open(dir.path()).unwrap();
+            assert_eq!(storage.batch_mapping_count().await.unwrap(), 0);
+            assert_eq!(storage.latest_batch_mapping().await.unwrap(), None);
+
+            for batch_id in 0..300u64 {
+                storage.save_batch_mapping(batch_mapping(batch_id)).await.unwrap();
+            }
+            // Overwriting a mapping does not count it again
+            storage.save_batch_mapping(batch_mapping(7)).await.unwrap();
+            storage.delete_batch_mapping(299).await.unwrap();
+            storage.delete_batch_mapping(299).await.unwrap();
+            storage.delete_batch_mappings_range(250, 400).await.unwrap();
+            assert_eq!(storage.batch_mapping_count().await.unwrap(), 250);
+            assert_eq!(storage.latest_batch_mapping().await.unwrap(), Some(batch_mapping(249)));
+        }
+
+        // Counts are persisted with the mappings
+        let storage = RocksMappingStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.batch_mapping_count().await.unwrap(), 250);
+        assert_eq!(storage.epoch_mapping_count().await.unwrap(), 0);
+        assert_eq!(storage.latest_epoch_mapping().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_secondary_follows_primary() {
+        let dir = tempfile::tempdir().unwrap();
+        let primary_path = dir.path().join("primary");
+        let primary = RocksMappingStorage::open(&primary_path).unwrap();
+        // The primary holds the write lock
+        assert!(RocksMappingStorage::open(&primary_path).is_err());
+
+        let secondary = RocksMappingStorage::open_secondary(&primary_path, dir.path().join("secondary")).unwrap();
+        primary.save_batch_mapping(batch_mapping(1)).await.unwrap();
+        assert_eq!(secondary.load_batch_mapping(1).await.unwrap(), Some(batch_mapping(1)));
+        assert!(secondary.save_batch_mapping(batch_mapping(2)).await.is_err());
+    }
+}
@@ -2,11 +2,75 @@
 
 use cdk_types::{Batch, BlockInBatch};
 use crate::{BlockInputs, IngestError, IngestResult};
-use alloy_primitives::U256;
+use alloy_primitives::{FixedBytes, U256};
+use rayon::prelude::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{debug, warn};
 
+/// Default minimum block gas limit (0 disables the lower bound)
+pub const DEFAULT_MIN_GAS_LIMIT: u64 = 0;
+
+/// Default maximum block gas limit (`2^63 - 1`, the EIP-1559 upper bound)
+pub const DEFAULT_MAX_GAS_LIMIT: u64 = i64::MAX as u64;
+
+/// Default number of blocks above which a batch's blocks are validated in parallel
+pub const DEFAULT_PARALLEL_VALIDATION_THRESHOLD: usize = 64;
+
+/// Default tolerance for block timestamps ahead of the local clock
+pub const DEFAULT_MAX_FUTURE_DRIFT: Duration = Duration::from_secs(15 * 60);
+
+/// How serious a finding in a `BatchValidationReport` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValidationSeverity {
+    /// Suspicious but accepted outside strict mode
+    Warning,
+    /// Makes the batch invalid
+    Error,
+}
+
+/// A single problem found while validating a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// How serious the problem is
+    pub severity: ValidationSeverity,
+    /// Index of the offending block, or `None` for batch-level problems
+    pub block_index: Option<usize>,
+    /// Description of the problem
+    pub message: String,
+}
+
+/// Every problem found in a batch, in batch-level then block order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchValidationReport {
+    /// Number of the validated batch
+    pub batch_number: U256,
+    /// Problems found
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl BatchValidationReport {
+    fn push(&mut self, severity: ValidationSeverity, block_index: Option<usize>, message: String) {
+        self.issues.push(ValidationIssue { severity, block_index, message });
+    }
+
+    /// Whether no error-level problem was found
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Error-level problems
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|issue| issue.severity == ValidationSeverity::Error)
+    }
+
+    /// Warning-level problems
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|issue| issue.severity == ValidationSeverity::Warning)
+    }
+}
+
 /// Batch validator for ensuring data integrity
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BatchValidator {
     /// Maximum blocks per batch
     pub max_blocks_per_batch: u32,
@@ -14,6 +78,14 @@ pub struct BatchValidator {
     pub max_batch_size_bytes: u64,
     /// Enable strict validation
     pub strict_mode: bool,
+    /// Minimum accepted block gas limit (0 to disable)
+    pub min_gas_limit: u64,
+    /// Maximum accepted block gas limit
+    pub max_gas_limit: u64,
+    /// Batches with more blocks than this have their blocks validated in parallel
+    pub parallel_threshold: usize,
+    /// How far ahead of the local clock a block timestamp may be in strict mode
+    pub max_future_drift: Duration,
 }
 
 impl Default for BatchValidator {
@@ -22,6 +94,10 @@ impl Default for BatchValidator {
             max_blocks_per_batch: 1000,
             max_batch_size_bytes: 10 * 1024 * 1024, // 10MB
             strict_mode: true,
+            min_gas_limit: DEFAULT_MIN_GAS_LIMIT,
+            max_gas_limit: DEFAULT_MAX_GAS_LIMIT,
+            parallel_threshold: DEFAULT_PARALLEL_VALIDATION_THRESHOLD,
+            max_future_drift: DEFAULT_MAX_FUTURE_DRIFT,
         }
     }
 }
@@ -33,143 +109,307 @@ impl BatchValidator {
             max_blocks_per_batch,
             max_batch_size_bytes,
             strict_mode,
+            min_gas_limit: DEFAULT_MIN_GAS_LIMIT,
+            max_gas_limit: DEFAULT_MAX_GAS_LIMIT,
+            parallel_threshold: DEFAULT_PARALLEL_VALIDATION_THRESHOLD,
+            max_future_drift: DEFAULT_MAX_FUTURE_DRIFT,
         }
     }
 
+    /// Set the number of blocks above which blocks are validated in parallel
+    pub fn with_parallel_threshold(mut self, parallel_threshold: usize) -> Self {
+        self.parallel_threshold = parallel_threshold;
+        self
+    }
+
+    /// Set how far ahead of the local clock a block timestamp may be
+    pub fn with_max_future_drift(mut self, max_future_drift: Duration) -> Self {
+        self.max_future_drift = max_future_drift;
+        self
+    }
+
+    /// Set the accepted block gas limit range
+    pub fn with_gas_limit_bounds(mut self, min_gas_limit: u64, max_gas_limit: u64) -> Self {
+        self.min_gas_limit = min_gas_limit;
+        self.max_gas_limit = max_gas_limit;
+        self
+    }
+
     /// Validate a batch
     pub async fn validate_batch(&self, batch: &Batch) -> IngestResult<()> {
+        self.validate_batch_with_parent(batch, None).await
+    }
+
+    /// Validate a batch, checking that its first block links to `prev_block_hash`
+    ///
+    /// `prev_block_hash` is the hash of the last block of the previous batch and
+    /// is only checked in strict mode.
+    pub async fn validate_batch_with_parent(
+        &self,
+        batch: &Batch,
+        prev_block_hash: Option<FixedBytes<32>>,
+    ) -> IngestResult<()> {
         debug!("Validating batch {}", batch.id.number);
 
-        // Check batch ID
+        // Check batch ID, L1 origin, block count and size
+        if let Some(reason) = self.batch_violations(batch).into_iter().next() {
+            return Err(IngestError::InvalidBatchData(reason));
+        }
+
+        // Validate each block in the batch
+        self.validate_blocks(&batch.blocks).await?;
+
+        // Check block ordering and hash linkage
+        if self.strict_mode {
+            self.validate_block_ordering(&batch.blocks).await?;
+            self.validate_parent_linkage(&batch.blocks, prev_block_hash).await?;
+        }
+
+        debug!("Batch {} validation passed", batch.id.number);
+        Ok(())
+    }
+
+    /// Validate a batch, collecting every violation instead of stopping at the first
+    ///
+    /// Batch-level and per-block problems are errors. Ordering and linkage
+    /// problems are errors in strict mode and warnings otherwise.
+    pub fn validate_batch_report(&self, batch: &Batch) -> BatchValidationReport {
+        let mut report = BatchValidationReport { batch_number: batch.id.number, issues: Vec::new() };
+
+        for reason in self.batch_violations(batch) {
+            report.push(ValidationSeverity::Error, None, reason);
+        }
+
+        let block_violations = |(index, block): (usize, &BlockInBatch)| {
+            self.block_violations(block, index as u32).into_iter().map(move |reason| (index, reason))
+        };
+        let block_violations: Vec<_> = if batch.blocks.len() > self.parallel_threshold {
+            batch.blocks.par_iter().enumerate().flat_map_iter(block_violations).collect()
+        } else {
+            batch.blocks.iter().enumerate().flat_map(block_violations).collect()
+        };
+        for (index, reason) in block_violations {
+            report.push(ValidationSeverity::Error, Some(index), reason);
+        }
+
+        let severity = if self.strict_mode { ValidationSeverity::Error } else { ValidationSeverity::Warning };
+        let sequence_violations =
+            self.ordering_violations(&batch.blocks).into_iter().chain(self.linkage_violations(&batch.blocks, None));
+        for (index, reason) in sequence_violations {
+            report.push(severity, Some(index), reason);
+        }
+
+        report
+    }
+
+    /// Batch-level violations: zero ID or L1 origin, too many blocks, too large
+    fn batch_violations(&self, batch: &Batch) -> Vec<String> {
+        let mut violations = Vec::new();
+
         if batch.id.number == U256::ZERO {
-            return Err(IngestError::InvalidBatchData("Batch ID cannot be zero".to_string()));
+            violations.push("Batch ID cannot be zero".to_string());
         }
 
-        // Check L1 origin
         if batch.l1_origin == U256::ZERO {
-            return Err(IngestError::InvalidBatchData("L1 origin cannot be zero".to_string()));
+            violations.push("L1 origin cannot be zero".to_string());
         }
 
-        // Check block count
         if batch.blocks.len() > self.max_blocks_per_batch as usize {
-            return Err(IngestError::InvalidBatchData(format!(
+            violations.push(format!(
                 "Too many blocks in batch: {} > {}",
                 batch.blocks.len(),
                 self.max_blocks_per_batch
-            )));
+            ));
         }
 
-        // Check batch size
         let batch_size = self.estimate_batch_size(batch);
         if batch_size > self.max_batch_size_bytes {
-            return Err(IngestError::InvalidBatchData(format!(
-                "Batch too large: {} bytes > {} bytes",
-                batch_size, self.max_batch_size_bytes
-            )));
+            violations.push(format!("Batch too large: {} bytes > {} bytes", batch_size, self.max_batch_size_bytes));
         }
 
-        // Validate each block in the batch
-        for (index, block) in batch.blocks.iter().enumerate() {
-            self.validate_block_in_batch(block, index as u32).await?;
-        }
+        violations
+    }
 
-        // Check block ordering
-        if self.strict_mode {
-            self.validate_block_ordering(&batch.blocks).await?;
+    /// Validate every block of a batch, reporting the invalid block with the lowest index
+    ///
+    /// Blocks are checked on the rayon thread pool when there are more than
+    /// `parallel_threshold` of them, from a blocking task so the async worker
+    /// is not held while rayon runs.
+    async fn validate_blocks(&self, blocks: &[BlockInBatch]) -> IngestResult<()> {
+        let first_invalid = if blocks.len() > self.parallel_threshold {
+            let validator = self.clone();
+            let blocks = blocks.to_vec();
+            tokio::task::spawn_blocking(move || validator.first_invalid_block(&blocks))
+                .await
+                .map_err(|e| IngestError::ValidationError(format!("Block validation task failed: {}", e)))?
+        } else {
+            self.first_invalid_block(blocks)
+        };
+
+        match first_invalid {
+            Some((index, IngestError::InvalidBatchData(reason))) => {
+                Err(IngestError::InvalidBatchData(format!("Block {}: {}", index, reason)))
+            }
+            Some((_, e)) => Err(e),
+            None => Ok(()),
         }
+    }
 
-        debug!("Batch {} validation passed", batch.id.number);
-        Ok(())
+    /// Find the invalid block with the lowest index, on rayon above `parallel_threshold` blocks
+    fn first_invalid_block(&self, blocks: &[BlockInBatch]) -> Option<(usize, IngestError)> {
+        let invalid_block = |(index, block): (usize, &BlockInBatch)| {
+            self.validate_block_in_batch(block, index as u32).err().map(|e| (index, e))
+        };
+        if blocks.len() > self.parallel_threshold {
+            blocks.par_iter().enumerate().find_map_first(invalid_block)
+        } else {
+            blocks.iter().enumerate().find_map(invalid_block)
+        }
     }
 
     /// Validate a block within a batch
-    async fn validate_block_in_batch(
+    fn validate_block_in_batch(
         &self,
         block: &BlockInBatch,
         expected_index: u32,
     ) -> IngestResult<()> {
+        match self.block_violations(block, expected_index).into_iter().next() {
+            Some(reason) => Err(IngestError::InvalidBatchData(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Violations of a single block expected at `expected_index` in its batch
+    fn block_violations(&self, block: &BlockInBatch, expected_index: u32) -> Vec<String> {
+        let mut violations = Vec::new();
+
         // Check batch index
         if block.batch_index != expected_index {
-            return Err(IngestError::InvalidBatchData(format!(
+            violations.push(format!(
                 "Block batch index mismatch: expected {}, got {}",
                 expected_index, block.batch_index
-            )));
+            ));
         }
 
         // Check block number
         if block.number == U256::ZERO {
-            return Err(IngestError::InvalidBatchData("Block number cannot be zero".to_string()));
+            violations.push("Block number cannot be zero".to_string());
         }
 
         // Check hashes
         if block.hash.is_zero() {
-            return Err(IngestError::InvalidBatchData("Block hash cannot be zero".to_string()));
+            violations.push("Block hash cannot be zero".to_string());
         }
 
         if block.parent_hash.is_zero() && block.number != U256::from(1) {
-            return Err(IngestError::InvalidBatchData(
-                "Non-genesis block must have non-zero parent hash".to_string(),
-            ));
+            violations.push("Non-genesis block must have non-zero parent hash".to_string());
         }
 
         // Check roots
         if block.state_root.is_zero() {
-            return Err(IngestError::InvalidBatchData("State root cannot be zero".to_string()));
+            violations.push("State root cannot be zero".to_string());
         }
 
         if block.receipt_root.is_zero() {
-            return Err(IngestError::InvalidBatchData("Receipts root cannot be zero".to_string()));
+            violations.push("Receipts root cannot be zero".to_string());
         }
 
         if block.tx_root.is_zero() {
-            return Err(IngestError::InvalidBatchData("Transactions root cannot be zero".to_string()));
+            violations.push("Transactions root cannot be zero".to_string());
         }
 
         // Check timestamp
         if block.timestamp == 0 {
-            return Err(IngestError::InvalidBatchData("Block timestamp cannot be zero".to_string()));
+            violations.push("Block timestamp cannot be zero".to_string());
         }
 
-        Ok(())
+        if self.strict_mode {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            let latest = now.saturating_add(self.max_future_drift.as_secs());
+            if block.timestamp > latest {
+                violations.push(format!(
+                    "Block timestamp {} is more than {}s ahead of local time {}",
+                    block.timestamp,
+                    self.max_future_drift.as_secs(),
+                    now
+                ));
+            }
+        }
+
+        violations
     }
 
     /// Validate block ordering within a batch
     async fn validate_block_ordering(&self, blocks: &[BlockInBatch]) -> IngestResult<()> {
-        if blocks.is_empty() {
-            return Ok(());
+        match self.ordering_violations(blocks).into_iter().next() {
+            Some((_, reason)) => Err(IngestError::InvalidBatchData(reason)),
+            None => Ok(()),
         }
+    }
+
+    /// Block number and timestamp ordering violations, with the index of the offending block
+    fn ordering_violations(&self, blocks: &[BlockInBatch]) -> Vec<(usize, String)> {
+        let mut violations = Vec::new();
 
-        let mut prev_block_number = blocks[0].number;
-        let mut prev_timestamp = blocks[0].timestamp;
+        for (index, pair) in blocks.windows(2).enumerate() {
+            let (prev, block) = (&pair[0], &pair[1]);
 
-        for block in blocks.iter().skip(1) {
             // Check block number ordering
-            if block.number <= prev_block_number {
-                return Err(IngestError::InvalidBatchData(format!(
-                    "Block numbers not in order: {} <= {}",
-                    block.number, prev_block_number
-                )));
+            if block.number <= prev.number {
+                violations.push((index + 1, format!("Block numbers not in order: {} <= {}", block.number, prev.number)));
             }
 
             // Check timestamp ordering
-            if block.timestamp < prev_timestamp {
-                warn!(
-                    "Block timestamp {} is before previous block timestamp {}",
-                    block.timestamp, prev_timestamp
-                );
-                if self.strict_mode {
-                    return Err(IngestError::InvalidBatchData(format!(
-                        "Block timestamps not in order: {} < {}",
-                        block.timestamp, prev_timestamp
-                    )));
+            if block.timestamp < prev.timestamp {
+                warn!("Block timestamp {} is before previous block timestamp {}", block.timestamp, prev.timestamp);
+                violations.push((
+                    index + 1,
+                    format!("Block timestamps not in order: {} < {}", block.timestamp, prev.timestamp),
+                ));
+            }
+        }
+
+        violations
+    }
+
+    /// Validate that each block's parent hash matches the previous block's hash
+    async fn validate_parent_linkage(
+        &self,
+        blocks: &[BlockInBatch],
+        prev_block_hash: Option<FixedBytes<32>>,
+    ) -> IngestResult<()> {
+        match self.linkage_violations(blocks, prev_block_hash).into_iter().next() {
+            Some((_, reason)) => Err(IngestError::InvalidBatchData(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Parent hash linkage violations, with the index of the offending block
+    fn linkage_violations(
+        &self,
+        blocks: &[BlockInBatch],
+        prev_block_hash: Option<FixedBytes<32>>,
+    ) -> Vec<(usize, String)> {
+        let mut violations = Vec::new();
+        let mut expected_parent = prev_block_hash;
+
+        for (index, block) in blocks.iter().enumerate() {
+            if let Some(expected) = expected_parent {
+                if block.parent_hash != expected {
+                    violations.push((
+                        index,
+                        format!(
+                            "Block {} parent hash {} does not match previous block hash {}",
+                            block.number, block.parent_hash, expected
+                        ),
+                    ));
                 }
             }
 
-            prev_block_number = block.number;
-            prev_timestamp = block.timestamp;
+            expected_parent = Some(block.hash);
         }
 
-        Ok(())
+        violations
     }
 
     /// Estimate batch size in bytes
@@ -233,6 +473,13 @@ impl BatchValidator {
             return Err(IngestError::InvalidBlockData("Gas limit cannot be zero".to_string()));
         }
 
+        if block.gas_limit < self.min_gas_limit || block.gas_limit > self.max_gas_limit {
+            return Err(IngestError::InvalidBlockData(format!(
+                "Block {} gas limit {} outside allowed range [{}, {}]",
+                block.number, block.gas_limit, self.min_gas_limit, self.max_gas_limit
+            )));
+        }
+
         if block.gas_used > block.gas_limit {
             return Err(IngestError::InvalidBlockData(format!(
                 "Gas used {} exceeds gas limit {}",
@@ -240,6 +487,14 @@ impl BatchValidator {
             )));
         }
 
+        // Post-London blocks must carry a non-zero base fee
+        if self.strict_mode && block.base_fee_per_gas == Some(0) {
+            return Err(IngestError::InvalidBlockData(format!(
+                "Block {} base fee per gas cannot be zero",
+                block.number
+            )));
+        }
+
         // Check timestamp
         if block.timestamp == 0 {
             return Err(IngestError::InvalidBlockData("Block timestamp cannot be zero".to_string()));
@@ -265,6 +520,18 @@ impl BatchValidator {
             return Err(IngestError::InvalidBlockData("Transaction gas limit cannot be zero".to_string()));
         }
 
+        // Check EIP-1559 fee caps
+        if self.strict_mode {
+            if let (Some(max_fee), Some(max_priority_fee)) = (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+                if max_fee < max_priority_fee {
+                    return Err(IngestError::InvalidBlockData(format!(
+                        "Transaction {} max fee per gas {} is below max priority fee per gas {}",
+                        tx.hash, max_fee, max_priority_fee
+                    )));
+                }
+            }
+        }
+
         // Check nonce
         if tx.nonce == 0 && tx.value > U256::ZERO {
             return Err(IngestError::InvalidBlockData(
@@ -280,7 +547,56 @@ impl BatchValidator {
 mod tests {
     use super::*;
     use cdk_types::{Batch, BatchId, BlockInBatch, ProofMetadata};
-    use alloy_primitives::U256;
+    use alloy_primitives::{FixedBytes, U256};
+
+    fn linked_blocks(first_number: u64, count: u64, first_parent: FixedBytes<32>) -> Vec<BlockInBatch> {
+        let mut parent_hash = first_parent;
+        (0..count)
+            .map(|i| {
+                let hash = FixedBytes::from([(first_number + i) as u8; 32]);
+                let block = BlockInBatch::new(
+                    i as u32,
+                    hash,
+                    U256::from(first_number + i),
+                    parent_hash,
+                    FixedBytes::from([3u8; 32]),
+                    FixedBytes::from([4u8; 32]),
+                    FixedBytes::from([5u8; 32]),
+                    1234567890 + i,
+                );
+                parent_hash = hash;
+                block
+            })
+            .collect()
+    }
+
+    fn batch_with_blocks(blocks: Vec<BlockInBatch>) -> Batch {
+        Batch::new(
+            BatchId::new(U256::from(1), FixedBytes::from([1u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            blocks,
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
+
+    fn block_inputs_with_gas_limit(gas_limit: u64) -> BlockInputs {
+        BlockInputs {
+            number: 100,
+            hash: FixedBytes::from([1u8; 32]),
+            parent_hash: FixedBytes::from([2u8; 32]),
+            state_root: FixedBytes::from([3u8; 32]),
+            receipts_root: FixedBytes::from([4u8; 32]),
+            transactions_root: FixedBytes::from([5u8; 32]),
+            timestamp: 1234567890,
+            gas_limit,
+            gas_used: 0,
+            base_fee_per_gas: Some(1000000000),
+            extra_data: alloy_primitives::Bytes::new(),
+            transactions: vec![],
+        }
+    }
 
     #[tokio::test]
     async fn test_batch_validator_default() {
@@ -341,6 +657,82 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_large_batch_reports_lowest_invalid_block() {
+        let mut blocks = linked_blocks(1, 200, FixedBytes::from([9u8; 32]));
+        blocks[120].state_root = FixedBytes::ZERO;
+        blocks[180].timestamp = 0;
+        let batch = batch_with_blocks(blocks);
+
+        let sequential = BatchValidator::default().with_parallel_threshold(usize::MAX);
+        for validator in [BatchValidator::default(), sequential] {
+            match validator.validate_batch(&batch).await {
+                Err(IngestError::InvalidBatchData(message)) => {
+                    assert_eq!(message, "Block 120: State root cannot be zero");
+                }
+                other => panic!("expected an invalid block, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_future_timestamp_rejected_in_strict_mode() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let mut blocks = linked_blocks(1, 3, FixedBytes::from([9u8; 32]));
+        blocks[2].timestamp = now + 3600;
+        let batch = batch_with_blocks(blocks);
+
+        match BatchValidator::default().validate_batch(&batch).await {
+            Err(IngestError::InvalidBatchData(message)) => {
+                assert!(message.starts_with(&format!("Block 2: Block timestamp {} is more", now + 3600)), "{}", message);
+                assert!(message.contains("ahead of local time"), "{}", message);
+            }
+            other => panic!("expected a future timestamp rejection, got {:?}", other),
+        }
+
+        let tolerant = BatchValidator::default().with_max_future_drift(Duration::from_secs(2 * 3600));
+        assert!(tolerant.validate_batch(&batch).await.is_ok());
+        let lenient = BatchValidator::new(1000, 10 * 1024 * 1024, false);
+        assert!(lenient.validate_batch(&batch).await.is_ok());
+    }
+
+    #[test]
+    fn test_validation_report_collects_every_violation() {
+        let mut blocks = linked_blocks(1, 4, FixedBytes::from([9u8; 32]));
+        blocks[1].state_root = FixedBytes::ZERO;
+        blocks[2].parent_hash = FixedBytes::from([7u8; 32]);
+        blocks[3].timestamp = 1;
+        let mut batch = batch_with_blocks(blocks);
+        batch.l1_origin = U256::ZERO;
+
+        let report = BatchValidator::default().validate_batch_report(&batch);
+        let found: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.block_index, issue.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (ValidationSeverity::Error, None, "L1 origin cannot be zero"),
+                (ValidationSeverity::Error, Some(1), "State root cannot be zero"),
+                (ValidationSeverity::Error, Some(3), "Block timestamps not in order: 1 < 1234567892"),
+                (
+                    ValidationSeverity::Error,
+                    Some(2),
+                    "Block 3 parent hash 0x0707070707070707070707070707070707070707070707070707070707070707 \
+                     does not match previous block hash 0x0202020202020202020202020202020202020202020202020202020202020202",
+                ),
+            ]
+        );
+        assert!(!report.is_valid());
+
+        // Outside strict mode ordering and linkage problems are only warnings
+        let report = BatchValidator::new(1000, 10 * 1024 * 1024, false).validate_batch_report(&batch);
+        assert_eq!(report.errors().count(), 2);
+        assert_eq!(report.warnings().count(), 2);
+    }
+
     #[tokio::test]
     async fn test_block_inputs_validation() {
         let validator = BatchValidator::default();
@@ -385,4 +777,106 @@ mod tests {
         let result = validator.validate_block_inputs(&block).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_parent_linkage_valid_chain() {
+        let validator = BatchValidator::default();
+        let parent = FixedBytes::from([9u8; 32]);
+        let batch = batch_with_blocks(linked_blocks(10, 3, parent));
+
+        validator.validate_batch(&batch).await.unwrap();
+        validator.validate_batch_with_parent(&batch, Some(parent)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parent_linkage_broken_link() {
+        let validator = BatchValidator::default();
+        let mut blocks = linked_blocks(10, 3, FixedBytes::from([9u8; 32]));
+        blocks[2].parent_hash = FixedBytes::from([0xaau8; 32]);
+        let batch = batch_with_blocks(blocks);
+
+        let result = validator.validate_batch(&batch).await;
+        assert!(matches!(result, Err(IngestError::InvalidBatchData(_))));
+    }
+
+    #[tokio::test]
+    async fn test_parent_linkage_across_batches() {
+        let validator = BatchValidator::default();
+        let batch = batch_with_blocks(linked_blocks(10, 2, FixedBytes::from([9u8; 32])));
+
+        let result = validator
+            .validate_batch_with_parent(&batch, Some(FixedBytes::from([8u8; 32])))
+            .await;
+        assert!(matches!(result, Err(IngestError::InvalidBatchData(_))));
+
+        // Linkage is only enforced in strict mode
+        let lenient = BatchValidator::new(1000, 10 * 1024 * 1024, false);
+        lenient
+            .validate_batch_with_parent(&batch, Some(FixedBytes::from([8u8; 32])))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_block_inputs_gas_limit_below_min() {
+        let validator = BatchValidator::default().with_gas_limit_bounds(1_000_000, 100_000_000);
+
+        validator.validate_block_inputs(&block_inputs_with_gas_limit(1_000_000)).await.unwrap();
+        let result = validator.validate_block_inputs(&block_inputs_with_gas_limit(999_999)).await;
+        assert!(matches!(result, Err(IngestError::InvalidBlockData(msg)) if msg.contains("outside allowed range")));
+    }
+
+    #[tokio::test]
+    async fn test_default_gas_limit_has_no_lower_bound() {
+        let validator = BatchValidator::default();
+        assert_eq!(validator.min_gas_limit, 0);
+
+        validator.validate_block_inputs(&block_inputs_with_gas_limit(1)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_block_inputs_gas_limit_above_max() {
+        let validator = BatchValidator::default().with_gas_limit_bounds(1_000_000, 100_000_000);
+
+        validator.validate_block_inputs(&block_inputs_with_gas_limit(100_000_000)).await.unwrap();
+        let result = validator.validate_block_inputs(&block_inputs_with_gas_limit(100_000_001)).await;
+        assert!(matches!(result, Err(IngestError::InvalidBlockData(msg)) if msg.contains("outside allowed range")));
+    }
+
+    #[tokio::test]
+    async fn test_block_inputs_zero_base_fee() {
+        let mut block = block_inputs_with_gas_limit(30_000_000);
+        block.base_fee_per_gas = Some(0);
+
+        let result = BatchValidator::default().validate_block_inputs(&block).await;
+        assert!(matches!(result, Err(IngestError::InvalidBlockData(msg)) if msg.contains("base fee")));
+
+        // Only enforced in strict mode
+        let lenient = BatchValidator::new(1000, 10 * 1024 * 1024, false);
+        lenient.validate_block_inputs(&block).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_block_inputs_inverted_fee_cap() {
+        let mut block = block_inputs_with_gas_limit(30_000_000);
+        block.transactions.push(crate::TransactionInput {
+            hash: FixedBytes::from([6u8; 32]),
+            tx_type: 2,
+            gas_limit: 21000,
+            gas_price: None,
+            max_fee_per_gas: Some(1_000_000_000),
+            max_priority_fee_per_gas: Some(2_000_000_000),
+            nonce: 1,
+            value: U256::from(1),
+            to: Some(alloy_primitives::Address::ZERO),
+            data: alloy_primitives::Bytes::new(),
+            access_list: vec![],
+        });
+
+        let result = BatchValidator::default().validate_block_inputs(&block).await;
+        assert!(matches!(result, Err(IngestError::InvalidBlockData(msg)) if msg.contains("priority fee")));
+
+        block.transactions[0].max_fee_per_gas = Some(2_000_000_000);
+        BatchValidator::default().validate_block_inputs(&block).await.unwrap();
+    }
 }
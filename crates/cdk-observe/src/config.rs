@@ -1,8 +1,12 @@
 //! Configuration for CDK observability
 
+use crate::{ObservabilityError, ObservabilityResult};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
+/// Log levels accepted in `ObservabilityConfig::log_level`
+pub const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
 /// Configuration for CDK observability features
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObservabilityConfig {
@@ -68,7 +72,7 @@ impl Default for ObservabilityConfig {
             log_level: "info".to_string(),
             log_format: LogFormat::Pretty,
             enable_metrics: true,
-            metrics_address: "127.0.0.1:9000".parse().unwrap(),
+            metrics_address: SocketAddr::from(([127, 0, 0, 1], 9000)),
             enable_tracing: true,
             batch_metrics: BatchMetricsConfig::default(),
             finality_metrics: FinalityMetricsConfig::default(),
@@ -104,6 +108,38 @@ impl ObservabilityConfig {
         Self::default()
     }
 
+    /// Create a validated configuration from user-supplied values
+    pub fn from_parts(log_level: &str, log_format: LogFormat, metrics_address: &str) -> ObservabilityResult<Self> {
+        let metrics_address = metrics_address.parse().map_err(|e| {
+            ObservabilityError::ConfigError(format!("Invalid metrics address '{}': {}", metrics_address, e))
+        })?;
+
+        let config = Self::default()
+            .with_logging(log_level, log_format)
+            .with_metrics(metrics_address);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the configuration is usable
+    pub fn validate(&self) -> ObservabilityResult<()> {
+        if !LOG_LEVELS.contains(&self.log_level.as_str()) {
+            return Err(ObservabilityError::ConfigError(format!(
+                "Unknown log level '{}', expected one of {:?}",
+                self.log_level, LOG_LEVELS
+            )));
+        }
+
+        if self.enable_metrics && self.metrics_address.port() == 0 {
+            return Err(ObservabilityError::ConfigError(format!(
+                "Metrics address {} must specify a port",
+                self.metrics_address
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Enable logging with specific level and format
     pub fn with_logging(mut self, level: &str, format: LogFormat) -> Self {
         self.enable_logging = true;
@@ -165,6 +201,37 @@ mod tests {
         assert!(config.enable_tracing);
     }
 
+    #[test]
+    fn test_from_parts_valid() {
+        let config = ObservabilityConfig::from_parts("debug", LogFormat::Json, "0.0.0.0:9100").unwrap();
+        assert_eq!(config.log_level, "debug");
+        assert_eq!(config.metrics_address.port(), 9100);
+        assert!(ObservabilityConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_from_parts_invalid() {
+        assert!(matches!(
+            ObservabilityConfig::from_parts("verbose", LogFormat::Json, "127.0.0.1:9000"),
+            Err(ObservabilityError::ConfigError(_))
+        ));
+        assert!(matches!(
+            ObservabilityConfig::from_parts("info", LogFormat::Json, "not-an-address"),
+            Err(ObservabilityError::ConfigError(_))
+        ));
+        assert!(matches!(
+            ObservabilityConfig::from_parts("info", LogFormat::Json, "127.0.0.1:0"),
+            Err(ObservabilityError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_address_fails_to_deserialize() {
+        let mut value = serde_json::to_value(ObservabilityConfig::default()).unwrap();
+        value["metrics_address"] = serde_json::json!("127.0.0.1");
+        assert!(serde_json::from_value::<ObservabilityConfig>(value).is_err());
+    }
+
     #[test]
     fn test_serialization() {
         let config = ObservabilityConfig::default();
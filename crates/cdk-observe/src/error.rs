@@ -8,6 +8,12 @@ pub enum ObservabilityError {
     #[error("Metrics error: {0}")]
     MetricsError(String),
 
+    #[error("Metric registration error: {0}")]
+    Registration(String),
+
+    #[error("Metric creation error: {0}")]
+    MetricCreation(String),
+
     #[error("Tracing error: {0}")]
     TracingError(String),
 
@@ -27,9 +33,15 @@ pub enum ObservabilityError {
 /// Result type for observability operations
 pub type ObservabilityResult<T> = Result<T, ObservabilityError>;
 
+/// Registry conflicts become `Registration`, anything else `MetricCreation`
 impl From<prometheus::Error> for ObservabilityError {
     fn from(err: prometheus::Error) -> Self {
-        ObservabilityError::MetricsError(err.to_string())
+        match err {
+            prometheus::Error::AlreadyReg | prometheus::Error::InconsistentCardinality { .. } => {
+                ObservabilityError::Registration(err.to_string())
+            }
+            _ => ObservabilityError::MetricCreation(err.to_string()),
+        }
     }
 }
 
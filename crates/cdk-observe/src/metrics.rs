@@ -1,10 +1,19 @@
 //! Prometheus metrics for CDK observability
 
 use alloy_primitives::U256;
-use metrics::{Counter, Gauge, Histogram};
-use metrics_exporter_prometheus::PrometheusBuilder;
-use std::net::SocketAddr;
-use tracing::info;
+use http_body_util::Full;
+use hyper::{body::Bytes, header::CONTENT_TYPE, server::conn::http1, service::service_fn, Response};
+use hyper_util::rt::TokioIo;
+use metrics::{counter, gauge, histogram, Counter, Gauge, Histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle, PrometheusRecorder};
+use prometheus::{Registry, TextEncoder};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tracing::{info, warn};
 
 /// CDK metrics collector
 pub struct CdkMetrics {
@@ -13,6 +22,7 @@ pub struct CdkMetrics {
     pub epoch_height: Gauge,
     pub ingest_tps: Gauge,
     pub batch_processing_time: Histogram,
+    pub batches_processed: Counter,
     
     // Finality metrics
     pub l1_lag: Gauge,
@@ -34,19 +44,24 @@ impl Default for CdkMetrics {
 
 impl CdkMetrics {
     /// Create a new metrics collector
+    ///
+    /// Metrics are registered with the currently installed recorder, so the
+    /// Prometheus recorder must be installed (see `MetricsServer::start`)
+    /// before this is called; otherwise every metric is a no-op.
     pub fn new() -> Self {
         Self {
-            batch_height: Gauge::noop(),
-            epoch_height: Gauge::noop(),
-            ingest_tps: Gauge::noop(),
-            batch_processing_time: Histogram::noop(),
-            l1_lag: Gauge::noop(),
-            reorg_count: Counter::noop(),
-            finality_status: Gauge::noop(),
-            rollback_count: Counter::noop(),
-            active_connections: Gauge::noop(),
-            error_count: Counter::noop(),
-            warning_count: Counter::noop(),
+            batch_height: gauge!("cdk_batch_height"),
+            epoch_height: gauge!("cdk_epoch_height"),
+            ingest_tps: gauge!("cdk_ingest_tps"),
+            batch_processing_time: histogram!("cdk_batch_processing_seconds"),
+            batches_processed: counter!("cdk_batches_processed_total"),
+            l1_lag: gauge!("cdk_l1_lag_blocks"),
+            reorg_count: counter!("cdk_reorg_total"),
+            finality_status: gauge!("cdk_finality_status"),
+            rollback_count: counter!("cdk_rollback_total"),
+            active_connections: gauge!("cdk_active_connections"),
+            error_count: counter!("cdk_errors_total"),
+            warning_count: counter!("cdk_warnings_total"),
         }
     }
 
@@ -76,6 +91,11 @@ impl CdkMetrics {
         self.batch_processing_time.record(duration_secs);
     }
 
+    /// Increment processed batch counter
+    pub fn increment_batches_processed(&self) {
+        self.batches_processed.increment(1);
+    }
+
     /// Update L1 lag metric
     pub fn update_l1_lag(&self, lag_blocks: u64) {
         self.l1_lag.set(lag_blocks as f64);
@@ -112,32 +132,235 @@ impl CdkMetrics {
     }
 }
 
+/// Transactions per second over a sliding time window
+///
+/// Until a full window has passed, the rate is taken over the time since the
+/// tracker was created.
+#[derive(Debug, Clone)]
+pub struct RollingTps {
+    window: Duration,
+    started: Instant,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl RollingTps {
+    /// Create a tracker averaging over `window`
+    pub fn new(window: Duration) -> Self {
+        Self::starting_at(window, Instant::now())
+    }
+
+    /// Create a tracker averaging over `window`, starting at `started`
+    pub fn starting_at(window: Duration, started: Instant) -> Self {
+        Self {
+            window,
+            started,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record `tx_count` transactions processed at `now`, returning the updated rate
+    pub fn record(&mut self, tx_count: u64, now: Instant) -> f64 {
+        self.samples.push_back((now, tx_count));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > self.window)
+        {
+            self.samples.pop_front();
+        }
+        self.tps(now)
+    }
+
+    /// Transactions per second over the window ending at `now`
+    pub fn tps(&self, now: Instant) -> f64 {
+        let span = now.saturating_duration_since(self.started).min(self.window);
+        if span.is_zero() {
+            return 0.0;
+        }
+        let total: u64 = self
+            .samples
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) <= self.window)
+            .map(|(_, count)| count)
+            .sum();
+        total as f64 / span.as_secs_f64()
+    }
+}
+
+/// Interval between upkeep runs draining the recorder's histogram data
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
 /// Metrics server for Prometheus
+///
+/// Serves the metrics recorded through the `metrics` facade (`CdkMetrics`)
+/// and, when one is set, those of a `prometheus` registry (`PerformanceMetrics`)
+/// from the same endpoint.
 pub struct MetricsServer {
     address: SocketAddr,
+    registry: Option<Registry>,
+}
+
+/// Handle to a running metrics server
+#[derive(Debug)]
+pub struct RunningMetricsServer {
+    address: SocketAddr,
+    handle: PrometheusHandle,
+    registry: Option<Registry>,
+    task: JoinHandle<()>,
+}
+
+impl RunningMetricsServer {
+    /// Address the scrape endpoint listens on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Handle for rendering the exported metrics directly
+    pub fn handle(&self) -> &PrometheusHandle {
+        &self.handle
+    }
+
+    /// Render every served metric in the Prometheus text format, as a scrape would
+    pub fn gather(&self) -> String {
+        render_metrics(&self.handle, self.registry.as_ref())
+    }
+
+    /// Stop serving the scrape endpoint
+    pub fn shutdown(self) {
+        self.task.abort();
+        info!("Shutting down metrics server");
+    }
 }
 
 impl MetricsServer {
     /// Create a new metrics server
     pub fn new(address: SocketAddr) -> Self {
-        Self { address }
+        Self { address, registry: None }
     }
 
-    /// Start the metrics server
+    /// Also serve the metrics of `registry`, e.g. the one `PerformanceMetrics` is registered into
+    pub fn with_registry(mut self, registry: Registry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Install the Prometheus recorder globally and serve `http://{address}/metrics`
+    ///
+    /// Must run before any `CdkMetrics` is created for its values to be exported.
+    pub fn spawn(&self) -> Result<RunningMetricsServer, Box<dyn std::error::Error + Send + Sync>> {
+        let (recorder, server) = self.spawn_with_recorder()?;
+        if let Err(e) = metrics::set_global_recorder(recorder) {
+            server.shutdown();
+            return Err(e.into());
+        }
+        Ok(server)
+    }
+
+    /// Build a Prometheus recorder and serve its metrics without installing it
+    ///
+    /// The caller decides how to install the recorder (`metrics::set_global_recorder`
+    /// or `metrics::with_local_recorder`), so several servers can run in one
+    /// process; `CdkMetrics` must be created under it for its values to be exported.
+    pub fn spawn_with_recorder(
+        &self,
+    ) -> Result<(PrometheusRecorder, RunningMetricsServer), Box<dyn std::error::Error + Send + Sync>> {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let server = self.serve(recorder.handle())?;
+        Ok((recorder, server))
+    }
+
+    /// Start the metrics server and run it until ctrl-c
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let builder = PrometheusBuilder::new();
-        
-        // Install the metrics recorder
-        builder.install_recorder()?;
-        
-        info!("Metrics server started on {}", self.address);
-        
+        self.start_with_shutdown(async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                warn!("Failed to listen for ctrl-c: {}", e);
+            }
+        })
+        .await
+    }
+
+    /// Start the metrics server and run it until `shutdown` completes
+    ///
+    /// Like `spawn`, this installs the Prometheus recorder globally.
+    pub async fn start_with_shutdown(
+        &self,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let server = self.spawn()?;
+
         // Keep the server running
-        tokio::signal::ctrl_c().await?;
-        info!("Shutting down metrics server");
-        
+        shutdown.await;
+        server.shutdown();
+
         Ok(())
     }
+
+    /// Spawn the HTTP exporter rendering `handle`
+    fn serve(&self, handle: PrometheusHandle) -> Result<RunningMetricsServer, Box<dyn std::error::Error + Send + Sync>> {
+        let listener = std::net::TcpListener::bind(self.address)?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        let address = listener.local_addr()?;
+
+        let task = tokio::spawn(serve_metrics(listener, handle.clone(), self.registry.clone()));
+        info!("Metrics server started on {}", address);
+
+        Ok(RunningMetricsServer {
+            address,
+            handle,
+            registry: self.registry.clone(),
+            task,
+        })
+    }
+}
+
+/// Render the recorder's metrics followed by the registry's
+fn render_metrics(handle: &PrometheusHandle, registry: Option<&Registry>) -> String {
+    let mut rendered = handle.render();
+    if let Some(registry) = registry {
+        match TextEncoder::new().encode_to_string(&registry.gather()) {
+            Ok(text) => rendered.push_str(&text),
+            Err(e) => warn!("Failed to encode prometheus registry: {}", e),
+        }
+    }
+    rendered
+}
+
+/// Answer every request on `listener` with the rendered metrics, running recorder upkeep in between
+async fn serve_metrics(listener: TcpListener, handle: PrometheusHandle, registry: Option<Registry>) {
+    let mut upkeep = tokio::time::interval(UPKEEP_INTERVAL);
+    loop {
+        let stream = tokio::select! {
+            _ = upkeep.tick() => {
+                handle.run_upkeep();
+                continue;
+            }
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Failed to accept metrics connection: {}", e);
+                    continue;
+                }
+            },
+        };
+
+        let handle = handle.clone();
+        let registry = registry.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |_request| {
+                let body = render_metrics(&handle, registry.as_ref());
+                async move {
+                    Response::builder().header(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE).body(Full::new(Bytes::from(body)))
+                }
+            });
+            if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+                warn!("Metrics connection failed: {}", e);
+            }
+        });
+    }
 }
 
 #[cfg(test)]
@@ -162,6 +385,90 @@ mod tests {
         metrics.increment_warning_count();
     }
 
+    #[test]
+    fn test_metrics_exported_through_recorder() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let metrics = metrics::with_local_recorder(&recorder, CdkMetrics::new);
+
+        metrics.update_batch_height(U256::from(42));
+        metrics.increment_reorg_count();
+
+        let rendered = handle.render();
+        assert!(rendered.contains("cdk_batch_height 42"));
+        assert!(rendered.contains("cdk_reorg_total 1"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server_serves_scrape_endpoint() {
+        // Reserve an ephemeral port, then hand it to the exporter
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let (recorder, server) = MetricsServer::new(address).spawn_with_recorder().unwrap();
+        let metrics = metrics::with_local_recorder(&recorder, CdkMetrics::new);
+        metrics.update_l1_lag(7);
+
+        let response = reqwest::get(format!("http://{}/metrics", server.address())).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let content_type = response.headers()["content-type"].to_str().unwrap().to_string();
+        assert!(content_type.starts_with("text/plain"));
+        assert!(response.text().await.unwrap().contains("cdk_l1_lag_blocks 7"));
+
+        server.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server_serves_performance_metrics() {
+        let registry = Registry::new();
+        let performance = crate::PerformanceMetrics::new(&registry, crate::HistogramBuckets::default()).unwrap();
+        performance.update_head_block(U256::from(1000));
+
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let (recorder, server) = MetricsServer::new(address).with_registry(registry).spawn_with_recorder().unwrap();
+        let metrics = metrics::with_local_recorder(&recorder, CdkMetrics::new);
+        metrics.update_batch_height(U256::from(42));
+
+        let body = reqwest::get(format!("http://{}/metrics", server.address())).await.unwrap().text().await.unwrap();
+        assert!(body.contains("cdk_batch_height 42"));
+        assert!(body.contains("cdk_head_block 1000"));
+        let gathered = server.gather();
+        assert!(gathered.contains("cdk_batch_height 42") && gathered.contains("cdk_head_block 1000"));
+
+        server.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server_graceful_shutdown() {
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let task = tokio::spawn(async move {
+            MetricsServer::new(address)
+                .start_with_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await
+        });
+
+        shutdown_tx.send(()).unwrap();
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), task)
+            .await
+            .expect("metrics server did not shut down")
+            .unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_rolling_tps_drops_old_samples() {
+        let start = Instant::now();
+        let mut tps = RollingTps::starting_at(Duration::from_secs(10), start);
+
+        assert_eq!(tps.record(50, start + Duration::from_secs(5)), 10.0);
+        assert_eq!(tps.record(50, start + Duration::from_secs(10)), 10.0);
+        // The first sample falls out of the window
+        assert_eq!(tps.record(100, start + Duration::from_secs(16)), 15.0);
+        assert_eq!(tps.tps(start + Duration::from_secs(30)), 0.0);
+    }
+
     #[test]
     fn test_metrics_server_creation() {
         let address: SocketAddr = "127.0.0.1:9000".parse().unwrap();
@@ -1,14 +1,49 @@
 //! Performance monitoring and caching for CDK
 
-use crate::{ObservabilityError, ObservabilityResult};
+use crate::ObservabilityResult;
 use alloy_primitives::U256;
+use async_trait::async_trait;
 use cdk_types::{Batch, Epoch, FinalityTag};
-use moka::future::Cache;
+use moka::{future::Cache, notification::RemovalCause};
 use prometheus::{Counter, Histogram, Gauge, Registry, Opts, HistogramOpts};
 use rayon::prelude::*;
-use std::time::{Duration, Instant};
+use std::{
+    future::Future,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use tracing::{debug, info};
 
+/// Histogram bucket boundaries, in seconds, for each duration metric
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBuckets {
+    /// Buckets for `cdk_batch_import_duration_seconds`
+    pub batch_import_duration: Vec<f64>,
+    /// Buckets for `cdk_epoch_processing_duration_seconds`
+    pub epoch_processing_duration: Vec<f64>,
+    /// Buckets for `cdk_finality_check_duration_seconds`
+    pub finality_check_duration: Vec<f64>,
+}
+
+impl Default for HistogramBuckets {
+    fn default() -> Self {
+        Self {
+            // Batch imports are typically sub-millisecond to a few hundred milliseconds
+            batch_import_duration: vec![
+                0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+            ],
+            // Epoch processing spans many batches and can take seconds
+            epoch_processing_duration: vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0],
+            // Finality checks are dominated by L1 RPC latency
+            finality_check_duration: vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+        }
+    }
+}
+
 /// Performance metrics for CDK operations
 pub struct PerformanceMetrics {
     /// Batch import counter
@@ -35,39 +70,35 @@ pub struct PerformanceMetrics {
 
 impl PerformanceMetrics {
     /// Create new performance metrics
-    pub fn new(registry: &Registry) -> ObservabilityResult<Self> {
-        let batches_imported = Counter::with_opts(Opts::new("cdk_batches_imported", "Total number of batches imported"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create counter: {}", e)))?;
+    pub fn new(registry: &Registry, buckets: HistogramBuckets) -> ObservabilityResult<Self> {
+        let batches_imported = Counter::with_opts(Opts::new("cdk_batches_imported", "Total number of batches imported"))?;
         
         let batch_import_duration = Histogram::with_opts(
             HistogramOpts::new("cdk_batch_import_duration_seconds", "Duration of batch import operations")
-        ).map_err(|e| ObservabilityError::MetricsError(format!("Failed to create histogram: {}", e)))?;
+                .buckets(buckets.batch_import_duration)
+        )?;
         
-        let epochs_processed = Counter::with_opts(Opts::new("cdk_epochs_processed", "Total number of epochs processed"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create counter: {}", e)))?;
+        let epochs_processed = Counter::with_opts(Opts::new("cdk_epochs_processed", "Total number of epochs processed"))?;
         
         let epoch_processing_duration = Histogram::with_opts(
             HistogramOpts::new("cdk_epoch_processing_duration_seconds", "Duration of epoch processing operations")
-        ).map_err(|e| ObservabilityError::MetricsError(format!("Failed to create histogram: {}", e)))?;
+                .buckets(buckets.epoch_processing_duration)
+        )?;
         
-        let finality_checks = Counter::with_opts(Opts::new("cdk_finality_checks", "Total number of finality checks"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create counter: {}", e)))?;
+        let finality_checks = Counter::with_opts(Opts::new("cdk_finality_checks", "Total number of finality checks"))?;
         
         let finality_check_duration = Histogram::with_opts(
             HistogramOpts::new("cdk_finality_check_duration_seconds", "Duration of finality check operations")
-        ).map_err(|e| ObservabilityError::MetricsError(format!("Failed to create histogram: {}", e)))?;
+                .buckets(buckets.finality_check_duration)
+        )?;
         
-        let head_block = Gauge::with_opts(Opts::new("cdk_head_block", "Current head block number"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create gauge: {}", e)))?;
+        let head_block = Gauge::with_opts(Opts::new("cdk_head_block", "Current head block number"))?;
         
-        let finalized_block = Gauge::with_opts(Opts::new("cdk_finalized_block", "Current finalized block number"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create gauge: {}", e)))?;
+        let finalized_block = Gauge::with_opts(Opts::new("cdk_finalized_block", "Current finalized block number"))?;
         
-        let cache_hit_rate = Gauge::with_opts(Opts::new("cdk_cache_hit_rate", "Cache hit rate percentage"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create gauge: {}", e)))?;
+        let cache_hit_rate = Gauge::with_opts(Opts::new("cdk_cache_hit_rate", "Cache hit rate percentage"))?;
         
-        let memory_usage = Gauge::with_opts(Opts::new("cdk_memory_usage_bytes", "Memory usage in bytes"))
-            .map_err(|e| ObservabilityError::MetricsError(format!("Failed to create gauge: {}", e)))?;
+        let memory_usage = Gauge::with_opts(Opts::new("cdk_memory_usage_bytes", "Memory usage in bytes"))?;
 
         // Register metrics
         registry.register(Box::new(batches_imported.clone()))?;
@@ -134,6 +165,23 @@ impl PerformanceMetrics {
     }
 }
 
+/// Storage the caches are warmed up from
+#[async_trait]
+pub trait CacheWarmupSource: Send + Sync {
+    /// Load the batches numbered within `batches`, in ascending order
+    ///
+    /// Sources without batch bodies keep the default, which preloads no batches.
+    async fn load_batches(&self, _batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<Batch>> {
+        Ok(Vec::new())
+    }
+
+    /// Load the last `limit` epochs holding batches within `batches`, in ascending order
+    async fn load_epochs(&self, batches: RangeInclusive<u64>, limit: usize) -> ObservabilityResult<Vec<Epoch>>;
+
+    /// Load the finality tags of batches within `batches`, in ascending order
+    async fn load_finality_tags(&self, batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<FinalityTag>>;
+}
+
 /// Cache for CDK data
 pub struct CdkCache {
     /// Batch cache
@@ -142,8 +190,8 @@ pub struct CdkCache {
     epoch_cache: Cache<u64, Epoch>,
     /// Finality tag cache
     finality_cache: Cache<u64, FinalityTag>,
-    /// Cache statistics
-    stats: CacheStats,
+    /// Cache statistics, shared with the eviction listeners
+    stats: Arc<AtomicCacheStats>,
 }
 
 /// Cache statistics
@@ -155,6 +203,40 @@ pub struct CacheStats {
     pub evictions: u64,
 }
 
+/// Cache statistics counters, updated without locking
+#[derive(Debug, Default)]
+struct AtomicCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl AtomicCacheStats {
+    /// Read the current counters
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset all counters to zero
+    fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.inserts.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Increment a statistics counter
+fn bump(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
 impl CacheStats {
     /// Get hit rate
     pub fn hit_rate(&self) -> f64 {
@@ -175,39 +257,53 @@ impl CdkCache {
         finality_capacity: u64,
         ttl: Duration,
     ) -> Self {
+        let stats = Arc::new(AtomicCacheStats::default());
+
         let batch_cache = Cache::builder()
             .max_capacity(batch_capacity)
             .time_to_live(ttl)
+            .eviction_listener(Self::eviction_listener(stats.clone()))
             .build();
 
         let epoch_cache = Cache::builder()
             .max_capacity(epoch_capacity)
             .time_to_live(ttl)
+            .eviction_listener(Self::eviction_listener(stats.clone()))
             .build();
 
         let finality_cache = Cache::builder()
             .max_capacity(finality_capacity)
             .time_to_live(ttl)
+            .eviction_listener(Self::eviction_listener(stats.clone()))
             .build();
 
         Self {
             batch_cache,
             epoch_cache,
             finality_cache,
-            stats: CacheStats::default(),
+            stats,
+        }
+    }
+
+    /// Listener counting entries removed due to capacity or TTL expiry
+    fn eviction_listener<K, V>(stats: Arc<AtomicCacheStats>) -> impl Fn(Arc<K>, V, RemovalCause) + Send + Sync + 'static {
+        move |_key, _value, cause| {
+            if cause.was_evicted() {
+                bump(&stats.evictions);
+            }
         }
     }
 
     /// Get batch from cache
-    pub async fn get_batch(&mut self, batch_id: u64) -> Option<Batch> {
+    pub async fn get_batch(&self, batch_id: u64) -> Option<Batch> {
         match self.batch_cache.get(&batch_id).await {
             Some(batch) => {
-                self.stats.hits += 1;
+                bump(&self.stats.hits);
                 debug!("Cache hit for batch {}", batch_id);
                 Some(batch)
             }
             None => {
-                self.stats.misses += 1;
+                bump(&self.stats.misses);
                 debug!("Cache miss for batch {}", batch_id);
                 None
             }
@@ -215,22 +311,22 @@ impl CdkCache {
     }
 
     /// Insert batch into cache
-    pub async fn insert_batch(&mut self, batch_id: u64, batch: Batch) {
+    pub async fn insert_batch(&self, batch_id: u64, batch: Batch) {
         self.batch_cache.insert(batch_id, batch).await;
-        self.stats.inserts += 1;
+        bump(&self.stats.inserts);
         debug!("Inserted batch {} into cache", batch_id);
     }
 
     /// Get epoch from cache
-    pub async fn get_epoch(&mut self, epoch_id: u64) -> Option<Epoch> {
+    pub async fn get_epoch(&self, epoch_id: u64) -> Option<Epoch> {
         match self.epoch_cache.get(&epoch_id).await {
             Some(epoch) => {
-                self.stats.hits += 1;
+                bump(&self.stats.hits);
                 debug!("Cache hit for epoch {}", epoch_id);
                 Some(epoch)
             }
             None => {
-                self.stats.misses += 1;
+                bump(&self.stats.misses);
                 debug!("Cache miss for epoch {}", epoch_id);
                 None
             }
@@ -238,22 +334,22 @@ impl CdkCache {
     }
 
     /// Insert epoch into cache
-    pub async fn insert_epoch(&mut self, epoch_id: u64, epoch: Epoch) {
+    pub async fn insert_epoch(&self, epoch_id: u64, epoch: Epoch) {
         self.epoch_cache.insert(epoch_id, epoch).await;
-        self.stats.inserts += 1;
+        bump(&self.stats.inserts);
         debug!("Inserted epoch {} into cache", epoch_id);
     }
 
     /// Get finality tag from cache
-    pub async fn get_finality_tag(&mut self, batch_id: u64) -> Option<FinalityTag> {
+    pub async fn get_finality_tag(&self, batch_id: u64) -> Option<FinalityTag> {
         match self.finality_cache.get(&batch_id).await {
             Some(tag) => {
-                self.stats.hits += 1;
+                bump(&self.stats.hits);
                 debug!("Cache hit for finality tag {}", batch_id);
                 Some(tag)
             }
             None => {
-                self.stats.misses += 1;
+                bump(&self.stats.misses);
                 debug!("Cache miss for finality tag {}", batch_id);
                 None
             }
@@ -261,23 +357,129 @@ impl CdkCache {
     }
 
     /// Insert finality tag into cache
-    pub async fn insert_finality_tag(&mut self, batch_id: u64, tag: FinalityTag) {
+    pub async fn insert_finality_tag(&self, batch_id: u64, tag: FinalityTag) {
         self.finality_cache.insert(batch_id, tag).await;
-        self.stats.inserts += 1;
+        bump(&self.stats.inserts);
         debug!("Inserted finality tag {} into cache", batch_id);
     }
 
+    /// Get a batch from the cache, loading and inserting it on a miss
+    ///
+    /// Concurrent calls for the same batch run `loader` only once; the others
+    /// wait for its result and count as hits.
+    pub async fn get_or_insert_batch<F, Fut>(&self, batch_id: u64, loader: F) -> Batch
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Batch>,
+    {
+        self.get_or_insert_with(&self.batch_cache, "batch", batch_id, loader).await
+    }
+
+    /// Get an epoch from the cache, loading and inserting it on a miss
+    pub async fn get_or_insert_epoch<F, Fut>(&self, epoch_id: u64, loader: F) -> Epoch
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Epoch>,
+    {
+        self.get_or_insert_with(&self.epoch_cache, "epoch", epoch_id, loader).await
+    }
+
+    /// Get a finality tag from the cache, loading and inserting it on a miss
+    pub async fn get_or_insert_finality_tag<F, Fut>(&self, batch_id: u64, loader: F) -> FinalityTag
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = FinalityTag>,
+    {
+        self.get_or_insert_with(&self.finality_cache, "finality tag", batch_id, loader).await
+    }
+
+    /// Get an entry of `cache`, running `loader` at most once per key on a miss
+    async fn get_or_insert_with<V, F, Fut>(&self, cache: &Cache<u64, V>, kind: &str, id: u64, loader: F) -> V
+    where
+        V: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let mut loaded = false;
+        let value = cache
+            .get_with(id, async {
+                loaded = true;
+                loader().await
+            })
+            .await;
+
+        if loaded {
+            bump(&self.stats.misses);
+            bump(&self.stats.inserts);
+            debug!("Loaded {} {} into cache", kind, id);
+        } else {
+            bump(&self.stats.hits);
+            debug!("Cache hit for {} {}", kind, id);
+        }
+        value
+    }
+
+    /// Preload the caches with the entries of `source` for the batches in `batches`
+    ///
+    /// Only the most recent entries fitting in each cache are requested from
+    /// `source`, so earlier ones are not loaded just to be evicted. Returns
+    /// the number of batches, epochs and finality tags inserted.
+    pub async fn warmup(
+        &self,
+        source: &dyn CacheWarmupSource,
+        batches: RangeInclusive<u64>,
+    ) -> ObservabilityResult<(usize, usize, usize)> {
+        let (start, end) = (*batches.start(), *batches.end());
+        if start > end {
+            return Ok((0, 0, 0));
+        }
+
+        let loaded_batches = source.load_batches(recent_batches(start, end, &self.batch_cache)).await?;
+        let loaded_batches = most_recent(loaded_batches, &self.batch_cache);
+        let batch_count = loaded_batches.len();
+        for batch in loaded_batches {
+            self.insert_batch(batch.id.number.saturating_to(), batch).await;
+        }
+
+        let epochs = source.load_epochs(batches, capacity(&self.epoch_cache)).await?;
+        let epochs = most_recent(epochs, &self.epoch_cache);
+        let epoch_count = epochs.len();
+        for epoch in epochs {
+            self.insert_epoch(epoch.id.number.saturating_to(), epoch).await;
+        }
+
+        let tags = source.load_finality_tags(recent_batches(start, end, &self.finality_cache)).await?;
+        let tags = most_recent(tags, &self.finality_cache);
+        let tag_count = tags.len();
+        for tag in tags {
+            self.insert_finality_tag(tag.batch_id.saturating_to(), tag).await;
+        }
+
+        info!(
+            "Warmed up caches with {} batches, {} epochs and {} finality tags",
+            batch_count, epoch_count, tag_count
+        );
+        Ok((batch_count, epoch_count, tag_count))
+    }
+
     /// Get cache statistics
     pub fn get_stats(&self) -> CacheStats {
-        self.stats.clone()
+        self.stats.snapshot()
+    }
+
+    /// Run pending cache maintenance so evictions are reflected in the statistics
+    pub async fn run_pending_tasks(&self) {
+        self.batch_cache.run_pending_tasks().await;
+        self.epoch_cache.run_pending_tasks().await;
+        self.finality_cache.run_pending_tasks().await;
     }
 
     /// Clear all caches
-    pub async fn clear(&mut self) {
+    pub async fn clear(&self) {
         self.batch_cache.invalidate_all();
         self.epoch_cache.invalidate_all();
         self.finality_cache.invalidate_all();
-        self.stats = CacheStats::default();
+        self.stats.reset();
         info!("Cleared all caches");
     }
 
@@ -288,14 +490,65 @@ impl CdkCache {
         let finality_size = self.finality_cache.entry_count() as usize;
         (batch_size, epoch_size, finality_size)
     }
+
+    /// Estimate the memory held by all caches
+    ///
+    /// Each cache's entry count is multiplied by the serialized size of one
+    /// sampled entry from that cache.
+    pub async fn estimate_memory_usage(&self) -> u64 {
+        self.run_pending_tasks().await;
+        let (batch_size, epoch_size, finality_size) = self.get_sizes().await;
+
+        batch_size as u64 * sampled_entry_size(&self.batch_cache)
+            + epoch_size as u64 * sampled_entry_size(&self.epoch_cache)
+            + finality_size as u64 * sampled_entry_size(&self.finality_cache)
+    }
+}
+
+/// Maximum number of entries `cache` holds
+fn capacity<V>(cache: &Cache<u64, V>) -> usize
+where
+    V: Clone + Send + Sync + 'static,
+{
+    cache.policy().max_capacity().map_or(usize::MAX, |capacity| capacity.try_into().unwrap_or(usize::MAX))
+}
+
+/// The last batches of `start..=end` that fit in `cache`, one entry per batch
+fn recent_batches<V>(start: u64, end: u64, cache: &Cache<u64, V>) -> RangeInclusive<u64>
+where
+    V: Clone + Send + Sync + 'static,
+{
+    let capacity = u64::try_from(capacity(cache)).unwrap_or(u64::MAX);
+    end.saturating_sub(capacity.saturating_sub(1)).max(start)..=end
+}
+
+/// Keep the last entries of `entries` that fit in `cache`
+fn most_recent<V>(mut entries: Vec<V>, cache: &Cache<u64, V>) -> Vec<V>
+where
+    V: Clone + Send + Sync + 'static,
+{
+    entries.drain(..entries.len().saturating_sub(capacity(cache)));
+    entries
+}
+
+/// Serialized size in bytes of an arbitrary entry in the cache, or 0 if empty
+fn sampled_entry_size<V>(cache: &Cache<u64, V>) -> u64
+where
+    V: serde::Serialize + Clone + Send + Sync + 'static,
+{
+    cache
+        .iter()
+        .next()
+        .and_then(|(_, value)| serde_json::to_vec(&value).ok())
+        .map_or(0, |bytes| (std::mem::size_of::<u64>() + bytes.len()) as u64)
 }
 
 /// Performance monitor for CDK operations
 pub struct PerformanceMonitor {
     /// Performance metrics
     metrics: PerformanceMetrics,
-    /// CDK cache
-    cache: CdkCache,
+    /// CDK cache, shareable across tasks
+    cache: Arc<CdkCache>,
     /// Start time
     start_time: Instant,
 }
@@ -303,13 +556,13 @@ pub struct PerformanceMonitor {
 impl PerformanceMonitor {
     /// Create new performance monitor
     pub fn new(registry: &Registry) -> ObservabilityResult<Self> {
-        let metrics = PerformanceMetrics::new(registry)?;
-        let cache = CdkCache::new(
+        let metrics = PerformanceMetrics::new(registry, HistogramBuckets::default())?;
+        let cache = Arc::new(CdkCache::new(
             1000, // batch capacity
             100,  // epoch capacity
             1000, // finality capacity
             Duration::from_secs(3600), // 1 hour TTL
-        );
+        ));
 
         Ok(Self {
             metrics,
@@ -323,27 +576,18 @@ impl PerformanceMonitor {
         &self.metrics
     }
 
-    /// Get cache reference
-    pub fn cache(&mut self) -> &mut CdkCache {
-        &mut self.cache
+    /// Get the shared cache
+    pub fn cache(&self) -> &Arc<CdkCache> {
+        &self.cache
     }
 
     /// Update performance metrics
-    pub fn update_metrics(&self) {
+    pub async fn update_metrics(&self) {
         let stats = self.cache.get_stats();
         self.metrics.update_cache_hit_rate(stats.hit_rate());
-        
-        // Update memory usage (simplified)
-        let memory_usage = self.estimate_memory_usage();
-        self.metrics.update_memory_usage(memory_usage);
-    }
 
-    /// Estimate memory usage
-    fn estimate_memory_usage(&self) -> u64 {
-        // Simplified memory estimation
-        // In a real implementation, this would use proper memory tracking
-        let (batch_size, epoch_size, finality_size) = futures::executor::block_on(self.cache.get_sizes());
-        (batch_size + epoch_size + finality_size) as u64 * 1024 // Rough estimate
+        let memory_usage = self.cache.estimate_memory_usage().await;
+        self.metrics.update_memory_usage(memory_usage);
     }
 
     /// Get uptime
@@ -373,15 +617,17 @@ impl ConcurrentBatchProcessor {
     }
 
     /// Process batches concurrently
-    pub fn process_batches(&self, batches: Vec<Batch>) -> ObservabilityResult<Vec<ObservabilityResult<()>>> {
+    ///
+    /// Each result is paired with its batch number, in the same order as the input.
+    pub fn process_batches(&self, batches: Vec<Batch>) -> ObservabilityResult<Vec<(U256, ObservabilityResult<()>)>> {
         info!("Processing {} batches with {} workers", batches.len(), self.num_workers);
         
-        let results: Vec<ObservabilityResult<()>> = batches
-            .par_iter()
-            .map(|batch| (self.processor)(batch.clone()))
+        let results: Vec<(U256, ObservabilityResult<()>)> = batches
+            .into_par_iter()
+            .map(|batch| (batch.id.number, (self.processor)(batch)))
             .collect();
         
-        let success_count = results.iter().filter(|r| r.is_ok()).count();
+        let success_count = results.iter().filter(|(_, r)| r.is_ok()).count();
         info!("Processed {} batches successfully", success_count);
         
         Ok(results)
@@ -391,12 +637,26 @@ impl ConcurrentBatchProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ObservabilityError;
+    use alloy_primitives::FixedBytes;
+    use cdk_types::{BatchId, ProofMetadata};
     use prometheus::Registry;
 
+    fn test_batch(number: u64) -> Batch {
+        Batch::new(
+            BatchId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        )
+    }
+
     #[test]
     fn test_performance_metrics_creation() {
         let registry = Registry::new();
-        let metrics = PerformanceMetrics::new(&registry).unwrap();
+        let metrics = PerformanceMetrics::new(&registry, HistogramBuckets::default()).unwrap();
         
         // Test metric recording
         metrics.record_batch_import(Duration::from_millis(100));
@@ -409,6 +669,42 @@ mod tests {
         metrics.update_memory_usage(1024 * 1024);
     }
 
+    #[test]
+    fn test_duplicate_registration_is_reported() {
+        let registry = Registry::new();
+        PerformanceMetrics::new(&registry, HistogramBuckets::default()).unwrap();
+
+        match PerformanceMetrics::new(&registry, HistogramBuckets::default()) {
+            Err(ObservabilityError::Registration(_)) => {}
+            other => panic!("expected a registration error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_custom_histogram_buckets() {
+        let registry = Registry::new();
+        let buckets = HistogramBuckets {
+            batch_import_duration: vec![0.001, 0.01, 0.1],
+            ..Default::default()
+        };
+        let metrics = PerformanceMetrics::new(&registry, buckets).unwrap();
+
+        metrics.record_batch_import(Duration::from_millis(5));
+
+        let families = registry.gather();
+        let family = families
+            .iter()
+            .find(|family| family.get_name() == "cdk_batch_import_duration_seconds")
+            .unwrap();
+        let histogram = family.get_metric()[0].get_histogram();
+        let cumulative: Vec<_> = histogram
+            .get_bucket()
+            .iter()
+            .map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+            .collect();
+        assert_eq!(cumulative, vec![(0.001, 0), (0.01, 1), (0.1, 1)]);
+    }
+
     #[test]
     fn test_cache_stats() {
         let stats = CacheStats {
@@ -423,10 +719,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_cdk_cache() {
-        let mut cache = CdkCache::new(10, 10, 10, Duration::from_secs(60));
+        let cache = CdkCache::new(10, 10, 10, Duration::from_secs(60));
         
         // Test batch operations
-        let batch = Batch::new(1, vec![]);
+        let batch = test_batch(1);
         cache.insert_batch(1, batch.clone()).await;
         
         let retrieved = cache.get_batch(1).await;
@@ -437,6 +733,219 @@ mod tests {
         assert_eq!(stats.inserts, 1);
     }
 
+    #[tokio::test]
+    async fn test_get_or_insert_loads_once_per_key() {
+        let cache = CdkCache::new(10, 10, 10, Duration::from_secs(60));
+        let loads = std::sync::atomic::AtomicUsize::new(0);
+        let loader = || async {
+            if loads.fetch_add(1, std::sync::atomic::Ordering::SeqCst) > 0 {
+                panic!("batch loaded twice");
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            test_batch(1)
+        };
+
+        let (first, second) = tokio::join!(
+            cache.get_or_insert_batch(1, loader),
+            cache.get_or_insert_batch(1, loader),
+        );
+        assert_eq!(first, test_batch(1));
+        assert_eq!(second, test_batch(1));
+        assert_eq!(cache.get_or_insert_batch(1, loader).await, test_batch(1));
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.inserts, 1);
+        assert_eq!(stats.hits, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_shared_cache_counts_stats_across_tasks() {
+        let cache = Arc::new(CdkCache::new(100, 10, 10, Duration::from_secs(60)));
+        for number in 0..10 {
+            cache.insert_batch(number, test_batch(number)).await;
+        }
+
+        let lookups: Vec<_> = (0..2)
+            .map(|_| {
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    for number in 0..20 {
+                        cache.get_batch(number).await;
+                    }
+                })
+            })
+            .collect();
+        for lookup in lookups {
+            lookup.await.unwrap();
+        }
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 20);
+        assert_eq!(stats.misses, 20);
+        assert_eq!(stats.inserts, 10);
+    }
+
+    /// Storage holding batches 0 to 99, ten per epoch, all finalized
+    struct MockWarmupSource;
+
+    #[async_trait]
+    impl CacheWarmupSource for MockWarmupSource {
+        async fn load_batches(&self, batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<Batch>> {
+            Ok(batches.filter(|number| *number < 100).map(test_batch).collect())
+        }
+
+        async fn load_epochs(&self, batches: RangeInclusive<u64>, limit: usize) -> ObservabilityResult<Vec<Epoch>> {
+            let last = batches.end().min(&99) / 10;
+            let epochs = (*batches.start() / 10).max((last + 1).saturating_sub(limit as u64))..=last;
+            Ok(epochs
+                .map(|number| {
+                    Epoch::new(
+                        cdk_types::EpochId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+                        U256::from(number * 100),
+                        U256::from(number * 100 + 99),
+                        U256::from(number * 10),
+                        U256::from(number * 10 + 9),
+                        1234567890,
+                        1234567899,
+                    )
+                })
+                .collect())
+        }
+
+        async fn load_finality_tags(&self, batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<FinalityTag>> {
+            Ok(batches
+                .filter(|number| *number < 100)
+                .map(|number| {
+                    FinalityTag::new(
+                        U256::from(number),
+                        U256::from(1000 + number),
+                        FixedBytes::from([1u8; 32]),
+                        cdk_types::FinalityStatus::Finalized,
+                        1234567890,
+                        None,
+                    )
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warmup_preloads_recent_entries() {
+        let cache = CdkCache::new(20, 10, 50, Duration::from_secs(60));
+
+        let warmed = cache.warmup(&MockWarmupSource, 0..=99).await.unwrap();
+        assert_eq!(warmed, (20, 10, 50));
+        assert_eq!(cache.get_stats().inserts, 80);
+
+        assert_eq!(cache.get_batch(99).await, Some(test_batch(99)));
+        assert_eq!(cache.get_batch(80).await, Some(test_batch(80)));
+        assert!(cache.get_epoch(9).await.is_some());
+        assert!(cache.get_finality_tag(50).await.is_some());
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.hits, 4);
+        assert_eq!(stats.misses, 0);
+    }
+
+    /// Kind of entries, batch range and epoch limit of a warmup request
+    type WarmupRequest = (&'static str, RangeInclusive<u64>, Option<usize>);
+
+    /// Source recording the ranges it is asked for
+    #[derive(Default)]
+    struct RecordingWarmupSource {
+        requests: std::sync::Mutex<Vec<WarmupRequest>>,
+    }
+
+    #[async_trait]
+    impl CacheWarmupSource for RecordingWarmupSource {
+        async fn load_batches(&self, batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<Batch>> {
+            self.requests.lock().unwrap().push(("batches", batches, None));
+            Ok(Vec::new())
+        }
+
+        async fn load_epochs(&self, batches: RangeInclusive<u64>, limit: usize) -> ObservabilityResult<Vec<Epoch>> {
+            self.requests.lock().unwrap().push(("epochs", batches, Some(limit)));
+            Ok(Vec::new())
+        }
+
+        async fn load_finality_tags(&self, batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<FinalityTag>> {
+            self.requests.lock().unwrap().push(("finality tags", batches, None));
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warmup_requests_only_what_fits() {
+        let cache = CdkCache::new(20, 10, 50, Duration::from_secs(60));
+        let source = RecordingWarmupSource::default();
+
+        cache.warmup(&source, 0..=999).await.unwrap();
+
+        let requests = source.requests.lock().unwrap();
+        assert_eq!(
+            *requests,
+            vec![
+                ("batches", 980..=999, None),
+                ("epochs", 0..=999, Some(10)),
+                ("finality tags", 950..=999, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cdk_cache_counts_evictions() {
+        let cache = CdkCache::new(10, 10, 10, Duration::from_secs(60));
+
+        for number in 0..100 {
+            cache.insert_batch(number, test_batch(number)).await;
+        }
+        cache.run_pending_tasks().await;
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.inserts, 100);
+        assert!(stats.evictions > 0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_usage_tracks_entry_sizes() {
+        async fn memory_after_insert(batch: Batch) -> f64 {
+            let monitor = PerformanceMonitor::new(&Registry::new()).unwrap();
+            monitor.cache().insert_batch(batch.id.number.to::<u64>(), batch).await;
+            monitor.update_metrics().await;
+            monitor.metrics().memory_usage.get()
+        }
+
+        let mut large_batch = test_batch(2);
+        large_batch.blocks = (0..50)
+            .map(|i| {
+                cdk_types::BlockInBatch::new(
+                    i,
+                    FixedBytes::from([i as u8; 32]),
+                    U256::from(i),
+                    FixedBytes::ZERO,
+                    FixedBytes::ZERO,
+                    FixedBytes::ZERO,
+                    FixedBytes::ZERO,
+                    1234567890,
+                )
+            })
+            .collect();
+
+        let small = memory_after_insert(test_batch(1)).await;
+        let large = memory_after_insert(large_batch).await;
+        assert!(small > 0.0);
+        assert!(large > small);
+
+        // More entries of the same size report proportionally more memory
+        let monitor = PerformanceMonitor::new(&Registry::new()).unwrap();
+        for number in 0..4 {
+            monitor.cache().insert_batch(number, test_batch(number)).await;
+        }
+        monitor.update_metrics().await;
+        assert!(monitor.metrics().memory_usage.get() >= small * 3.0);
+    }
+
     #[test]
     fn test_concurrent_batch_processor() {
         let processor = ConcurrentBatchProcessor::new(4, |batch| {
@@ -445,13 +954,37 @@ mod tests {
         });
         
         let batches = vec![
-            Batch::new(1, vec![]),
-            Batch::new(2, vec![]),
-            Batch::new(3, vec![]),
+            test_batch(1),
+            test_batch(2),
+            test_batch(3),
         ];
         
         let results = processor.process_batches(batches).unwrap();
         assert_eq!(results.len(), 3);
-        assert!(results.iter().all(|r| r.is_ok()));
+        assert!(results.iter().all(|(_, r)| r.is_ok()));
+    }
+
+    #[test]
+    fn test_concurrent_batch_processor_reports_failed_batch_ids() {
+        let processor = ConcurrentBatchProcessor::new(4, |batch| {
+            if batch.id.number % U256::from(2) == U256::ZERO {
+                Err(ObservabilityError::InternalError(format!("batch {} failed", batch.id.number)))
+            } else {
+                Ok(())
+            }
+        });
+
+        let batches = (1..=10).map(test_batch).collect();
+        let results = processor.process_batches(batches).unwrap();
+
+        let ids: Vec<u64> = results.iter().map(|(id, _)| id.to::<u64>()).collect();
+        assert_eq!(ids, (1..=10).collect::<Vec<_>>());
+
+        let failed: Vec<u64> = results
+            .iter()
+            .filter(|(_, r)| r.is_err())
+            .map(|(id, _)| id.to::<u64>())
+            .collect();
+        assert_eq!(failed, vec![2, 4, 6, 8, 10]);
     }
 }
@@ -1,12 +1,18 @@
 //! Unified tracing configuration for CDK observability
 
 use alloy_primitives::U256;
-use tracing::{info, warn, Level};
-use tracing_subscriber::EnvFilter;
+use tracing::{info, warn, Instrument, Level};
+use tracing::Subscriber;
+use tracing_subscriber::{
+    fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt, EnvFilter,
+    Layer,
+};
 
 /// Tracing configuration
 pub struct TracingConfig {
     level: Level,
+    format: LogFormat,
+    otlp_endpoint: Option<String>,
 }
 
 impl TracingConfig {
@@ -21,20 +27,94 @@ impl TracingConfig {
             _ => Level::INFO,
         };
 
-        Self { level }
+        Self {
+            level,
+            format: config.log_format.clone(),
+            otlp_endpoint: None,
+        }
+    }
+
+    /// Export spans to the OTLP collector at `endpoint`
+    ///
+    /// Only takes effect when built with the `otel` feature.
+    pub fn with_otlp_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.otlp_endpoint = Some(endpoint.into());
+        self
     }
 
     /// Initialize tracing with the configuration
     pub fn init(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let filter = EnvFilter::from_default_env()
             .add_directive(format!("{}", self.level).parse()?);
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(Self::fmt_layer(&self.format, std::io::stdout));
+
+        #[cfg(feature = "otel")]
+        if let Some(endpoint) = &self.otlp_endpoint {
+            let provider = otel::otlp_tracer_provider(endpoint)?;
+            subscriber.with(otel::layer(&provider)).try_init()?;
+            opentelemetry::global::set_tracer_provider(provider);
+            info!("Exporting spans to OTLP collector at {}", endpoint);
+            return Ok(());
+        }
 
-        tracing_subscriber::fmt()
-            .with_env_filter(filter)
-            .init();
+        subscriber.try_init()?;
+
+        #[cfg(not(feature = "otel"))]
+        if self.otlp_endpoint.is_some() {
+            warn!("OTLP endpoint configured but cdk-observe was built without the `otel` feature");
+        }
 
         Ok(())
     }
+
+    /// Build the fmt layer for the given log format
+    fn fmt_layer<S, W>(format: &LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+        W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+    {
+        let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+        match format {
+            LogFormat::Json => layer.json().boxed(),
+            LogFormat::Pretty => layer.pretty().boxed(),
+            LogFormat::Compact => layer.compact().boxed(),
+        }
+    }
+}
+
+/// OpenTelemetry span export
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+    use tracing::Subscriber;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Build a tracer provider batching spans to an OTLP gRPC endpoint
+    pub(super) fn otlp_tracer_provider(
+        endpoint: &str,
+    ) -> Result<SdkTracerProvider, Box<dyn std::error::Error + Send + Sync>> {
+        let exporter = SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()?;
+
+        Ok(SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name("cdk").build())
+            .build())
+    }
+
+    /// Tracing layer forwarding spans to the given provider
+    pub(super) fn layer<S>(provider: &SdkTracerProvider) -> impl tracing_subscriber::Layer<S>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("cdk-observe"))
+    }
 }
 
 /// Tracing utilities for CDK operations
@@ -51,8 +131,8 @@ impl CdkTracing {
         F: FnOnce() -> Fut,
         Fut: std::future::Future,
     {
-        let _span = tracing::info_span!("batch_processing", batch_id = %batch_id, batch_height = %batch_height);
-        f().await
+        let span = tracing::info_span!("batch_processing", batch_id = %batch_id, batch_height = %batch_height);
+        f().instrument(span).await
     }
 
     /// Create a span for epoch processing
@@ -65,8 +145,8 @@ impl CdkTracing {
         F: FnOnce() -> Fut,
         Fut: std::future::Future,
     {
-        let _span = tracing::info_span!("epoch_processing", epoch_id = %epoch_id, epoch_height = %epoch_height);
-        f().await
+        let span = tracing::info_span!("epoch_processing", epoch_id = %epoch_id, epoch_height = %epoch_height);
+        f().instrument(span).await
     }
 
     /// Create a span for finality checking
@@ -79,8 +159,8 @@ impl CdkTracing {
         F: FnOnce() -> Fut,
         Fut: std::future::Future,
     {
-        let _span = tracing::info_span!("finality_check", batch_id = %batch_id, l1_block = %l1_block);
-        f().await
+        let span = tracing::info_span!("finality_check", batch_id = %batch_id, l1_block = %l1_block);
+        f().instrument(span).await
     }
 
     /// Log ingestion start
@@ -110,7 +190,7 @@ impl CdkTracing {
 }
 
 // Import the config types
-use crate::config::ObservabilityConfig;
+use crate::config::{LogFormat, ObservabilityConfig};
 
 #[cfg(test)]
 mod tests {
@@ -124,6 +204,89 @@ mod tests {
         assert_eq!(tracing_config.level, Level::INFO);
     }
 
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn test_otel_layer_records_batch_span() {
+        use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry().with(otel::layer(&provider));
+
+        let guard = tracing::subscriber::set_default(subscriber);
+        CdkTracing::trace_batch_processing(U256::from(7), U256::from(700), || async {}).await;
+        drop(guard);
+        provider.force_flush().unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let span = spans.iter().find(|span| span.name == "batch_processing").unwrap();
+        let attribute = |key: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.as_str().into_owned())
+        };
+        assert_eq!(attribute("batch_id").as_deref(), Some("7"));
+        assert_eq!(attribute("batch_height").as_deref(), Some("700"));
+    }
+
+    /// Writer capturing formatted log output in memory
+    #[derive(Clone, Default)]
+    struct CapturedWriter(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CapturedWriter {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn capture_logs(format: LogFormat) -> String {
+        let writer = CapturedWriter::default();
+        let subscriber = tracing_subscriber::registry().with(TracingConfig::fmt_layer(&format, writer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            CdkTracing::log_ingestion_start(U256::from(5), 3);
+        });
+        let output = writer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_log_formats() {
+        for format in [LogFormat::Json, LogFormat::Pretty, LogFormat::Compact] {
+            let output = capture_logs(format);
+            assert!(output.contains("Starting ingestion for batch 5 with 3 blocks"));
+        }
+
+        let json = capture_logs(LogFormat::Json);
+        let line: serde_json::Value = serde_json::from_str(json.lines().next().unwrap()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "Starting ingestion for batch 5 with 3 blocks");
+    }
+
+    #[test]
+    fn test_init_with_each_format() {
+        for format in [LogFormat::Json, LogFormat::Pretty, LogFormat::Compact] {
+            let config = ObservabilityConfig::default().with_logging("info", format);
+            // Only the first initialization can install the global subscriber
+            let _ = TracingConfig::new(&config).init();
+        }
+    }
+
     #[test]
     fn test_tracing_spans() {
         // Initialize tracing for tests
@@ -2,16 +2,27 @@
 
 use async_trait::async_trait;
 use alloy_primitives::U256;
+use std::time::Duration;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tokio::sync::RwLock;
 use tracing::{info, warn, instrument};
 
 use crate::{
-    CdkRpcError, CdkRpcResult,
+    CdkRpcConfig, CdkRpcError, CdkRpcResult,
     types::*,
+    StorageWarmupSource,
 };
-use cdk_types::{Batch, BatchId, Epoch};
+use cdk_types::{Batch, Epoch, FinalityStatus};
 use cdk_datastream::BatchSource;
 use cdk_ingest::MappingStorage;
 use cdk_finality::FinalityOracle;
+use cdk_observe::CdkCache;
+
+/// How long a cached entry is served before it has to be reloaded
+const CACHE_TTL: Duration = Duration::from_secs(3600);
 
 /// CDK RPC API trait definition
 #[async_trait]
@@ -23,17 +34,69 @@ pub trait CdkRpcApi {
     async fn get_epoch_by_block(&self, block_number: String) -> Result<Option<EpochResponse>, CdkRpcError>;
 
     /// Get the latest finalized batch
-    async fn finalized_batch(&mut self) -> Result<Option<FinalizedBatchResponse>, CdkRpcError>;
+    async fn finalized_batch(&self) -> Result<Option<FinalizedBatchResponse>, CdkRpcError>;
+
+    /// Get the finality status of a batch (see `FinalityStatus::as_str`), or `None` if the batch is unknown
+    async fn get_finality_status(&self, batch_number: String) -> Result<Option<String>, CdkRpcError>;
 
     /// Get CDK metrics and statistics
     async fn metrics(&self) -> Result<CdkMetrics, CdkRpcError>;
+
+    /// Get the health of the batch source, finality oracle and mapping storage
+    ///
+    /// Unhealthy components are reported in the returned `HealthReport`
+    /// rather than failing the call.
+    async fn health(&self) -> Result<HealthReport, CdkRpcError>;
+}
+
+/// Counters maintained by the ingestion pipeline and reported through `metrics`
+///
+/// Clones share the same underlying values, so the ingest loop can keep a handle
+/// and update it while the RPC layer reads from its own copy.
+#[derive(Debug, Clone, Default)]
+pub struct IngestCounters {
+    reorg_count: Arc<AtomicU64>,
+    ingest_tps_bits: Arc<AtomicU64>,
+}
+
+impl IngestCounters {
+    /// Create a new set of counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Increment the reorg counter
+    pub fn increment_reorg_count(&self) {
+        self.reorg_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Set the current ingest TPS
+    pub fn set_ingest_tps(&self, tps: f64) {
+        self.ingest_tps_bits.store(tps.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Get the number of reorgs observed so far
+    pub fn reorg_count(&self) -> u64 {
+        self.reorg_count.load(Ordering::Relaxed)
+    }
+
+    /// Get the current ingest TPS
+    pub fn ingest_tps(&self) -> f64 {
+        f64::from_bits(self.ingest_tps_bits.load(Ordering::Relaxed))
+    }
 }
 
 /// CDK RPC API implementation
+///
+/// The finality oracle sits behind its own lock: only polling it needs
+/// exclusive access, so the API itself can be shared read-only.
 pub struct CdkRpcApiImpl {
     batch_source: Box<dyn BatchSource + Send + Sync>,
     mapping_storage: Box<dyn MappingStorage + Send + Sync>,
-    finality_oracle: Box<dyn FinalityOracle + Send + Sync>,
+    finality_oracle: RwLock<Box<dyn FinalityOracle + Send + Sync>>,
+    counters: IngestCounters,
+    config: CdkRpcConfig,
+    cache: CdkCache,
 }
 
 impl CdkRpcApiImpl {
@@ -43,13 +106,88 @@ impl CdkRpcApiImpl {
         mapping_storage: Box<dyn MappingStorage + Send + Sync>,
         finality_oracle: Box<dyn FinalityOracle + Send + Sync>,
     ) -> Self {
+        let config = CdkRpcConfig::default();
         Self {
             batch_source,
             mapping_storage,
-            finality_oracle,
+            finality_oracle: RwLock::new(finality_oracle),
+            counters: IngestCounters::default(),
+            cache: Self::build_cache(&config),
+            config,
         }
     }
 
+    /// Apply the feature flags and history limits of a server configuration
+    pub fn with_config(mut self, config: CdkRpcConfig) -> Self {
+        self.cache = Self::build_cache(&config);
+        self.config = config;
+        self
+    }
+
+    /// Cache sized to the configured batch and epoch history
+    fn build_cache(config: &CdkRpcConfig) -> CdkCache {
+        CdkCache::new(config.max_batch_history, config.max_epoch_history, config.max_batch_history, CACHE_TTL)
+    }
+
+    /// Cache the API serves from
+    pub fn cache(&self) -> &CdkCache {
+        &self.cache
+    }
+
+    /// Preload the epoch and finality caches for the last `max_batch_history` batches of the mapping storage
+    ///
+    /// The mapping storage keeps no batch bodies, so the batch cache is left
+    /// cold. Returns the number of epochs and finality tags loaded.
+    pub async fn warmup_cache(&self) -> CdkRpcResult<(usize, usize)> {
+        let Some(latest) = self.mapping_storage.latest_batch_mapping().await? else {
+            return Ok((0, 0));
+        };
+        let latest = latest.batch_id;
+        let first = latest.saturating_sub(self.config.max_batch_history.saturating_sub(1));
+
+        let finality_oracle = self.finality_oracle.read().await;
+        let source = StorageWarmupSource::new(self.mapping_storage.as_ref(), finality_oracle.as_ref());
+        let (_, epochs, tags) = self
+            .cache
+            .warmup(&source, first..=latest)
+            .await
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+        Ok((epochs, tags))
+    }
+
+    /// Poll the finality oracle and return the batches it newly reports as finalized
+    pub async fn poll_finalized_batches(&self) -> CdkRpcResult<Vec<FinalizedBatchResponse>> {
+        let finality_tags = self.finality_oracle.write().await.poll().await?;
+        let mut finalized = Vec::new();
+        for tag in finality_tags.into_iter().filter(|tag| tag.status == FinalityStatus::Finalized) {
+            finalized.push(FinalizedBatchResponse::from(&tag));
+            if let Ok(batch_id) = u64::try_from(tag.batch_id) {
+                self.cache.insert_finality_tag(batch_id, tag).await;
+            }
+        }
+        Ok(finalized)
+    }
+
+    /// Polling interval of the underlying finality oracle
+    pub async fn finality_polling_interval(&self) -> Duration {
+        self.finality_oracle.read().await.get_polling_interval()
+    }
+
+    /// Fail with `MethodDisabled` unless the method's feature flag is set
+    fn ensure_enabled(enabled: bool, method: &str) -> CdkRpcResult<()> {
+        if enabled {
+            Ok(())
+        } else {
+            Err(CdkRpcError::MethodDisabled(method.to_string()))
+        }
+    }
+
+    /// Use externally maintained ingest counters for `reorg_count` and `ingest_tps`
+    pub fn with_counters(mut self, counters: IngestCounters) -> Self {
+        self.counters = counters;
+        self
+    }
+
     /// Parse hex string to U256
     fn parse_hex_number(hex_str: &str) -> CdkRpcResult<U256> {
         let cleaned = hex_str.strip_prefix("0x").unwrap_or(hex_str);
@@ -60,8 +198,7 @@ impl CdkRpcApiImpl {
     /// Get batch metadata
     async fn get_batch_metadata(&self, batch: &Batch) -> CdkRpcResult<BatchMetadata> {
         let block_count = batch.blocks.len() as u64;
-        // Note: BlockInBatch doesn't have transactions field, using placeholder
-        let transaction_count = 0u64;
+        let transaction_count = batch.transaction_count();
         
         // Estimate size (simplified)
         let size_bytes = serde_json::to_vec(batch)
@@ -96,6 +233,7 @@ impl CdkRpcApi for CdkRpcApiImpl {
     #[instrument(skip(self), fields(batch_number = %batch_number))]
     async fn get_batch_by_number(&self, batch_number: String) -> Result<Option<BatchResponse>, CdkRpcError> {
         info!("Getting batch by number: {}", batch_number);
+        Self::ensure_enabled(self.config.enable_batch_queries, "cdk_getBatchByNumber")?;
         
         let _batch_num = Self::parse_hex_number(&batch_number)?;
         
@@ -108,6 +246,7 @@ impl CdkRpcApi for CdkRpcApiImpl {
     #[instrument(skip(self), fields(block_number = %block_number))]
     async fn get_epoch_by_block(&self, block_number: String) -> Result<Option<EpochResponse>, CdkRpcError> {
         info!("Getting epoch by block number: {}", block_number);
+        Self::ensure_enabled(self.config.enable_epoch_queries, "cdk_getEpochByBlock")?;
         
         let _block_num = Self::parse_hex_number(&block_number)?;
         
@@ -118,43 +257,105 @@ impl CdkRpcApi for CdkRpcApiImpl {
     }
 
     #[instrument(skip(self))]
-    async fn finalized_batch(&mut self) -> Result<Option<FinalizedBatchResponse>, CdkRpcError> {
+    async fn finalized_batch(&self) -> Result<Option<FinalizedBatchResponse>, CdkRpcError> {
         info!("Getting finalized batch");
-        
-        // Poll finality oracle for latest finality tags
-        let finality_tags = self.finality_oracle.poll().await
+        Self::ensure_enabled(self.config.enable_finality_queries, "cdk_finalizedBatch")?;
+
+        // Read the oracle's finalized set; polling here would consume events
+        // meant for the subscription poll loop
+        let finality_tags = self.finality_oracle.read().await.get_finalized_batches().await
             .map_err(|e| CdkRpcError::FinalityOracleError(e.to_string()))?;
-        
-        if let Some(latest_tag) = finality_tags.last() {
-            Ok(Some(FinalizedBatchResponse {
-                batch_id: BatchId::new(
-                    latest_tag.batch_id,
-                    latest_tag.l1_block_hash,
-                ),
-                status: format!("{:?}", latest_tag.status),
-                l1_block: latest_tag.l1_block,
-                timestamp: latest_tag.timestamp,
-            }))
-        } else {
-            Ok(None)
-        }
+
+        Ok(finality_tags
+            .iter()
+            .filter(|tag| tag.status == FinalityStatus::Finalized)
+            .max_by_key(|tag| tag.batch_id)
+            .map(FinalizedBatchResponse::from))
+    }
+
+    #[instrument(skip(self), fields(batch_number = %batch_number))]
+    async fn get_finality_status(&self, batch_number: String) -> Result<Option<String>, CdkRpcError> {
+        info!("Getting finality status for batch: {}", batch_number);
+        Self::ensure_enabled(self.config.enable_finality_queries, "cdk_getFinalityStatus")?;
+
+        let batch_num = Self::parse_hex_number(&batch_number)?;
+        let batch_id = u64::try_from(batch_num)
+            .map_err(|_| CdkRpcError::InvalidParameter(format!("Batch number out of range: {}", batch_number)))?;
+
+        // Only finalized tags are cached, and finalization is final
+        let status = match self.cache.get_finality_tag(batch_id).await {
+            Some(tag) => Some(tag.status),
+            None => self.finality_oracle.read().await.get_finality_status(batch_id).await?,
+        };
+        Ok(status.map(|status| status.as_str().to_string()))
     }
 
     #[instrument(skip(self))]
     async fn metrics(&self) -> Result<CdkMetrics, CdkRpcError> {
         info!("Getting CDK metrics");
-        
-        // TODO: Implement actual metrics collection
-        // For now, return placeholder metrics
+        Self::ensure_enabled(self.config.enable_metrics, "cdk_metrics")?;
+
+        // Sources without any processed batch have no valid checkpoint yet
+        let checkpoint = self.batch_source.checkpoint().await
+            .ok()
+            .filter(|checkpoint| checkpoint.is_valid());
+
+        let total_batches = self.mapping_storage.batch_mapping_count().await?;
+        let total_epochs = self.mapping_storage.epoch_mapping_count().await?;
+        let latest_epoch = self.mapping_storage.latest_epoch_mapping().await?
+            .map(|mapping| U256::from(mapping.epoch_id));
+
+        let finality_oracle = self.finality_oracle.read().await;
+        let latest_finalized_batch = finality_oracle.get_finalized_batches().await?
+            .into_iter()
+            .map(|tag| tag.batch_id)
+            .max();
+
+        let l1_lag = match &checkpoint {
+            Some(checkpoint) => {
+                let oracle_metadata = finality_oracle.metadata().await?;
+                let last_l1_origin = checkpoint.last_l1_block.saturating_to::<u64>();
+                Some(oracle_metadata.current_l1_block.saturating_sub(last_l1_origin))
+            }
+            None => None,
+        };
+
         Ok(CdkMetrics {
-            total_batches: 0,
-            total_epochs: 0,
-            latest_batch: None,
-            latest_epoch: None,
-            latest_finalized_batch: None,
-            l1_lag: None,
-            reorg_count: 0,
-            ingest_tps: 0.0,
+            total_batches,
+            total_epochs,
+            latest_batch: checkpoint.as_ref().map(|checkpoint| checkpoint.last_batch_id),
+            latest_epoch,
+            latest_finalized_batch,
+            l1_lag,
+            reorg_count: self.counters.reorg_count(),
+            ingest_tps: self.counters.ingest_tps(),
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn health(&self) -> Result<HealthReport, CdkRpcError> {
+        info!("Checking CDK health");
+
+        let (batch_source, finality_oracle, mapping_storage) = tokio::join!(
+            self.batch_source.health_check(),
+            async { self.finality_oracle.read().await.health_check().await },
+            // Mapping storage has no dedicated health check; a read must succeed
+            self.mapping_storage.load_batch_mapping(0),
+        );
+
+        let batch_source = ComponentHealth::from(batch_source);
+        let finality_oracle = ComponentHealth::from(finality_oracle);
+        let mapping_storage = ComponentHealth::from(mapping_storage.map(|_| ()));
+        let healthy = batch_source.healthy && finality_oracle.healthy && mapping_storage.healthy;
+        if !healthy {
+            warn!("CDK subsystems degraded");
+        }
+
+        Ok(HealthReport {
+            batch_source,
+            finality_oracle,
+            mapping_storage,
+            healthy,
         })
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,9 @@
 //! Error types for CDK RPC operations
 
+use jsonrpsee::types::{
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE, METHOD_NOT_FOUND_CODE},
+    ErrorObjectOwned,
+};
 use thiserror::Error;
 
 /// Result type for CDK RPC operations
@@ -35,6 +39,10 @@ pub enum CdkRpcError {
     /// Service unavailable
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
+
+    /// Method disabled by the server configuration
+    #[error("Method {0} is disabled")]
+    MethodDisabled(String),
 }
 
 impl From<cdk_datastream::DatastreamError> for CdkRpcError {
@@ -54,3 +62,14 @@ impl From<cdk_finality::FinalityError> for CdkRpcError {
         CdkRpcError::FinalityOracleError(err.to_string())
     }
 }
+
+impl From<CdkRpcError> for ErrorObjectOwned {
+    fn from(err: CdkRpcError) -> Self {
+        let code = match err {
+            CdkRpcError::InvalidParameter(_) => INVALID_PARAMS_CODE,
+            CdkRpcError::MethodDisabled(_) => METHOD_NOT_FOUND_CODE,
+            _ => INTERNAL_ERROR_CODE,
+        };
+        ErrorObjectOwned::owned(code, err.to_string(), None::<()>)
+    }
+}
@@ -7,11 +7,13 @@ pub mod api;
 pub mod error;
 pub mod server;
 pub mod types;
+pub mod warmup;
 
-pub use api::{CdkRpcApi, CdkRpcApiImpl};
+pub use api::{CdkRpcApi, CdkRpcApiImpl, IngestCounters};
 pub use error::{CdkRpcError, CdkRpcResult};
-pub use server::{CdkRpcConfig, CdkRpcServer};
+pub use server::{CdkRpcConfig, CdkRpcServer, RunningCdkRpcServer};
 pub use types::*;
+pub use warmup::StorageWarmupSource;
 
 /// Re-export commonly used types
 pub use cdk_types::{Batch, BatchId, Epoch, EpochId, FinalityTag};
@@ -2,12 +2,21 @@
 
 use alloy_provider::ProviderBuilder;
 use alloy_network::Ethereum;
-use std::net::SocketAddr;
-use tracing::{info, instrument};
+use jsonrpsee::{
+    server::{middleware::rpc::RpcServiceT, RpcServiceBuilder, Server, ServerHandle},
+    types::{error::ErrorCode, ErrorObject, ErrorObjectOwned, Request},
+    MethodResponse, PendingSubscriptionSink, RpcModule, SubscriptionMessage,
+};
+use std::{future::Future, net::SocketAddr, pin::Pin, sync::Arc};
+use tokio::{
+    sync::{broadcast, RwLock, Semaphore},
+    task::JoinHandle,
+};
+use tracing::{debug, info, instrument, warn};
 
 use crate::{
-    CdkRpcError, CdkRpcResult,
-    api::CdkRpcApiImpl,
+    CdkRpcError, CdkRpcResult, FinalizedBatchResponse,
+    api::{CdkRpcApi, CdkRpcApiImpl},
 };
 use cdk_datastream::BatchSource;
 use cdk_ingest::MappingStorage;
@@ -30,6 +39,12 @@ pub struct CdkRpcConfig {
     pub max_epoch_history: u64,
     /// Server address
     pub address: SocketAddr,
+    /// Maximum size of a request body in bytes
+    pub max_request_body_bytes: u32,
+    /// Maximum number of calls processed at once; excess calls are rejected
+    pub max_concurrent_requests: usize,
+    /// Chain id the batch source must serve; the server refuses to start on a mismatch
+    pub expected_chain_id: Option<u64>,
 }
 
 impl Default for CdkRpcConfig {
@@ -42,10 +57,18 @@ impl Default for CdkRpcConfig {
             max_batch_history: 1000,
             max_epoch_history: 100,
             address: "127.0.0.1:8545".parse().unwrap(),
+            max_request_body_bytes: 10 * 1024 * 1024, // 10MB
+            max_concurrent_requests: 128,
+            expected_chain_id: None,
         }
     }
 }
 
+/// Number of finalized batch events buffered for subscribers
+///
+/// A subscriber falling further behind than this is dropped with a lag notice.
+pub const FINALIZED_BATCH_CHANNEL_CAPACITY: usize = 256;
+
 /// CDK RPC Server using Alloy Provider
 pub struct CdkRpcServer {
     config: CdkRpcConfig,
@@ -79,25 +102,255 @@ impl CdkRpcServer {
     }
 
     /// Start the RPC server
+    ///
+    /// Preloads the cache from the mapping storage, then binds an HTTP JSON-RPC
+    /// server on `config.address` serving the CDK methods and returns a handle to it. Fails without binding if the batch
+    /// source serves another chain than `config.expected_chain_id`.
     #[instrument(skip(self))]
-    pub async fn start(self) -> CdkRpcResult<()> {
+    pub async fn start(self) -> CdkRpcResult<RunningCdkRpcServer> {
         info!("Starting CDK RPC server on {}", self.config.address);
-        
-        let _api_impl = CdkRpcApiImpl::new(
+
+        if let Some(chain_id) = self.config.expected_chain_id {
+            self.batch_source.metadata().await?.ensure_chain_id(chain_id)?;
+        }
+
+        let api_impl = CdkRpcApiImpl::new(
             self.batch_source,
             self.mapping_storage,
             self.finality_oracle,
-        );
-        
-        // Use Alloy Provider for RPC operations
-        // This is a simplified implementation - in practice, you would
-        // integrate with the existing Reth RPC infrastructure
-        info!("CDK RPC server started successfully with Alloy Provider");
-        Ok(())
+        )
+        .with_config(self.config.clone());
+        // A cold cache only costs latency, so a failed warmup does not stop the server
+        match api_impl.warmup_cache().await {
+            Ok((epochs, tags)) => debug!("Warmed up cache with {} epochs and {} finality tags", epochs, tags),
+            Err(e) => warn!("Failed to warm up cache: {}", e),
+        }
+        let api = Arc::new(RwLock::new(api_impl));
+        let (finalized_batches, _) = broadcast::channel(FINALIZED_BATCH_CHANNEL_CAPACITY);
+        let module = Self::rpc_module(api.clone(), finalized_batches.clone(), &self.config)?;
+
+        let permits = Arc::new(Semaphore::new(self.config.max_concurrent_requests));
+        let rpc_middleware = RpcServiceBuilder::new().layer_fn(move |service| ConcurrencyLimit {
+            service,
+            permits: permits.clone(),
+        });
+        let server = Server::builder()
+            .max_request_body_size(self.config.max_request_body_bytes)
+            .set_rpc_middleware(rpc_middleware)
+            .build(self.config.address)
+            .await
+            .map_err(|e| CdkRpcError::InternalError(format!("Failed to bind {}: {}", self.config.address, e)))?;
+        let local_addr = server
+            .local_addr()
+            .map_err(|e| CdkRpcError::InternalError(format!("Failed to get local address: {}", e)))?;
+        let handle = server.start(module);
+
+        let finality_task = self
+            .config
+            .enable_finality_queries
+            .then(|| tokio::spawn(Self::broadcast_finalized_batches(api, finalized_batches)));
+
+        info!("CDK RPC server listening on {}", local_addr);
+        Ok(RunningCdkRpcServer { local_addr, handle, finality_task })
+    }
+
+    /// Poll the finality oracle and publish newly finalized batches to subscribers
+    async fn broadcast_finalized_batches(
+        api: Arc<RwLock<CdkRpcApiImpl>>,
+        finalized_batches: broadcast::Sender<FinalizedBatchResponse>,
+    ) {
+        let polling_interval = api.read().await.finality_polling_interval().await;
+        let mut interval = tokio::time::interval(polling_interval);
+
+        loop {
+            interval.tick().await;
+            match api.read().await.poll_finalized_batches().await {
+                Ok(batches) => {
+                    for batch in batches {
+                        debug!("Publishing finalized batch {}", batch.batch_id.number);
+                        // Sending only fails when there are no subscribers
+                        let _ = finalized_batches.send(batch);
+                    }
+                }
+                Err(e) => warn!("Failed to poll finality oracle: {}", e),
+            }
+        }
+    }
+
+    /// Build the JSON-RPC module exposing the CDK methods of `api`
+    ///
+    /// `cdk_subscribeFinalizedBatches` subscribers receive the events sent on
+    /// `finalized_batches`.
+    pub fn rpc_module(
+        api: Arc<RwLock<CdkRpcApiImpl>>,
+        finalized_batches: broadcast::Sender<FinalizedBatchResponse>,
+        config: &CdkRpcConfig,
+    ) -> CdkRpcResult<RpcModule<Arc<RwLock<CdkRpcApiImpl>>>> {
+        let mut module = RpcModule::new(api);
+
+        module
+            .register_async_method("cdk_getBatchByNumber", |params, api, _| async move {
+                let batch_number: String = params.one()?;
+                Ok::<_, ErrorObjectOwned>(api.read().await.get_batch_by_number(batch_number).await?)
+            })
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        module
+            .register_async_method("cdk_getEpochByBlock", |params, api, _| async move {
+                let block_number: String = params.one()?;
+                Ok::<_, ErrorObjectOwned>(api.read().await.get_epoch_by_block(block_number).await?)
+            })
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        module
+            .register_async_method("cdk_finalizedBatch", |_, api, _| async move {
+                Ok::<_, ErrorObjectOwned>(api.read().await.finalized_batch().await?)
+            })
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        module
+            .register_async_method("cdk_getFinalityStatus", |params, api, _| async move {
+                let batch_number: String = params.one()?;
+                Ok::<_, ErrorObjectOwned>(api.read().await.get_finality_status(batch_number).await?)
+            })
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        module
+            .register_async_method("cdk_metrics", |_, api, _| async move {
+                Ok::<_, ErrorObjectOwned>(api.read().await.metrics().await?)
+            })
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        module
+            .register_async_method("cdk_health", |_, api, _| async move {
+                Ok::<_, ErrorObjectOwned>(api.read().await.health().await?)
+            })
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        let subscriptions_enabled = config.enable_finality_queries;
+        module
+            .register_subscription(
+                "cdk_subscribeFinalizedBatches",
+                "cdk_finalizedBatches",
+                "cdk_unsubscribeFinalizedBatches",
+                move |_, pending, _, _| {
+                    let receiver = finalized_batches.subscribe();
+                    async move {
+                        if !subscriptions_enabled {
+                            let err = CdkRpcError::MethodDisabled("cdk_subscribeFinalizedBatches".to_string());
+                            pending.reject(ErrorObjectOwned::from(err)).await;
+                            return Ok(());
+                        }
+                        Self::forward_finalized_batches(pending, receiver).await
+                    }
+                },
+            )
+            .map_err(|e| CdkRpcError::InternalError(e.to_string()))?;
+
+        Ok(module)
+    }
+
+    /// Forward finalized batch events to a subscriber until it disconnects
+    ///
+    /// Slow subscribers are never waited on: if either the broadcast channel
+    /// or the connection buffer overflows, the subscription is closed with a
+    /// lag notice.
+    async fn forward_finalized_batches(
+        pending: PendingSubscriptionSink,
+        mut receiver: broadcast::Receiver<FinalizedBatchResponse>,
+    ) -> Result<(), jsonrpsee::core::StringError> {
+        let mut sink = pending.accept().await?;
+
+        loop {
+            let batch = tokio::select! {
+                _ = sink.closed() => return Ok(()),
+                batch = receiver.recv() => batch,
+            };
+
+            match batch {
+                Ok(batch) => {
+                    if let Err(e) = sink.try_send(SubscriptionMessage::from_json(&batch)?) {
+                        warn!("Dropping lagging finalized batch subscriber: {}", e);
+                        return Err(format!("Subscriber lagged behind, dropped at batch {}", batch.batch_id.number).into());
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Dropping finalized batch subscriber that missed {} events", skipped);
+                    return Err(format!("Subscriber lagged behind, missed {} finalized batches", skipped).into());
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
     }
 
     /// Get the underlying provider for direct RPC calls
     pub fn provider(&self) -> &dyn alloy_provider::Provider<Ethereum> {
         self.provider.as_ref()
     }
-}
\ No newline at end of file
+}
+
+/// RPC middleware rejecting calls beyond the concurrency limit
+///
+/// Calls that cannot get a permit immediately are answered with a
+/// "server is busy" JSON-RPC error instead of being queued.
+#[derive(Debug, Clone)]
+struct ConcurrencyLimit<S> {
+    service: S,
+    permits: Arc<Semaphore>,
+}
+
+impl<'a, S> RpcServiceT<'a> for ConcurrencyLimit<S>
+where
+    S: RpcServiceT<'a>,
+    S::Future: 'a,
+{
+    type Future = Pin<Box<dyn Future<Output = MethodResponse> + Send + 'a>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let Ok(permit) = self.permits.clone().try_acquire_owned() else {
+            warn!("Rejecting {} call: too many concurrent requests", request.method_name());
+            let response = MethodResponse::error(request.id, ErrorObject::from(ErrorCode::ServerIsBusy));
+            return Box::pin(std::future::ready(response));
+        };
+
+        let response = self.service.call(request);
+        Box::pin(async move {
+            let response = response.await;
+            drop(permit);
+            response
+        })
+    }
+}
+
+/// Handle to a running CDK RPC server
+#[derive(Debug)]
+pub struct RunningCdkRpcServer {
+    local_addr: SocketAddr,
+    handle: ServerHandle,
+    finality_task: Option<JoinHandle<()>>,
+}
+
+impl RunningCdkRpcServer {
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Handle of the underlying jsonrpsee server
+    pub fn handle(&self) -> &ServerHandle {
+        &self.handle
+    }
+
+    /// Stop the server and wait for it to shut down
+    pub async fn stop(self) -> CdkRpcResult<()> {
+        if let Some(task) = &self.finality_task {
+            task.abort();
+        }
+        self.handle
+            .stop()
+            .map_err(|e| CdkRpcError::InternalError(format!("Failed to stop RPC server: {}", e)))?;
+        self.handle.stopped().await;
+        info!("CDK RPC server stopped");
+        Ok(())
+    }
+}
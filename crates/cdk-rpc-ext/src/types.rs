@@ -2,7 +2,7 @@
 
 use alloy_primitives::U256;
 use serde::{Deserialize, Serialize};
-use cdk_types::{Batch, BatchId, Epoch};
+use cdk_types::{Batch, BatchId, Epoch, FinalityTag};
 
 /// Request to get batch by number
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,7 +44,7 @@ pub struct CdkMetrics {
 pub struct FinalizedBatchResponse {
     /// Finalized batch ID
     pub batch_id: BatchId,
-    /// Finality status
+    /// Finality status, as returned by `cdk_getFinalityStatus`
     pub status: String,
     /// L1 block number
     pub l1_block: U256,
@@ -52,6 +52,17 @@ pub struct FinalizedBatchResponse {
     pub timestamp: u64,
 }
 
+impl From<&FinalityTag> for FinalizedBatchResponse {
+    fn from(tag: &FinalityTag) -> Self {
+        Self {
+            batch_id: BatchId::new(tag.batch_id, tag.l1_block_hash),
+            status: tag.status.as_str().to_string(),
+            l1_block: tag.l1_block,
+            timestamp: tag.timestamp,
+        }
+    }
+}
+
 /// Batch response with additional metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchResponse {
@@ -95,3 +106,34 @@ pub struct EpochMetadata {
     /// Average batch size
     pub avg_batch_size_bytes: u64,
 }
+
+/// Health of a single subsystem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    /// Whether the component passed its health check
+    pub healthy: bool,
+    /// Error reported by the component when unhealthy
+    pub error: Option<String>,
+}
+
+impl<E: std::fmt::Display> From<Result<(), E>> for ComponentHealth {
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self { healthy: true, error: None },
+            Err(e) => Self { healthy: false, error: Some(e.to_string()) },
+        }
+    }
+}
+
+/// Aggregated health of the CDK subsystems
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Batch source health
+    pub batch_source: ComponentHealth,
+    /// Finality oracle health
+    pub finality_oracle: ComponentHealth,
+    /// Mapping storage health
+    pub mapping_storage: ComponentHealth,
+    /// Whether every component is healthy
+    pub healthy: bool,
+}
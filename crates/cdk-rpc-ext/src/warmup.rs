@@ -0,0 +1,99 @@
+//! Cache warmup from the ingest mapping storage and the finality oracle
+
+use alloy_primitives::U256;
+use async_trait::async_trait;
+use cdk_finality::FinalityOracle;
+use cdk_ingest::MappingStorage;
+use cdk_observe::{CacheWarmupSource, ObservabilityError, ObservabilityResult};
+use cdk_types::{Epoch, EpochId, FinalityStatus, FinalityTag};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+/// Warmup source reading epochs from the mapping storage and finality tags from the oracle
+///
+/// The mapping storage keeps no batch bodies, so no batches are preloaded.
+/// `CdkRpcApiImpl::warmup_cache` only warms up the epoch and finality caches.
+pub struct StorageWarmupSource<'a> {
+    mapping_storage: &'a dyn MappingStorage,
+    finality_oracle: &'a (dyn FinalityOracle + Send + Sync),
+}
+
+/// Batches and timestamps of an epoch seen in the batch mappings
+struct EpochSpan {
+    first_batch: u64,
+    last_batch: u64,
+    start_timestamp: u64,
+    end_timestamp: u64,
+}
+
+impl<'a> StorageWarmupSource<'a> {
+    /// Create a warmup source over `mapping_storage` and `finality_oracle`
+    pub fn new(
+        mapping_storage: &'a dyn MappingStorage,
+        finality_oracle: &'a (dyn FinalityOracle + Send + Sync),
+    ) -> Self {
+        Self { mapping_storage, finality_oracle }
+    }
+}
+
+#[async_trait]
+impl CacheWarmupSource for StorageWarmupSource<'_> {
+    /// Epochs are rebuilt from their epoch mapping, with the batch span and
+    /// timestamps of their batch mappings within `batches`
+    async fn load_epochs(&self, batches: RangeInclusive<u64>, limit: usize) -> ObservabilityResult<Vec<Epoch>> {
+        let batch_mappings = self
+            .mapping_storage
+            .get_batch_mappings_range(*batches.start(), *batches.end())
+            .await
+            .map_err(cache_error)?;
+
+        let mut spans: BTreeMap<u64, EpochSpan> = BTreeMap::new();
+        for mapping in batch_mappings {
+            let span = spans.entry(mapping.epoch_id).or_insert(EpochSpan {
+                first_batch: mapping.batch_id,
+                last_batch: mapping.batch_id,
+                start_timestamp: mapping.timestamp,
+                end_timestamp: mapping.timestamp,
+            });
+            span.first_batch = span.first_batch.min(mapping.batch_id);
+            span.last_batch = span.last_batch.max(mapping.batch_id);
+            span.start_timestamp = span.start_timestamp.min(mapping.timestamp);
+            span.end_timestamp = span.end_timestamp.max(mapping.timestamp);
+        }
+
+        let mut epochs = Vec::new();
+        for (epoch_id, span) in spans.into_iter().rev().take(limit) {
+            let Some(mapping) = self.mapping_storage.load_epoch_mapping(epoch_id).await.map_err(cache_error)? else {
+                continue;
+            };
+            epochs.push(Epoch::new(
+                EpochId::new(U256::from(epoch_id), mapping.epoch_hash),
+                U256::from(mapping.start_block),
+                U256::from(mapping.end_block),
+                U256::from(span.first_batch),
+                U256::from(span.last_batch),
+                span.start_timestamp,
+                span.end_timestamp,
+            ));
+        }
+        epochs.reverse();
+        Ok(epochs)
+    }
+
+    async fn load_finality_tags(&self, batches: RangeInclusive<u64>) -> ObservabilityResult<Vec<FinalityTag>> {
+        let mut tags: Vec<FinalityTag> = self
+            .finality_oracle
+            .get_finalized_batches()
+            .await
+            .map_err(cache_error)?
+            .into_iter()
+            .filter(|tag| tag.status == FinalityStatus::Finalized)
+            .filter(|tag| u64::try_from(tag.batch_id).is_ok_and(|batch_id| batches.contains(&batch_id)))
+            .collect();
+        tags.sort_by_key(|tag| tag.batch_id);
+        Ok(tags)
+    }
+}
+
+fn cache_error(err: impl std::fmt::Display) -> ObservabilityError {
+    ObservabilityError::CacheError(err.to_string())
+}
@@ -4,7 +4,7 @@ use cdk_rpc_ext::{
     CdkRpcConfig, CdkRpcServer,
     CdkRpcError, CdkRpcResult,
 };
-use cdk_datastream::{BatchSource, Checkpoint, DatastreamError, SourceMetadata};
+use cdk_datastream::{BatchSource, BatchStream, Checkpoint, DatastreamError, SourceMetadata};
 use cdk_ingest::{MappingStorage, IngestError, BlockMapping, BatchMapping, EpochMapping};
 use cdk_finality::{FinalityOracle, FinalityError, OracleMetadata};
 use alloy_primitives::{FixedBytes, U256, Address};
@@ -17,14 +17,28 @@ use tokio_test;
 #[derive(Debug)]
 struct MockBatchSource {
     batches: HashMap<U256, cdk_types::Batch>,
+    checkpoint_delay: Duration,
+    chain_id: u64,
 }
 
 impl MockBatchSource {
     fn new() -> Self {
         Self {
             batches: HashMap::new(),
+            checkpoint_delay: Duration::ZERO,
+            chain_id: 0,
         }
     }
+
+    fn with_checkpoint_delay(mut self, delay: Duration) -> Self {
+        self.checkpoint_delay = delay;
+        self
+    }
+
+    fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
 }
 
 #[async_trait]
@@ -34,6 +48,7 @@ impl BatchSource for MockBatchSource {
     }
     
     async fn checkpoint(&self) -> Result<Checkpoint, DatastreamError> {
+        tokio::time::sleep(self.checkpoint_delay).await;
         Ok(Checkpoint::new(
             U256::from(0),
             FixedBytes::from([0u8; 32]),
@@ -56,7 +71,12 @@ impl BatchSource for MockBatchSource {
             "1.0.0".to_string(),
             "mock://test".to_string(),
             true,
-        ))
+        )
+        .with_chain_id(self.chain_id))
+    }
+
+    async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> Result<BatchStream, DatastreamError> {
+        Ok(Box::new(futures::stream::empty()))
     }
 }
 
@@ -109,6 +129,26 @@ impl MappingStorage for MockMappingStorage {
         Ok(vec![])
     }
 
+    async fn get_blocks_for_batch(&self, _batch_id: u64) -> Result<Vec<u64>, IngestError> {
+        Ok(vec![])
+    }
+
+    async fn batch_mapping_count(&self) -> Result<u64, IngestError> {
+        Ok(0)
+    }
+
+    async fn latest_batch_mapping(&self) -> Result<Option<BatchMapping>, IngestError> {
+        Ok(None)
+    }
+
+    async fn epoch_mapping_count(&self) -> Result<u64, IngestError> {
+        Ok(0)
+    }
+
+    async fn latest_epoch_mapping(&self) -> Result<Option<EpochMapping>, IngestError> {
+        Ok(None)
+    }
+
     async fn delete_block_mapping(&self, _block_number: u64) -> Result<(), IngestError> {
         Ok(())
     }
@@ -175,6 +215,60 @@ impl FinalityOracle for MockFinalityOracle {
     }
 }
 
+/// Finality oracle whose poll drains tags pushed through a shared handle
+#[derive(Debug, Clone, Default)]
+struct QueuedFinalityOracle {
+    pending: std::sync::Arc<std::sync::Mutex<Vec<cdk_types::FinalityTag>>>,
+    poll_delay: Duration,
+}
+
+impl QueuedFinalityOracle {
+    fn finalize(&self, batch_id: u64) {
+        self.pending.lock().unwrap().push(cdk_types::FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(100),
+            FixedBytes::from([1u8; 32]),
+            cdk_types::FinalityStatus::Finalized,
+            1234567890,
+            None,
+        ));
+    }
+}
+
+#[async_trait]
+impl FinalityOracle for QueuedFinalityOracle {
+    async fn poll(&mut self) -> Result<Vec<cdk_types::FinalityTag>, FinalityError> {
+        tokio::time::sleep(self.poll_delay).await;
+        Ok(std::mem::take(&mut *self.pending.lock().unwrap()))
+    }
+
+    async fn get_finality_status(&self, _batch_id: u64) -> Result<Option<cdk_types::FinalityStatus>, FinalityError> {
+        Ok(None)
+    }
+
+    async fn get_finalized_batches(&self) -> Result<Vec<cdk_types::FinalityTag>, FinalityError> {
+        Ok(vec![])
+    }
+
+    async fn get_rolled_back_batches(&self) -> Result<Vec<cdk_types::FinalityTag>, FinalityError> {
+        Ok(vec![])
+    }
+
+    async fn health_check(&self) -> Result<(), FinalityError> {
+        Ok(())
+    }
+
+    async fn metadata(&self) -> Result<OracleMetadata, FinalityError> {
+        Ok(OracleMetadata::new("queued".to_string(), "1.0.0".to_string(), 1, Address::ZERO))
+    }
+
+    fn set_polling_interval(&mut self, _interval: Duration) {}
+
+    fn get_polling_interval(&self) -> Duration {
+        Duration::from_millis(20)
+    }
+}
+
 #[tokio::test]
 async fn test_server_config_default() {
     let config = CdkRpcConfig::default();
@@ -196,6 +290,9 @@ async fn test_server_config_custom() {
         max_batch_history: 500,
         max_epoch_history: 50,
         address: "127.0.0.1:8546".parse().unwrap(),
+        max_request_body_bytes: 1024,
+        max_concurrent_requests: 4,
+        expected_chain_id: Some(1),
     };
     
     assert!(!config.enable_batch_queries);
@@ -204,6 +301,28 @@ async fn test_server_config_custom() {
     assert!(config.enable_metrics);
     assert_eq!(config.max_batch_history, 500);
     assert_eq!(config.max_epoch_history, 50);
+    assert_eq!(config.max_request_body_bytes, 1024);
+    assert_eq!(config.max_concurrent_requests, 4);
+    assert_eq!(config.expected_chain_id, Some(1));
+}
+
+#[tokio::test]
+async fn test_server_start_rejects_source_for_other_chain() {
+    let config = CdkRpcConfig {
+        address: "127.0.0.1:0".parse().unwrap(),
+        expected_chain_id: Some(1),
+        ..Default::default()
+    };
+    let server = CdkRpcServer::new(
+        config,
+        Box::new(MockBatchSource::new().with_chain_id(2)),
+        Box::new(MockMappingStorage::new()),
+        Box::new(MockFinalityOracle::new()),
+        "http://localhost:8545".to_string(),
+    ).await.unwrap();
+
+    let result = server.start().await;
+    assert!(matches!(result, Err(CdkRpcError::DataSourceError(message)) if message.contains("Chain id mismatch")));
 }
 
 #[tokio::test]
@@ -244,6 +363,200 @@ async fn test_rpc_module_creation() {
     assert!(true);
 }
 
+#[tokio::test]
+async fn test_server_serves_json_rpc_over_http() {
+    use jsonrpsee::{core::client::ClientT, http_client::HttpClientBuilder, rpc_params};
+
+    let config = CdkRpcConfig {
+        address: "127.0.0.1:0".parse().unwrap(),
+        ..Default::default()
+    };
+    let server = CdkRpcServer::new(
+        config,
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(MockFinalityOracle::new()),
+        "http://localhost:8545".to_string(),
+    ).await.unwrap();
+    let running = server.start().await.unwrap();
+
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}", running.local_addr()))
+        .unwrap();
+
+    let metrics: cdk_rpc_ext::CdkMetrics = client.request("cdk_metrics", rpc_params![]).await.unwrap();
+    assert_eq!(metrics.total_batches, 0);
+    assert_eq!(metrics.reorg_count, 0);
+
+    let batch: Option<cdk_rpc_ext::BatchResponse> =
+        client.request("cdk_getBatchByNumber", rpc_params!["0x1"]).await.unwrap();
+    assert!(batch.is_none());
+
+    let invalid: Result<Option<cdk_rpc_ext::BatchResponse>, _> =
+        client.request("cdk_getBatchByNumber", rpc_params!["invalid_hex"]).await;
+    assert!(invalid.is_err());
+
+    running.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_finalized_batches() {
+    use jsonrpsee::{core::client::SubscriptionClientT, rpc_params, ws_client::WsClientBuilder};
+
+    let oracle = QueuedFinalityOracle::default();
+    let config = CdkRpcConfig {
+        address: "127.0.0.1:0".parse().unwrap(),
+        ..Default::default()
+    };
+    let server = CdkRpcServer::new(
+        config,
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(oracle.clone()),
+        "http://localhost:8545".to_string(),
+    ).await.unwrap();
+    let running = server.start().await.unwrap();
+
+    let client = WsClientBuilder::default()
+        .build(format!("ws://{}", running.local_addr()))
+        .await
+        .unwrap();
+    let mut subscription = client
+        .subscribe::<cdk_rpc_ext::FinalizedBatchResponse, _>(
+            "cdk_subscribeFinalizedBatches",
+            rpc_params![],
+            "cdk_unsubscribeFinalizedBatches",
+        )
+        .await
+        .unwrap();
+
+    oracle.finalize(7);
+
+    let event = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .expect("no finalized batch event received")
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.batch_id.number, U256::from(7));
+    assert_eq!(event.status, "finalized");
+
+    running.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_slow_finality_poll_does_not_block_requests() {
+    use jsonrpsee::{core::client::ClientT, http_client::HttpClientBuilder, rpc_params};
+
+    let oracle = QueuedFinalityOracle {
+        poll_delay: Duration::from_secs(30),
+        ..Default::default()
+    };
+    let config = CdkRpcConfig {
+        address: "127.0.0.1:0".parse().unwrap(),
+        ..Default::default()
+    };
+    let server = CdkRpcServer::new(
+        config,
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(oracle),
+        "http://localhost:8545".to_string(),
+    ).await.unwrap();
+    let running = server.start().await.unwrap();
+
+    // Let the poll loop start its first, stalled poll
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}", running.local_addr()))
+        .unwrap();
+    let batch = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.request::<Option<cdk_rpc_ext::BatchResponse>, _>("cdk_getBatchByNumber", rpc_params!["0x1"]),
+    )
+    .await
+    .expect("request blocked behind the finality poll");
+    assert!(batch.unwrap().is_none());
+
+    running.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_excess_concurrent_requests_rejected() {
+    use jsonrpsee::{
+        core::{client::ClientT, ClientError},
+        http_client::HttpClientBuilder,
+        rpc_params,
+        types::error::SERVER_IS_BUSY_CODE,
+    };
+
+    let config = CdkRpcConfig {
+        address: "127.0.0.1:0".parse().unwrap(),
+        max_concurrent_requests: 2,
+        ..Default::default()
+    };
+    let server = CdkRpcServer::new(
+        config,
+        Box::new(MockBatchSource::new().with_checkpoint_delay(Duration::from_millis(500))),
+        Box::new(MockMappingStorage::new()),
+        Box::new(MockFinalityOracle::new()),
+        "http://localhost:8545".to_string(),
+    ).await.unwrap();
+    let running = server.start().await.unwrap();
+    let url = format!("http://{}", running.local_addr());
+
+    let calls = (0..6).map(|_| {
+        let client = HttpClientBuilder::default().build(&url).unwrap();
+        async move { client.request::<cdk_rpc_ext::CdkMetrics, _>("cdk_metrics", rpc_params![]).await }
+    });
+    let results = tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(calls))
+        .await
+        .expect("requests were queued instead of rejected");
+
+    let succeeded = results.iter().filter(|result| result.is_ok()).count();
+    let rejected = results
+        .iter()
+        .filter(|result| matches!(result, Err(ClientError::Call(err)) if err.code() == SERVER_IS_BUSY_CODE))
+        .count();
+    assert!((1..=2).contains(&succeeded), "succeeded: {}", succeeded);
+    assert_eq!(succeeded + rejected, 6);
+
+    running.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_oversized_request_rejected() {
+    use jsonrpsee::{core::client::ClientT, http_client::HttpClientBuilder, rpc_params};
+
+    let config = CdkRpcConfig {
+        address: "127.0.0.1:0".parse().unwrap(),
+        max_request_body_bytes: 1024,
+        ..Default::default()
+    };
+    let server = CdkRpcServer::new(
+        config,
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(MockFinalityOracle::new()),
+        "http://localhost:8545".to_string(),
+    ).await.unwrap();
+    let running = server.start().await.unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}", running.local_addr()))
+        .unwrap();
+
+    let oversized = format!("0x{}", "1".repeat(2048));
+    let result: Result<Option<cdk_rpc_ext::BatchResponse>, _> =
+        client.request("cdk_getBatchByNumber", rpc_params![oversized]).await;
+    assert!(result.is_err());
+
+    let batch: Option<cdk_rpc_ext::BatchResponse> =
+        client.request("cdk_getBatchByNumber", rpc_params!["0x1"]).await.unwrap();
+    assert!(batch.is_none());
+
+    running.stop().await.unwrap();
+}
+
 #[tokio::test]
 async fn test_error_conversions() {
     // Test error conversion from datastream error
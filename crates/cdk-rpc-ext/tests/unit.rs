@@ -1,17 +1,21 @@
 //! Unit tests for CDK RPC Extensions
 
 use cdk_rpc_ext::{
-    CdkRpcApi, CdkRpcApiImpl, CdkRpcConfig, CdkRpcServer,
+    CdkRpcApi, CdkRpcApiImpl, CdkRpcConfig, CdkRpcServer, IngestCounters,
     CdkRpcError, CdkRpcResult,
     types::*,
 };
 use cdk_types::{Batch, BatchId, Epoch, EpochId, FinalityTag, FinalityStatus, ProofMetadata};
-use cdk_datastream::{BatchSource, Checkpoint, DatastreamError, SourceMetadata};
+use cdk_datastream::{BatchSource, BatchStream, Checkpoint, DatastreamError, SourceMetadata};
 use cdk_ingest::{MappingStorage, IngestError, BlockMapping, BatchMapping, EpochMapping};
 use cdk_finality::{FinalityOracle, FinalityError, OracleMetadata};
 use alloy_primitives::{FixedBytes, U256, Address};
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 use std::time::Duration;
 use tokio_test;
 
@@ -19,12 +23,19 @@ use tokio_test;
 #[derive(Debug)]
 struct MockBatchSource {
     batches: HashMap<U256, Batch>,
+    checkpoint: Checkpoint,
 }
 
 impl MockBatchSource {
     fn new() -> Self {
         Self {
             batches: HashMap::new(),
+            checkpoint: Checkpoint::new(
+                U256::from(0),
+                FixedBytes::from([0u8; 32]),
+                U256::from(0),
+                0,
+            ),
         }
     }
     
@@ -40,12 +51,7 @@ impl BatchSource for MockBatchSource {
     }
     
     async fn checkpoint(&self) -> Result<Checkpoint, DatastreamError> {
-        Ok(Checkpoint::new(
-            U256::from(0),
-            FixedBytes::from([0u8; 32]),
-            U256::from(0),
-            0,
-        ))
+        Ok(self.checkpoint.clone())
     }
 
     async fn set_checkpoint(&mut self, _checkpoint: Checkpoint) -> Result<(), DatastreamError> {
@@ -64,12 +70,18 @@ impl BatchSource for MockBatchSource {
             true,
         ))
     }
+
+    async fn fetch_batch_stream(&self, _start_batch_number: Option<u64>) -> Result<BatchStream, DatastreamError> {
+        Ok(Box::new(futures::stream::empty()))
+    }
 }
 
 #[derive(Debug)]
 struct MockMappingStorage {
     block_to_epoch: HashMap<U256, EpochId>,
     epochs: HashMap<EpochId, Epoch>,
+    batch_mappings: Vec<BatchMapping>,
+    epoch_mappings: Vec<EpochMapping>,
 }
 
 impl MockMappingStorage {
@@ -77,6 +89,8 @@ impl MockMappingStorage {
         Self {
             block_to_epoch: HashMap::new(),
             epochs: HashMap::new(),
+            batch_mappings: vec![],
+            epoch_mappings: vec![],
         }
     }
     
@@ -115,10 +129,35 @@ impl MappingStorage for MockMappingStorage {
         Ok(vec![])
     }
 
-    async fn get_batch_mappings_range(&self, _start_batch: u64, _end_batch: u64) -> Result<Vec<BatchMapping>, IngestError> {
+    async fn get_batch_mappings_range(&self, start_batch: u64, end_batch: u64) -> Result<Vec<BatchMapping>, IngestError> {
+        Ok(self
+            .batch_mappings
+            .iter()
+            .filter(|mapping| mapping.batch_id >= start_batch && mapping.batch_id <= end_batch)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_blocks_for_batch(&self, _batch_id: u64) -> Result<Vec<u64>, IngestError> {
         Ok(vec![])
     }
 
+    async fn batch_mapping_count(&self) -> Result<u64, IngestError> {
+        Ok(self.batch_mappings.len() as u64)
+    }
+
+    async fn latest_batch_mapping(&self) -> Result<Option<BatchMapping>, IngestError> {
+        Ok(self.batch_mappings.iter().max_by_key(|mapping| mapping.batch_id).cloned())
+    }
+
+    async fn epoch_mapping_count(&self) -> Result<u64, IngestError> {
+        Ok(self.epoch_mappings.len() as u64)
+    }
+
+    async fn latest_epoch_mapping(&self) -> Result<Option<EpochMapping>, IngestError> {
+        Ok(self.epoch_mappings.iter().max_by_key(|mapping| mapping.epoch_id).cloned())
+    }
+
     async fn delete_block_mapping(&self, _block_number: u64) -> Result<(), IngestError> {
         Ok(())
     }
@@ -135,12 +174,18 @@ impl MappingStorage for MockMappingStorage {
 #[derive(Debug)]
 struct MockFinalityOracle {
     finality_tags: Vec<FinalityTag>,
+    current_l1_block: u64,
+    healthy: bool,
+    polls: Arc<AtomicUsize>,
 }
 
 impl MockFinalityOracle {
     fn new() -> Self {
         Self {
             finality_tags: vec![],
+            current_l1_block: 0,
+            healthy: true,
+            polls: Arc::new(AtomicUsize::new(0)),
         }
     }
     
@@ -152,11 +197,16 @@ impl MockFinalityOracle {
 #[async_trait]
 impl FinalityOracle for MockFinalityOracle {
     async fn poll(&mut self) -> Result<Vec<FinalityTag>, FinalityError> {
+        self.polls.fetch_add(1, Ordering::SeqCst);
         Ok(self.finality_tags.clone())
     }
 
-    async fn get_finality_status(&self, _batch_id: u64) -> Result<Option<FinalityStatus>, FinalityError> {
-        Ok(None)
+    async fn get_finality_status(&self, batch_id: u64) -> Result<Option<FinalityStatus>, FinalityError> {
+        Ok(self
+            .finality_tags
+            .iter()
+            .find(|tag| tag.batch_id == U256::from(batch_id))
+            .map(|tag| tag.status.clone()))
     }
 
     async fn get_finalized_batches(&self) -> Result<Vec<FinalityTag>, FinalityError> {
@@ -168,7 +218,11 @@ impl FinalityOracle for MockFinalityOracle {
     }
 
     async fn health_check(&self) -> Result<(), FinalityError> {
-        Ok(())
+        if self.healthy {
+            Ok(())
+        } else {
+            Err(FinalityError::HealthCheckError("L1 unreachable".to_string()))
+        }
     }
 
     async fn metadata(&self) -> Result<OracleMetadata, FinalityError> {
@@ -177,7 +231,7 @@ impl FinalityOracle for MockFinalityOracle {
             "1.0.0".to_string(),
             1,
             Address::ZERO,
-        ))
+        ).update_l1_block(self.current_l1_block))
     }
 
     fn set_polling_interval(&mut self, _interval: Duration) {
@@ -233,6 +287,27 @@ async fn test_get_batch_by_number_invalid_hex() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_get_batch_by_number_disabled() {
+    let config = CdkRpcConfig {
+        enable_batch_queries: false,
+        ..Default::default()
+    };
+    let api = CdkRpcApiImpl::new(
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(MockFinalityOracle::new()),
+    )
+    .with_config(config);
+
+    let result = api.get_batch_by_number("0x1".to_string()).await;
+    assert!(matches!(result, Err(CdkRpcError::MethodDisabled(_))));
+
+    // Other method groups stay available
+    assert!(api.get_epoch_by_block("0x64".to_string()).await.is_ok());
+    assert!(api.metrics().await.is_ok());
+}
+
 #[tokio::test]
 async fn test_get_epoch_by_block_success() {
     let batch_source = MockBatchSource::new();
@@ -312,6 +387,79 @@ async fn test_finalized_batch_no_tags() {
     assert!(response.is_none());
 }
 
+#[tokio::test]
+async fn test_finalized_batch_does_not_poll_oracle() {
+    let mut finality_oracle = MockFinalityOracle::new();
+    let polls = finality_oracle.polls.clone();
+    for batch_id in [1u64, 3, 2] {
+        finality_oracle.add_finality_tag(FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(100),
+            FixedBytes::from([1u8; 32]),
+            FinalityStatus::Finalized,
+            1234567890,
+            None,
+        ));
+    }
+    let api = CdkRpcApiImpl::new(
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(finality_oracle),
+    );
+
+    let response = api.finalized_batch().await.unwrap().unwrap();
+    assert_eq!(response.batch_id.number, U256::from(3));
+    assert_eq!(polls.load(Ordering::SeqCst), 0);
+
+    // Only the subscription poll loop advances the oracle
+    assert_eq!(api.poll_finalized_batches().await.unwrap().len(), 3);
+    assert_eq!(polls.load(Ordering::SeqCst), 1);
+}
+
+fn api_with_statuses(statuses: &[(u64, FinalityStatus)]) -> CdkRpcApiImpl {
+    let mut finality_oracle = MockFinalityOracle::new();
+    for (batch_id, status) in statuses {
+        finality_oracle.add_finality_tag(FinalityTag::new(
+            U256::from(*batch_id),
+            U256::from(100),
+            FixedBytes::from([1u8; 32]),
+            status.clone(),
+            1234567890,
+            None,
+        ));
+    }
+
+    CdkRpcApiImpl::new(
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(finality_oracle),
+    )
+}
+
+#[tokio::test]
+async fn test_get_finality_status_finalized() {
+    let api = api_with_statuses(&[(1, FinalityStatus::Finalized), (2, FinalityStatus::Pending)]);
+
+    let status = api.get_finality_status("0x1".to_string()).await.unwrap();
+    assert_eq!(status.as_deref(), Some("finalized"));
+}
+
+#[tokio::test]
+async fn test_get_finality_status_pending() {
+    let api = api_with_statuses(&[(1, FinalityStatus::Finalized), (2, FinalityStatus::Pending)]);
+
+    let status = api.get_finality_status("0x2".to_string()).await.unwrap();
+    assert_eq!(status.as_deref(), Some("pending"));
+}
+
+#[tokio::test]
+async fn test_get_finality_status_unknown() {
+    let api = api_with_statuses(&[(1, FinalityStatus::Finalized)]);
+
+    assert_eq!(api.get_finality_status("0x3".to_string()).await.unwrap(), None);
+    assert!(api.get_finality_status("invalid_hex".to_string()).await.is_err());
+}
+
 #[tokio::test]
 async fn test_metrics() {
     let batch_source = MockBatchSource::new();
@@ -332,3 +480,169 @@ async fn test_metrics() {
     assert_eq!(metrics.reorg_count, 0);
     assert_eq!(metrics.ingest_tps, 0.0);
 }
+
+#[tokio::test]
+async fn test_metrics_populated_from_sources() {
+    let mut batch_source = MockBatchSource::new();
+    batch_source.checkpoint = Checkpoint::new(
+        U256::from(7),
+        FixedBytes::from([7u8; 32]),
+        U256::from(1000),
+        1234567890,
+    );
+
+    let mut mapping_storage = MockMappingStorage::new();
+    for (batch_id, epoch_id) in [(5u64, 1u64), (6, 1), (7, 2)] {
+        mapping_storage.batch_mappings.push(BatchMapping {
+            batch_id,
+            batch_hash: FixedBytes::from([batch_id as u8; 32]),
+            start_block: batch_id * 10,
+            end_block: batch_id * 10 + 9,
+            block_count: 10,
+            epoch_id,
+            timestamp: 1234567890,
+        });
+    }
+    for epoch_id in [1u64, 2] {
+        mapping_storage.epoch_mappings.push(EpochMapping {
+            epoch_id,
+            epoch_hash: FixedBytes::from([epoch_id as u8; 32]),
+            start_block: epoch_id * 30,
+            end_block: epoch_id * 30 + 29,
+            block_count: 30,
+            batch_count: 3,
+            timestamp: 1234567890,
+        });
+    }
+
+    let mut finality_oracle = MockFinalityOracle::new();
+    finality_oracle.current_l1_block = 1012;
+    for batch_id in [4u64, 5] {
+        finality_oracle.add_finality_tag(FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(990),
+            FixedBytes::from([1u8; 32]),
+            FinalityStatus::Finalized,
+            1234567890,
+            None,
+        ));
+    }
+
+    let counters = IngestCounters::new();
+    counters.increment_reorg_count();
+    counters.increment_reorg_count();
+    counters.set_ingest_tps(12.5);
+
+    let api = CdkRpcApiImpl::new(
+        Box::new(batch_source),
+        Box::new(mapping_storage),
+        Box::new(finality_oracle),
+    )
+    .with_counters(counters);
+
+    let metrics = api.metrics().await.unwrap();
+    assert_eq!(metrics.total_batches, 3);
+    assert_eq!(metrics.total_epochs, 2);
+    assert_eq!(metrics.latest_batch, Some(U256::from(7)));
+    assert_eq!(metrics.latest_epoch, Some(U256::from(2)));
+    assert_eq!(metrics.latest_finalized_batch, Some(U256::from(5)));
+    assert_eq!(metrics.l1_lag, Some(12));
+    assert_eq!(metrics.reorg_count, 2);
+    assert_eq!(metrics.ingest_tps, 12.5);
+}
+
+#[tokio::test]
+async fn test_health_reports_degraded_component() {
+    let mut finality_oracle = MockFinalityOracle::new();
+    finality_oracle.healthy = false;
+
+    let api = CdkRpcApiImpl::new(
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(finality_oracle),
+    );
+
+    let report = api.health().await.unwrap();
+    assert!(report.batch_source.healthy);
+    assert!(report.mapping_storage.healthy);
+    assert!(!report.finality_oracle.healthy);
+    assert!(report.finality_oracle.error.as_deref().unwrap().contains("L1 unreachable"));
+    assert!(!report.healthy);
+}
+
+#[tokio::test]
+async fn test_health_all_components_healthy() {
+    let api = CdkRpcApiImpl::new(
+        Box::new(MockBatchSource::new()),
+        Box::new(MockMappingStorage::new()),
+        Box::new(MockFinalityOracle::new()),
+    );
+
+    let report = api.health().await.unwrap();
+    assert!(report.healthy);
+    assert_eq!(report.finality_oracle.error, None);
+}
+
+#[tokio::test]
+async fn test_warmup_cache_serves_finality_from_cache() {
+    let mapping_storage = cdk_ingest::MemoryMappingStorage::default();
+    for batch_id in 0..6u64 {
+        mapping_storage
+            .save_batch_mapping(BatchMapping {
+                batch_id,
+                batch_hash: FixedBytes::from([batch_id as u8; 32]),
+                start_block: batch_id * 10,
+                end_block: batch_id * 10 + 9,
+                block_count: 10,
+                epoch_id: batch_id / 3,
+                timestamp: 1234567890 + batch_id,
+            })
+            .await
+            .unwrap();
+    }
+    for epoch_id in 0..2u64 {
+        mapping_storage
+            .save_epoch_mapping(EpochMapping {
+                epoch_id,
+                epoch_hash: FixedBytes::from([epoch_id as u8; 32]),
+                start_block: epoch_id * 30,
+                end_block: epoch_id * 30 + 29,
+                block_count: 30,
+                batch_count: 3,
+                timestamp: 1234567890,
+            })
+            .await
+            .unwrap();
+    }
+
+    let mut finality_oracle = MockFinalityOracle::new();
+    for batch_id in [1u64, 2] {
+        finality_oracle.add_finality_tag(FinalityTag::new(
+            U256::from(batch_id),
+            U256::from(100),
+            FixedBytes::from([1u8; 32]),
+            FinalityStatus::Finalized,
+            1234567890,
+            None,
+        ));
+    }
+
+    // Room for the last four batches only
+    let config = CdkRpcConfig { max_batch_history: 4, ..CdkRpcConfig::default() };
+    let api = CdkRpcApiImpl::new(
+        Box::new(MockBatchSource::new()),
+        Box::new(mapping_storage),
+        Box::new(finality_oracle),
+    )
+    .with_config(config);
+
+    assert_eq!(api.warmup_cache().await.unwrap(), (2, 1));
+
+    let epoch = api.cache().get_epoch(1).await.unwrap();
+    assert_eq!((epoch.start_batch, epoch.end_batch), (U256::from(3), U256::from(5)));
+    assert_eq!((epoch.start_timestamp, epoch.end_timestamp), (1234567893, 1234567895));
+    assert_eq!(api.get_finality_status("0x2".to_string()).await.unwrap().as_deref(), Some("finalized"));
+
+    let stats = api.cache().get_stats();
+    assert_eq!((stats.hits, stats.misses), (2, 0));
+}
@@ -1,51 +1,366 @@
 //! Database converter for Reth <-> Erigon MDBX interoperability
 
-use crate::{SnapResult, SnapError, SnapRecord, SnapMetadata, DatabaseType, ConversionOptions};
-use std::path::Path;
-use tokio::fs;
+use crate::{
+    format::{encode_footer, encode_header, encode_record, SourceReader},
+    validator::SnapValidator,
+    ConversionCheckpoint, ConversionOptions, ConversionProgress, DatabaseType, SnapError, SnapMetadata, SnapRecord,
+    SnapResult, CDK_SNAP_VERSION,
+};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufWriter},
+    sync::mpsc,
+};
 
 /// Database converter trait
 #[async_trait::async_trait]
-pub trait DatabaseConverter {
+pub trait DatabaseConverter: Send + Sync {
     /// Convert from source to target format
-    async fn convert(&self, source_path: &Path, target_path: &Path, options: &ConversionOptions) -> SnapResult<SnapMetadata>;
-    
+    async fn convert(&self, source_path: &Path, target_path: &Path, options: &ConversionOptions) -> SnapResult<SnapMetadata> {
+        self.convert_with_progress(source_path, target_path, options, None).await
+    }
+
+    /// Convert from source to target format, reporting progress every
+    /// `options.progress_interval` records and once more when the run ends
+    ///
+    /// The conversion waits for room in the channel, so the receiver must be
+    /// drained while it runs.
+    async fn convert_with_progress(
+        &self,
+        source_path: &Path,
+        target_path: &Path,
+        options: &ConversionOptions,
+        progress: Option<mpsc::Sender<ConversionProgress>>,
+    ) -> SnapResult<SnapMetadata>;
+
     /// Validate conversion
     async fn validate(&self, source_path: &Path, target_path: &Path) -> SnapResult<bool>;
 }
 
+/// Path of the sidecar file tracking the progress of a conversion into `target_path`
+pub fn progress_path(target_path: &Path) -> PathBuf {
+    let mut path = target_path.as_os_str().to_owned();
+    path.push(".progress");
+    PathBuf::from(path)
+}
+
+/// Path of the temporary file written by a conversion into `target_path`
+pub fn temp_path(target_path: &Path) -> PathBuf {
+    target_path.with_extension("tmp")
+}
+
+/// Size of the buffer used to hash converted output
+const CHECKSUM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Compute the SHA-256 checksum of a file without loading it into memory
+async fn file_checksum(path: &Path) -> SnapResult<String> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; CHECKSUM_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Load the progress file of an interrupted conversion, if any
+async fn load_checkpoint(progress_path: &Path) -> SnapResult<Option<ConversionCheckpoint>> {
+    match fs::read(progress_path).await {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Atomically replace the progress file
+async fn save_checkpoint(progress_path: &Path, checkpoint: &ConversionCheckpoint) -> SnapResult<()> {
+    let tmp_path = progress_path.with_extension("progress.tmp");
+    fs::write(&tmp_path, serde_json::to_vec(checkpoint)?).await?;
+    fs::rename(&tmp_path, progress_path).await?;
+    Ok(())
+}
+
+/// Build the worker pool encoding records
+fn encoding_pool(options: &ConversionOptions) -> SnapResult<Arc<rayon::ThreadPool>> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(options.parallelism.unwrap_or(0))
+        .build()
+        .map(Arc::new)
+        .map_err(|e| SnapError::Conversion(format!("Failed to start encoding workers: {}", e)))
+}
+
+/// Encode a chunk of records on the worker pool, keeping their order
+async fn encode_chunk(pool: &Arc<rayon::ThreadPool>, chunk: Vec<SnapRecord>, options: &ConversionOptions) -> SnapResult<Vec<Vec<u8>>> {
+    let pool = pool.clone();
+    let options = options.clone();
+    tokio::task::spawn_blocking(move || pool.install(|| chunk.par_iter().map(|record| encode_record(record, &options)).collect()))
+        .await
+        .map_err(|e| SnapError::Conversion(format!("Encoding task failed: {}", e)))?
+}
+
+/// Convert the records of `source_path` into `target_path`
+///
+/// The output starts with the snapshot header and, once the source is
+/// exhausted, ends with the metadata footer. It is written to `temp_path` and
+/// only renamed to `target_path` once complete, so a partial snapshot is never
+/// visible there. Progress is committed to a sidecar file every
+/// `options.progress_interval` records. With `options.resume` set and an
+/// existing temporary file, conversion continues after the last committed
+/// record; anything written past it is discarded. Otherwise a leftover
+/// temporary file and progress file are deleted first.
+///
+/// Records are read in chunks of `options.batch_size`, and each chunk is
+/// encoded in parallel, written in source order and flushed before the next
+/// one is read, so memory use is bounded by the chunk size rather than the
+/// size of the source.
+async fn convert_records(
+    source_path: &Path,
+    target_path: &Path,
+    options: &ConversionOptions,
+    source_type: DatabaseType,
+    target_type: DatabaseType,
+    progress: Option<mpsc::Sender<ConversionProgress>>,
+) -> SnapResult<SnapMetadata> {
+    let started = Instant::now();
+    tracing::info!("Source: {:?}", source_path);
+    tracing::info!("Target: {:?}", target_path);
+
+    if options.dry_run {
+        return estimate_records(source_path, options, source_type, target_type, progress, started).await;
+    }
+
+    // Create target directory if it doesn't exist
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let progress_path = progress_path(target_path);
+    let temp_path = temp_path(target_path);
+    let resume_from = if options.resume && fs::try_exists(&temp_path).await? {
+        load_checkpoint(&progress_path).await?
+    } else {
+        None
+    };
+    if resume_from.is_none() {
+        if fs::try_exists(&temp_path).await? {
+            tracing::warn!("Deleting stale temporary output {:?}", temp_path);
+            fs::remove_file(&temp_path).await?;
+        }
+        // A later resume must not pick up the progress of an earlier run
+        if fs::try_exists(&progress_path).await? {
+            fs::remove_file(&progress_path).await?;
+        }
+    }
+    let mut checkpoint = resume_from.unwrap_or_default();
+    if resume_from.is_some() {
+        tracing::info!("Resuming conversion after record {}", checkpoint.records_committed);
+    }
+
+    let mut reader = SourceReader::open(source_path).await?;
+    reader.skip(checkpoint.records_committed).await?;
+
+    let mut file = OpenOptions::new().create(true).write(true).truncate(false).open(&temp_path).await?;
+    file.set_len(checkpoint.bytes_committed).await?;
+    file.seek(SeekFrom::Start(checkpoint.bytes_committed)).await?;
+    let mut writer = BufWriter::new(file);
+    if checkpoint.bytes_committed == 0 {
+        let header = encode_header();
+        writer.write_all(&header).await?;
+        checkpoint.bytes_committed = header.len() as u64;
+    }
+
+    let pool = encoding_pool(options)?;
+
+    let progress_interval = options.progress_interval.max(1);
+    let batch_size = options.batch_size.max(1) as u64;
+    let mut remaining = options.record_limit.unwrap_or(u64::MAX);
+    let mut since_commit = 0;
+    let mut exhausted = false;
+
+    while remaining > 0 {
+        let chunk = reader.next_chunk(remaining.min(batch_size) as usize).await?;
+        if chunk.is_empty() {
+            exhausted = true;
+            break;
+        }
+        remaining -= chunk.len() as u64;
+
+        let mut in_flight = chunk.len() as u64;
+        for frame in encode_chunk(&pool, chunk, options).await? {
+            writer.write_all(&frame).await?;
+            checkpoint.records_committed += 1;
+            checkpoint.bytes_committed += frame.len() as u64;
+            in_flight -= 1;
+            since_commit += 1;
+
+            if since_commit >= progress_interval {
+                writer.flush().await?;
+                writer.get_ref().sync_data().await?;
+                save_checkpoint(&progress_path, &checkpoint).await?;
+                report_progress(progress.as_ref(), &checkpoint, in_flight, started).await;
+                since_commit = 0;
+            }
+        }
+        writer.flush().await?;
+    }
+
+    writer.flush().await?;
+    let metadata = SnapMetadata {
+        version: CDK_SNAP_VERSION,
+        timestamp: chrono::Utc::now().timestamp() as u64,
+        source_type,
+        target_type,
+        checksum: file_checksum(&temp_path).await?,
+        record_count: checkpoint.records_committed,
+        total_size: checkpoint.bytes_committed,
+    };
+
+    if exhausted {
+        writer.write_all(&encode_footer(&metadata)?).await?;
+        writer.flush().await?;
+        writer.get_ref().sync_data().await?;
+        drop(writer);
+        fs::rename(&temp_path, target_path).await?;
+        if fs::try_exists(&progress_path).await? {
+            fs::remove_file(&progress_path).await?;
+        }
+    } else {
+        writer.get_ref().sync_data().await?;
+        save_checkpoint(&progress_path, &checkpoint).await?;
+        tracing::info!("Conversion stopped after record {}", checkpoint.records_committed);
+    }
+    if since_commit > 0 {
+        report_progress(progress.as_ref(), &checkpoint, 0, started).await;
+    }
+
+    Ok(metadata)
+}
+
+/// Encode the records of `source_path` without writing anything
+///
+/// The returned metadata describes the output a real run would produce: the
+/// record count, the encoded (and compressed) size and its checksum.
+async fn estimate_records(
+    source_path: &Path,
+    options: &ConversionOptions,
+    source_type: DatabaseType,
+    target_type: DatabaseType,
+    progress: Option<mpsc::Sender<ConversionProgress>>,
+    started: Instant,
+) -> SnapResult<SnapMetadata> {
+    tracing::info!("Dry run, no target will be written");
+
+    let mut reader = SourceReader::open(source_path).await?;
+    let pool = encoding_pool(options)?;
+    let progress_interval = options.progress_interval.max(1);
+    let batch_size = options.batch_size.max(1) as u64;
+    let mut remaining = options.record_limit.unwrap_or(u64::MAX);
+    let header = encode_header();
+    let mut totals = ConversionCheckpoint {
+        records_committed: 0,
+        bytes_committed: header.len() as u64,
+    };
+    let mut since_report = 0;
+    let mut hasher = Sha256::new();
+    hasher.update(&header);
+
+    while remaining > 0 {
+        let chunk = reader.next_chunk(remaining.min(batch_size) as usize).await?;
+        if chunk.is_empty() {
+            break;
+        }
+        remaining -= chunk.len() as u64;
+
+        let mut in_flight = chunk.len() as u64;
+        for frame in encode_chunk(&pool, chunk, options).await? {
+            hasher.update(&frame);
+            totals.records_committed += 1;
+            totals.bytes_committed += frame.len() as u64;
+            in_flight -= 1;
+            since_report += 1;
+
+            if since_report >= progress_interval {
+                report_progress(progress.as_ref(), &totals, in_flight, started).await;
+                since_report = 0;
+            }
+        }
+    }
+    if since_report > 0 {
+        report_progress(progress.as_ref(), &totals, 0, started).await;
+    }
+
+    Ok(SnapMetadata {
+        version: CDK_SNAP_VERSION,
+        timestamp: chrono::Utc::now().timestamp() as u64,
+        source_type,
+        target_type,
+        checksum: format!("{:x}", hasher.finalize()),
+        record_count: totals.records_committed,
+        total_size: totals.bytes_committed,
+    })
+}
+
+/// Check that the target holds the same records as the source
+async fn validate_conversion(source_path: &Path, target_path: &Path) -> SnapResult<bool> {
+    match SnapValidator.compare_records(source_path, target_path).await? {
+        Some(divergence) => {
+            tracing::warn!("Conversion validation failed: {}", divergence);
+            Ok(false)
+        }
+        None => Ok(true),
+    }
+}
+
+/// Send a progress report, ignoring a receiver that has gone away
+async fn report_progress(
+    progress: Option<&mpsc::Sender<ConversionProgress>>,
+    checkpoint: &ConversionCheckpoint,
+    in_flight: u64,
+    started: Instant,
+) {
+    if let Some(sender) = progress {
+        let report = ConversionProgress {
+            records_done: checkpoint.records_committed,
+            bytes_done: checkpoint.bytes_committed,
+            records_in_flight: in_flight,
+            elapsed: started.elapsed(),
+        };
+        let _ = sender.send(report).await;
+    }
+}
+
 /// Reth to Erigon converter
 pub struct RethToErigonConverter;
 
 #[async_trait::async_trait]
 impl DatabaseConverter for RethToErigonConverter {
-    async fn convert(&self, source_path: &Path, target_path: &Path, options: &ConversionOptions) -> SnapResult<SnapMetadata> {
-        // Placeholder implementation
+    async fn convert_with_progress(
+        &self,
+        source_path: &Path,
+        target_path: &Path,
+        options: &ConversionOptions,
+        progress: Option<mpsc::Sender<ConversionProgress>>,
+    ) -> SnapResult<SnapMetadata> {
         tracing::info!("Converting Reth database to Erigon MDBX format");
-        tracing::info!("Source: {:?}", source_path);
-        tracing::info!("Target: {:?}", target_path);
-        
-        // Create target directory if it doesn't exist
-        if let Some(parent) = target_path.parent() {
-            fs::create_dir_all(parent).await?;
-        }
-        
-        // Placeholder metadata
-        Ok(SnapMetadata {
-            version: 1,
-            timestamp: chrono::Utc::now().timestamp() as u64,
-            source_type: DatabaseType::Reth,
-            target_type: DatabaseType::ErigonMdbx,
-            checksum: "placeholder_checksum".to_string(),
-            record_count: 0,
-            total_size: 0,
-        })
-    }
-    
+        convert_records(source_path, target_path, options, DatabaseType::Reth, DatabaseType::ErigonMdbx, progress).await
+    }
+
     async fn validate(&self, source_path: &Path, target_path: &Path) -> SnapResult<bool> {
-        // Placeholder validation
         tracing::info!("Validating Reth to Erigon conversion");
-        Ok(true)
+        validate_conversion(source_path, target_path).await
     }
 }
 
@@ -54,32 +369,61 @@ pub struct ErigonToRethConverter;
 
 #[async_trait::async_trait]
 impl DatabaseConverter for ErigonToRethConverter {
-    async fn convert(&self, source_path: &Path, target_path: &Path, options: &ConversionOptions) -> SnapResult<SnapMetadata> {
-        // Placeholder implementation
+    async fn convert_with_progress(
+        &self,
+        source_path: &Path,
+        target_path: &Path,
+        options: &ConversionOptions,
+        progress: Option<mpsc::Sender<ConversionProgress>>,
+    ) -> SnapResult<SnapMetadata> {
         tracing::info!("Converting Erigon MDBX database to Reth format");
-        tracing::info!("Source: {:?}", source_path);
-        tracing::info!("Target: {:?}", target_path);
-        
-        // Create target directory if it doesn't exist
-        if let Some(parent) = target_path.parent() {
-            fs::create_dir_all(parent).await?;
-        }
-        
-        // Placeholder metadata
-        Ok(SnapMetadata {
-            version: 1,
-            timestamp: chrono::Utc::now().timestamp() as u64,
-            source_type: DatabaseType::ErigonMdbx,
-            target_type: DatabaseType::Reth,
-            checksum: "placeholder_checksum".to_string(),
-            record_count: 0,
-            total_size: 0,
-        })
-    }
-    
+        convert_records(source_path, target_path, options, DatabaseType::ErigonMdbx, DatabaseType::Reth, progress).await
+    }
+
     async fn validate(&self, source_path: &Path, target_path: &Path) -> SnapResult<bool> {
-        // Placeholder validation
         tracing::info!("Validating Erigon to Reth conversion");
-        Ok(true)
+        validate_conversion(source_path, target_path).await
+    }
+}
+
+/// Registry of converters keyed by source and target database type
+pub struct ConverterRegistry {
+    converters: HashMap<(DatabaseType, DatabaseType), Box<dyn DatabaseConverter>>,
+}
+
+impl ConverterRegistry {
+    /// Create a registry holding the built-in Reth and Erigon converters
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(DatabaseType::Reth, DatabaseType::ErigonMdbx, Box::new(RethToErigonConverter));
+        registry.register(DatabaseType::ErigonMdbx, DatabaseType::Reth, Box::new(ErigonToRethConverter));
+        registry
+    }
+
+    /// Create a registry without any converters
+    pub fn empty() -> Self {
+        Self { converters: HashMap::new() }
+    }
+
+    /// Register the converter from `source_type` to `target_type`, replacing any previous one
+    pub fn register(&mut self, source_type: DatabaseType, target_type: DatabaseType, converter: Box<dyn DatabaseConverter>) {
+        self.converters.insert((source_type, target_type), converter);
+    }
+
+    /// Get the converter from `source` to `target`
+    pub fn get(&self, source: &DatabaseType, target: &DatabaseType) -> SnapResult<&dyn DatabaseConverter> {
+        self.converters
+            .get(&(source.clone(), target.clone()))
+            .map(|converter| converter.as_ref())
+            .ok_or_else(|| SnapError::UnsupportedConversion {
+                from: source.clone(),
+                to: target.clone(),
+            })
+    }
+}
+
+impl Default for ConverterRegistry {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,5 +1,6 @@
 //! Error types for CDK snapshot operations
 
+use crate::{DatabaseType, RecordType};
 use thiserror::Error;
 
 /// Result type for CDK snapshot operations
@@ -17,6 +18,12 @@ pub enum SnapError {
     #[error("Invalid snapshot format: {0}")]
     InvalidFormat(String),
 
+    #[error("Missing snapshot magic bytes")]
+    InvalidMagic,
+
+    #[error("Unsupported snapshot version {found}, newest supported is {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
     #[error("Snapshot version mismatch: expected {expected}, got {actual}")]
     VersionMismatch { expected: u32, actual: u32 },
 
@@ -31,4 +38,10 @@ pub enum SnapError {
 
     #[error("Validation error: {0}")]
     Validation(String),
+
+    #[error("Invalid {record_type:?} record: {reason}")]
+    InvalidRecord { record_type: RecordType, reason: String },
+
+    #[error("No converter registered from {from:?} to {to:?}")]
+    UnsupportedConversion { from: DatabaseType, to: DatabaseType },
 }
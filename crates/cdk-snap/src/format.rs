@@ -0,0 +1,233 @@
+//! On-disk record formats used by the converters
+//!
+//! Source files hold one JSON-encoded `SnapRecord` per line. Converted output
+//! starts with `CDK_SNAP_MAGIC` and the format version as a big-endian `u32`,
+//! followed by a sequence of frames: a flag byte (`FRAME_COMPRESSED` if the
+//! payload is zstd-compressed), the payload length as a big-endian `u32`, and
+//! the JSON-encoded record. A completed conversion ends with a
+//! `FRAME_METADATA` frame holding the `SnapMetadata`, followed by the length
+//! of its payload as a big-endian `u32` so it can be located from the end of
+//! the file. Converted output can itself be used as a source.
+
+use crate::{ConversionOptions, SnapError, SnapMetadata, SnapRecord, SnapResult, CDK_SNAP_MAGIC, CDK_SNAP_VERSION};
+use std::path::Path;
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader, Lines},
+};
+
+/// Frame flag marking a zstd-compressed payload
+pub const FRAME_COMPRESSED: u8 = 1;
+
+/// Frame flag marking the trailing metadata frame
+pub const FRAME_METADATA: u8 = 0x80;
+
+/// Size of the frame header (flag byte and payload length)
+pub const FRAME_HEADER_LEN: usize = 5;
+
+/// Size of the file header (magic bytes and version)
+pub const SNAP_HEADER_LEN: usize = CDK_SNAP_MAGIC.len() + 4;
+
+/// Encode the file header
+pub fn encode_header() -> Vec<u8> {
+    let mut header = Vec::with_capacity(SNAP_HEADER_LEN);
+    header.extend_from_slice(CDK_SNAP_MAGIC);
+    header.extend_from_slice(&CDK_SNAP_VERSION.to_be_bytes());
+    header
+}
+
+/// Check the file header at the start of `bytes`, returning the format version
+pub fn check_header(bytes: &[u8]) -> SnapResult<u32> {
+    if !bytes.starts_with(CDK_SNAP_MAGIC) {
+        return Err(SnapError::InvalidMagic);
+    }
+    let header = bytes
+        .get(..SNAP_HEADER_LEN)
+        .ok_or_else(|| SnapError::InvalidFormat("Truncated snapshot header".to_string()))?;
+
+    let mut version = [0u8; 4];
+    version.copy_from_slice(&header[CDK_SNAP_MAGIC.len()..]);
+    let version = u32::from_be_bytes(version);
+    if version > CDK_SNAP_VERSION {
+        return Err(SnapError::UnsupportedVersion {
+            found: version,
+            supported: CDK_SNAP_VERSION,
+        });
+    }
+    Ok(version)
+}
+
+/// Encode a frame around `payload`
+fn encode_frame(flags: u8, payload: &[u8]) -> SnapResult<Vec<u8>> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| SnapError::Conversion(format!("Frame payload too large: {} bytes", payload.len())))?;
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.push(flags);
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// Encode a record as an output frame
+pub fn encode_record(record: &SnapRecord, options: &ConversionOptions) -> SnapResult<Vec<u8>> {
+    let json = serde_json::to_vec(record)?;
+    if options.compress {
+        let compressed = zstd::bulk::compress(&json, options.compression_level as i32)?;
+        encode_frame(FRAME_COMPRESSED, &compressed)
+    } else {
+        encode_frame(0, &json)
+    }
+}
+
+/// Encode the metadata frame and trailing length closing a completed conversion
+pub fn encode_footer(metadata: &SnapMetadata) -> SnapResult<Vec<u8>> {
+    let json = serde_json::to_vec(metadata)?;
+    let mut footer = encode_frame(FRAME_METADATA, &json)?;
+    footer.extend_from_slice(&(json.len() as u32).to_be_bytes());
+    Ok(footer)
+}
+
+/// Decode the payload of a frame
+fn decode_payload(flags: u8, payload: &[u8]) -> SnapResult<SnapRecord> {
+    if flags & FRAME_COMPRESSED != 0 {
+        Ok(serde_json::from_slice(&zstd::stream::decode_all(payload)?)?)
+    } else {
+        Ok(serde_json::from_slice(payload)?)
+    }
+}
+
+/// Decode all records of converted output
+pub fn decode_records(bytes: &[u8]) -> SnapResult<Vec<SnapRecord>> {
+    check_header(bytes)?;
+    let mut bytes = &bytes[SNAP_HEADER_LEN..];
+    let mut records = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < FRAME_HEADER_LEN {
+            return Err(SnapError::InvalidFormat("Truncated frame header".to_string()));
+        }
+        let flags = bytes[0];
+        let len = u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize;
+        let payload = bytes
+            .get(FRAME_HEADER_LEN..FRAME_HEADER_LEN + len)
+            .ok_or_else(|| SnapError::InvalidFormat("Truncated frame payload".to_string()))?;
+        if flags & FRAME_METADATA != 0 {
+            break;
+        }
+
+        records.push(decode_payload(flags, payload)?);
+        bytes = &bytes[FRAME_HEADER_LEN + len..];
+    }
+    Ok(records)
+}
+
+/// Read and decode all records of a converted output file
+pub async fn read_converted_records(path: &Path) -> SnapResult<Vec<SnapRecord>> {
+    decode_records(&tokio::fs::read(path).await?)
+}
+
+/// Sequential reader over a source file
+///
+/// Accepts both JSON-lines sources and converted output, told apart by the
+/// magic bytes at the start of the file.
+#[derive(Debug)]
+pub struct SourceReader {
+    input: SourceInput,
+    position: u64,
+}
+
+/// Encoding of the file behind a `SourceReader`
+#[derive(Debug)]
+enum SourceInput {
+    /// One JSON-encoded record per line
+    Lines(Lines<BufReader<File>>),
+    /// Frames as written by the converters
+    Frames(BufReader<File>),
+    /// Converted output whose metadata frame has been reached
+    Done,
+}
+
+impl SourceReader {
+    /// Open a source file
+    pub async fn open(path: &Path) -> SnapResult<Self> {
+        let mut reader = BufReader::new(File::open(path).await?);
+        let input = if reader.fill_buf().await?.starts_with(CDK_SNAP_MAGIC) {
+            let mut header = [0u8; SNAP_HEADER_LEN];
+            reader
+                .read_exact(&mut header)
+                .await
+                .map_err(|_| SnapError::InvalidFormat("Truncated snapshot header".to_string()))?;
+            check_header(&header)?;
+            SourceInput::Frames(reader)
+        } else {
+            SourceInput::Lines(reader.lines())
+        };
+        Ok(Self { input, position: 0 })
+    }
+
+    /// Read the next record, or `None` at the end of the file
+    pub async fn next_record(&mut self) -> SnapResult<Option<SnapRecord>> {
+        match &mut self.input {
+            SourceInput::Lines(lines) => {
+                while let Some(line) = lines.next_line().await? {
+                    self.position += 1;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let record = serde_json::from_str(&line).map_err(|e| {
+                        SnapError::InvalidFormat(format!("Invalid record on line {}: {}", self.position, e))
+                    })?;
+                    return Ok(Some(record));
+                }
+                Ok(None)
+            }
+            SourceInput::Frames(reader) => {
+                if reader.fill_buf().await?.is_empty() {
+                    return Ok(None);
+                }
+                let mut header = [0u8; FRAME_HEADER_LEN];
+                reader
+                    .read_exact(&mut header)
+                    .await
+                    .map_err(|_| SnapError::InvalidFormat(format!("Truncated header of frame {}", self.position)))?;
+                if header[0] & FRAME_METADATA != 0 {
+                    self.input = SourceInput::Done;
+                    return Ok(None);
+                }
+                let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+                let mut payload = vec![0u8; len];
+                reader
+                    .read_exact(&mut payload)
+                    .await
+                    .map_err(|_| SnapError::InvalidFormat(format!("Truncated payload of frame {}", self.position)))?;
+                self.position += 1;
+                decode_payload(header[0], &payload).map(Some)
+            }
+            SourceInput::Done => Ok(None),
+        }
+    }
+
+    /// Read up to `max` records
+    pub async fn next_chunk(&mut self, max: usize) -> SnapResult<Vec<SnapRecord>> {
+        let mut chunk = Vec::with_capacity(max);
+        while chunk.len() < max {
+            match self.next_record().await? {
+                Some(record) => chunk.push(record),
+                None => break,
+            }
+        }
+        Ok(chunk)
+    }
+
+    /// Skip `count` records
+    pub async fn skip(&mut self, count: u64) -> SnapResult<()> {
+        for skipped in 0..count {
+            if self.next_record().await?.is_none() {
+                return Err(SnapError::Conversion(format!(
+                    "Source ended after {} records while skipping {}",
+                    skipped, count
+                )));
+            }
+        }
+        Ok(())
+    }
+}
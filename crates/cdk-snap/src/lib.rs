@@ -4,6 +4,8 @@
 //! Reth and Erigon MDBX databases, enabling data migration and validation.
 
 pub mod converter;
+pub mod format;
+pub mod reader;
 pub mod validator;
 pub mod error;
 pub mod types;
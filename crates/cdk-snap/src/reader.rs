@@ -0,0 +1,92 @@
+//! Streaming reader over converted snapshot files
+
+use crate::{
+    format::{check_header, SourceReader, FRAME_HEADER_LEN, FRAME_METADATA, SNAP_HEADER_LEN},
+    SnapError, SnapMetadata, SnapRecord, SnapResult,
+};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::{io::SeekFrom, path::Path};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+
+/// Stream of records read from a snapshot
+pub type RecordStream = BoxStream<'static, SnapResult<SnapRecord>>;
+
+/// Reader over a snapshot written by a converter
+///
+/// Opening checks the magic bytes and version of the header and loads the
+/// metadata footer, so only completed conversions can be read.
+#[derive(Debug)]
+pub struct SnapReader {
+    metadata: SnapMetadata,
+    records: SourceReader,
+}
+
+impl SnapReader {
+    /// Open a snapshot file
+    pub async fn open(path: &Path) -> SnapResult<Self> {
+        let mut file = File::open(path).await?;
+        let mut header = [0u8; SNAP_HEADER_LEN];
+        file.read_exact(&mut header)
+            .await
+            .map_err(|_| SnapError::InvalidFormat("Truncated snapshot header".to_string()))?;
+        check_header(&header)?;
+
+        let metadata = read_footer(&mut file).await?;
+        let records = SourceReader::open(path).await?;
+        Ok(Self { metadata, records })
+    }
+
+    /// Get the snapshot metadata
+    pub fn metadata(&self) -> &SnapMetadata {
+        &self.metadata
+    }
+
+    /// Read the next record, or `None` after the last one
+    pub async fn next_record(&mut self) -> SnapResult<Option<SnapRecord>> {
+        self.records.next_record().await
+    }
+
+    /// Stream the records of the snapshot
+    pub fn into_stream(self) -> RecordStream {
+        stream::unfold(Some(self.records), |records| async move {
+            let mut records = records?;
+            match records.next_record().await {
+                Ok(Some(record)) => Some((Ok(record), Some(records))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed()
+    }
+}
+
+/// Locate and decode the metadata footer at the end of a snapshot
+async fn read_footer(file: &mut File) -> SnapResult<SnapMetadata> {
+    let missing = || SnapError::InvalidFormat("Missing snapshot metadata footer".to_string());
+    let file_len = file.metadata().await?.len();
+    if file_len < (SNAP_HEADER_LEN + FRAME_HEADER_LEN + 4) as u64 {
+        return Err(missing());
+    }
+
+    file.seek(SeekFrom::Start(file_len - 4)).await?;
+    let len = file.read_u32().await? as u64;
+    let frame_start = file_len
+        .checked_sub(4 + len + FRAME_HEADER_LEN as u64)
+        .filter(|start| *start >= SNAP_HEADER_LEN as u64)
+        .ok_or_else(missing)?;
+
+    file.seek(SeekFrom::Start(frame_start)).await?;
+    let mut frame_header = [0u8; FRAME_HEADER_LEN];
+    file.read_exact(&mut frame_header).await?;
+    let frame_len = u32::from_be_bytes([frame_header[1], frame_header[2], frame_header[3], frame_header[4]]) as u64;
+    if frame_header[0] != FRAME_METADATA || frame_len != len {
+        return Err(missing());
+    }
+
+    let mut payload = vec![0u8; len as usize];
+    file.read_exact(&mut payload).await?;
+    Ok(serde_json::from_slice(&payload)?)
+}
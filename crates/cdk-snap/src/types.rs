@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use alloy_primitives::{U256, FixedBytes, Address};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Snapshot metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,7 +25,7 @@ pub struct SnapMetadata {
 }
 
 /// Database types
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum DatabaseType {
     /// Reth database
     Reth,
@@ -35,7 +36,7 @@ pub enum DatabaseType {
 }
 
 /// Snapshot record
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SnapRecord {
     /// Record key
     pub key: Vec<u8>,
@@ -48,7 +49,7 @@ pub struct SnapRecord {
 }
 
 /// Record types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RecordType {
     /// Block header
     BlockHeader,
@@ -68,6 +69,24 @@ pub enum RecordType {
     Other(String),
 }
 
+impl RecordType {
+    /// Length in bytes of the keys of this record type, if fixed
+    ///
+    /// Block headers, bodies, transactions and receipts are keyed by 8-byte
+    /// big-endian numbers, accounts by their 20-byte address, state trie nodes
+    /// by their 32-byte hash and storage trie nodes by the account address
+    /// followed by the 32-byte slot.
+    pub fn key_len(&self) -> Option<usize> {
+        match self {
+            Self::BlockHeader | Self::BlockBody | Self::Transaction | Self::Receipt => Some(8),
+            Self::Account => Some(20),
+            Self::StateNode => Some(32),
+            Self::StorageNode => Some(52),
+            Self::Other(_) => None,
+        }
+    }
+}
+
 /// Conversion options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversionOptions {
@@ -81,6 +100,18 @@ pub struct ConversionOptions {
     pub validate_checksums: bool,
     /// Progress callback interval
     pub progress_interval: u64,
+    /// Resume from the progress file left by an interrupted conversion
+    #[serde(default)]
+    pub resume: bool,
+    /// Maximum number of records to convert in this run
+    #[serde(default)]
+    pub record_limit: Option<u64>,
+    /// Number of worker threads encoding records (defaults to the number of CPUs)
+    #[serde(default)]
+    pub parallelism: Option<usize>,
+    /// Read and encode the source without writing the target
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 impl Default for ConversionOptions {
@@ -91,6 +122,32 @@ impl Default for ConversionOptions {
             batch_size: 1000,
             validate_checksums: true,
             progress_interval: 1000,
+            resume: false,
+            record_limit: None,
+            parallelism: None,
+            dry_run: false,
         }
     }
 }
+
+/// Progress report emitted while a conversion runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionProgress {
+    /// Number of source records written to the target so far
+    pub records_done: u64,
+    /// Number of bytes written to the target so far
+    pub bytes_done: u64,
+    /// Number of records read from the source but not yet written
+    pub records_in_flight: u64,
+    /// Time elapsed since the conversion started
+    pub elapsed: Duration,
+}
+
+/// Progress of a conversion as persisted in its sidecar progress file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversionCheckpoint {
+    /// Number of source records durably written to the target
+    pub records_committed: u64,
+    /// Length of the target file covering the committed records
+    pub bytes_committed: u64,
+}
@@ -1,6 +1,10 @@
 //! Snapshot validator for data integrity checks
 
-use crate::{SnapResult, SnapError, SnapRecord, SnapMetadata};
+use crate::{
+    converter::{DatabaseConverter, ErigonToRethConverter, RethToErigonConverter},
+    format::{check_header, SourceReader},
+    ConversionOptions, SnapResult, SnapError, SnapRecord, SnapMetadata,
+};
 use std::path::Path;
 use sha2::{Sha256, Digest};
 use tokio::fs;
@@ -42,6 +46,7 @@ impl SnapValidator {
         
         // Placeholder: calculate and validate checksum
         let content = fs::read(file_path).await?;
+        check_header(&content)?;
         let mut hasher = Sha256::new();
         hasher.update(&content);
         let checksum = format!("{:x}", hasher.finalize());
@@ -61,6 +66,15 @@ impl SnapValidator {
             return Err(SnapError::Validation("Empty record value".to_string()));
         }
         
+        if let Some(expected) = record.record_type.key_len() {
+            if record.key.len() != expected {
+                return Err(SnapError::InvalidRecord {
+                    record_type: record.record_type.clone(),
+                    reason: format!("key must be {} bytes, got {}", expected, record.key.len()),
+                });
+            }
+        }
+        
         Ok(())
     }
     
@@ -73,4 +87,61 @@ impl SnapValidator {
         
         Ok(())
     }
+    
+    /// Compare the records of two files by key and value
+    ///
+    /// Returns a description of the first divergence, or `None` if both files
+    /// hold the same records in the same order.
+    pub async fn compare_records(&self, expected: &Path, actual: &Path) -> SnapResult<Option<String>> {
+        let mut expected = SourceReader::open(expected).await?;
+        let mut actual = SourceReader::open(actual).await?;
+        let mut index = 0u64;
+        
+        loop {
+            match (expected.next_record().await?, actual.next_record().await?) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => return Ok(Some(format!("Record {} is missing", index))),
+                (None, Some(_)) => return Ok(Some(format!("Unexpected extra record {}", index))),
+                (Some(want), Some(got)) => {
+                    if want.key != got.key {
+                        return Ok(Some(format!(
+                            "Record {} key differs: expected 0x{}, got 0x{}",
+                            index,
+                            hex::encode(&want.key),
+                            hex::encode(&got.key)
+                        )));
+                    }
+                    if want.value != got.value {
+                        return Ok(Some(format!("Record {} (key 0x{}) value differs", index, hex::encode(&want.key))));
+                    }
+                }
+            }
+            index += 1;
+        }
+    }
+    
+    /// Convert `source` from Reth to Erigon and back, checking that the
+    /// re-derived records match the originals
+    pub async fn verify_roundtrip(&self, source: &Path, options: &ConversionOptions) -> SnapResult<bool> {
+        let scratch = tempfile::tempdir()?;
+        let intermediate = scratch.path().join("erigon");
+        RethToErigonConverter.convert(source, &intermediate, options).await?;
+        self.verify_intermediate(source, &intermediate, options).await
+    }
+    
+    /// Convert an Erigon `intermediate` produced from `source` back to Reth,
+    /// checking that the re-derived records match the originals
+    pub async fn verify_intermediate(&self, source: &Path, intermediate: &Path, options: &ConversionOptions) -> SnapResult<bool> {
+        let scratch = tempfile::tempdir()?;
+        let rederived = scratch.path().join("reth");
+        ErigonToRethConverter.convert(intermediate, &rederived, options).await?;
+        
+        match self.compare_records(source, &rederived).await? {
+            Some(divergence) => {
+                tracing::warn!("Round-trip verification failed: {}", divergence);
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
 }
@@ -1,18 +1,37 @@
 //! Integration tests for CDK snapshot module
 
 use cdk_snap::*;
-use cdk_snap::converter::{DatabaseConverter, RethToErigonConverter, ErigonToRethConverter};
+use cdk_snap::converter::{ConverterRegistry, DatabaseConverter, RethToErigonConverter, ErigonToRethConverter};
 use cdk_snap::validator::SnapValidator;
+use cdk_snap::converter::{progress_path, temp_path};
+use cdk_snap::format::{encode_header, encode_record, read_converted_records};
+use cdk_snap::reader::SnapReader;
+use futures::TryStreamExt;
+use std::path::Path;
 use tempfile::TempDir;
 
+/// Write a source file of `count` account records and return them
+fn write_source(path: &Path, count: u64) -> Vec<SnapRecord> {
+    let records: Vec<SnapRecord> = (0..count)
+        .map(|i| SnapRecord {
+            key: [i as u8; 20].to_vec(),
+            value: format!("account-{}", i).into_bytes(),
+            record_type: RecordType::Account,
+            block_number: Some(alloy_primitives::U256::from(i)),
+        })
+        .collect();
+    let lines: Vec<String> = records.iter().map(|record| serde_json::to_string(record).unwrap()).collect();
+    std::fs::write(path, lines.join("\n")).unwrap();
+    records
+}
+
 #[test]
 fn test_reth_to_erigon_conversion() {
     let temp_dir = TempDir::new().unwrap();
     let source_path = temp_dir.path().join("source");
     let target_path = temp_dir.path().join("target");
     
-    // Create dummy source file
-    std::fs::write(&source_path, b"dummy source data").unwrap();
+    write_source(&source_path, 3);
     
     let converter = RethToErigonConverter;
     let options = ConversionOptions::default();
@@ -25,6 +44,7 @@ fn test_reth_to_erigon_conversion() {
     assert_eq!(metadata.version, 1);
     assert_eq!(metadata.source_type, DatabaseType::Reth);
     assert_eq!(metadata.target_type, DatabaseType::ErigonMdbx);
+    assert_eq!(metadata.record_count, 3);
 }
 
 #[test]
@@ -33,8 +53,7 @@ fn test_erigon_to_reth_conversion() {
     let source_path = temp_dir.path().join("source");
     let target_path = temp_dir.path().join("target");
     
-    // Create dummy source file
-    std::fs::write(&source_path, b"dummy source data").unwrap();
+    write_source(&source_path, 3);
     
     let converter = ErigonToRethConverter;
     let options = ConversionOptions::default();
@@ -49,13 +68,306 @@ fn test_erigon_to_reth_conversion() {
     assert_eq!(metadata.target_type, DatabaseType::Reth);
 }
 
+#[test]
+fn test_converter_registry_lookup() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    write_source(&source_path, 2);
+
+    let registry = ConverterRegistry::new();
+    let options = ConversionOptions::default();
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    for (source_type, target_type) in [
+        (DatabaseType::Reth, DatabaseType::ErigonMdbx),
+        (DatabaseType::ErigonMdbx, DatabaseType::Reth),
+    ] {
+        let target_path = temp_dir.path().join(format!("{:?}", target_type));
+        let converter = registry.get(&source_type, &target_type).unwrap();
+        let metadata = rt.block_on(converter.convert(&source_path, &target_path, &options)).unwrap();
+        assert_eq!(metadata.source_type, source_type);
+        assert_eq!(metadata.target_type, target_type);
+    }
+
+    let result = registry.get(&DatabaseType::Reth, &DatabaseType::Snapshot);
+    assert!(matches!(
+        result,
+        Err(SnapError::UnsupportedConversion { from: DatabaseType::Reth, to: DatabaseType::Snapshot })
+    ));
+}
+
+#[test]
+fn test_interrupted_conversion_resumes() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let records = write_source(&source_path, 25);
+    let converter = RethToErigonConverter;
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    // Uninterrupted reference run
+    let reference_path = temp_dir.path().join("reference");
+    let options = ConversionOptions {
+        progress_interval: 4,
+        batch_size: 3,
+        ..Default::default()
+    };
+    let reference = rt.block_on(converter.convert(&source_path, &reference_path, &options)).unwrap();
+    assert_eq!(reference.record_count, 25);
+
+    // Stop after 10 records, leaving a progress file behind
+    let target_path = temp_dir.path().join("target");
+    let interrupted = ConversionOptions {
+        resume: true,
+        record_limit: Some(10),
+        ..options.clone()
+    };
+    let partial = rt.block_on(converter.convert(&source_path, &target_path, &interrupted)).unwrap();
+    assert_eq!(partial.record_count, 10);
+    assert!(progress_path(&target_path).exists());
+    assert!(!target_path.exists());
+
+    // Resume to completion
+    let resumed = ConversionOptions { resume: true, ..options };
+    let metadata = rt.block_on(converter.convert(&source_path, &target_path, &resumed)).unwrap();
+    assert_eq!(metadata.record_count, 25);
+    assert_eq!(metadata.checksum, reference.checksum);
+    assert!(!progress_path(&target_path).exists());
+    assert!(!temp_path(&target_path).exists());
+
+    let converted = rt.block_on(read_converted_records(&target_path)).unwrap();
+    assert_eq!(converted, records);
+}
+
+#[test]
+fn test_fresh_conversion_discards_earlier_progress() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let records = write_source(&source_path, 25);
+    let broken_path = temp_dir.path().join("broken");
+    let first_line = std::fs::read_to_string(&source_path).unwrap().lines().next().unwrap().to_string();
+    std::fs::write(&broken_path, format!("{}\nnot a record", first_line)).unwrap();
+    let target_path = temp_dir.path().join("target");
+    let converter = RethToErigonConverter;
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let options = ConversionOptions {
+        progress_interval: 4,
+        batch_size: 3,
+        ..Default::default()
+    };
+
+    // An earlier run leaves progress for 10 records behind
+    let interrupted = ConversionOptions {
+        resume: true,
+        record_limit: Some(10),
+        ..options.clone()
+    };
+    rt.block_on(converter.convert(&source_path, &target_path, &interrupted)).unwrap();
+    assert!(progress_path(&target_path).exists());
+
+    // A fresh run fails before its first checkpoint
+    assert!(rt.block_on(converter.convert(&broken_path, &target_path, &options)).is_err());
+    assert!(!progress_path(&target_path).exists());
+
+    // Resuming starts over instead of trusting the earlier run's progress
+    let resumed = ConversionOptions { resume: true, ..options };
+    let metadata = rt.block_on(converter.convert(&source_path, &target_path, &resumed)).unwrap();
+    assert_eq!(metadata.record_count, 25);
+    let converted = rt.block_on(read_converted_records(&target_path)).unwrap();
+    assert_eq!(converted, records);
+}
+
+#[test]
+fn test_failed_conversion_leaves_no_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let target_path = temp_dir.path().join("target.snap");
+    write_source(&source_path, 5);
+    let mut source = std::fs::read_to_string(&source_path).unwrap();
+    source.push_str("\nnot a record");
+    std::fs::write(&source_path, source).unwrap();
+
+    // A stale temporary file from an earlier crash is replaced
+    std::fs::write(temp_path(&target_path), b"stale").unwrap();
+
+    let converter = RethToErigonConverter;
+    let options = ConversionOptions {
+        batch_size: 2,
+        ..Default::default()
+    };
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let result = rt.block_on(converter.convert(&source_path, &target_path, &options));
+
+    assert!(matches!(result, Err(SnapError::InvalidFormat(_))));
+    assert!(!target_path.exists());
+    assert_ne!(std::fs::read(temp_path(&target_path)).unwrap(), b"stale");
+}
+
+#[test]
+fn test_conversion_reports_progress() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let target_path = temp_dir.path().join("target");
+    write_source(&source_path, 10);
+
+    let converter = ErigonToRethConverter;
+    let options = ConversionOptions {
+        progress_interval: 3,
+        batch_size: 4,
+        ..Default::default()
+    };
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let metadata = rt
+        .block_on(converter.convert_with_progress(&source_path, &target_path, &options, Some(sender)))
+        .unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(event) = receiver.try_recv() {
+        events.push(event);
+    }
+    let records: Vec<u64> = events.iter().map(|event| event.records_done).collect();
+    assert_eq!(records, vec![3, 6, 9, 10]);
+    let in_flight: Vec<u64> = events.iter().map(|event| event.records_in_flight).collect();
+    assert_eq!(in_flight, vec![1, 2, 1, 0]);
+    assert!(events.windows(2).all(|pair| pair[0].bytes_done < pair[1].bytes_done && pair[0].elapsed <= pair[1].elapsed));
+
+    let last = events.last().unwrap();
+    assert_eq!(last.records_done, metadata.record_count);
+    assert_eq!(last.bytes_done, metadata.total_size);
+}
+
+#[test]
+fn test_parallel_conversion_matches_single_threaded() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    write_source(&source_path, 50);
+
+    let converter = RethToErigonConverter;
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let mut outputs = Vec::new();
+    for parallelism in [1, 4] {
+        let target_path = temp_dir.path().join(format!("target-{}", parallelism));
+        let options = ConversionOptions {
+            batch_size: 7,
+            parallelism: Some(parallelism),
+            ..Default::default()
+        };
+        let metadata = rt.block_on(converter.convert(&source_path, &target_path, &options)).unwrap();
+        assert_eq!(metadata.record_count, 50);
+        // The footer carries the conversion time, so compare what precedes it
+        outputs.push((metadata.checksum, rt.block_on(read_converted_records(&target_path)).unwrap()));
+    }
+
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+fn test_dry_run_does_not_write_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    write_source(&source_path, 12);
+
+    let converter = RethToErigonConverter;
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let dry_target = temp_dir.path().join("out").join("dry");
+    let options = ConversionOptions {
+        dry_run: true,
+        resume: true,
+        ..Default::default()
+    };
+    let estimate = rt.block_on(converter.convert(&source_path, &dry_target, &options)).unwrap();
+
+    assert_eq!(estimate.record_count, 12);
+    assert!(!dry_target.exists());
+    assert!(!progress_path(&dry_target).exists());
+
+    // The estimate matches what a real run writes
+    let target_path = temp_dir.path().join("target");
+    let metadata = rt
+        .block_on(converter.convert(&source_path, &target_path, &ConversionOptions::default()))
+        .unwrap();
+    assert_eq!(estimate.total_size, metadata.total_size);
+    assert_eq!(estimate.checksum, metadata.checksum);
+}
+
+#[test]
+fn test_roundtrip_verification() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    write_source(&source_path, 8);
+
+    let validator = SnapValidator;
+    let options = ConversionOptions::default();
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    assert!(rt.block_on(validator.verify_roundtrip(&source_path, &options)).unwrap());
+
+    // Corrupt one record of the intermediate Erigon output
+    let intermediate = temp_dir.path().join("erigon");
+    let converter = RethToErigonConverter;
+    rt.block_on(converter.convert(&source_path, &intermediate, &options)).unwrap();
+    assert!(rt.block_on(converter.validate(&source_path, &intermediate)).unwrap());
+
+    let mut records = rt.block_on(read_converted_records(&intermediate)).unwrap();
+    records[5].value = b"tampered".to_vec();
+    let mut corrupted = encode_header();
+    for record in &records {
+        corrupted.extend(encode_record(record, &options).unwrap());
+    }
+    std::fs::write(&intermediate, corrupted).unwrap();
+
+    assert!(!rt.block_on(converter.validate(&source_path, &intermediate)).unwrap());
+    assert!(!rt.block_on(validator.verify_intermediate(&source_path, &intermediate, &options)).unwrap());
+}
+
+#[test]
+fn test_snap_reader_streams_converted_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let target_path = temp_dir.path().join("target");
+    let records = write_source(&source_path, 15);
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let metadata = rt
+        .block_on(RethToErigonConverter.convert(&source_path, &target_path, &ConversionOptions::default()))
+        .unwrap();
+
+    let reader = rt.block_on(SnapReader::open(&target_path)).unwrap();
+    assert_eq!(reader.metadata().record_count, 15);
+    assert_eq!(reader.metadata().checksum, metadata.checksum);
+    assert_eq!(reader.metadata().target_type, DatabaseType::ErigonMdbx);
+
+    let read: Vec<SnapRecord> = rt.block_on(reader.into_stream().try_collect()).unwrap();
+    assert_eq!(read, records);
+}
+
+#[test]
+fn test_snap_reader_rejects_incomplete_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let target_path = temp_dir.path().join("target");
+    write_source(&source_path, 5);
+
+    let options = ConversionOptions {
+        record_limit: Some(2),
+        ..Default::default()
+    };
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(RethToErigonConverter.convert(&source_path, &target_path, &options)).unwrap();
+
+    assert!(!target_path.exists());
+    assert!(matches!(rt.block_on(SnapReader::open(&temp_path(&target_path))), Err(SnapError::InvalidFormat(_))));
+}
+
 #[test]
 fn test_snapshot_validation() {
     let temp_dir = TempDir::new().unwrap();
     let file_path = temp_dir.path().join("test_snapshot");
     
-    // Create test file
-    std::fs::write(&file_path, b"test snapshot data").unwrap();
+    // Create test file with a valid header
+    let mut contents = encode_header();
+    contents.extend_from_slice(b"test snapshot data");
+    std::fs::write(&file_path, contents).unwrap();
     
     let validator = SnapValidator;
     let rt = tokio::runtime::Runtime::new().unwrap();
@@ -64,6 +376,46 @@ fn test_snapshot_validation() {
     assert!(result.unwrap());
 }
 
+#[test]
+fn test_snapshot_header_enforcement() {
+    let temp_dir = TempDir::new().unwrap();
+    let validator = SnapValidator;
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    // Converter output carries a good header
+    let source_path = temp_dir.path().join("source");
+    let target_path = temp_dir.path().join("target");
+    write_source(&source_path, 2);
+    rt.block_on(RethToErigonConverter.convert(&source_path, &target_path, &ConversionOptions::default()))
+        .unwrap();
+    assert!(std::fs::read(&target_path).unwrap().starts_with(CDK_SNAP_MAGIC));
+    assert!(rt.block_on(validator.validate_file(&target_path)).unwrap());
+    assert!(rt.block_on(SnapReader::open(&target_path)).is_ok());
+
+    // Wrong magic
+    let wrong_magic = temp_dir.path().join("wrong_magic");
+    let mut contents = std::fs::read(&target_path).unwrap();
+    contents[0] = b'X';
+    std::fs::write(&wrong_magic, contents).unwrap();
+    assert!(matches!(rt.block_on(validator.validate_file(&wrong_magic)), Err(SnapError::InvalidMagic)));
+    assert!(matches!(rt.block_on(SnapReader::open(&wrong_magic)), Err(SnapError::InvalidMagic)));
+
+    // Version newer than this build supports
+    let future_version = temp_dir.path().join("future_version");
+    let mut contents = std::fs::read(&target_path).unwrap();
+    contents[CDK_SNAP_MAGIC.len()..CDK_SNAP_MAGIC.len() + 4].copy_from_slice(&(CDK_SNAP_VERSION + 1).to_be_bytes());
+    std::fs::write(&future_version, contents).unwrap();
+    let expected_version = CDK_SNAP_VERSION + 1;
+    assert!(matches!(
+        rt.block_on(validator.validate_file(&future_version)),
+        Err(SnapError::UnsupportedVersion { found, supported }) if found == expected_version && supported == CDK_SNAP_VERSION
+    ));
+    assert!(matches!(
+        rt.block_on(SnapReader::open(&future_version)),
+        Err(SnapError::UnsupportedVersion { .. })
+    ));
+}
+
 #[test]
 fn test_metadata_validation() {
     let validator = SnapValidator;
@@ -133,3 +485,42 @@ fn test_conversion_options_default() {
     assert!(options.validate_checksums);
     assert_eq!(options.progress_interval, 1000);
 }
+
+#[test]
+fn test_record_key_shape_per_type() {
+    let validator = SnapValidator;
+    let cases = [
+        (RecordType::BlockHeader, 8),
+        (RecordType::BlockBody, 8),
+        (RecordType::Transaction, 8),
+        (RecordType::Receipt, 8),
+        (RecordType::Account, 20),
+        (RecordType::StateNode, 32),
+        (RecordType::StorageNode, 52),
+    ];
+
+    for (record_type, key_len) in cases {
+        let record = |len: usize| SnapRecord {
+            key: vec![1u8; len],
+            value: b"test_value".to_vec(),
+            record_type: record_type.clone(),
+            block_number: None,
+        };
+
+        assert!(validator.validate_record(&record(key_len)).is_ok(), "{:?}", record_type);
+        match validator.validate_record(&record(key_len + 1)) {
+            Err(SnapError::InvalidRecord { record_type: rejected, .. }) => assert_eq!(rejected, record_type),
+            other => panic!("{:?} with a {}-byte key: {:?}", record_type, key_len + 1, other),
+        }
+    }
+
+    // Other records accept keys of any length
+    let other = SnapRecord {
+        key: vec![1u8; 3],
+        value: b"test_value".to_vec(),
+        record_type: RecordType::Other("custom".to_string()),
+        block_number: None,
+    };
+    assert!(validator.validate_record(&other).is_ok());
+    assert!(validator.validate_record(&SnapRecord { key: vec![], ..other }).is_err());
+}
@@ -0,0 +1,76 @@
+//! Memory bounds of the conversion pipeline
+//!
+//! Kept in its own test binary so the counting allocator only sees this test.
+
+use cdk_snap::converter::{DatabaseConverter, RethToErigonConverter};
+use cdk_snap::*;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tempfile::TempDir;
+
+/// Allocator tracking the current and peak number of allocated bytes
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_conversion_memory_is_bounded_by_batch_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("source");
+    let target_path = temp_dir.path().join("target");
+
+    // Around 16 MiB of JSON records, written without holding them in memory
+    let record_count = 8_000u64;
+    let mut source = std::io::BufWriter::new(std::fs::File::create(&source_path).unwrap());
+    for i in 0..record_count {
+        let record = SnapRecord {
+            key: (i as u32).to_be_bytes().repeat(5),
+            value: vec![(i % 251) as u8; 512],
+            record_type: RecordType::Account,
+            block_number: None,
+        };
+        writeln!(source, "{}", serde_json::to_string(&record).unwrap()).unwrap();
+    }
+    source.flush().unwrap();
+    drop(source);
+    let source_size = std::fs::metadata(&source_path).unwrap().len() as usize;
+
+    let options = ConversionOptions {
+        batch_size: 64,
+        parallelism: Some(2),
+        ..Default::default()
+    };
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let metadata = rt.block_on(RethToErigonConverter.convert(&source_path, &target_path, &options)).unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+    assert_eq!(metadata.record_count, record_count);
+    assert!(peak < 4 * 1024 * 1024, "peak allocation of {} bytes", peak);
+    assert!(peak < source_size / 8, "peak allocation of {} bytes for a {} byte source", peak, source_size);
+}
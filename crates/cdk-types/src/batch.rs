@@ -4,21 +4,27 @@
 //! as a single unit. Each batch contains metadata about its L1 origin
 //! and proof information for data availability verification.
 
+use crate::BatchError;
 use alloy_primitives::{Bytes, FixedBytes, U256};
 use serde::{Deserialize, Serialize};
 
 /// A batch of blocks submitted to L1
+///
+/// Field names on the wire are camelCase and locked by a golden test.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Batch {
     /// Unique identifier for this batch
     pub id: BatchId,
     /// L1 block number where this batch was submitted
+    #[serde(with = "crate::quantity")]
     pub l1_origin: U256,
     /// L1 block hash where this batch was submitted
     pub l1_origin_hash: FixedBytes<32>,
     /// Blocks contained in this batch
     pub blocks: Vec<BlockInBatch>,
     /// Proof metadata for data availability verification
+    #[serde(rename = "proofMetadata")]
     pub proof_meta: ProofMetadata,
     /// Timestamp when batch was created
     pub timestamp: u64,
@@ -26,8 +32,10 @@ pub struct Batch {
 
 /// Unique identifier for a batch
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BatchId {
     /// Sequential batch number
+    #[serde(with = "crate::quantity")]
     pub number: U256,
     /// Hash of the batch contents
     pub hash: FixedBytes<32>,
@@ -35,27 +43,38 @@ pub struct BatchId {
 
 /// A block within a batch
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BlockInBatch {
     /// Block number within the batch
     pub batch_index: u32,
     /// Block hash
     pub hash: FixedBytes<32>,
     /// Block number in the L2 chain
+    #[serde(with = "crate::quantity")]
     pub number: U256,
     /// Parent block hash
     pub parent_hash: FixedBytes<32>,
     /// State root
     pub state_root: FixedBytes<32>,
     /// Transaction root
+    #[serde(rename = "transactionsRoot")]
     pub tx_root: FixedBytes<32>,
     /// Receipt root
+    #[serde(rename = "receiptsRoot")]
     pub receipt_root: FixedBytes<32>,
     /// Block timestamp
     pub timestamp: u64,
+    /// Number of transactions in the block
+    #[serde(default, rename = "transactionCount")]
+    pub tx_count: u32,
+    /// Gas used by the block
+    #[serde(default)]
+    pub gas_used: u64,
 }
 
 /// Proof metadata for data availability verification
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ProofMetadata {
     /// Data availability proof
     pub data_proof: Bytes,
@@ -117,6 +136,52 @@ impl Batch {
     pub fn block_hashes(&self) -> Vec<FixedBytes<32>> {
         self.blocks.iter().map(|b| b.hash).collect()
     }
+
+    /// Get the total number of transactions across all blocks, saturating on overflow
+    pub fn transaction_count(&self) -> u64 {
+        self.blocks.iter().fold(0u64, |total, b| total.saturating_add(b.tx_count as u64))
+    }
+
+    /// Get the total gas used across all blocks, saturating on overflow
+    pub fn total_gas_used(&self) -> u64 {
+        self.blocks.iter().fold(0u64, |total, b| total.saturating_add(b.gas_used))
+    }
+
+    /// Check that the blocks of this batch are consistent with each other
+    ///
+    /// Batch indices must cover exactly `0..blocks.len()` without repeats, so
+    /// no block claims a position the batch does not hold, and block numbers
+    /// must be strictly increasing.
+    pub fn validate_internal_consistency(&self) -> Result<(), BatchError> {
+        let mut seen = vec![false; self.blocks.len()];
+        for block in &self.blocks {
+            let slot = seen.get_mut(block.batch_index as usize).ok_or(BatchError::IndexOutOfRange {
+                batch_index: block.batch_index,
+                block_count: self.blocks.len(),
+            })?;
+            if std::mem::replace(slot, true) {
+                return Err(BatchError::DuplicateIndex(block.batch_index));
+            }
+        }
+
+        for pair in self.blocks.windows(2) {
+            if pair[1].number <= pair[0].number {
+                return Err(BatchError::NonIncreasingNumber {
+                    previous: pair[0].number,
+                    number: pair[1].number,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the lowest and highest block numbers in this batch
+    pub fn block_range(&self) -> Option<(U256, U256)> {
+        let min = self.blocks.iter().map(|b| b.number).min()?;
+        let max = self.blocks.iter().map(|b| b.number).max()?;
+        Some((min, max))
+    }
 }
 
 impl BatchId {
@@ -148,8 +213,17 @@ impl BlockInBatch {
             tx_root,
             receipt_root,
             timestamp,
+            tx_count: 0,
+            gas_used: 0,
         }
     }
+
+    /// Set the transaction count and gas used of the block
+    pub fn with_tx_stats(mut self, tx_count: u32, gas_used: u64) -> Self {
+        self.tx_count = tx_count;
+        self.gas_used = gas_used;
+        self
+    }
 }
 
 impl ProofMetadata {
@@ -215,4 +289,224 @@ mod tests {
         assert_eq!(block.batch_index, 0);
         assert_eq!(block.number, U256::from(1000));
     }
+
+    #[test]
+    fn test_batch_transaction_totals() {
+        let block = |index: u32, tx_count: u32, gas_used: u64| {
+            BlockInBatch::new(
+                index,
+                FixedBytes::from([index as u8 + 1; 32]),
+                U256::from(1000 + index),
+                FixedBytes::from([2u8; 32]),
+                FixedBytes::from([3u8; 32]),
+                FixedBytes::from([4u8; 32]),
+                FixedBytes::from([5u8; 32]),
+                1234567890,
+            )
+            .with_tx_stats(tx_count, gas_used)
+        };
+
+        let batch = Batch::new(
+            BatchId::new(U256::from(1), FixedBytes::from([1u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![block(0, 3, 63_000), block(1, 0, 0), block(2, 5, 105_000)],
+            ProofMetadata::default(),
+            1234567890,
+        );
+
+        assert_eq!(batch.transaction_count(), 8);
+        assert_eq!(batch.total_gas_used(), 168_000);
+        assert_eq!(batch.block_range(), Some((U256::from(1000), U256::from(1002))));
+    }
+
+    #[test]
+    fn test_batch_aggregates_saturate_and_handle_empty() {
+        let block = |index: u32, number: u64, gas_used: u64| {
+            BlockInBatch::new(
+                index,
+                FixedBytes::from([index as u8 + 1; 32]),
+                U256::from(number),
+                FixedBytes::from([2u8; 32]),
+                FixedBytes::from([3u8; 32]),
+                FixedBytes::from([4u8; 32]),
+                FixedBytes::from([5u8; 32]),
+                1234567890,
+            )
+            .with_tx_stats(u32::MAX, gas_used)
+        };
+
+        let mut batch = Batch::new(
+            BatchId::new(U256::from(1), FixedBytes::from([1u8; 32])),
+            U256::from(100),
+            FixedBytes::from([2u8; 32]),
+            vec![block(0, 1005, u64::MAX), block(1, 1001, 1), block(2, 1009, 1)],
+            ProofMetadata::default(),
+            1234567890,
+        );
+
+        assert_eq!(batch.total_gas_used(), u64::MAX);
+        assert_eq!(batch.transaction_count(), 3 * u32::MAX as u64);
+        assert_eq!(batch.block_range(), Some((U256::from(1001), U256::from(1009))));
+
+        batch.blocks.clear();
+        assert_eq!(batch.total_gas_used(), 0);
+        assert_eq!(batch.transaction_count(), 0);
+        assert_eq!(batch.block_range(), None);
+    }
+
+    #[test]
+    fn test_block_in_batch_without_tx_stats_deserializes() {
+        let block = BlockInBatch::new(
+            0,
+            FixedBytes::from([1u8; 32]),
+            U256::from(1000),
+            FixedBytes::from([2u8; 32]),
+            FixedBytes::from([3u8; 32]),
+            FixedBytes::from([4u8; 32]),
+            FixedBytes::from([5u8; 32]),
+            1234567890,
+        );
+        let mut json = serde_json::to_value(&block).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("transactionCount");
+        fields.remove("gasUsed");
+
+        let decoded: BlockInBatch = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, block);
+    }
+
+    #[test]
+    fn test_batch_wire_schema_golden() {
+        let block = BlockInBatch::new(
+            0,
+            FixedBytes::from([1u8; 32]),
+            U256::from(1000),
+            FixedBytes::from([2u8; 32]),
+            FixedBytes::from([3u8; 32]),
+            FixedBytes::from([4u8; 32]),
+            FixedBytes::from([5u8; 32]),
+            1234567890,
+        )
+        .with_tx_stats(2, 42_000);
+        let batch = Batch::new(
+            BatchId::new(U256::from(7), FixedBytes::from([6u8; 32])),
+            U256::from(100),
+            FixedBytes::from([7u8; 32]),
+            vec![block],
+            ProofMetadata::new(
+                Bytes::from(vec![1, 2]),
+                FixedBytes::from([8u8; 8]),
+                FixedBytes::from([9u8; 32]),
+                Bytes::from(vec![3, 4]),
+            ),
+            1234567890,
+        );
+
+        let hash = |byte: u8| format!("0x{}", format!("{:02x}", byte).repeat(32));
+        let expected = serde_json::json!({
+            "id": { "number": "0x7", "hash": hash(6) },
+            "l1Origin": "0x64",
+            "l1OriginHash": hash(7),
+            "blocks": [{
+                "batchIndex": 0,
+                "hash": hash(1),
+                "number": "0x3e8",
+                "parentHash": hash(2),
+                "stateRoot": hash(3),
+                "transactionsRoot": hash(4),
+                "receiptsRoot": hash(5),
+                "timestamp": 1234567890,
+                "transactionCount": 2,
+                "gasUsed": 42000
+            }],
+            "proofMetadata": {
+                "dataProof": "0x0102",
+                "namespaceId": "0x0808080808080808",
+                "commitment": hash(9),
+                "inclusionProof": "0x0304"
+            },
+            "timestamp": 1234567890
+        });
+
+        assert_eq!(serde_json::to_value(&batch).unwrap(), expected);
+        assert_eq!(serde_json::from_value::<Batch>(expected).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_batch_numbers_accept_decimal() {
+        let batch = Batch::new(
+            BatchId::new(U256::from(7), FixedBytes::from([6u8; 32])),
+            U256::from(100),
+            FixedBytes::from([7u8; 32]),
+            vec![],
+            ProofMetadata::default(),
+            1234567890,
+        );
+        let mut json = serde_json::to_value(&batch).unwrap();
+        assert_eq!(json["l1Origin"], "0x64");
+        assert_eq!(json["id"]["number"], "0x7");
+
+        json["l1Origin"] = serde_json::json!("100");
+        json["id"]["number"] = serde_json::json!(7);
+        assert_eq!(serde_json::from_value::<Batch>(json).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_batch_internal_consistency() {
+        let block = |batch_index: u32, number: u64| {
+            BlockInBatch::new(
+                batch_index,
+                FixedBytes::from([batch_index as u8 + 1; 32]),
+                U256::from(number),
+                FixedBytes::from([2u8; 32]),
+                FixedBytes::from([3u8; 32]),
+                FixedBytes::from([4u8; 32]),
+                FixedBytes::from([5u8; 32]),
+                1234567890,
+            )
+        };
+        let batch = |blocks: Vec<BlockInBatch>| {
+            Batch::new(
+                BatchId::new(U256::from(1), FixedBytes::from([1u8; 32])),
+                U256::from(100),
+                FixedBytes::from([2u8; 32]),
+                blocks,
+                ProofMetadata::default(),
+                1234567890,
+            )
+        };
+
+        assert_eq!(batch(vec![]).validate_internal_consistency(), Ok(()));
+        assert_eq!(
+            batch(vec![block(0, 1000), block(1, 1001), block(2, 1002)]).validate_internal_consistency(),
+            Ok(())
+        );
+
+        assert_eq!(
+            batch(vec![block(0, 1000), block(3, 1001)]).validate_internal_consistency(),
+            Err(BatchError::IndexOutOfRange {
+                batch_index: 3,
+                block_count: 2
+            })
+        );
+        assert_eq!(
+            batch(vec![block(0, 1000), block(0, 1001)]).validate_internal_consistency(),
+            Err(BatchError::DuplicateIndex(0))
+        );
+        assert_eq!(
+            batch(vec![block(0, 1000), block(1, 1000)]).validate_internal_consistency(),
+            Err(BatchError::NonIncreasingNumber {
+                previous: U256::from(1000),
+                number: U256::from(1000)
+            })
+        );
+        assert_eq!(
+            batch(vec![block(0, 1002), block(1, 1001)]).validate_internal_consistency(),
+            Err(BatchError::NonIncreasingNumber {
+                previous: U256::from(1002),
+                number: U256::from(1001)
+            })
+        );
+    }
 }
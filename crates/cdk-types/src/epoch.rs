@@ -4,7 +4,8 @@
 //! start and end block boundaries. Epochs are used for organizing
 //! batches and tracking system state over time.
 
-use alloy_primitives::{FixedBytes, U256};
+use crate::EpochError;
+use alloy_primitives::{keccak256, FixedBytes, U256};
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
@@ -87,6 +88,107 @@ impl Epoch {
     pub fn is_empty(&self) -> bool {
         self.start_block > self.end_block
     }
+
+    /// Hash of the epoch metadata: its number, block and batch ranges and timestamps
+    pub fn metadata_hash(&self) -> FixedBytes<32> {
+        let mut preimage = Vec::with_capacity(5 * 32 + 2 * 8);
+        for value in [self.id.number, self.start_block, self.end_block, self.start_batch, self.end_batch] {
+            preimage.extend_from_slice(&value.to_be_bytes::<32>());
+        }
+        preimage.extend_from_slice(&self.start_timestamp.to_be_bytes());
+        preimage.extend_from_slice(&self.end_timestamp.to_be_bytes());
+        keccak256(preimage)
+    }
+
+    /// Combine this epoch with an adjacent one
+    ///
+    /// The epochs may be given in either order but their block ranges must be
+    /// contiguous. The merged epoch keeps the number of the earlier epoch,
+    /// spans both block and batch ranges and timestamps, and carries a hash
+    /// recomputed from the combined metadata.
+    pub fn merge(&self, other: &Epoch) -> Result<Epoch, EpochError> {
+        let (first, second) = if self.start_block <= other.start_block { (self, other) } else { (other, self) };
+        if second.start_block <= first.end_block {
+            return Err(EpochError::Overlap {
+                first_end: first.end_block,
+                second_start: second.start_block,
+            });
+        }
+        if second.start_block != first.end_block + U256::from(1) {
+            return Err(EpochError::Gap {
+                first_end: first.end_block,
+                second_start: second.start_block,
+            });
+        }
+
+        let mut merged = Epoch::new(
+            EpochId::new(first.id.number, FixedBytes::ZERO),
+            first.start_block,
+            second.end_block,
+            first.start_batch.min(second.start_batch),
+            first.end_batch.max(second.end_batch),
+            first.start_timestamp.min(second.start_timestamp),
+            first.end_timestamp.max(second.end_timestamp),
+        );
+        merged.id.hash = merged.metadata_hash();
+        Ok(merged)
+    }
+
+    /// Split this epoch so that the second part starts at `block`
+    ///
+    /// `block` must lie strictly between the start and end blocks. The batch
+    /// boundary and the split timestamp are interpolated in proportion to the
+    /// blocks on each side; both parts keep the boundary batch, since it may
+    /// span the split block. Both hashes are recomputed.
+    ///
+    /// The first part keeps this epoch's number and the second part is
+    /// numbered `second_number`, which must differ from it. The epochs after
+    /// this one keep their numbers, so `this number + 1` usually belongs to the
+    /// next epoch already: the caller picks an unused number, or renumbers the
+    /// later epochs itself if numbers must stay consecutive.
+    pub fn split_at(&self, block: U256, second_number: U256) -> Result<(Epoch, Epoch), EpochError> {
+        if second_number == self.id.number {
+            return Err(EpochError::DuplicateNumber { number: second_number });
+        }
+        if block <= self.start_block || block >= self.end_block {
+            return Err(EpochError::BlockOutOfRange {
+                block,
+                start_block: self.start_block,
+                end_block: self.end_block,
+            });
+        }
+
+        let blocks_before = block - self.start_block;
+        let total_blocks = self.block_count();
+        let split_batch =
+            self.start_batch + self.end_batch.saturating_sub(self.start_batch) * blocks_before / total_blocks;
+        let duration = U256::from(self.duration_seconds());
+        let split_timestamp = self.start_timestamp + (duration * blocks_before / total_blocks).to::<u64>();
+
+        let mut first = Epoch::new(
+            EpochId::new(self.id.number, FixedBytes::ZERO),
+            self.start_block,
+            block - U256::from(1),
+            self.start_batch,
+            split_batch,
+            self.start_timestamp,
+            split_timestamp,
+        );
+        first.id.hash = first.metadata_hash();
+
+        let mut second = Epoch::new(
+            EpochId::new(second_number, FixedBytes::ZERO),
+            block,
+            self.end_block,
+            split_batch,
+            self.end_batch,
+            split_timestamp,
+            self.end_timestamp,
+        );
+        second.id.hash = second.metadata_hash();
+
+        Ok((first, second))
+    }
 }
 
 impl EpochId {
@@ -127,4 +229,98 @@ mod tests {
         assert!(epoch.contains_block(U256::from(150)));
         assert!(!epoch.contains_block(U256::from(250)));
     }
+
+    fn epoch(number: u64, blocks: (u64, u64), batches: (u64, u64), timestamps: (u64, u64)) -> Epoch {
+        Epoch::new(
+            EpochId::new(U256::from(number), FixedBytes::from([number as u8; 32])),
+            U256::from(blocks.0),
+            U256::from(blocks.1),
+            U256::from(batches.0),
+            U256::from(batches.1),
+            timestamps.0,
+            timestamps.1,
+        )
+    }
+
+    #[test]
+    fn test_merge_contiguous_epochs() {
+        let first = epoch(1, (100, 199), (10, 19), (1000, 1999));
+        let second = epoch(2, (200, 299), (20, 29), (2000, 2999));
+
+        let merged = first.merge(&second).unwrap();
+        assert_eq!(merged.id.number, U256::from(1));
+        assert_eq!((merged.start_block, merged.end_block), (U256::from(100), U256::from(299)));
+        assert_eq!((merged.start_batch, merged.end_batch), (U256::from(10), U256::from(29)));
+        assert_eq!((merged.start_timestamp, merged.end_timestamp), (1000, 2999));
+        assert_eq!(merged.id.hash, merged.metadata_hash());
+        assert_ne!(merged.id.hash, first.id.hash);
+
+        // Order of the arguments does not matter
+        assert_eq!(second.merge(&first).unwrap(), merged);
+    }
+
+    #[test]
+    fn test_merge_overlapping_epochs() {
+        let first = epoch(1, (100, 199), (10, 19), (1000, 1999));
+        let second = epoch(2, (150, 299), (20, 29), (2000, 2999));
+
+        assert_eq!(
+            first.merge(&second),
+            Err(EpochError::Overlap {
+                first_end: U256::from(199),
+                second_start: U256::from(150)
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_gapped_epochs() {
+        let first = epoch(1, (100, 199), (10, 19), (1000, 1999));
+        let second = epoch(2, (201, 299), (20, 29), (2000, 2999));
+
+        assert_eq!(
+            first.merge(&second),
+            Err(EpochError::Gap {
+                first_end: U256::from(199),
+                second_start: U256::from(201)
+            })
+        );
+    }
+
+    #[test]
+    fn test_split_at_block() {
+        let epoch = epoch(3, (100, 199), (10, 30), (1000, 2000));
+
+        let (first, second) = epoch.split_at(U256::from(125), U256::from(9)).unwrap();
+        assert_eq!((first.start_block, first.end_block), (U256::from(100), U256::from(124)));
+        assert_eq!((second.start_block, second.end_block), (U256::from(125), U256::from(199)));
+        assert_eq!((first.start_batch, first.end_batch), (U256::from(10), U256::from(15)));
+        assert_eq!((second.start_batch, second.end_batch), (U256::from(15), U256::from(30)));
+        assert_eq!((first.start_timestamp, first.end_timestamp), (1000, 1250));
+        assert_eq!((second.start_timestamp, second.end_timestamp), (1250, 2000));
+        assert_eq!((first.id.number, second.id.number), (U256::from(3), U256::from(9)));
+        assert_eq!(first.id.hash, first.metadata_hash());
+        assert_eq!(second.id.hash, second.metadata_hash());
+        assert_eq!(first.block_count() + second.block_count(), epoch.block_count());
+    }
+
+    #[test]
+    fn test_split_at_out_of_range() {
+        let epoch = epoch(3, (100, 199), (10, 30), (1000, 2000));
+
+        for block in [50, 100, 199, 300] {
+            assert_eq!(
+                epoch.split_at(U256::from(block), U256::from(4)),
+                Err(EpochError::BlockOutOfRange {
+                    block: U256::from(block),
+                    start_block: U256::from(100),
+                    end_block: U256::from(199)
+                })
+            );
+        }
+        assert_eq!(
+            epoch.split_at(U256::from(125), U256::from(3)),
+            Err(EpochError::DuplicateNumber { number: U256::from(3) })
+        );
+    }
 }
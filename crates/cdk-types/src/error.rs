@@ -1,5 +1,6 @@
 //! Error types for CDK integration
 
+use alloy_primitives::U256;
 use thiserror::Error;
 
 /// Errors that can occur in CDK operations
@@ -33,5 +34,46 @@ pub enum CdkError {
     InternalError(String),
 }
 
+/// Internal inconsistencies of a batch
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    #[error("Block batch index {batch_index} is out of range for a batch of {block_count} blocks")]
+    IndexOutOfRange { batch_index: u32, block_count: usize },
+
+    #[error("Block batch index {0} appears more than once")]
+    DuplicateIndex(u32),
+
+    #[error("Block number {number} does not follow block number {previous}")]
+    NonIncreasingNumber { previous: U256, number: U256 },
+}
+
+impl From<BatchError> for CdkError {
+    fn from(error: BatchError) -> Self {
+        Self::InvalidBatch(error.to_string())
+    }
+}
+
+/// Errors from combining or reshaping epochs
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EpochError {
+    #[error("Epochs overlap: first ends at block {first_end}, second starts at block {second_start}")]
+    Overlap { first_end: U256, second_start: U256 },
+
+    #[error("Epochs are not contiguous: first ends at block {first_end}, second starts at block {second_start}")]
+    Gap { first_end: U256, second_start: U256 },
+
+    #[error("Block {block} is not strictly inside epoch blocks {start_block}..={end_block}")]
+    BlockOutOfRange { block: U256, start_block: U256, end_block: U256 },
+
+    #[error("Split epoch part cannot reuse epoch number {number}")]
+    DuplicateNumber { number: U256 },
+}
+
+impl From<EpochError> for CdkError {
+    fn from(error: EpochError) -> Self {
+        Self::InvalidEpoch(error.to_string())
+    }
+}
+
 /// Result type for CDK operations
 pub type CdkResult<T> = Result<T, CdkError>;
@@ -11,6 +11,7 @@ pub mod batch;
 pub mod epoch;
 pub mod finality;
 pub mod error;
+pub mod quantity;
 
 pub use batch::*;
 pub use epoch::*;
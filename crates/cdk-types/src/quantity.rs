@@ -0,0 +1,56 @@
+//! Serde helpers encoding `U256` values as JSON-RPC quantities
+//!
+//! Values serialize as `0x`-prefixed hex strings without leading zeros. For
+//! leniency, deserialization also accepts decimal strings and JSON numbers.
+//! Use with `#[serde(with = "crate::quantity")]`.
+
+use alloy_primitives::U256;
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+/// Serialize a `U256` as a hex quantity
+pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#x}", value))
+}
+
+/// Deserialize a `U256` from a hex quantity, a decimal string or a number
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Quantity {
+        String(String),
+        Number(u64),
+    }
+
+    match Quantity::deserialize(deserializer)? {
+        Quantity::Number(value) => Ok(U256::from(value)),
+        Quantity::String(value) => match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) if !hex.is_empty() => U256::from_str_radix(hex, 16).map_err(de::Error::custom),
+            Some(_) => Err(de::Error::custom("empty hex quantity")),
+            None => U256::from_str_radix(&value, 10).map_err(de::Error::custom),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "crate::quantity")] U256);
+
+    #[test]
+    fn test_quantity_round_trip() {
+        assert_eq!(serde_json::to_string(&Wrapper(U256::from(100))).unwrap(), "\"0x64\"");
+        assert_eq!(serde_json::to_string(&Wrapper(U256::ZERO)).unwrap(), "\"0x0\"");
+        assert_eq!(serde_json::from_str::<Wrapper>("\"0x64\"").unwrap(), Wrapper(U256::from(100)));
+    }
+
+    #[test]
+    fn test_quantity_accepts_decimal() {
+        assert_eq!(serde_json::from_str::<Wrapper>("\"100\"").unwrap(), Wrapper(U256::from(100)));
+        assert_eq!(serde_json::from_str::<Wrapper>("100").unwrap(), Wrapper(U256::from(100)));
+        assert!(serde_json::from_str::<Wrapper>("\"0x\"").is_err());
+        assert!(serde_json::from_str::<Wrapper>("\"0xzz\"").is_err());
+    }
+}